@@ -0,0 +1,38 @@
+//! With the `embed-data` feature, runs the `parse_isa` pipeline over `amd_gpu_xmls/` (or
+//! `AMDGPU_LSP_XML_DIR`) and writes the compact result to `$OUT_DIR/isa.json` for `include_str!`.
+//! Without usable XMLs it warns and builds a server that loads its data at runtime instead.
+
+#[cfg(feature = "embed-data")]
+fn embed_data() -> Result<(), String> {
+  use std::env;
+  use std::fs;
+  use std::path::PathBuf;
+
+  println!("cargo:rerun-if-changed=parse_isa/src");
+  println!("cargo:rerun-if-env-changed=AMDGPU_LSP_XML_DIR");
+  let xml_dir = env::var("AMDGPU_LSP_XML_DIR").unwrap_or_else(|_| "amd_gpu_xmls".to_string());
+  println!("cargo:rerun-if-changed={xml_dir}");
+
+  if !PathBuf::from(&xml_dir).is_dir() {
+    return Err(format!("{xml_dir} not found; run ./build.sh --fetch-latest or set AMDGPU_LSP_XML_DIR"));
+  }
+  let xml_files = match parse_isa::generate::collect_xml_files(&[PathBuf::from(&xml_dir)]) {
+    Ok(files) if !files.is_empty() => files,
+    Ok(_) => return Err(format!("no XML files in {xml_dir}")),
+    Err(error) => return Err(format!("failed to read {xml_dir}: {error}")),
+  };
+  let isa_output = parse_isa::generate::build_isa_output(&xml_files)
+    .map_err(|error| format!("failed to parse {xml_dir}: {error}"))?;
+  let json = serde_json::to_string(&isa_output).map_err(|error| format!("failed to serialize isa.json: {error}"))?;
+  let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+  fs::write(out_dir.join("isa.json"), json).map_err(|error| format!("failed to write isa.json: {error}"))
+}
+
+fn main() {
+  println!("cargo::rustc-check-cfg=cfg(embedded_isa_data)");
+  #[cfg(feature = "embed-data")]
+  match embed_data() {
+    Ok(()) => println!("cargo::rustc-cfg=embedded_isa_data"),
+    Err(error) => println!("cargo::warning=embed-data: {error}; the server will load its data at runtime"),
+  }
+}
@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+
+/// Names a target can go by, keyed by the normalized architecture: LLVM `gfx` processors, chip
+/// codenames and product names. Written to `architecture_aliases` so the server learns new GPUs
+/// from regenerated data.
+const ARCHITECTURE_ALIASES: &[(&str, &[&str])] = &[
+  ("rdna1", &["gfx1010", "gfx1011", "gfx1012", "gfx1013", "navi10", "navi12", "navi14", "rx5700xt"]),
+  (
+    "rdna2",
+    &[
+      "gfx1030", "gfx1031", "gfx1032", "gfx1033", "gfx1034", "gfx1035", "gfx1036", "navi21", "navi22", "navi23",
+      "navi24", "rx6900xt", "rx6800xt",
+    ],
+  ),
+  (
+    "rdna3",
+    &[
+      "gfx1100", "gfx1101", "gfx1102", "gfx1103", "navi31", "navi32", "navi33", "phoenix", "rx7900xtx", "rx7900xt",
+      "rx7800xt", "rx7600",
+    ],
+  ),
+  ("rdna3.5", &["rdna35", "gfx1150", "gfx1151", "gfx1152", "gfx1153", "strixpoint", "strixhalo"]),
+  ("rdna4", &["gfx1200", "gfx1201", "navi44", "navi48", "rx9070xt", "rx9070", "rx9060xt"]),
+  ("cdna1", &["gfx908", "arcturus", "mi100"]),
+  ("cdna2", &["gfx90a", "aldebaran", "mi210", "mi250", "mi250x"]),
+  ("cdna3", &["gfx940", "gfx941", "gfx942", "aquavanjaram", "mi300", "mi300a", "mi300x", "mi325x"]),
+  ("cdna4", &["gfx950", "mi350x", "mi355x"]),
+];
+
+pub fn architecture_aliases() -> BTreeMap<String, Vec<String>> {
+  ARCHITECTURE_ALIASES
+    .iter()
+    .map(|(arch, aliases)| (arch.to_string(), aliases.iter().map(|alias| alias.to_string()).collect()))
+    .collect()
+}
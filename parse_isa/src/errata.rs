@@ -0,0 +1,41 @@
+use crate::model::ErratumOutput;
+
+/// `(instructions, architectures, note)`.
+type ErratumRow = (&'static [&'static str], &'static [&'static str], &'static str);
+
+const TRANSCENDENTALS: &[&str] = &[
+  "v_exp_f32", "v_log_f32", "v_rcp_f32", "v_rsq_f32", "v_sqrt_f32", "v_sin_f32", "v_cos_f32", "v_exp_f16", "v_log_f16",
+  "v_rcp_f16", "v_rsq_f16", "v_sqrt_f16", "v_sin_f16", "v_cos_f16",
+];
+
+/// Known hardware bugs and the software workarounds they need, per instruction and architecture.
+/// The XMLs do not document errata, so this table is maintained by hand and written to `errata`.
+const ERRATA: &[ErratumRow] = &[
+  (
+    &["v_mad_u64_u32", "v_mad_i64_i32"],
+    &["rdna3"],
+    "Intra-instruction forwarding bug: the result is corrupted when the destination overlaps src2. Keep the \
+     destination and src2 in disjoint registers; LLVM never allocates them overlapping on gfx11.",
+  ),
+  (
+    TRANSCENDENTALS,
+    &["rdna3", "rdna3.5"],
+    "The hardware does not always stall a VALU instruction that reads this transcendental result too soon \
+     (VALUTransUseHazard). Separate the consumer with `s_waitcnt_depctr` waiting for `va_vdst(0)`, as LLVM does.",
+  ),
+];
+
+fn strings(values: &[&str]) -> Vec<String> {
+  values.iter().map(|value| value.to_string()).collect()
+}
+
+pub fn errata() -> Vec<ErratumOutput> {
+  ERRATA
+    .iter()
+    .map(|&(instructions, architectures, note)| ErratumOutput {
+      instructions: strings(instructions),
+      architectures: strings(architectures),
+      note: note.to_string(),
+    })
+    .collect()
+}
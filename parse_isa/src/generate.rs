@@ -0,0 +1,189 @@
+use crate::aliases::architecture_aliases;
+use crate::errata::errata;
+use crate::instructions::parse_instruction_file;
+use crate::model::{InstructionDoc, IsaOutput, SpecialRegister};
+use crate::units::execution_unit;
+use crate::modifiers::memory_modifiers;
+use crate::special_registers::{
+  compress_special_registers, is_ignored_special_register, normalize_special_register, parse_special_registers,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maps Instinct product names (as used by the MI-series XMLs) to their CDNA generation.
+fn instinct_cdna_version(token: &str) -> Option<&'static str> {
+  let digits = token.strip_prefix("mi")?;
+  let model: u32 = digits
+    .trim_end_matches(|ch: char| ch.is_ascii_alphabetic())
+    .parse()
+    .ok()?;
+  match model {
+    100..=199 => Some("1"),
+    200..=299 => Some("2"),
+    300..=349 => Some("3"),
+    350..=399 => Some("4"),
+    _ => None,
+  }
+}
+
+fn normalize_architecture_name(raw: &str) -> String {
+  let lower = raw.trim().to_ascii_lowercase();
+  let tokens: Vec<&str> = lower
+    .split(|ch: char| ch.is_whitespace() || ch == '(' || ch == ')' || ch == '/' || ch == ',')
+    .filter(|token| !token.is_empty())
+    .collect();
+  let mut family: Option<&str> = None;
+  let mut version: Option<String> = None;
+  for token in &tokens {
+    if let Some(mi_version) = instinct_cdna_version(token) {
+      family = Some("cdna");
+      if version.is_none() {
+        version = Some(mi_version.to_string());
+      }
+      continue;
+    }
+    if token.contains("rdna") {
+      family = Some("rdna");
+      if let Some(remainder) = token.strip_prefix("rdna") && !remainder.is_empty() {
+        version = Some(remainder.to_string());
+      }
+      continue;
+    }
+    if token.contains("cdna") {
+      family = Some("cdna");
+      if let Some(remainder) = token.strip_prefix("cdna") && !remainder.is_empty() {
+        version = Some(remainder.to_string());
+      }
+      continue;
+    }
+    if family.is_some() && version.is_none() && token.chars().any(|ch| ch.is_ascii_digit()) {
+      version = Some(token.to_string());
+    }
+  }
+  if let Some(family) = family {
+    if let Some(version) = version {
+      return format!("{family}{version}");
+    }
+    return family.to_string();
+  }
+  lower.replace(' ', "")
+}
+
+pub fn collect_xml_files(inputs: &[PathBuf]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+  let mut xml_files = Vec::new();
+  for input in inputs {
+    if input.is_dir() {
+      for entry in fs::read_dir(input)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("xml") {
+          xml_files.push(path);
+        }
+      }
+    } else {
+      xml_files.push(input.clone());
+    }
+  }
+  Ok(xml_files)
+}
+
+fn is_rdna_source(path: &Path) -> bool {
+  path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .map(|name| name.contains("rdna"))
+    .unwrap_or(false)
+}
+
+fn merge_instructions(
+  merged: &mut Vec<InstructionDoc>,
+  key_to_index: &mut HashMap<String, usize>,
+  instructions: Vec<InstructionDoc>,
+) {
+  for inst in instructions {
+    let key = format!(
+      "{}|{}|{}|{}|{}",
+      inst.name,
+      inst.description.clone().unwrap_or_default(),
+      inst.args.join(","),
+      inst.arg_types.join(","),
+      inst.execution_unit.clone().unwrap_or_default()
+    );
+    if let Some(&index) = key_to_index.get(&key) {
+      let existing = &mut merged[index];
+      for arch in inst.architectures {
+        if !existing.architectures.contains(&arch) {
+          existing.architectures.push(arch);
+        }
+      }
+    } else {
+      key_to_index.insert(key, merged.len());
+      merged.push(inst);
+    }
+  }
+}
+
+/// Parses and merges every XML file into the `isa.json` model. Shared by the `parse_isa` binary and
+/// the `embed-data` build script.
+pub fn build_isa_output(xml_files: &[PathBuf]) -> Result<IsaOutput, Box<dyn Error>> {
+  let mut merged: Vec<InstructionDoc> = Vec::new();
+  let mut key_to_index: HashMap<String, usize> = HashMap::new();
+  let mut special_registers_by_name: BTreeMap<String, SpecialRegister> = BTreeMap::new();
+
+  for input in xml_files {
+    let (architecture_name, mut instructions) = parse_instruction_file(input)?;
+    let normalized_architecture = normalize_architecture_name(&architecture_name);
+    for inst in &mut instructions {
+      if inst.architectures.is_empty() {
+        inst.architectures.push(normalized_architecture.clone());
+      } else {
+        inst.architectures = inst
+          .architectures
+          .iter()
+          .map(|arch| normalize_architecture_name(arch))
+          .collect();
+      }
+      let architecture = inst.architectures.first().map(String::as_str).unwrap_or_default();
+      inst.execution_unit = execution_unit(&inst.name, &inst.available_encodings, architecture).map(str::to_string);
+    }
+    merge_instructions(&mut merged, &mut key_to_index, instructions);
+
+    if is_rdna_source(input) && let Ok(registers) = parse_special_registers(input) {
+      for reg in registers {
+        let name_lower = reg.name.to_ascii_lowercase();
+        if is_ignored_special_register(&name_lower) {
+          continue;
+        }
+        let reg = normalize_special_register(reg);
+        let key = reg.name.to_ascii_lowercase();
+        if let Some(existing) = special_registers_by_name.get_mut(&key) {
+          let SpecialRegister { description, .. } = reg;
+          if let Some(description) = description {
+            let should_replace = match &existing.description {
+              Some(current) => description.len() > current.len(),
+              None => true,
+            };
+            if should_replace {
+              existing.description = Some(description);
+            }
+          }
+        } else {
+          special_registers_by_name.insert(key, reg);
+        }
+      }
+    }
+  }
+
+  let mut all_special_registers: Vec<SpecialRegister> = special_registers_by_name.into_values().collect();
+  all_special_registers.sort_by(|a, b| a.name.cmp(&b.name));
+
+  Ok(IsaOutput {
+    instructions: merged,
+    special_registers: compress_special_registers(all_special_registers),
+    architecture_aliases: architecture_aliases(),
+    memory_modifiers: memory_modifiers(),
+    errata: errata(),
+  })
+}
@@ -1,5 +1,5 @@
 use crate::model::{InstructionDoc, InstructionEncoding, Operand};
-use crate::operand::{build_args, parse_operand_attributes};
+use crate::operand::{build_args, build_encoding_operands, parse_operand_attributes};
 use quick_xml::events::Event;
 use quick_xml::Reader;
 use std::collections::BTreeSet;
@@ -17,6 +17,7 @@ enum TextTarget {
   OperandType,
   OperandDataFormatName,
   OperandSize,
+  Note,
 }
 
 pub fn parse_instruction_file(path: &Path) -> Result<(String, Vec<InstructionDoc>), Box<dyn Error>> {
@@ -42,26 +43,20 @@ pub fn parse_instruction_file(path: &Path) -> Result<(String, Vec<InstructionDoc
         b"AliasedInstructionNames" => {
           in_aliased_names = true;
         }
-        b"InstructionName" => {
-          if !in_aliased_names {
-            text_target = Some(TextTarget::InstructionName);
-          }
+        b"InstructionName" if !in_aliased_names => {
+          text_target = Some(TextTarget::InstructionName);
         }
         b"ArchitectureName" => {
           text_target = Some(TextTarget::ArchitectureName);
         }
-        b"Description" => {
-          if current_instruction.is_some() {
-            text_target = Some(TextTarget::Description);
-          }
+        b"Description" if current_instruction.is_some() => {
+          text_target = Some(TextTarget::Description);
         }
         b"InstructionEncoding" => {
           current_encoding = Some(InstructionEncoding::default());
         }
-        b"EncodingName" => {
-          if current_encoding.is_some() {
-            text_target = Some(TextTarget::EncodingName);
-          }
+        b"EncodingName" if current_encoding.is_some() => {
+          text_target = Some(TextTarget::EncodingName);
         }
         b"Operand" => {
           current_operand = Some(parse_operand_attributes(event));
@@ -78,6 +73,11 @@ pub fn parse_instruction_file(path: &Path) -> Result<(String, Vec<InstructionDoc
         b"OperandSize" => {
           text_target = Some(TextTarget::OperandSize);
         }
+        b"Note" | b"Notes" | b"Restriction" | b"Restrictions"
+          if current_instruction.is_some() && current_operand.is_none() =>
+        {
+          text_target = Some(TextTarget::Note);
+        }
         _ => {}
       },
       Ok(Event::End(ref event)) => match event.local_name().as_ref() {
@@ -86,10 +86,12 @@ pub fn parse_instruction_file(path: &Path) -> Result<(String, Vec<InstructionDoc
         }
         b"Instruction" => {
           if let Some(mut inst) = current_instruction.take() {
-            let (args, arg_types, arg_data_types) = build_args(&inst.encodings);
-            inst.args = args;
-            inst.arg_types = arg_types;
-            inst.arg_data_types = arg_data_types;
+            let built = build_args(inst.encodings.first());
+            inst.encoding_operands = build_encoding_operands(&inst.encodings, &built);
+            inst.args = built.args;
+            inst.arg_types = built.arg_types;
+            inst.arg_data_types = built.arg_data_types;
+            inst.arg_sizes = built.arg_sizes;
             inst.available_encodings = inst
               .encodings
               .iter()
@@ -118,7 +120,11 @@ pub fn parse_instruction_file(path: &Path) -> Result<(String, Vec<InstructionDoc
         | b"FieldName"
         | b"OperandType"
         | b"DataFormatName"
-        | b"OperandSize" => {
+        | b"OperandSize"
+        | b"Note"
+        | b"Notes"
+        | b"Restriction"
+        | b"Restrictions" => {
           text_target = None;
         }
         _ => {}
@@ -167,6 +173,11 @@ pub fn parse_instruction_file(path: &Path) -> Result<(String, Vec<InstructionDoc
                 op.size = text.parse::<u32>().ok();
               }
             }
+            TextTarget::Note => {
+              if let Some(inst) = &mut current_instruction && !inst.notes.contains(&text) {
+                inst.notes.push(text);
+              }
+            }
           }
         }
       }
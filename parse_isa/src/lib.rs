@@ -0,0 +1,13 @@
+//! The `parse_isa` pipeline: reads AMD's machine-readable ISA XML specs and produces the `isa.json`
+//! data the server loads. Shared by the `parse_isa` binary, the `embed-data` build script and the
+//! server's loading of raw XMLs from a data directory.
+
+pub mod aliases;
+mod errata;
+pub mod generate;
+mod instructions;
+pub mod model;
+mod modifiers;
+mod operand;
+mod special_registers;
+mod units;
@@ -0,0 +1,120 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct Operand {
+  pub field_name: Option<String>,
+  pub operand_type: Option<String>,
+  pub data_format_name: Option<String>,
+  pub size: Option<u32>,
+  pub input: Option<bool>,
+  pub output: Option<bool>,
+  pub is_implicit: Option<bool>,
+  pub order: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct InstructionEncoding {
+  pub encoding_name: Option<String>,
+  pub operands: Vec<Operand>,
+}
+
+/// Operand list of one non-default encoding, parallel in shape to the top-level `args` fields.
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct EncodingOperands {
+  pub encoding: String,
+  pub args: Vec<String>,
+  pub arg_types: Vec<String>,
+  pub arg_data_types: Vec<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub arg_sizes: Vec<u32>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct InstructionDoc {
+  pub name: String,
+  pub architectures: Vec<String>,
+  pub description: Option<String>,
+  pub args: Vec<String>,
+  pub arg_types: Vec<String>,
+  pub arg_data_types: Vec<String>,
+  /// Operand sizes in bits, parallel to `args`. Empty when every operand fits in one dword.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub arg_sizes: Vec<u32>,
+  pub available_encodings: Vec<String>,
+  /// Operands of encodings whose list differs from `args` (which follows the first encoding).
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub encoding_operands: Vec<EncodingOperands>,
+  /// Free-form notes/restrictions attached to the instruction (wave64 limits, alignment rules, ...).
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub notes: Vec<String>,
+  /// Unit the instruction issues to (`salu`, `valu`, `trans`, `lds`, `texture`, `smem`, `branch`).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub execution_unit: Option<String>,
+  #[serde(skip_serializing)]
+  pub encodings: Vec<InstructionEncoding>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SpecialRegister {
+  pub name: String,
+  pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SpecialRegisterRangeOverride {
+  pub index: u32,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SpecialRegisterRange {
+  pub prefix: String,
+  pub start: u32,
+  pub count: u32,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub overrides: Vec<SpecialRegisterRangeOverride>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SpecialRegistersOutput {
+  pub singles: Vec<SpecialRegister>,
+  pub ranges: Vec<SpecialRegisterRange>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct IsaOutput {
+  pub instructions: Vec<InstructionDoc>,
+  pub special_registers: SpecialRegistersOutput,
+  /// Alternative target names (gfx processors, codenames, products) per architecture.
+  pub architecture_aliases: BTreeMap<String, Vec<String>>,
+  pub memory_modifiers: Vec<MemoryModifierOutput>,
+  pub errata: Vec<ErratumOutput>,
+}
+
+/// A hardware bug or required workaround for some instructions on some architectures.
+#[derive(Debug, Serialize, Clone)]
+pub struct ErratumOutput {
+  pub instructions: Vec<String>,
+  pub architectures: Vec<String>,
+  pub note: String,
+}
+
+/// A keyword modifier of memory instructions (`offset:`, `glc`, `th:`, ...).
+#[derive(Debug, Serialize, Clone)]
+pub struct MemoryModifierOutput {
+  pub name: String,
+  /// Written as `name:value` rather than a bare flag.
+  #[serde(skip_serializing_if = "std::ops::Not::not")]
+  pub takes_value: bool,
+  /// Instruction kinds that accept it: `ds`, `ds_2addr`, `buffer`, `flat` or `image`.
+  pub classes: Vec<String>,
+  pub architectures: Vec<String>,
+  /// Older modifiers this one supersedes on its architectures.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub replaces: Vec<String>,
+  pub description: String,
+}
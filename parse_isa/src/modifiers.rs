@@ -0,0 +1,87 @@
+use crate::model::MemoryModifierOutput;
+
+/// `(name, takes_value, classes, architectures, replaces, description)`.
+type ModifierRow = (&'static str, bool, &'static [&'static str], &'static [&'static str], &'static [&'static str], &'static str);
+
+const ADDRESSED: &[&str] = &["ds", "buffer", "flat"];
+const CACHED: &[&str] = &["buffer", "flat", "image"];
+const GLC_ARCHITECTURES: &[&str] = &["rdna1", "rdna2", "rdna3", "rdna3.5", "cdna1", "cdna2"];
+
+/// Keyword modifiers of memory instructions and the architectures that have them. The XMLs only
+/// describe encoding fields, so this table is maintained by hand and written to `memory_modifiers`.
+/// `replaces` names older spellings the modifier supersedes, for diagnostics.
+const MEMORY_MODIFIERS: &[ModifierRow] = &[
+  ("offset", true, ADDRESSED, &["rdna", "cdna"], &[], "Immediate byte offset added to the address."),
+  (
+    "offset0",
+    true,
+    &["ds_2addr"],
+    &["rdna", "cdna"],
+    &[],
+    "Offset of the first address, in elements of the data size (×64 for `st64`/`stride64` forms).",
+  ),
+  (
+    "offset1",
+    true,
+    &["ds_2addr"],
+    &["rdna", "cdna"],
+    &[],
+    "Offset of the second address, in elements of the data size (×64 for `st64`/`stride64` forms).",
+  ),
+  (
+    "glc",
+    false,
+    CACHED,
+    GLC_ARCHITECTURES,
+    &[],
+    "Globally coherent: bypass the near caches for loads; atomics return the pre-operation value.",
+  ),
+  ("slc", false, CACHED, GLC_ARCHITECTURES, &[], "System level coherent: streaming (non-temporal) hint for L2."),
+  ("dlc", false, CACHED, &["rdna1", "rdna2", "rdna3", "rdna3.5"], &[], "Device level coherent: bypass the L1 cache."),
+  (
+    "sc0",
+    false,
+    CACHED,
+    &["cdna3+"],
+    &["glc"],
+    "Scope bit 0 (with `sc1`: wave, group, device or system scope); atomics return the pre-operation value.",
+  ),
+  ("sc1", false, CACHED, &["cdna3+"], &["glc"], "Scope bit 1 (with `sc0`: wave, group, device or system scope)."),
+  ("nt", false, CACHED, &["cdna3+"], &["slc"], "Non-temporal: streaming hint."),
+  (
+    "th",
+    true,
+    CACHED,
+    &["rdna4+"],
+    &["glc", "slc", "dlc"],
+    "Temporal hint: `TH_LOAD_RT`, `TH_LOAD_NT`, `TH_LOAD_HT`, `TH_LOAD_LU`, ... (`TH_STORE_*` and `TH_ATOMIC_*` for \
+     stores and atomics).",
+  ),
+  (
+    "scope",
+    true,
+    CACHED,
+    &["rdna4+"],
+    &["glc", "slc", "dlc"],
+    "Coherence scope: `SCOPE_CU`, `SCOPE_SE`, `SCOPE_DEV` or `SCOPE_SYS`.",
+  ),
+  ("nv", false, CACHED, &["rdna4+"], &[], "Non-volatile: the data is not expected to change while cached."),
+];
+
+fn strings(values: &[&str]) -> Vec<String> {
+  values.iter().map(|value| value.to_string()).collect()
+}
+
+pub fn memory_modifiers() -> Vec<MemoryModifierOutput> {
+  MEMORY_MODIFIERS
+    .iter()
+    .map(|&(name, takes_value, classes, architectures, replaces, description)| MemoryModifierOutput {
+      name: name.to_string(),
+      takes_value,
+      classes: strings(classes),
+      architectures: strings(architectures),
+      replaces: strings(replaces),
+      description: description.to_string(),
+    })
+    .collect()
+}
@@ -0,0 +1,176 @@
+use crate::model::{EncodingOperands, InstructionEncoding, Operand};
+use quick_xml::events::BytesStart;
+
+fn parse_bool(raw: &str) -> Option<bool> {
+  match raw.to_ascii_lowercase().as_str() {
+    "true" => Some(true),
+    "false" => Some(false),
+    _ => None,
+  }
+}
+
+fn attr_value(attrs: &BytesStart<'_>, key: &[u8]) -> Option<String> {
+  for attr in attrs.attributes().flatten() {
+    if attr.key.as_ref() == key && let Ok(value) = attr.unescape_value() {
+      return Some(value.to_string());
+    }
+  }
+  None
+}
+
+pub fn parse_operand_attributes(attrs: &BytesStart<'_>) -> Operand {
+  Operand {
+    input: attr_value(attrs, b"Input").as_deref().and_then(parse_bool),
+    output: attr_value(attrs, b"Output").as_deref().and_then(parse_bool),
+    is_implicit: attr_value(attrs, b"IsImplicit").as_deref().and_then(parse_bool),
+    order: attr_value(attrs, b"Order").and_then(|val| val.parse::<u32>().ok()),
+    ..Operand::default()
+  }
+}
+
+fn operand_label(operand: &Operand) -> Option<String> {
+  if let Some(name) = &operand.field_name {
+    return Some(name.clone());
+  }
+  operand.operand_type.clone()
+}
+
+fn operand_kind(operand: &Operand) -> String {
+  let operand_type = match &operand.operand_type {
+    Some(value) => value.as_str(),
+    None => return "unknown".to_string(),
+  };
+  if operand_type.starts_with("OPR_SIMM")
+    || operand_type == "OPR_SMEM_OFFSET"
+    || operand_type == "OPR_DELAY"
+  {
+    return "immediate".to_string();
+  }
+  if operand_type == "OPR_LABEL" {
+    return "label".to_string();
+  }
+  if operand_type == "OPR_DSMEM" || operand_type == "OPR_FLAT_SCRATCH" {
+    return "memory".to_string();
+  }
+  // CDNA (MI100+) MAI operands can live in the accumulation register file. Operands that accept
+  // either file are spelled OPR_VGPR_OR_ACCVGPR / OPR_SRC_VGPR_OR_ACCVGPR in the MI-series XMLs.
+  if operand_type.contains("ACCVGPR") || operand_type.contains("AGPR") {
+    if operand_type.contains("VGPR_OR") || operand_type.contains("OR_ACC") {
+      return "register_or_accumulator".to_string();
+    }
+    return "accumulator".to_string();
+  }
+  if matches!(operand_type, "OPR_CBSZ" | "OPR_ABID" | "OPR_BLGP") {
+    return "modifier".to_string();
+  }
+  if matches!(
+    operand_type,
+    "OPR_VGPR"
+      | "OPR_SREG"
+      | "OPR_SDST"
+      | "OPR_SSRC"
+      | "OPR_SSRC_LANESEL"
+      | "OPR_SSRC_SPECIAL_SCC"
+      | "OPR_SRC"
+      | "OPR_SRC_VGPR"
+      | "OPR_SRC_VGPR_OR_INLINE"
+      | "OPR_VCC"
+      | "OPR_EXEC"
+      | "OPR_SDST_EXEC"
+      | "OPR_SDST_M0"
+      | "OPR_SDST_NULL"
+      | "OPR_PC"
+      | "OPR_TGT"
+  ) {
+    if operand_type == "OPR_SRC_VGPR_OR_INLINE" {
+      return "register_or_inline".to_string();
+    }
+    return "register".to_string();
+  }
+  if matches!(
+    operand_type,
+    "OPR_SENDMSG"
+      | "OPR_SENDMSG_RTN"
+      | "OPR_WAITCNT"
+      | "OPR_WAITCNT_DEPCTR"
+      | "OPR_WAIT_EVENT"
+      | "OPR_HWREG"
+      | "OPR_ATTR"
+      | "OPR_VERSION"
+      | "OPR_CLAUSE"
+  ) {
+    return "special".to_string();
+  }
+  "unknown".to_string()
+}
+
+pub struct BuiltArgs {
+  pub args: Vec<String>,
+  pub arg_types: Vec<String>,
+  pub arg_data_types: Vec<String>,
+  pub arg_sizes: Vec<u32>,
+}
+
+pub fn build_args(encoding: Option<&InstructionEncoding>) -> BuiltArgs {
+  let mut built = BuiltArgs {
+    args: Vec::new(),
+    arg_types: Vec::new(),
+    arg_data_types: Vec::new(),
+    arg_sizes: Vec::new(),
+  };
+  let encoding = match encoding {
+    Some(encoding) => encoding,
+    None => return built,
+  };
+  let mut operands = encoding.operands.clone();
+  operands.sort_by_key(|operand| operand.order.unwrap_or(u32::MAX));
+
+  for operand in operands {
+    if operand.is_implicit == Some(true) {
+      continue;
+    }
+    let label = operand_label(&operand).unwrap_or_else(|| "operand".to_string());
+    built.args.push(label);
+    built.arg_types.push(operand_kind(&operand));
+    built.arg_data_types.push(
+      operand
+        .data_format_name
+        .clone()
+        .unwrap_or_else(|| "unknown".to_string()),
+    );
+    built.arg_sizes.push(operand.size.unwrap_or(0));
+  }
+  // Sizes only matter for multi-register operands (MFMA A/B/C/D); drop the list when it carries
+  // nothing beyond the default dword so isa.json stays small.
+  if built.arg_sizes.iter().all(|&size| size <= 32) {
+    built.arg_sizes.clear();
+  }
+  built
+}
+
+/// Operand lists of the encodings whose explicit operands differ from the default (first) encoding,
+/// e.g. VOP3 forms of VOP2 instructions that gain `clamp`/`omod` or a carry-out destination.
+pub fn build_encoding_operands(encodings: &[InstructionEncoding], default: &BuiltArgs) -> Vec<EncodingOperands> {
+  let mut out = Vec::new();
+  for encoding in encodings.iter().skip(1) {
+    let name = match &encoding.encoding_name {
+      Some(name) => name,
+      None => continue,
+    };
+    let built = build_args(Some(encoding));
+    if built.args == default.args && built.arg_types == default.arg_types {
+      continue;
+    }
+    if out.iter().any(|existing: &EncodingOperands| &existing.encoding == name) {
+      continue;
+    }
+    out.push(EncodingOperands {
+      encoding: name.clone(),
+      args: built.args,
+      arg_types: built.arg_types,
+      arg_data_types: built.arg_data_types,
+      arg_sizes: built.arg_sizes,
+    });
+  }
+  out
+}
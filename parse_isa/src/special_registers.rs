@@ -51,6 +51,54 @@ fn special_register_override(name: &str) -> Option<&'static str> {
   }
 }
 
+/// Trap-handler conventions for individual trap temporaries, appended to their descriptions.
+fn trap_temp_convention(index: u32) -> Option<&'static str> {
+  match index {
+    0 => Some(
+      "All architectures: on trap entry the hardware saves the wave's PC in `{ttmp1, ttmp0}`; ttmp0 holds \
+       PC[31:0].",
+    ),
+    1 => Some(
+      "All architectures: on trap entry ttmp1[15:0] holds PC[47:32] of the saved PC and ttmp1[23:16] the trap \
+       ID from `s_trap` (0 for exceptions).",
+    ),
+    7 => Some(
+      "CDNA3 and RDNA4 (architected SGPRs): initialized at wave launch with workgroup ID Y in bits [15:0] and \
+       workgroup ID Z in bits [31:16]; kernels read the IDs from here rather than from user SGPRs.",
+    ),
+    9 => Some(
+      "CDNA3 and RDNA4 (architected SGPRs): initialized at wave launch with workgroup ID X.",
+    ),
+    _ => None,
+  }
+}
+
+/// Adds the trap-handler conventions to the `ttmp` range as per-index overrides.
+fn add_trap_temp_conventions(range: &mut SpecialRegisterRange) {
+  for index in range.start..range.start + range.count {
+    let convention = match trap_temp_convention(index) {
+      Some(convention) => convention,
+      None => continue,
+    };
+    let position = range.overrides.iter().position(|ov| ov.index == index);
+    let base = position
+      .and_then(|position| range.overrides[position].description.clone())
+      .or_else(|| range.description.clone());
+    let description = match base {
+      Some(base) => format!("{base}\n\n{convention}"),
+      None => convention.to_string(),
+    };
+    match position {
+      Some(position) => range.overrides[position].description = Some(description),
+      None => range.overrides.push(SpecialRegisterRangeOverride {
+        index,
+        description: Some(description),
+      }),
+    }
+  }
+  range.overrides.sort_by_key(|ov| ov.index);
+}
+
 fn split_numeric_suffix(name: &str) -> Option<(&str, u32)> {
   let mut split_at = None;
   for (i, ch) in name.char_indices() {
@@ -142,10 +190,8 @@ pub fn compress_special_registers(all: Vec<SpecialRegister>) -> SpecialRegisters
 
     let mut desc_counts: BTreeMap<String, u32> = BTreeMap::new();
     for (_idx, r) in &items {
-      if let Some(d) = &r.description {
-        if !d.trim().is_empty() && !is_see_above(d) {
-          *desc_counts.entry(d.clone()).or_insert(0) += 1;
-        }
+      if let Some(d) = &r.description && !d.trim().is_empty() && !is_see_above(d) {
+        *desc_counts.entry(d.clone()).or_insert(0) += 1;
       }
     }
     let range_description: Option<String> = desc_counts
@@ -156,15 +202,13 @@ pub fn compress_special_registers(all: Vec<SpecialRegister>) -> SpecialRegisters
     let mut overrides: Vec<SpecialRegisterRangeOverride> = Vec::new();
     for (idx, r) in &items {
       let mut override_desc = None;
-      if let Some(d) = &r.description {
-        if !d.trim().is_empty() {
-          let differs = match &range_description {
-            Some(rd) => rd != d,
-            None => true,
-          };
-          if differs && !is_see_above(d) {
-            override_desc = Some(d.clone());
-          }
+      if let Some(d) = &r.description && !d.trim().is_empty() {
+        let differs = match &range_description {
+          Some(rd) => rd != d,
+          None => true,
+        };
+        if differs && !is_see_above(d) {
+          override_desc = Some(d.clone());
         }
       }
       if override_desc.is_some() {
@@ -175,13 +219,17 @@ pub fn compress_special_registers(all: Vec<SpecialRegister>) -> SpecialRegisters
       }
     }
 
-    ranges.push(SpecialRegisterRange {
+    let mut range = SpecialRegisterRange {
       prefix,
       start,
       count: items.len() as u32,
       description: range_description,
       overrides,
-    });
+    };
+    if range.prefix == "ttmp" {
+      add_trap_temp_conventions(&mut range);
+    }
+    ranges.push(range);
   }
 
   singles.extend(leftover_singles);
@@ -199,10 +247,8 @@ pub fn compress_special_registers(all: Vec<SpecialRegister>) -> SpecialRegisters
 }
 
 pub fn normalize_special_register(mut reg: SpecialRegister) -> SpecialRegister {
-  if let Some(desc) = &reg.description {
-    if is_see_above(desc) {
-      reg.description = None;
-    }
+  if let Some(desc) = &reg.description && is_see_above(desc) {
+    reg.description = None;
   }
   if let Some(override_desc) = special_register_override(&reg.name.to_ascii_lowercase()) {
     reg.description = Some(override_desc.to_string());
@@ -233,28 +279,20 @@ pub fn parse_special_registers(path: &Path) -> Result<Vec<SpecialRegister>, Box<
         b"OperandPredefinedValues" => {
           in_predefined_values = true;
         }
-        b"PredefinedValue" => {
-          if in_predefined_values {
-            current_register = Some(SpecialRegister {
-              name: String::new(),
-              description: None,
-            });
-          }
+        b"PredefinedValue" if in_predefined_values => {
+          current_register = Some(SpecialRegister {
+            name: String::new(),
+            description: None,
+          });
         }
-        b"Name" => {
-          if current_register.is_some() {
-            text_target = Some(TextTarget::Name);
-          }
+        b"Name" if current_register.is_some() => {
+          text_target = Some(TextTarget::Name);
         }
-        b"Description" => {
-          if current_register.is_some() {
-            text_target = Some(TextTarget::Description);
-          }
+        b"Description" if current_register.is_some() => {
+          text_target = Some(TextTarget::Description);
         }
-        b"Value" => {
-          if current_register.is_some() {
-            text_target = None;
-          }
+        b"Value" if current_register.is_some() => {
+          text_target = None;
         }
         _ => {}
       },
@@ -263,10 +301,8 @@ pub fn parse_special_registers(path: &Path) -> Result<Vec<SpecialRegister>, Box<
           in_predefined_values = false;
         }
         b"PredefinedValue" => {
-          if let Some(reg) = current_register.take() {
-            if !reg.name.is_empty() {
-              special_registers.push(reg);
-            }
+          if let Some(reg) = current_register.take() && !reg.name.is_empty() {
+            special_registers.push(reg);
           }
         }
         b"Name" | b"Description" | b"Value" => {
@@ -0,0 +1,59 @@
+/// Transcendental opcode stems; RDNA3 and later issue these to a separate transcendental unit.
+const TRANSCENDENTAL_OPS: &[&str] = &["exp", "log", "rcp", "rcp_iflag", "rsq", "sqrt", "sin", "cos"];
+
+/// Architectures with a dedicated transcendental unit.
+fn has_transcendental_unit(architecture: &str) -> bool {
+  ["rdna3", "rdna4"].iter().any(|prefix| architecture.starts_with(prefix))
+}
+
+fn is_branch(name: &str) -> bool {
+  ["s_branch", "s_cbranch_", "s_setpc_", "s_swappc_", "s_call_"]
+    .iter()
+    .any(|prefix| name.starts_with(prefix))
+}
+
+fn is_transcendental(name: &str) -> bool {
+  let Some(op) = name.strip_prefix("v_") else {
+    return false;
+  };
+  TRANSCENDENTAL_OPS.iter().any(|stem| {
+    op.strip_prefix(stem)
+      .and_then(|rest| rest.strip_prefix('_'))
+      .is_some_and(|ty| matches!(ty, "f16" | "f32" | "f64"))
+  })
+}
+
+/// Execution unit an instruction issues to on the architecture, from its name and encodings:
+/// `salu`, `valu`, `trans`, `lds`, `texture` (vector memory), `smem` or `branch`. Program-control
+/// instructions handled by the sequencer (`s_nop`, `s_waitcnt`, ...) and exports have none.
+pub fn execution_unit(name: &str, encodings: &[String], architecture: &str) -> Option<&'static str> {
+  let name = name.to_ascii_lowercase();
+  if is_branch(&name) {
+    return Some("branch");
+  }
+  let encoding = encodings.first()?.to_ascii_uppercase();
+  let encoding = encoding.strip_prefix("ENC_").unwrap_or(&encoding);
+  if encoding.starts_with("SOPP") {
+    return None;
+  }
+  if encoding.starts_with("SOP") {
+    return Some("salu");
+  }
+  if encoding.starts_with("SMEM") || encoding.starts_with("SMRD") {
+    return Some("smem");
+  }
+  if encoding.starts_with("DS") || encoding.contains("DSDIR") {
+    return Some("lds");
+  }
+  let vector_memory = ["MUBUF", "MTBUF", "MIMG", "FLAT", "VBUFFER", "VIMAGE", "VSAMPLE", "VGLOBAL", "VSCRATCH"];
+  if vector_memory.iter().any(|prefix| encoding.starts_with(prefix)) {
+    return Some("texture");
+  }
+  if encoding.starts_with("VOP") || encoding.starts_with("VINTERP") {
+    if is_transcendental(&name) && has_transcendental_unit(architecture) {
+      return Some("trans");
+    }
+    return Some("valu");
+  }
+  None
+}
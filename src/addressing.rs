@@ -0,0 +1,93 @@
+use crate::architecture::arch_matches;
+use crate::modifiers::{memory_class, MemoryClass};
+use crate::special_operands::{off_allowed, special_operand_note};
+use crate::types::InstructionEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddressSpace {
+  Flat,
+  Global,
+  Scratch,
+}
+
+impl AddressSpace {
+  fn of(name: &str) -> Option<Self> {
+    let lower = name.to_ascii_lowercase();
+    if memory_class(&lower) != Some(MemoryClass::Flat) {
+      return None;
+    }
+    Some(if lower.starts_with("global_") {
+      AddressSpace::Global
+    } else if lower.starts_with("scratch_") {
+      AddressSpace::Scratch
+    } else {
+      AddressSpace::Flat
+    })
+  }
+
+  fn summary(self) -> &'static str {
+    match self {
+      AddressSpace::Flat => {
+        "flat (generic) address: the aperture picks global, LDS or scratch at run time, so the access waits on both \
+         the vector memory and LDS counters"
+      }
+      AddressSpace::Global => "global memory only: no aperture check, so cheaper than `flat_*` for device memory",
+      AddressSpace::Scratch => "per-lane private memory, addressed relative to the wave's scratch base",
+    }
+  }
+}
+
+/// What an address slot holds, given whether the instruction also has an SADDR slot.
+fn slot_role(space: AddressSpace, slot: &str, has_saddr: bool) -> Option<&'static str> {
+  match (space, slot.to_ascii_uppercase().as_str()) {
+    (AddressSpace::Global, "VADDR") if has_saddr => {
+      Some("32-bit VGPR offset when SADDR is an SGPR pair, otherwise the full 64-bit VGPR address")
+    }
+    (AddressSpace::Scratch, "VADDR") => Some("32-bit per-lane VGPR offset"),
+    (_, "VADDR") => Some("64-bit VGPR address"),
+    (AddressSpace::Global, "SADDR") => Some("64-bit SGPR base address"),
+    (AddressSpace::Scratch, "SADDR") => Some("32-bit SGPR offset into the wave's scratch"),
+    _ => None,
+  }
+}
+
+/// Whether scratch instructions have the ST mode, in which `vaddr` and `saddr` may both be `off`:
+/// CDNA3 (gfx940) and RDNA3 (gfx11) onwards.
+fn has_scratch_st_mode(arch: &str) -> bool {
+  arch_matches(arch, "cdna3+") || arch_matches(arch, "rdna3+")
+}
+
+/// Address space and address operands of a `flat_*`/`global_*`/`scratch_*` entry, built from its
+/// operand slots: what each address slot holds and, where `off` may fill it, what that means.
+pub fn addressing_section(entry: &InstructionEntry) -> Option<String> {
+  let space = AddressSpace::of(&entry.name)?;
+  let has_saddr = entry.args.iter().any(|slot| slot.eq_ignore_ascii_case("SADDR"));
+  let mut lines = vec![format!("**Addressing** · {}", space.summary())];
+  for slot in &entry.args {
+    let role = match slot_role(space, slot, has_saddr) {
+      Some(role) => role,
+      None => continue,
+    };
+    let mut line = format!("- `{}`: {role}", slot.to_ascii_lowercase());
+    if off_allowed(&entry.name, Some(slot)) && let Some(note) = special_operand_note(&entry.name, Some(slot), "off") {
+      line.push_str(&format!("; {note}"));
+    }
+    lines.push(line);
+  }
+  if space == AddressSpace::Scratch && has_saddr {
+    let without_st_mode: Vec<&str> =
+      entry.architectures.iter().map(String::as_str).filter(|arch| !has_scratch_st_mode(arch)).collect();
+    if without_st_mode.len() == entry.architectures.len() {
+      lines.push("- `vaddr` and `saddr` cannot both be `off`".to_string());
+    } else if without_st_mode.is_empty() {
+      lines.push("- `vaddr` and `saddr` may both be `off`: the address is the `offset:` immediate alone".to_string());
+    } else {
+      lines.push(format!(
+        "- `vaddr` and `saddr` cannot both be `off` on {}; later targets address by the `offset:` immediate alone",
+        without_st_mode.join(", ")
+      ));
+    }
+  }
+  lines.push("- `offset:` immediate byte offset added to the address".to_string());
+  Some(lines.join("\n"))
+}
@@ -1,46 +1,206 @@
 use crate::types::InstructionEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::Url;
 
-pub fn normalize_architecture_hint(raw: &str) -> String {
-  let cleaned = raw.trim().to_ascii_lowercase().replace(' ', "");
-  if let Some(rem) = cleaned.strip_prefix("rdna") {
-    if rem.len() == 2 && rem.chars().all(|ch| ch.is_ascii_digit()) {
-      let (major, minor) = rem.split_at(1);
-      return format!("rdna{major}.{minor}");
-    }
+/// `amdgpu/architectureChanged`: the architecture a document resolves to, sent when it is first
+/// known and whenever detection or configuration changes it (`null` when nothing filters the document).
+pub enum ArchitectureChanged {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchitectureChangedParams {
+  pub uri: Url,
+  pub architecture: Option<String>,
+}
+
+impl Notification for ArchitectureChanged {
+  type Params = ArchitectureChangedParams;
+  const METHOD: &'static str = "amdgpu/architectureChanged";
+}
+
+/// Lookup from alternative target names (`gfx90a`, `navi31`, `mi300x`, ...) to the architecture
+/// names used by the instruction data: the built-in table, with the data file's `architecture_aliases` on top.
+#[derive(Debug, Clone, Default)]
+pub struct ArchitectureAliases {
+  by_alias: HashMap<String, String>,
+}
+
+fn alias_key(raw: &str) -> String {
+  let cleaned = raw.trim().to_ascii_lowercase().replace([' ', '_', '-'], "");
+  let cleaned = cleaned.split(':').next().unwrap_or_default();
+  let mut cleaned = cleaned;
+  for prefix in ["amd", "radeon", "instinct"] {
+    cleaned = cleaned.strip_prefix(prefix).unwrap_or(cleaned);
   }
-  cleaned
+  cleaned.to_string()
 }
 
-pub fn architecture_filter(language_id: &str, override_arch: Option<&String>) -> Option<String> {
-  if let Some(override_arch) = override_arch {
-    if !override_arch.trim().is_empty() {
-      return Some(normalize_architecture_hint(override_arch));
+impl ArchitectureAliases {
+  pub fn from_table(table: &HashMap<String, Vec<String>>) -> Self {
+    let mut aliases = Self::default();
+    aliases.extend(table);
+    aliases
+  }
+
+  /// Adds a table's aliases; where an alias is already known, the table's architecture wins.
+  pub fn extend(&mut self, table: &HashMap<String, Vec<String>>) {
+    for (arch, aliases) in table {
+      self.by_alias.insert(alias_key(arch), arch.clone());
+      for alias in aliases {
+        self.by_alias.insert(alias_key(alias), arch.clone());
+      }
     }
   }
-  match language_id {
-    "rdna35" => Some("rdna3.5".to_string()),
-    "rdna3" => Some("rdna3".to_string()),
-    "rdna4" => Some("rdna4".to_string()),
-    "cdna3" => Some("cdna3".to_string()),
-    "cdna4" => Some("cdna4".to_string()),
-    "rdna" => Some("rdna".to_string()),
-    "cdna" => Some("cdna".to_string()),
-    _ => None,
+
+  /// Architecture for a target name such as `gfx942:sramecc+:xnack-`, `MI300X` or `rdna3.5`.
+  pub fn resolve(&self, target: &str) -> Option<&str> {
+    self.by_alias.get(&alias_key(target)).map(|arch| arch.as_str())
+  }
+
+  /// Number of architectures that have aliases.
+  pub fn architecture_count(&self) -> usize {
+    self.by_alias.values().collect::<HashSet<_>>().len()
   }
 }
 
-pub fn entry_matches_arch(entry: &InstructionEntry, filter: &str) -> bool {
-  if filter.starts_with("rdna") {
-    if filter == "rdna" {
-      return entry.architectures.iter().any(|arch| arch.starts_with("rdna"));
+/// Architecture from `HSA_OVERRIDE_GFX_VERSION`-style `major.minor.stepping` (`11.0.0` → gfx1100,
+/// `9.0.10` → gfx90a).
+pub fn hsa_gfx_version_architecture(version: &str, aliases: &ArchitectureAliases) -> Option<String> {
+  let mut parts = version.trim().split('.');
+  let major: u32 = parts.next()?.parse().ok()?;
+  let minor: u32 = parts.next()?.parse().ok()?;
+  let stepping: u32 = parts.next()?.parse().ok()?;
+  aliases
+    .resolve(&format!("gfx{major}{minor}{stepping:x}"))
+    .map(|arch| arch.to_string())
+}
+
+/// Default architecture from the environment: `AMDGPU_LSP_ARCH` (an architecture or `gfx` name),
+/// then `HSA_OVERRIDE_GFX_VERSION`.
+pub fn environment_architecture(aliases: &ArchitectureAliases) -> Option<String> {
+  if let Ok(value) = std::env::var("AMDGPU_LSP_ARCH") && !value.trim().is_empty() {
+    return Some(normalize_architecture_hint(&value, aliases));
+  }
+  let version = std::env::var("HSA_OVERRIDE_GFX_VERSION").ok()?;
+  hsa_gfx_version_architecture(&version, aliases)
+}
+
+pub fn normalize_architecture_hint(raw: &str, aliases: &ArchitectureAliases) -> String {
+  if let Some(arch) = aliases.resolve(raw) {
+    return arch.to_string();
+  }
+  // A trailing `+` after a `:feature` segment turns the feature on (`gfx90a:xnack+`); it is no range.
+  let trimmed = raw.trim();
+  let range = trimmed.strip_suffix('+').filter(|name| !name.contains(':'));
+  if let Some(name) = trimmed.strip_prefix(">=").or(range) {
+    return format!("{}+", normalize_architecture_hint(name, aliases));
+  }
+  let cleaned = raw.trim().to_ascii_lowercase().replace(' ', "");
+  if let Some(rem) = cleaned.strip_prefix("rdna") && rem.len() == 2 && rem.chars().all(|ch| ch.is_ascii_digit()) {
+    let (major, minor) = rem.split_at(1);
+    return format!("rdna{major}.{minor}");
+  }
+  cleaned
+}
+
+/// Resolves the filter for a document: an explicit override wins, then the language id. `fallback`
+/// (e.g. a target discovered from build files) applies when the language id names no architecture,
+/// or refines a bare `rdna`/`cdna` family it belongs to.
+pub fn architecture_filter(
+  language_id: &str,
+  override_arch: Option<&String>,
+  fallback: Option<&String>,
+  aliases: &ArchitectureAliases,
+) -> Option<String> {
+  if let Some(override_arch) = override_arch && !override_arch.trim().is_empty() {
+    return Some(normalize_architecture_hint(override_arch, aliases));
+  }
+  let from_language = language_architecture(language_id, aliases);
+  match (from_language, fallback) {
+    (None, Some(fallback)) => Some(fallback.clone()),
+    (Some(family), Some(fallback)) if (family == "rdna" || family == "cdna") && fallback.starts_with(&family) => {
+      Some(fallback.clone())
     }
-    return entry.architectures.iter().any(|arch| arch == filter);
+    (from_language, _) => from_language,
   }
-  if filter.starts_with("cdna") {
-    if filter == "cdna" {
-      return entry.architectures.iter().any(|arch| arch.starts_with("cdna"));
+}
+
+/// Architecture named by a document language id (`rdna35`, `cdna3`, or a bare `rdna`/`cdna`).
+fn language_architecture(language_id: &str, aliases: &ArchitectureAliases) -> Option<String> {
+  let arch = normalize_architecture_hint(language_id, aliases);
+  (arch.starts_with("rdna") || arch.starts_with("cdna")).then_some(arch)
+}
+
+/// An architecture name or range as used by filters and data annotations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchSpec<'a> {
+  /// A bare `rdna`/`cdna` family.
+  Family(&'a str),
+  /// One generation, e.g. `rdna3.5`.
+  Exact(&'a str, (u32, u32)),
+  /// A generation and everything after it in the family: `rdna3+` or `>=rdna3`.
+  AtLeast(&'a str, (u32, u32)),
+  Other(&'a str),
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+  let (major, minor) = version.split_once('.').unwrap_or((version, "0"));
+  Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+fn parse_arch_spec(raw: &str) -> ArchSpec<'_> {
+  let (name, at_least) = match raw.strip_prefix(">=") {
+    Some(name) => (name, true),
+    None => match raw.strip_suffix('+') {
+      Some(name) => (name, true),
+      None => (raw, false),
+    },
+  };
+  for family in ["rdna", "cdna"] {
+    if let Some(version) = name.strip_prefix(family) {
+      if version.is_empty() {
+        return ArchSpec::Family(family);
+      }
+      return match (parse_version(version), at_least) {
+        (Some(version), true) => ArchSpec::AtLeast(family, version),
+        (Some(version), false) => ArchSpec::Exact(family, version),
+        (None, _) => ArchSpec::Other(raw),
+      };
     }
-    return entry.architectures.iter().any(|arch| arch == filter);
   }
-  entry.architectures.iter().any(|arch| arch == filter)
+  ArchSpec::Other(raw)
+}
+
+fn spec_family<'a>(spec: &ArchSpec<'a>) -> Option<&'a str> {
+  match spec {
+    ArchSpec::Family(family) | ArchSpec::Exact(family, _) | ArchSpec::AtLeast(family, _) => Some(family),
+    ArchSpec::Other(_) => None,
+  }
+}
+
+/// Whether an entry architecture (possibly a range such as `rdna3+`) covers the filter (possibly a
+/// bare family or a range itself).
+pub fn arch_matches(entry_arch: &str, filter: &str) -> bool {
+  let entry_spec = parse_arch_spec(entry_arch);
+  let filter_spec = parse_arch_spec(filter);
+  if let (ArchSpec::Other(entry_arch), ArchSpec::Other(filter)) = (entry_spec, filter_spec) {
+    return entry_arch == filter;
+  }
+  if spec_family(&entry_spec).is_none() || spec_family(&entry_spec) != spec_family(&filter_spec) {
+    return false;
+  }
+  match (entry_spec, filter_spec) {
+    (ArchSpec::Exact(_, entry_version), ArchSpec::Exact(_, version)) => entry_version == version,
+    (ArchSpec::Exact(_, entry_version), ArchSpec::AtLeast(_, since)) => entry_version >= since,
+    (ArchSpec::AtLeast(_, since), ArchSpec::Exact(_, version)) => version >= since,
+    // A bare family matches any generation, and two open-ended ranges always overlap.
+    _ => true,
+  }
+}
+
+/// Filters and data may name a family (`rdna`), a generation (`rdna3.5`) or a range of generations
+/// (`rdna3+`, `>=cdna2`); ranges match every later member of the family.
+pub fn entry_matches_arch(entry: &InstructionEntry, filter: &str) -> bool {
+  entry.architectures.iter().any(|arch| arch_matches(arch, filter))
 }
@@ -0,0 +1,102 @@
+use crate::expression::parse_integer_literal;
+use crate::registers::parse_register;
+use crate::rename::is_branch_mnemonic;
+use crate::tokenizer::{
+  leading_label, line_comment_start, listing_address, Dialect, InstructionLine, Token,
+};
+
+/// The operand of a branch that names its target: the last one, unless it is a register
+/// (`s_cbranch_join` has no static target).
+pub fn branch_target_operand<'a>(parsed: &InstructionLine<'a>) -> Option<Token<'a>> {
+  if !is_branch_mnemonic(parsed.mnemonic.text) {
+    return None;
+  }
+  parsed.operands.last().copied().filter(|operand| parse_register(operand.text).is_none())
+}
+
+/// Sign-extended simm16 of a branch: written signed (`-3`) or as its unsigned 16-bit pattern (`0xfffd`).
+fn branch_offset(text: &str) -> Option<i64> {
+  let text = text.trim();
+  let value = match text.strip_prefix('-') {
+    Some(digits) => parse_integer_literal(digits)?.checked_neg()?,
+    None => parse_integer_literal(text)?,
+  };
+  (-(1 << 15)..1 << 16).contains(&value).then_some(value as u16 as i16 as i64)
+}
+
+/// Where a branch at `address` lands for a dword offset: relative to the next instruction.
+fn branch_target(address: u64, offset: i64) -> u64 {
+  address.wrapping_add(4).wrapping_add_signed(4 * offset)
+}
+
+/// Address of the first listing line at or after a label's definition line.
+fn label_address(text: &str, def_line: u32, dialect: Dialect) -> Option<u64> {
+  text.lines().skip(def_line as usize).find_map(|line| listing_address(line, dialect))
+}
+
+/// Labels defined right before the listing line at `address`.
+fn labels_at(text: &str, address: u64, dialect: Dialect) -> Vec<&str> {
+  let mut pending = Vec::new();
+  for line in text.lines() {
+    let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+    let line_address = listing_address(line, dialect);
+    // The `1000:` address column of a listing line reads as a label; a real label is anything else.
+    let is_address_column = |label: &Token<'_>| u64::from_str_radix(label.text, 16).ok() == line_address;
+    if let Some(label) = leading_label(code).filter(|label| line_address.is_none() || !is_address_column(label)) {
+      pending.push(label.text);
+    }
+    match line_address {
+      Some(line_address) if line_address == address => return pending,
+      Some(_) => pending.clear(),
+      None => {}
+    }
+  }
+  Vec::new()
+}
+
+/// Absolute target of a numeric branch offset on a listing line with an address column, with any
+/// label defined there.
+pub fn branch_target_note(
+  text: &str,
+  line: &str,
+  parsed: &InstructionLine<'_>,
+  operand: &Token<'_>,
+  dialect: Dialect,
+) -> Option<String> {
+  if branch_target_operand(parsed).as_ref() != Some(operand) {
+    return None;
+  }
+  let offset = branch_offset(operand.text)?;
+  let address = listing_address(line, dialect)?;
+  let target = branch_target(address, offset);
+  let sign = if offset < 0 { '-' } else { '+' };
+  let mut lines = vec![
+    format!("Branch target: `{target:#x}`"),
+    format!("- `{address:#x}` + 4 {sign} {} × 4", offset.abs()),
+  ];
+  for label in labels_at(text, target, dialect) {
+    lines.push(format!("- label `{label}`"));
+  }
+  Some(lines.join("\n"))
+}
+
+/// Absolute address of the label target of a branch on `line`, when the label precedes a listing line.
+pub fn label_target_note(
+  text: &str,
+  line: &str,
+  parsed: &InstructionLine<'_>,
+  label: &str,
+  def_line: u32,
+  dialect: Dialect,
+) -> Option<String> {
+  if branch_target_operand(parsed).is_none_or(|operand| operand.text != label) {
+    return None;
+  }
+  let target = label_address(text, def_line, dialect)?;
+  let mut note = format!("Branch target: `{target:#x}`");
+  if let Some(address) = listing_address(line, dialect) {
+    let offset = target.wrapping_sub(address).wrapping_sub(4) as i64 / 4;
+    note.push_str(&format!(" (simm16 {offset} from `{address:#x}`)"));
+  }
+  Some(note)
+}
@@ -0,0 +1,107 @@
+use crate::encoding::{entry_for_variant, find_matching_encoding, split_encoding_variant};
+use crate::index::find_entry;
+use crate::literals::needs_literal;
+use crate::tokenizer::{Dialect, InstructionLine};
+use crate::types::{EncodingVariant, InstructionEntry};
+use crate::vopd::{dual_entry, parse_dual_line};
+use std::collections::HashMap;
+
+/// Modifiers that select the DPP form of a VOP1/VOP2/VOPC instruction.
+const DPP_MODIFIERS: &[&str] =
+  &["quad_perm", "row_", "wave_", "dpp8", "bound_ctrl", "bank_mask", "row_mask", "fi"];
+/// Modifiers that select the SDWA form.
+const SDWA_MODIFIERS: &[&str] = &["dst_sel", "dst_unused", "src0_sel", "src1_sel"];
+
+/// Size in bytes of an encoding without its literal.
+fn encoding_bytes(encoding: &str) -> u32 {
+  let encoding = encoding.trim_start_matches("ENC_");
+  let is_vop3 = encoding.starts_with("VOP3");
+  if encoding.contains("DPP") || encoding.contains("SDWA") {
+    return if is_vop3 { 12 } else { 8 };
+  }
+  // The gfx12 vector memory encodings are 96-bit.
+  if ["VBUFFER", "VIMAGE", "VSAMPLE", "VFLAT", "VGLOBAL", "VSCRATCH"].iter().any(|name| encoding.contains(name)) {
+    return 12;
+  }
+  let is_dword = ["SOP", "VOP1", "VOP2", "VOPC", "LDSDIR"].iter().any(|prefix| encoding.starts_with(prefix));
+  if is_dword && !is_vop3 {
+    4
+  } else {
+    8
+  }
+}
+
+/// Whether the encoding can carry a trailing 32-bit literal constant.
+pub fn takes_literal(encoding: &str) -> bool {
+  let encoding = encoding.trim_start_matches("ENC_");
+  let is_alu = ["SOP1", "SOP2", "SOPC", "VOP"].iter().any(|prefix| encoding.starts_with(prefix));
+  is_alu && !encoding.contains("DPP") && !encoding.contains("SDWA")
+}
+
+/// Whether an operand uses a VOP3-only input modifier (`-v1`, `|v1|`, `abs(v1)`, `neg(v1)`).
+fn has_input_modifier(operand: &str) -> bool {
+  let text = operand.trim();
+  let negated_register = text.strip_prefix('-').is_some_and(|rest| rest.starts_with(|c: char| !c.is_ascii_digit()));
+  negated_register || text.starts_with('|') || text.starts_with("abs(") || text.starts_with("neg(")
+}
+
+/// Encoding the assembler picks for the line: the one its suffix names, or for an unsuffixed
+/// VOP1/VOP2/VOPC op the DPP, SDWA or VOP3 form its modifiers need.
+pub fn selected_encoding(entry: &InstructionEntry, parsed: &InstructionLine, variant: &EncodingVariant) -> Option<String> {
+  let native = find_matching_encoding(&entry.available_encodings, variant)?;
+  if *variant != EncodingVariant::Native || !["ENC_VOP1", "ENC_VOP2", "ENC_VOPC"].contains(&native.as_str()) {
+    return Some(native);
+  }
+  let has_modifier = |names: &[&str]| {
+    parsed.modifiers.iter().any(|modifier| {
+      let name = modifier.text.split(':').next().unwrap_or_default().to_ascii_lowercase();
+      names.iter().any(|prefix| name.starts_with(prefix))
+    })
+  };
+  let promoted = if has_modifier(DPP_MODIFIERS) {
+    EncodingVariant::Dpp
+  } else if has_modifier(SDWA_MODIFIERS) {
+    EncodingVariant::Sdwa
+  } else if !parsed.modifiers.is_empty() || parsed.operands.iter().any(|operand| has_input_modifier(operand.text)) {
+    EncodingVariant::E64
+  } else {
+    return Some(native);
+  };
+  find_matching_encoding(&entry.available_encodings, &promoted).or(Some(native))
+}
+
+/// Estimated size in bytes of a parsed instruction of a line: the encoding its suffix, modifiers and
+/// operands select, plus a literal dword. Mnemonics the index lacks count as one dword.
+pub fn instruction_bytes(
+  line: &str,
+  parsed: &InstructionLine,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  filter: Option<&str>,
+  dialect: Dialect,
+) -> u32 {
+  if let Some(dual) = parse_dual_line(line, dialect).filter(|dual| dual.x.mnemonic.start == parsed.mnemonic.start) {
+    let halves = std::iter::once(&dual.x).chain(dual.y.as_ref());
+    let literal = halves.into_iter().any(|half| {
+      let entry = dual_entry(index, half.mnemonic.text, filter);
+      half.operands.iter().enumerate().any(|(index, operand)| {
+        needs_literal(operand.text, entry.and_then(|entry| entry.arg_data_types.get(index)).map(String::as_str))
+      })
+    });
+    return if literal { 12 } else { 8 };
+  }
+  let split = split_encoding_variant(parsed.mnemonic.text);
+  let entry = match find_entry(index, &split.base, filter) {
+    Some(entry) => entry,
+    None => return 4,
+  };
+  let encoding = match selected_encoding(entry, parsed, &split.variant) {
+    Some(encoding) => encoding,
+    None => return 4,
+  };
+  let entry = entry_for_variant(entry, &split.variant);
+  let literal = takes_literal(&encoding)
+    && parsed.operands.iter().enumerate().any(|(index, operand)| {
+      needs_literal(operand.text, entry.arg_data_types.get(index).map(String::as_str))
+    });
+  encoding_bytes(&encoding) + if literal { 4 } else { 0 }
+}
@@ -0,0 +1,153 @@
+use crate::registers::{parse_register, RegisterClass};
+use crate::tokenizer::{data_directive, line_comment_start, parse_instruction_statements, Dialect};
+use std::collections::HashSet;
+use tower_lsp::lsp_types::Color;
+
+/// Data directives whose operands are dwords, and so may hold packed colors.
+const DWORD_DIRECTIVES: &[&str] = &[".long", ".int"];
+
+/// A `0xAARRGGBB` literal that looks like a packed color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorLiteral {
+  pub line: u32,
+  /// Byte range of the literal within its line.
+  pub start: usize,
+  pub end: usize,
+  pub argb: u32,
+}
+
+impl ColorLiteral {
+  pub fn color(&self) -> Color {
+    let channel = |shift: u32| ((self.argb >> shift) & 0xff) as f32 / 255.0;
+    Color {
+      red: channel(16),
+      green: channel(8),
+      blue: channel(0),
+      alpha: channel(24),
+    }
+  }
+}
+
+/// Value of a literal spelled as exactly eight hex digits after `0x`; shorter constants are
+/// masks and offsets far more often than colors.
+fn packed_color(text: &str) -> Option<u32> {
+  let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X"))?;
+  if digits.len() != 8 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+    return None;
+  }
+  u32::from_str_radix(digits, 16).ok()
+}
+
+fn is_export(mnemonic: &str) -> bool {
+  mnemonic.eq_ignore_ascii_case("exp") || mnemonic.eq_ignore_ascii_case("export")
+}
+
+/// VGPRs among the operands of an instruction line. Export operands are whitespace-separated after
+/// the target (`exp mrt0 v0, v1, v2, v3`), so words are checked too.
+fn vector_registers<'a>(operands: &'a [&str]) -> impl Iterator<Item = u32> + 'a {
+  operands
+    .iter()
+    .flat_map(|operand| operand.split_whitespace())
+    .filter_map(parse_register)
+    .filter(|register| register.class == RegisterClass::Vector)
+    .flat_map(|register| register.first..=register.last)
+}
+
+/// Movs among `movs` (line, mnemonic byte, destination VGPR; in document order) whose value reaches
+/// an export before anything else writes the register. One backward pass: an export makes the VGPRs
+/// it reads live, and any other instruction naming a register as its first operand counts as a write
+/// that ends it, which also ends the search at stores that use it as an address; that only hides a
+/// swatch.
+fn movs_feeding_exports(lines: &[&str], movs: &[(usize, usize, u32)], dialect: Dialect) -> HashSet<(usize, usize)> {
+  let mut live = HashSet::new();
+  let mut feeding = HashSet::new();
+  let mut pending = movs.iter().rev().peekable();
+  for (line_idx, line) in lines.iter().enumerate().rev() {
+    for parsed in parse_instruction_statements(line, dialect).into_iter().rev() {
+      if let Some(&&(mov_line, mov_start, vgpr)) = pending.peek()
+        && (mov_line, mov_start) == (line_idx, parsed.mnemonic.start)
+      {
+        if live.contains(&vgpr) {
+          feeding.insert((mov_line, mov_start));
+        }
+        pending.next();
+      }
+      let operands: Vec<&str> = parsed.operands.iter().map(|operand| operand.text).collect();
+      if is_export(parsed.mnemonic.text) {
+        live.extend(vector_registers(&operands));
+      } else if let Some(first) = operands.first() {
+        for vgpr in vector_registers(&[*first]) {
+          live.remove(&vgpr);
+        }
+      }
+    }
+  }
+  feeding
+}
+
+/// Packed colors in the document: `0xAARRGGBB` operands of `.long`/`.int` data and literals
+/// `v_mov_b32` places in a VGPR that is later exported.
+pub fn document_color_literals(text: &str, dialect: Dialect) -> Vec<ColorLiteral> {
+  let lines: Vec<&str> = text.lines().collect();
+  let mut colors = Vec::new();
+  // `v_mov_b32` literals, kept once a later pass shows they reach an export.
+  let mut movs = Vec::new();
+  let mut mov_colors = Vec::new();
+  for (line_idx, line) in lines.iter().enumerate() {
+    if let Some(directive) = data_directive(line, dialect) {
+      if !DWORD_DIRECTIVES.contains(&directive.text.to_ascii_lowercase().as_str()) {
+        continue;
+      }
+      let code_end = line_comment_start(line, dialect).unwrap_or(line.len());
+      let mut offset = directive.end();
+      for value in line[offset..code_end].split(',') {
+        let trimmed = value.trim();
+        let start = offset + (value.len() - value.trim_start().len());
+        offset += value.len() + 1;
+        if let Some(argb) = packed_color(trimmed) {
+          colors.push(ColorLiteral {
+            line: line_idx as u32,
+            start,
+            end: start + trimmed.len(),
+            argb,
+          });
+        }
+      }
+      continue;
+    }
+    for parsed in parse_instruction_statements(line, dialect) {
+      if !parsed.mnemonic.text.eq_ignore_ascii_case("v_mov_b32") || parsed.operands.len() != 2 {
+        continue;
+      }
+      let (destination, source) = (parsed.operands[0], parsed.operands[1]);
+      let (vgpr, argb) = match (parse_register(destination.text), packed_color(source.text)) {
+        (Some(register), Some(argb)) if register.class == RegisterClass::Vector => (register.first, argb),
+        _ => continue,
+      };
+      movs.push((line_idx, parsed.mnemonic.start, vgpr));
+      mov_colors.push(ColorLiteral {
+        line: line_idx as u32,
+        start: source.start,
+        end: source.end(),
+        argb,
+      });
+    }
+  }
+  let feeding = movs_feeding_exports(&lines, &movs, dialect);
+  let fed = mov_colors.into_iter().zip(&movs).filter(|(_, (line, start, _))| feeding.contains(&(*line, *start)));
+  colors.extend(fed.map(|(color, _)| color));
+  colors.sort_by_key(|color| color.line);
+  colors
+}
+
+/// `color` as a `0xAARRGGBB` literal, keeping the case of the hex digits in `original`.
+pub fn color_literal_text(color: &Color, original: &str) -> String {
+  let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u32;
+  let argb = channel(color.alpha) << 24 | channel(color.red) << 16 | channel(color.green) << 8 | channel(color.blue);
+  let uppercase = original.bytes().skip(2).any(|b| b.is_ascii_uppercase());
+  if uppercase {
+    format!("0x{argb:08X}")
+  } else {
+    format!("0x{argb:08x}")
+  }
+}
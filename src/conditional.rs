@@ -0,0 +1,200 @@
+use crate::symbols::{evaluate_with_symbols, SymbolDefinition};
+use crate::tokenizer::{line_comment_start, strip_leading_label, Dialect};
+use std::collections::HashSet;
+
+/// One branch of an `.if` block: from its opening directive (`.if`, `.elseif`, `.else`) up to the
+/// line before the next directive of the same block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConditionalBranch {
+  pub start_line: u32,
+  pub end_line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionalIssue {
+  pub line: u32,
+  /// Byte range of the offending directive within its line.
+  pub start: usize,
+  pub end: usize,
+  pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ConditionalStructure {
+  pub branches: Vec<ConditionalBranch>,
+  pub issues: Vec<ConditionalIssue>,
+  /// Lines inside a branch that the symbol values prove will not be assembled.
+  pub inactive_lines: HashSet<u32>,
+}
+
+struct Frame {
+  directive_line: u32,
+  directive_start: usize,
+  directive_end: usize,
+  branch_start: u32,
+  /// Whether an earlier branch of this block was taken (`None` when it cannot be decided).
+  any_taken: Option<bool>,
+  /// Whether the current branch is assembled (`None` when it cannot be decided).
+  active: Option<bool>,
+  seen_else: bool,
+}
+
+fn is_defined(definitions: &[SymbolDefinition], name: &str, line: u32) -> Option<bool> {
+  if definitions
+    .iter()
+    .any(|definition| definition.name == name && definition.line < line)
+  {
+    return Some(true);
+  }
+  // The symbol may still come from the command line (`-defsym`) or an include.
+  None
+}
+
+fn evaluate_condition(
+  directive: &str,
+  argument: &str,
+  definitions: &[SymbolDefinition],
+  line: u32,
+) -> Option<bool> {
+  let value = || evaluate_with_symbols(definitions, argument, line);
+  match directive {
+    ".if" | ".elseif" | ".ifne" => value().map(|value| value != 0),
+    ".ifeq" => value().map(|value| value == 0),
+    ".ifgt" => value().map(|value| value > 0),
+    ".ifge" => value().map(|value| value >= 0),
+    ".iflt" => value().map(|value| value < 0),
+    ".ifle" => value().map(|value| value <= 0),
+    ".ifdef" => is_defined(definitions, argument, line),
+    ".ifndef" | ".ifnotdef" => is_defined(definitions, argument, line).map(|defined| !defined),
+    // `\arg` inside a macro body is only known once the macro is expanded.
+    ".ifb" | ".ifnb" if argument.contains('\\') => None,
+    ".ifb" => Some(argument.is_empty()),
+    ".ifnb" => Some(!argument.is_empty()),
+    _ => None,
+  }
+}
+
+fn is_if_directive(directive: &str) -> bool {
+  matches!(
+    directive,
+    ".if" | ".ifdef" | ".ifndef" | ".ifnotdef" | ".ifeq" | ".ifne" | ".ifgt" | ".ifge" | ".iflt" | ".ifle"
+      | ".ifb" | ".ifnb" | ".ifc" | ".ifnc" | ".ifeqs" | ".ifnes"
+  )
+}
+
+/// Walks `.if`/`.elseif`/`.else`/`.endif` structure, recording branch ranges, unbalanced
+/// directives and the lines of branches that are provably not assembled.
+pub fn analyze_conditionals(text: &str, definitions: &[SymbolDefinition], dialect: Dialect) -> ConditionalStructure {
+  let mut structure = ConditionalStructure::default();
+  let mut stack: Vec<Frame> = Vec::new();
+  let mut line_count = 0;
+  for (line_idx, line) in text.lines().enumerate() {
+    let line_idx = line_idx as u32;
+    line_count = line_idx + 1;
+    let code = match line_comment_start(line, dialect) {
+      Some(comment_start) => &line[..comment_start],
+      None => line,
+    };
+    let (offset, rest) = strip_leading_label(code);
+    let directive_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let directive = rest[..directive_len].to_ascii_lowercase();
+    let argument = rest[directive_len..].trim();
+    let directive_range = (offset, offset + directive_len);
+    let enclosing_inactive = stack.iter().any(|frame| frame.active == Some(false));
+
+    if is_if_directive(&directive) {
+      if enclosing_inactive {
+        structure.inactive_lines.insert(line_idx);
+      }
+      let active = evaluate_condition(&directive, argument, definitions, line_idx);
+      stack.push(Frame {
+        directive_line: line_idx,
+        directive_start: directive_range.0,
+        directive_end: directive_range.1,
+        branch_start: line_idx,
+        any_taken: active,
+        active,
+        seen_else: false,
+      });
+      continue;
+    }
+
+    let is_branch = directive == ".elseif" || directive == ".else";
+    if is_branch || directive == ".endif" {
+      let frame = match stack.last_mut() {
+        Some(frame) => frame,
+        None => {
+          structure.issues.push(ConditionalIssue {
+            line: line_idx,
+            start: directive_range.0,
+            end: directive_range.1,
+            message: format!("`{directive}` without a matching `.if`"),
+          });
+          continue;
+        }
+      };
+      if frame.branch_start < line_idx {
+        structure.branches.push(ConditionalBranch {
+          start_line: frame.branch_start,
+          end_line: line_idx - 1,
+        });
+      }
+      if directive == ".endif" {
+        stack.pop();
+        if stack.iter().any(|frame| frame.active == Some(false)) {
+          structure.inactive_lines.insert(line_idx);
+        }
+        continue;
+      }
+      if frame.seen_else {
+        structure.issues.push(ConditionalIssue {
+          line: line_idx,
+          start: directive_range.0,
+          end: directive_range.1,
+          message: format!("`{directive}` after `.else`"),
+        });
+      }
+      frame.branch_start = line_idx;
+      if directive == ".else" {
+        frame.seen_else = true;
+        frame.active = frame.any_taken.map(|taken| !taken);
+        frame.any_taken = Some(true);
+      } else {
+        let condition = evaluate_condition(&directive, argument, definitions, line_idx);
+        match frame.any_taken {
+          Some(true) => frame.active = Some(false),
+          Some(false) => {
+            frame.active = condition;
+            frame.any_taken = condition;
+          }
+          None => {
+            frame.active = if condition == Some(false) { Some(false) } else { None };
+          }
+        }
+      }
+      if stack[..stack.len() - 1].iter().any(|frame| frame.active == Some(false)) {
+        structure.inactive_lines.insert(line_idx);
+      }
+      continue;
+    }
+
+    if stack.iter().any(|frame| frame.active == Some(false)) {
+      structure.inactive_lines.insert(line_idx);
+    }
+  }
+  for frame in stack {
+    if frame.branch_start + 1 < line_count {
+      structure.branches.push(ConditionalBranch {
+        start_line: frame.branch_start,
+        end_line: line_count - 1,
+      });
+    }
+    structure.issues.push(ConditionalIssue {
+      line: frame.directive_line,
+      start: frame.directive_start,
+      end: frame.directive_end,
+      message: "Conditional block is missing `.endif`".to_string(),
+    });
+  }
+  structure
+}
@@ -0,0 +1,86 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Settings accepted in `initializationOptions` and `workspace/didChangeConfiguration`. A setting
+/// left out keeps its current value; keys the server does not know (client-only settings such as
+/// `serverPath`) are ignored.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConfig {
+  pub architecture_override: Option<String>,
+  /// The name synchronized settings use for `architectureOverride`, which wins when both are set.
+  pub architecture: Option<String>,
+  pub discover_target: Option<bool>,
+  pub hover_sections: Option<Map<String, Value>>,
+  pub isa_overlays: Option<Vec<String>>,
+  pub external_symbols: Option<ExternalSymbolsSetting>,
+  pub slow_request_ms: Option<u64>,
+  pub log_slow_requests: Option<bool>,
+  pub workspace_index_cache: Option<bool>,
+  pub color_decorators: Option<bool>,
+  pub explain_idioms: Option<bool>,
+  pub encoding_suffix_style: Option<String>,
+  pub operand_inlay_hints: Option<String>,
+  pub formatting: Option<Map<String, Value>>,
+  pub assembler_dialect: Option<String>,
+}
+
+/// `externalSymbols`: names, or names mapped to hover descriptions.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ExternalSymbolsSetting {
+  Names(Vec<String>),
+  Described(Map<String, Value>),
+}
+
+impl ExternalSymbolsSetting {
+  pub fn to_value(&self) -> Value {
+    match self {
+      ExternalSymbolsSetting::Names(names) => Value::from(names.clone()),
+      ExternalSymbolsSetting::Described(map) => Value::Object(map.clone()),
+    }
+  }
+}
+
+/// Values the string settings take; others are reported and ignored.
+const CHOICES: &[(&str, &[&str])] = &[
+  ("encodingSuffixStyle", &["suffixed", "bare", ""]),
+  ("operandInlayHints", &["names", "types", "off"]),
+  ("assemblerDialect", &["llvm", "gnu", "sp3"]),
+];
+
+impl ServerConfig {
+  /// Parses the settings object, keeping every valid setting; the returned messages name each
+  /// invalid one and why it was ignored.
+  pub fn parse(options: &Value) -> (Self, Vec<String>) {
+    let object = match options {
+      Value::Object(object) => object,
+      Value::Null => return (Self::default(), Vec::new()),
+      _ => return (Self::default(), vec!["settings must be an object".to_string()]),
+    };
+    let mut errors = Vec::new();
+    let mut valid = Map::new();
+    for (key, value) in object {
+      let single = Value::Object(Map::from_iter([(key.clone(), value.clone())]));
+      if let Err(error) = serde_json::from_value::<Self>(single) {
+        errors.push(format!("`{key}`: {error}"));
+        continue;
+      }
+      let choices = CHOICES.iter().find(|(name, _)| name == key).map(|(_, choices)| *choices);
+      if let (Some(choices), Some(text)) = (choices, value.as_str())
+        && !choices.contains(&text.to_ascii_lowercase().as_str())
+      {
+        let expected: Vec<String> = choices.iter().map(|choice| format!("`{choice}`")).collect();
+        errors.push(format!("`{key}`: `{text}` is not one of {}", expected.join(", ")));
+        continue;
+      }
+      valid.insert(key.clone(), value.clone());
+    }
+    (serde_json::from_value(Value::Object(valid)).unwrap_or_default(), errors)
+  }
+
+  /// `architectureOverride`, else `architecture`.
+  pub fn architecture_override(&self) -> Option<&str> {
+    self.architecture_override.as_deref().or(self.architecture.as_deref())
+  }
+}
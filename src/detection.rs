@@ -0,0 +1,112 @@
+use crate::architecture::entry_matches_arch;
+use crate::encoding::split_encoding_variant;
+use crate::tokenizer::{parse_instruction_statements, Dialect};
+use crate::types::InstructionEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::Url;
+
+/// `amdgpu/architectureDetected`: the architecture guessed for a document that has no filter, with
+/// the instruction that gave it away, so the client can offer to set it explicitly.
+pub enum ArchitectureDetected {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchitectureDetectedParams {
+  pub uri: Url,
+  pub architecture: String,
+  pub evidence: String,
+}
+
+impl Notification for ArchitectureDetected {
+  type Params = ArchitectureDetectedParams;
+  const METHOD: &'static str = "amdgpu/architectureDetected";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchitectureGuess {
+  pub architecture: String,
+  /// First mnemonic in the document that rules out other architectures.
+  pub evidence: String,
+}
+
+fn family(arch: &str) -> &str {
+  arch.get(..4).unwrap_or(arch)
+}
+
+/// The distinct base mnemonics of a document's instructions, lowercased, in order of first use.
+pub fn document_mnemonics(text: &str, dialect: Dialect) -> Vec<String> {
+  let mut seen = HashSet::new();
+  text
+    .lines()
+    .flat_map(|line| parse_instruction_statements(line, dialect))
+    .map(|parsed| split_encoding_variant(parsed.mnemonic.text).base.to_ascii_lowercase())
+    .filter(|base| seen.insert(base.clone()))
+    .collect()
+}
+
+/// Guesses the architecture from the instructions a document uses: every known mnemonic votes for
+/// the architectures that have it, and mnemonics available everywhere are ignored. Returns a bare
+/// `rdna`/`cdna` family when the best generations tie within one family, and nothing when the
+/// evidence points at more than one family.
+pub fn detect_architecture(
+  text: &str,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  dialect: Dialect,
+) -> Option<ArchitectureGuess> {
+  let candidates: BTreeSet<&str> = index
+    .values()
+    .flatten()
+    .flat_map(|entry| entry.architectures.iter())
+    .map(|arch| arch.as_str())
+    .filter(|arch| (arch.starts_with("rdna") || arch.starts_with("cdna")) && !arch.ends_with('+'))
+    .collect();
+  if candidates.len() < 2 {
+    return None;
+  }
+
+  let mut votes: Vec<(String, Vec<&str>)> = Vec::new();
+  for base in document_mnemonics(text, dialect) {
+    let entries = match index.get(&base) {
+      Some(entries) => entries,
+      None => continue,
+    };
+    let supported: Vec<&str> = candidates
+      .iter()
+      .copied()
+      .filter(|arch| entries.iter().any(|entry| entry_matches_arch(entry, arch)))
+      .collect();
+    if supported.is_empty() || supported.len() == candidates.len() {
+      continue;
+    }
+    votes.push((base, supported));
+  }
+
+  let mut scores: HashMap<&str, usize> = HashMap::new();
+  for (_, supported) in &votes {
+    for arch in supported {
+      *scores.entry(arch).or_default() += 1;
+    }
+  }
+  let best_score = *scores.values().max()?;
+  let best: Vec<&str> = candidates
+    .iter()
+    .copied()
+    .filter(|arch| scores.get(arch) == Some(&best_score))
+    .collect();
+  let first_family = family(best[0]);
+  if best.iter().any(|arch| family(arch) != first_family) {
+    return None;
+  }
+  let architecture = if best.len() == 1 {
+    best[0].to_string()
+  } else {
+    first_family.to_string()
+  };
+  let evidence = votes
+    .iter()
+    .find(|(_, supported)| supported.iter().all(|arch| family(arch) == first_family))
+    .or_else(|| votes.first())
+    .map(|(mnemonic, _)| mnemonic.clone())?;
+  Some(ArchitectureGuess { architecture, evidence })
+}
@@ -0,0 +1,498 @@
+use crate::architecture::entry_matches_arch;
+use crate::conditional::analyze_conditionals;
+use crate::encoding::{entry_for_variant, split_encoding_variant};
+use crate::errata::{errata_for, Erratum};
+use crate::fuzzy::closest_names;
+use crate::hwreg::setreg_issues;
+use crate::index::find_entry;
+use crate::lds::analyze_lds;
+use crate::literals::literal_issues;
+use crate::modifiers::{memory_class, memory_modifiers, MemoryModifier};
+use crate::op_sel::{is_vop3p, op_sel_issues};
+use crate::registers::{parse_register, register_issue};
+use crate::relocations::{analyze_relocations, RelocationIssueKind};
+use crate::scratch::analyze_scratch;
+use crate::smem::smem_offset_issues;
+use crate::special_operands::special_operand_issue;
+use crate::symbols::{collect_macro_names, collect_symbol_definitions};
+use crate::text_utils::{byte_offset_to_utf16_position, utf16_position_to_byte_offset};
+use crate::tokenizer::{parse_instruction_statements, Dialect, InstructionLine, Token};
+use crate::types::{InstructionEntry, IsaLoadInfo};
+use crate::waits::{split_wait_replacement, statement_wait};
+use std::collections::HashMap;
+use tower_lsp::lsp_types::{
+  CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range,
+  TextEdit, Url, WorkspaceEdit,
+};
+
+pub const DIAGNOSTIC_SOURCE: &str = "amdgpu-lsp";
+pub const UNKNOWN_MNEMONIC_CODE: &str = "unknown-mnemonic";
+pub const UNSUPPORTED_MODIFIER_CODE: &str = "unsupported-modifier";
+pub const LEGACY_WAITCNT_CODE: &str = "legacy-waitcnt";
+pub const UNAVAILABLE_INSTRUCTION_CODE: &str = "unavailable-instruction";
+pub const ERRATUM_CODE: &str = "erratum";
+pub const MISSING_DATA_CODE: &str = "missing-isa-data";
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Restrictions from the ISA notes that can be verified from the source text alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteCheck {
+  EvenAlignedDestination,
+  Wave32Only,
+  Wave64Only,
+}
+
+pub fn note_checks(notes: &[String]) -> Vec<NoteCheck> {
+  let mut checks = Vec::new();
+  for note in notes {
+    let lower = note.to_ascii_lowercase();
+    if lower.contains("even") && lower.contains("align") && (lower.contains("vdst") || lower.contains("dest")) {
+      checks.push(NoteCheck::EvenAlignedDestination);
+    }
+    if lower.contains("not supported in wave64") || lower.contains("wave32 only") || lower.contains("only supported in wave32") {
+      checks.push(NoteCheck::Wave32Only);
+    }
+    if lower.contains("not supported in wave32") || lower.contains("wave64 only") || lower.contains("only supported in wave64") {
+      checks.push(NoteCheck::Wave64Only);
+    }
+  }
+  checks.dedup();
+  checks
+}
+
+pub fn token_range(line_idx: usize, line: &str, token: &Token<'_>) -> Range {
+  byte_range(line_idx as u32, line, token.start, token.end())
+}
+
+fn byte_range(line_idx: u32, line: &str, start: usize, end: usize) -> Range {
+  Range {
+    start: Position {
+      line: line_idx,
+      character: byte_offset_to_utf16_position(line, start),
+    },
+    end: Position {
+      line: line_idx,
+      character: byte_offset_to_utf16_position(line, end),
+    },
+  }
+}
+
+fn diagnostic(range: Range, severity: DiagnosticSeverity, message: String) -> Diagnostic {
+  Diagnostic {
+    range,
+    severity: Some(severity),
+    source: Some(DIAGNOSTIC_SOURCE.to_string()),
+    message,
+    ..Diagnostic::default()
+  }
+}
+
+/// Banner on the first line of every open file when the ISA data failed to load, published with the
+/// checks that need no data; the instruction checks would otherwise pass silently.
+pub fn missing_data_diagnostic(load_info: &IsaLoadInfo) -> Option<Diagnostic> {
+  let error = load_info.load_error.as_ref()?;
+  Some(Diagnostic {
+    code: Some(NumberOrString::String(MISSING_DATA_CODE.to_string())),
+    ..diagnostic(
+      Range::default(),
+      DiagnosticSeverity::WARNING,
+      format!(
+        "AMDGPU ISA data is missing, so instruction hover, completion and checks are off: {error} (path: {}). \
+         Point the `amdgpuLsp.dataPath` setting, `--data` or `AMDGPU_LSP_DATA` at an isa.json (or a directory of \
+         them) generated by parse_isa.",
+        load_info.data_path
+      ),
+    )
+  })
+}
+
+/// Wave size declared by `.amdhsa_wavefront_size32`, if the document has a kernel descriptor.
+fn declared_wave_size(text: &str) -> Option<u32> {
+  for line in text.lines() {
+    let mut parts = line.split_whitespace();
+    if parts.next() != Some(".amdhsa_wavefront_size32") {
+      continue;
+    }
+    return match parts.next() {
+      Some("1") => Some(32),
+      Some("0") => Some(64),
+      _ => None,
+    };
+  }
+  None
+}
+
+fn check_notes(
+  entry: &InstructionEntry,
+  parsed: &InstructionLine<'_>,
+  wave_size: Option<u32>,
+  line_idx: usize,
+  line: &str,
+  out: &mut Vec<Diagnostic>,
+) {
+  let mnemonic_range = token_range(line_idx, line, &parsed.mnemonic);
+  for check in note_checks(&entry.notes) {
+    match check {
+      NoteCheck::EvenAlignedDestination => {
+        let dst = match parsed.operands.first() {
+          Some(dst) => dst,
+          None => continue,
+        };
+        if let Some(register) = parse_register(dst.text) && register.first % 2 != 0 {
+          out.push(diagnostic(
+            token_range(line_idx, line, dst),
+            DiagnosticSeverity::WARNING,
+            format!("{} requires an even-aligned destination register", parsed.mnemonic.text),
+          ));
+        }
+      }
+      NoteCheck::Wave32Only => {
+        if wave_size == Some(64) {
+          out.push(diagnostic(
+            mnemonic_range,
+            DiagnosticSeverity::WARNING,
+            format!("{} is not supported in wave64 (kernel declares wave64)", parsed.mnemonic.text),
+          ));
+        }
+      }
+      NoteCheck::Wave64Only => {
+        if wave_size == Some(32) {
+          out.push(diagnostic(
+            mnemonic_range,
+            DiagnosticSeverity::WARNING,
+            format!("{} is not supported in wave32 (kernel declares wave32)", parsed.mnemonic.text),
+          ));
+        }
+      }
+    }
+  }
+}
+
+/// Memory modifiers that exist for the instruction kind but not on the active architecture (`dlc` on
+/// CDNA, `glc`/`slc` on RDNA4), pointing at the modifiers that replace them there.
+fn check_memory_modifiers(
+  parsed: &InstructionLine<'_>,
+  modifiers: &[MemoryModifier],
+  filter: &str,
+  line_idx: usize,
+  line: &str,
+  dialect: Dialect,
+  out: &mut Vec<Diagnostic>,
+) {
+  let class = match memory_class(parsed.mnemonic.text) {
+    Some(class) => class,
+    None => return,
+  };
+  for token in &parsed.modifiers {
+    let name = token.text.split(':').next().unwrap_or(token.text);
+    let mut known = modifiers
+      .iter()
+      .filter(|modifier| modifier.name.eq_ignore_ascii_case(name) && modifier.classes.contains(&class))
+      .peekable();
+    if known.peek().is_none() || known.any(|modifier| modifier.available_on(Some(filter))) {
+      continue;
+    }
+    let replacements: Vec<String> = memory_modifiers(modifiers, class, Some(filter))
+      .filter(|modifier| modifier.replaces.iter().any(|replaced| replaced.eq_ignore_ascii_case(name)))
+      .map(|modifier| {
+        if modifier.takes_value {
+          format!("{}:", modifier.name)
+        } else {
+          modifier.name.clone()
+        }
+      })
+      .collect();
+    // SP3 spells cache policies with the legacy keywords (`glc`, `slc`) on every target.
+    if !replacements.is_empty() && dialect == Dialect::Sp3 {
+      continue;
+    }
+    let mut message = format!("`{name}` is not supported on {filter}");
+    if !replacements.is_empty() {
+      let replacements: Vec<String> = replacements.iter().map(|name| format!("`{name}`")).collect();
+      message.push_str(&format!("; use {} instead", replacements.join("/")));
+    }
+    out.push(Diagnostic {
+      code: Some(NumberOrString::String(UNSUPPORTED_MODIFIER_CODE.to_string())),
+      ..diagnostic(token_range(line_idx, line, token), DiagnosticSeverity::WARNING, message)
+    });
+  }
+}
+
+/// gfx12 split the counters of `s_waitcnt`/`s_waitcnt_vscnt`; suggests the equivalent `s_wait_*`
+/// sequence.
+fn legacy_waitcnt_diagnostic(parsed: &InstructionLine<'_>, filter: &str, line_idx: usize, line: &str) -> Option<Diagnostic> {
+  let mnemonic = parsed.mnemonic.text.to_ascii_lowercase();
+  if !mnemonic.starts_with("s_waitcnt") {
+    return None;
+  }
+  let requirements = statement_wait(line, parsed, Some(filter))?;
+  let replacement = split_wait_replacement(&requirements);
+  let mut message = format!("`{mnemonic}` does not exist on {filter}");
+  if replacement.is_empty() {
+    message.push_str("; it waits on nothing and can be removed");
+  } else {
+    let replacement: Vec<String> = replacement.iter().map(|line| format!("`{line}`")).collect();
+    message.push_str(&format!("; use {}", replacement.join(", ")));
+  }
+  Some(Diagnostic {
+    code: Some(NumberOrString::String(LEGACY_WAITCNT_CODE.to_string())),
+    ..diagnostic(token_range(line_idx, line, &parsed.mnemonic), DiagnosticSeverity::WARNING, message)
+  })
+}
+
+/// `null`/`off` placed where the instruction cannot take them.
+fn check_special_operands(
+  entry: &InstructionEntry,
+  parsed: &InstructionLine<'_>,
+  filter: Option<&str>,
+  line_idx: usize,
+  line: &str,
+  out: &mut Vec<Diagnostic>,
+) {
+  let operands: Vec<&str> = parsed.operands.iter().map(|operand| operand.text).collect();
+  for (index, operand) in parsed.operands.iter().enumerate() {
+    if let Some(message) = special_operand_issue(parsed.mnemonic.text, &entry.args, &operands, index, filter) {
+      out.push(diagnostic(token_range(line_idx, line, operand), DiagnosticSeverity::ERROR, message));
+    }
+  }
+}
+
+fn check_registers(parsed: &InstructionLine<'_>, filter: Option<&str>, line_idx: usize, line: &str, out: &mut Vec<Diagnostic>) {
+  for operand in &parsed.operands {
+    // `-v1` and `|v1|` are source modifiers around a register.
+    let register = match parse_register(operand.text.trim_start_matches('-').trim_matches('|')) {
+      Some(register) => register,
+      None => continue,
+    };
+    if let Some(message) = register_issue(&register, filter) {
+      out.push(diagnostic(token_range(line_idx, line, operand), DiagnosticSeverity::ERROR, message));
+    }
+  }
+}
+
+/// `op_sel`/`op_sel_hi` vectors that do not fit a VOP3P instruction's sources.
+fn check_op_sel(entry: &InstructionEntry, parsed: &InstructionLine<'_>, line_idx: usize, line: &str, out: &mut Vec<Diagnostic>) {
+  if !is_vop3p(entry) {
+    return;
+  }
+  for (token, message) in op_sel_issues(parsed) {
+    out.push(diagnostic(token_range(line_idx, line, &token), DiagnosticSeverity::ERROR, message));
+  }
+}
+
+/// Index names usable on the active architecture, used as "did you mean" candidates.
+fn suggestion_candidates<'a>(index: &'a HashMap<String, Vec<InstructionEntry>>, filter: Option<&str>) -> Vec<&'a str> {
+  index
+    .iter()
+    .filter(|(_, entries)| match filter {
+      Some(filter) => entries.iter().any(|entry| entry_matches_arch(entry, filter)),
+      None => true,
+    })
+    .map(|(name, _)| name.as_str())
+    .collect()
+}
+
+fn unknown_mnemonic_diagnostic(range: Range, mnemonic: &str, suggestions: &[&str]) -> Diagnostic {
+  let mut message = format!("Unknown instruction `{mnemonic}`");
+  if !suggestions.is_empty() {
+    message.push_str(&format!(". Did you mean: {}?", suggestions.join(", ")));
+  }
+  Diagnostic {
+    code: Some(NumberOrString::String(UNKNOWN_MNEMONIC_CODE.to_string())),
+    data: Some(serde_json::json!(suggestions)),
+    ..diagnostic(range, DiagnosticSeverity::WARNING, message)
+  }
+}
+
+/// Quick fixes replacing an unknown mnemonic with each suggestion its diagnostic carries, keeping
+/// the typed encoding suffix (`v_fmc_f32_e64` -> `v_fmac_f32_e64`); the nearest is preferred.
+pub fn mnemonic_quick_fixes(text: &str, uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeActionOrCommand> {
+  let lines: Vec<&str> = text.lines().collect();
+  let mut actions = Vec::new();
+  for diagnostic in diagnostics {
+    if diagnostic.code != Some(NumberOrString::String(UNKNOWN_MNEMONIC_CODE.to_string())) {
+      continue;
+    }
+    let suggestions: Vec<String> = match diagnostic.data.clone().map(serde_json::from_value) {
+      Some(Ok(suggestions)) => suggestions,
+      _ => continue,
+    };
+    let range = diagnostic.range;
+    let line = match lines.get(range.start.line as usize) {
+      Some(line) if range.start.line == range.end.line => line,
+      _ => continue,
+    };
+    let start = utf16_position_to_byte_offset(line, range.start);
+    let end = utf16_position_to_byte_offset(line, range.end);
+    let typed = match line.get(start..end) {
+      Some(typed) if !typed.is_empty() => typed,
+      _ => continue,
+    };
+    let split = split_encoding_variant(typed);
+    let suffix = typed.get(split.base.len()..).unwrap_or_default();
+    let uppercase = typed.bytes().all(|b| !b.is_ascii_lowercase());
+    for (rank, suggestion) in suggestions.iter().enumerate() {
+      let suggestion = if uppercase { suggestion.to_ascii_uppercase() } else { suggestion.clone() };
+      let replacement = format!("{suggestion}{suffix}");
+      actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Change to `{replacement}`"),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+          changes: Some(HashMap::from([(uri.clone(), vec![TextEdit::new(range, replacement)])])),
+          ..Default::default()
+        }),
+        is_preferred: Some(rank == 0),
+        ..Default::default()
+      }));
+    }
+  }
+  actions
+}
+
+/// A known mnemonic none of whose entries exists on the active architecture; `data` lists the
+/// architectures that have it.
+fn unavailable_instruction_diagnostic(
+  range: Range,
+  mnemonic: &str,
+  filter: &str,
+  entries: &[InstructionEntry],
+) -> Diagnostic {
+  let mut architectures: Vec<&str> = entries
+    .iter()
+    .flat_map(|entry| entry.architectures.iter().map(|arch| arch.as_str()))
+    .collect();
+  architectures.sort();
+  architectures.dedup();
+  let mut message = format!("`{mnemonic}` is not available on {filter}");
+  if !architectures.is_empty() {
+    message.push_str(&format!("; it exists on {}", architectures.join(", ")));
+  }
+  Diagnostic {
+    code: Some(NumberOrString::String(UNAVAILABLE_INSTRUCTION_CODE.to_string())),
+    data: Some(serde_json::json!(architectures)),
+    ..diagnostic(range, DiagnosticSeverity::WARNING, message)
+  }
+}
+
+fn is_plain_mnemonic(mnemonic: &str) -> bool {
+  mnemonic
+    .bytes()
+    .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'.')
+}
+
+pub fn document_diagnostics(
+  text: &str,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  modifiers: &[MemoryModifier],
+  errata: &[Erratum],
+  filter: Option<&str>,
+  dialect: Dialect,
+) -> Vec<Diagnostic> {
+  let wave_size = declared_wave_size(text);
+  let macros = collect_macro_names(text, dialect);
+  let conditionals = analyze_conditionals(text, &collect_symbol_definitions(text, dialect), dialect);
+  let mut candidates: Option<Vec<&str>> = None;
+  let mut out = Vec::new();
+  for issue in conditionals.issues {
+    let line = text.lines().nth(issue.line as usize).unwrap_or_default();
+    out.push(diagnostic(
+      byte_range(issue.line, line, issue.start, issue.end),
+      DiagnosticSeverity::ERROR,
+      issue.message,
+    ));
+  }
+  for issue in analyze_relocations(text, &conditionals.inactive_lines, dialect) {
+    let line = text.lines().nth(issue.line as usize).unwrap_or_default();
+    let severity = match issue.kind {
+      RelocationIssueKind::UnknownSpecifier => DiagnosticSeverity::ERROR,
+      RelocationIssueKind::Unpaired => DiagnosticSeverity::WARNING,
+    };
+    out.push(diagnostic(byte_range(issue.line, line, issue.start, issue.end), severity, issue.message));
+  }
+  let segment_issues = analyze_lds(text, dialect).issues.into_iter().chain(analyze_scratch(text, dialect).issues);
+  for issue in segment_issues {
+    if conditionals.inactive_lines.contains(&issue.line) {
+      continue;
+    }
+    let line = text.lines().nth(issue.line as usize).unwrap_or_default();
+    out.push(diagnostic(
+      byte_range(issue.line, line, issue.start, issue.end),
+      DiagnosticSeverity::WARNING,
+      issue.message,
+    ));
+  }
+  // Code in branches that will not be assembled is not validated.
+  let statements = text
+    .lines()
+    .enumerate()
+    .filter(|(line_idx, _)| !conditionals.inactive_lines.contains(&(*line_idx as u32)))
+    .flat_map(|(line_idx, line)| {
+      parse_instruction_statements(line, dialect).into_iter().map(move |parsed| (line_idx, line, parsed))
+    });
+  for (line_idx, line, parsed) in statements {
+    let split = split_encoding_variant(parsed.mnemonic.text);
+    let base = split.base.to_ascii_lowercase();
+    if !index.is_empty()
+      && !index.contains_key(&base)
+      && !macros.contains(&parsed.mnemonic.text.to_ascii_lowercase())
+      && is_plain_mnemonic(parsed.mnemonic.text)
+    {
+      let candidates = candidates.get_or_insert_with(|| suggestion_candidates(index, filter));
+      let suggestions = closest_names(&base, candidates.iter().copied(), MAX_SUGGESTIONS);
+      out.push(unknown_mnemonic_diagnostic(
+        token_range(line_idx, line, &parsed.mnemonic),
+        parsed.mnemonic.text,
+        &suggestions,
+      ));
+      continue;
+    }
+    if let Some(filter) = filter.filter(|filter| filter.starts_with("rdna4"))
+      && let Some(diagnostic) = legacy_waitcnt_diagnostic(&parsed, filter, line_idx, line)
+    {
+      out.push(diagnostic);
+      continue;
+    }
+    let entry = match find_entry(index, &base, filter) {
+      Some(entry) => entry,
+      None => {
+        if let (Some(filter), Some(entries)) = (filter, index.get(&base)) {
+          out.push(unavailable_instruction_diagnostic(
+            token_range(line_idx, line, &parsed.mnemonic),
+            parsed.mnemonic.text,
+            filter,
+            entries,
+          ));
+        }
+        continue;
+      }
+    };
+    check_notes(entry, &parsed, wave_size, line_idx, line, &mut out);
+    check_special_operands(&entry_for_variant(entry, &split.variant), &parsed, filter, line_idx, line, &mut out);
+    check_registers(&parsed, filter, line_idx, line, &mut out);
+    check_op_sel(entry, &parsed, line_idx, line, &mut out);
+    for (token, message) in setreg_issues(&parsed) {
+      out.push(diagnostic(token_range(line_idx, line, &token), DiagnosticSeverity::WARNING, message));
+    }
+    for (token, message) in literal_issues(entry, &parsed, filter) {
+      out.push(diagnostic(token_range(line_idx, line, &token), DiagnosticSeverity::ERROR, message));
+    }
+    for (token, message) in smem_offset_issues(&parsed, filter) {
+      out.push(diagnostic(token_range(line_idx, line, &token), DiagnosticSeverity::ERROR, message));
+    }
+    if let Some(filter) = filter {
+      check_memory_modifiers(&parsed, modifiers, filter, line_idx, line, dialect, &mut out);
+      for erratum in errata_for(errata, &base, Some(filter)) {
+        out.push(Diagnostic {
+          code: Some(NumberOrString::String(ERRATUM_CODE.to_string())),
+          ..diagnostic(
+            token_range(line_idx, line, &parsed.mnemonic),
+            DiagnosticSeverity::INFORMATION,
+            format!("Erratum on {filter}: {}", erratum.note),
+          )
+        });
+      }
+    }
+  }
+  out
+}
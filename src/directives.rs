@@ -0,0 +1,319 @@
+/// An assembler directive documented on hover.
+pub struct Directive {
+  pub name: &'static str,
+  /// Operands as written after the name; empty when it takes none.
+  pub syntax: &'static str,
+  pub description: &'static str,
+}
+
+const fn directive(name: &'static str, syntax: &'static str, description: &'static str) -> Directive {
+  Directive {
+    name,
+    syntax,
+    description,
+  }
+}
+
+/// The directives LLVM's AMDGPU assembler and compiler output use most, with the generic ELF ones
+/// that appear in the same files.
+const DIRECTIVES: &[Directive] = &[
+  // Target and code object.
+  directive(
+    ".amdgcn_target",
+    "\"<triple>-<gpu>[:<features>]\"",
+    "Target the code object is assembled for, such as `\"amdgcn-amd-amdhsa--gfx1100\"`. Must match the `-mcpu` the \
+     assembler runs with, including `xnack`/`sramecc` features.",
+  ),
+  directive(
+    ".amdhsa_code_object_version",
+    "<version>",
+    "Code object version (4, 5, 6, ...) the metadata and kernel descriptors follow.",
+  ),
+  directive(
+    ".amdgpu_metadata",
+    "",
+    "Starts the YAML code object metadata (`amdhsa.kernels`, `amdhsa.version`, ...), ended by \
+     `.end_amdgpu_metadata`.",
+  ),
+  directive(".end_amdgpu_metadata", "", "Ends the block started by `.amdgpu_metadata`."),
+  directive(".amdgpu_hsa_kernel", "<symbol>", "Marks a symbol as an HSA kernel entry point (code object v2/v3 style)."),
+  directive(
+    ".amdgpu_lds",
+    "<symbol>, <size>[, <align>]",
+    "Declares an LDS (group segment) variable of `size` bytes, allocated by the linker with the given alignment.",
+  ),
+  directive(
+    ".amd_kernel_code_t",
+    "",
+    "Starts a legacy (code object v2) kernel descriptor made of `key = value` lines, ended by \
+     `.end_amd_kernel_code_t`.",
+  ),
+  directive(".end_amd_kernel_code_t", "", "Ends the block started by `.amd_kernel_code_t`."),
+  // Kernel descriptor.
+  directive(
+    ".amdhsa_kernel",
+    "<name>",
+    "Starts the kernel descriptor for the kernel `name`, ended by `.end_amdhsa_kernel`. The `.amdhsa_*` directives \
+     inside fill in its fields; the assembler emits it as `<name>.kd` in `.rodata`.",
+  ),
+  directive(
+    ".end_amdhsa_kernel",
+    "",
+    "Ends the block started by `.amdhsa_kernel` and emits the 64-byte kernel descriptor.",
+  ),
+  directive(
+    ".amdhsa_group_segment_fixed_size",
+    "<bytes>",
+    "Statically allocated LDS per workgroup, in bytes. Dynamic LDS requested at launch is added to it.",
+  ),
+  directive(
+    ".amdhsa_private_segment_fixed_size",
+    "<bytes>",
+    "Scratch (private segment) per work-item, in bytes, excluding a dynamically sized stack.",
+  ),
+  directive(
+    ".amdhsa_kernarg_size",
+    "<bytes>",
+    "Size of the kernel argument segment, in bytes, including hidden arguments.",
+  ),
+  directive(
+    ".amdhsa_user_sgpr_count",
+    "<count>",
+    "Total user SGPRs set up before the wave starts. Defaults to the sum of the enabled `.amdhsa_user_sgpr_*` \
+     inputs.",
+  ),
+  directive(
+    ".amdhsa_user_sgpr_private_segment_buffer",
+    "0|1",
+    "Loads the 128-bit scratch buffer resource into the first four user SGPRs (GFX9 and earlier, CDNA).",
+  ),
+  directive(
+    ".amdhsa_user_sgpr_dispatch_ptr",
+    "0|1",
+    "Loads the 64-bit address of the AQL dispatch packet into two user SGPRs.",
+  ),
+  directive(".amdhsa_user_sgpr_queue_ptr", "0|1", "Loads the 64-bit address of the HSA queue into two user SGPRs."),
+  directive(
+    ".amdhsa_user_sgpr_kernarg_segment_ptr",
+    "0|1",
+    "Loads the 64-bit address of the kernel arguments into two user SGPRs.",
+  ),
+  directive(".amdhsa_user_sgpr_dispatch_id", "0|1", "Loads the 64-bit dispatch id into two user SGPRs."),
+  directive(
+    ".amdhsa_user_sgpr_flat_scratch_init",
+    "0|1",
+    "Loads the flat scratch base and size into two user SGPRs, for kernels that initialize `flat_scratch` \
+     themselves.",
+  ),
+  directive(
+    ".amdhsa_user_sgpr_private_segment_size",
+    "0|1",
+    "Loads the per-work-item scratch size into one user SGPR.",
+  ),
+  directive(
+    ".amdhsa_user_sgpr_kernarg_preload_length",
+    "<dwords>",
+    "Dwords of kernel arguments preloaded into user SGPRs after the other user inputs (GFX90A+).",
+  ),
+  directive(
+    ".amdhsa_user_sgpr_kernarg_preload_offset",
+    "<dwords>",
+    "Offset into the kernel arguments, in dwords, where SGPR preloading starts.",
+  ),
+  directive(".amdhsa_wavefront_size32", "0|1", "Runs the kernel in wave32 (1) or wave64 (0) mode. RDNA only."),
+  directive(
+    ".amdhsa_uses_dynamic_stack",
+    "0|1",
+    "The kernel's scratch use is not known statically, so the runtime sizes the stack at launch.",
+  ),
+  directive(
+    ".amdhsa_enable_private_segment",
+    "0|1",
+    "Enables scratch for the kernel (GFX10+); the wave offset is then set up in an SGPR.",
+  ),
+  directive(
+    ".amdhsa_system_sgpr_private_segment_wavefront_offset",
+    "0|1",
+    "Passes the wave's scratch offset in a system SGPR (GFX9 and earlier, CDNA).",
+  ),
+  directive(
+    ".amdhsa_system_sgpr_workgroup_id_x",
+    "0|1",
+    "Passes the workgroup id in X in a system SGPR after the user SGPRs. Enabled by default.",
+  ),
+  directive(
+    ".amdhsa_system_sgpr_workgroup_id_y",
+    "0|1",
+    "Passes the workgroup id in Y in a system SGPR after the user SGPRs.",
+  ),
+  directive(
+    ".amdhsa_system_sgpr_workgroup_id_z",
+    "0|1",
+    "Passes the workgroup id in Z in a system SGPR after the user SGPRs.",
+  ),
+  directive(".amdhsa_system_sgpr_workgroup_info", "0|1", "Passes workgroup information in a system SGPR."),
+  directive(
+    ".amdhsa_system_vgpr_workitem_id",
+    "0|1|2",
+    "Work-item ids set up in VGPRs: 0 for X only, 1 for X and Y, 2 for X, Y and Z (in v0–v2, or packed into v0 on \
+     GFX90A+ and RDNA3+).",
+  ),
+  directive(
+    ".amdhsa_next_free_vgpr",
+    "<count>",
+    "One past the highest VGPR the kernel uses; the VGPR allocation, and so occupancy, is derived from it.",
+  ),
+  directive(
+    ".amdhsa_next_free_sgpr",
+    "<count>",
+    "One past the highest SGPR the kernel uses, excluding VCC, FLAT_SCRATCH and XNACK_MASK, which are added per the \
+     `.amdhsa_reserve_*` settings.",
+  ),
+  directive(
+    ".amdhsa_accum_offset",
+    "<offset>",
+    "Index of the first AGPR in the unified register file, a multiple of 4 (GFX90A+ / CDNA2+). VGPRs are allocated \
+     below it and AGPRs from it.",
+  ),
+  directive(".amdhsa_reserve_vcc", "0|1", "Reserves SGPRs for VCC in the allocation. Enabled by default."),
+  directive(
+    ".amdhsa_reserve_flat_scratch",
+    "0|1",
+    "Reserves SGPRs for FLAT_SCRATCH in the allocation (GFX9 and earlier).",
+  ),
+  directive(".amdhsa_reserve_xnack_mask", "0|1", "Reserves SGPRs for XNACK_MASK in the allocation (GFX9 and earlier)."),
+  directive(
+    ".amdhsa_float_round_mode_32",
+    "<mode>",
+    "Initial f32 rounding mode: 0 nearest even, 1 toward +inf, 2 toward -inf, 3 toward zero.",
+  ),
+  directive(
+    ".amdhsa_float_round_mode_16_64",
+    "<mode>",
+    "Initial f16/f64 rounding mode: 0 nearest even, 1 toward +inf, 2 toward -inf, 3 toward zero.",
+  ),
+  directive(
+    ".amdhsa_float_denorm_mode_32",
+    "<mode>",
+    "Initial f32 denormal handling: 0 flush inputs and outputs, 1 flush outputs, 2 flush inputs, 3 keep denormals.",
+  ),
+  directive(
+    ".amdhsa_float_denorm_mode_16_64",
+    "<mode>",
+    "Initial f16/f64 denormal handling: 0 flush inputs and outputs, 1 flush outputs, 2 flush inputs, 3 keep \
+     denormals (the default).",
+  ),
+  directive(".amdhsa_dx10_clamp", "0|1", "Clamps NaN to 0 in VALU clamp operations. Not available on GFX12."),
+  directive(
+    ".amdhsa_ieee_mode",
+    "0|1",
+    "IEEE-conformant NaN handling for min/max and signaling NaNs. Not available on GFX12.",
+  ),
+  directive(".amdhsa_fp16_overflow", "0|1", "f16 overflow saturates to max-float instead of infinity (GFX9+)."),
+  directive(".amdhsa_tg_split", "0|1", "Lets the waves of a workgroup be split across CUs (GFX90A+)."),
+  directive(
+    ".amdhsa_workgroup_processor_mode",
+    "0|1",
+    "WGP mode (1) schedules a workgroup across both CUs of a WGP; CU mode (0) keeps it on one (RDNA).",
+  ),
+  directive(".amdhsa_memory_ordered", "0|1", "Returns memory operations in order (RDNA)."),
+  directive(".amdhsa_forward_progress", "0|1", "Guarantees forward progress between the waves of a workgroup (RDNA)."),
+  directive(
+    ".amdhsa_shared_vgpr_count",
+    "<count>",
+    "VGPRs shared between the two waves of a wave64 pair on RDNA1/2 in wave64 mode.",
+  ),
+  directive(".amdhsa_round_robin_scheduling", "0|1", "Round-robin rather than oldest-first wave scheduling (GFX12)."),
+  directive(".amdhsa_exception_fp_ieee_invalid_op", "0|1", "Traps on IEEE invalid-operation exceptions."),
+  directive(".amdhsa_exception_fp_denorm_src", "0|1", "Traps on denormal source operands."),
+  directive(".amdhsa_exception_fp_ieee_div_zero", "0|1", "Traps on IEEE division by zero."),
+  directive(".amdhsa_exception_fp_ieee_overflow", "0|1", "Traps on IEEE overflow."),
+  directive(".amdhsa_exception_fp_ieee_underflow", "0|1", "Traps on IEEE underflow."),
+  directive(".amdhsa_exception_fp_ieee_inexact", "0|1", "Traps on IEEE inexact results."),
+  directive(".amdhsa_exception_int_div_zero", "0|1", "Traps on integer division by zero."),
+  // Sections and symbols.
+  directive(".text", "", "Switches to the code section."),
+  directive(".data", "", "Switches to the data section."),
+  directive(".rodata", "", "Switches to the read-only data section, where kernel descriptors live."),
+  directive(
+    ".section",
+    "<name>[, \"<flags>\"[, @<type>]]",
+    "Switches to the named section, creating it with the given flags and type.",
+  ),
+  directive(".globl", "<symbol>", "Makes a symbol visible outside the object, as kernels must be."),
+  directive(".global", "<symbol>", "Makes a symbol visible outside the object, as kernels must be."),
+  directive(".local", "<symbol>", "Keeps a symbol local to the object."),
+  directive(".weak", "<symbol>", "Makes a symbol global but overridable by a strong definition."),
+  directive(
+    ".hidden",
+    "<symbol>",
+    "Gives a symbol hidden visibility: global within the linked code object, not exported from it.",
+  ),
+  directive(
+    ".protected",
+    "<symbol>",
+    "Gives a symbol protected visibility: exported but not preemptible. Kernels are protected.",
+  ),
+  directive(".type", "<symbol>, @function|@object", "Sets a symbol's ELF type; kernels and functions are `@function`."),
+  directive(".size", "<symbol>, <expression>", "Sets a symbol's size, usually `.Lfunc_end0-name`."),
+  directive(".set", "<symbol>, <expression>", "Defines (or redefines) a symbol's value, as in `.set .Lnum_vgprs, 32`."),
+  directive(".equ", "<symbol>, <expression>", "Same as `.set`."),
+  directive(".equiv", "<symbol>, <expression>", "Like `.set`, but an error if the symbol is already defined."),
+  directive(".comm", "<symbol>, <size>[, <align>]", "Declares a common symbol of `size` bytes."),
+  directive(".ident", "\"<string>\"", "Records the producer (compiler version) in the `.comment` section."),
+  // Alignment and data.
+  directive(
+    ".p2align",
+    "<log2>[, <fill>[, <max>]]",
+    "Pads to a `2^log2`-byte boundary. In code sections the padding is `s_nop` instructions.",
+  ),
+  directive(
+    ".align",
+    "<bytes>[, <fill>[, <max>]]",
+    "Pads to a multiple of `bytes` (a byte count on AMDGPU, like `.balign`).",
+  ),
+  directive(".balign", "<bytes>[, <fill>[, <max>]]", "Pads to a multiple of `bytes`."),
+  directive(".byte", "<value>[, ...]", "Emits 8-bit values."),
+  directive(".short", "<value>[, ...]", "Emits 16-bit values."),
+  directive(".long", "<value>[, ...]", "Emits 32-bit values."),
+  directive(".int", "<value>[, ...]", "Emits 32-bit values."),
+  directive(".quad", "<value>[, ...]", "Emits 64-bit values."),
+  directive(".zero", "<bytes>", "Emits `bytes` zero bytes."),
+  directive(".fill", "<count>[, <size>[, <value>]]", "Emits `count` copies of a `size`-byte value."),
+  directive(".ascii", "\"<string>\"", "Emits a string without a terminating NUL."),
+  directive(".asciz", "\"<string>\"", "Emits a NUL-terminated string."),
+  // Macros and conditionals.
+  directive(
+    ".macro",
+    "<name> [<parameter>[=<default>], ...]",
+    "Starts a macro definition, ended by `.endm`. Parameters are referenced as `\\parameter`.",
+  ),
+  directive(".endm", "", "Ends a `.macro` definition."),
+  directive(".rept", "<count>", "Repeats the lines up to `.endr` `count` times."),
+  directive(
+    ".irp",
+    "<symbol>, <value>[, ...]",
+    "Repeats the lines up to `.endr` once per value, substituting `\\symbol`.",
+  ),
+  directive(".endr", "", "Ends a `.rept` or `.irp` block."),
+  directive(".if", "<expression>", "Assembles the following lines only if the expression is non-zero."),
+  directive(".ifdef", "<symbol>", "Assembles the following lines only if the symbol is defined."),
+  directive(".ifndef", "<symbol>", "Assembles the following lines only if the symbol is not defined."),
+  directive(".elseif", "<expression>", "Alternative branch of an `.if` block."),
+  directive(".else", "", "Alternative branch of an `.if` block."),
+  directive(".endif", "", "Ends an `.if` block."),
+  directive(".include", "\"<file>\"", "Assembles another file in place."),
+  directive(".error", "\"<message>\"", "Stops assembly with an error."),
+  directive(".warning", "\"<message>\"", "Reports a warning."),
+];
+
+/// Documentation of a directive, matched case-insensitively with its leading dot.
+pub fn find_directive(name: &str) -> Option<&'static Directive> {
+  DIRECTIVES.iter().find(|directive| directive.name.eq_ignore_ascii_case(name))
+}
+
+/// Directives whose names start with `prefix` (leading dot included), for completion.
+pub fn directives_with_prefix(prefix: &str) -> impl Iterator<Item = &'static Directive> + '_ {
+  let prefix = prefix.to_ascii_lowercase();
+  DIRECTIVES.iter().filter(move |directive| directive.name.starts_with(&prefix))
+}
@@ -0,0 +1,64 @@
+use crate::architecture::{normalize_architecture_hint, ArchitectureAliases};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TARGET_FILE: &str = ".target";
+const COMPILE_COMMANDS: &str = "compile_commands.json";
+/// Where CMake/Meson usually leave `compile_commands.json` relative to the workspace root.
+const COMPILE_COMMANDS_DIRS: &[&str] = &["", "build", "out", "cmake-build-debug", "cmake-build-release"];
+const TARGET_FLAGS: &[&str] = &["--offload-arch=", "--amdgpu-target=", "--cuda-gpu-arch=", "-mcpu="];
+
+/// Architecture named by a `.target` file: the first non-comment line, either a `gfx` processor or
+/// an architecture name such as `rdna3.5`.
+fn target_file_architecture(path: &Path, aliases: &ArchitectureAliases) -> Option<String> {
+  let text = fs::read_to_string(path).ok()?;
+  let line = text
+    .lines()
+    .map(str::trim)
+    .find(|line| !line.is_empty() && !line.starts_with('#'))?;
+  let arch = normalize_architecture_hint(line, aliases);
+  (arch.starts_with("rdna") || arch.starts_with("cdna")).then_some(arch)
+}
+
+/// Most common GPU target passed to the compiler across `compile_commands.json` entries.
+fn compile_commands_architecture(path: &Path, aliases: &ArchitectureAliases) -> Option<String> {
+  let text = fs::read_to_string(path).ok()?;
+  let mut counts: HashMap<&str, usize> = HashMap::new();
+  for flag in TARGET_FLAGS {
+    for (idx, _) in text.match_indices(flag) {
+      let rest = &text[idx + flag.len()..];
+      let end = rest
+        .find(|ch: char| !(ch.is_ascii_alphanumeric() || matches!(ch, ':' | '+' | '-' | ',')))
+        .unwrap_or(rest.len());
+      // `--offload-arch=gfx90a,gfx942` lists several targets.
+      for target in rest[..end].split(',') {
+        if let Some(arch) = aliases.resolve(target) {
+          *counts.entry(arch).or_default() += 1;
+        }
+      }
+    }
+  }
+  counts
+    .into_iter()
+    .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+    .map(|(arch, _)| arch.to_string())
+}
+
+/// Looks for a `.target` file or a `compile_commands.json` in each workspace root and returns the
+/// architecture it implies together with the file it came from.
+pub fn discover_target_architecture(roots: &[PathBuf], aliases: &ArchitectureAliases) -> Option<(String, PathBuf)> {
+  for root in roots {
+    let target_file = root.join(TARGET_FILE);
+    if let Some(arch) = target_file_architecture(&target_file, aliases) {
+      return Some((arch, target_file));
+    }
+    for dir in COMPILE_COMMANDS_DIRS {
+      let path = root.join(dir).join(COMPILE_COMMANDS);
+      if let Some(arch) = compile_commands_architecture(&path, aliases) {
+        return Some((arch, path));
+      }
+    }
+  }
+  None
+}
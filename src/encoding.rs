@@ -1,4 +1,5 @@
-use crate::types::{EncodingVariant, SplitInstruction};
+use crate::types::{EncodingVariant, InstructionEntry, SplitInstruction};
+use std::borrow::Cow;
 
 pub fn split_encoding_variant(mnemonic: &str) -> SplitInstruction {
   // Order matters: check longer suffixes first to avoid partial matches
@@ -98,6 +99,33 @@ pub fn get_encoding_description(encoding_name: &str) -> Option<&'static str> {
   }
 }
 
+/// How the encoding an `_e32`/`_e64`/`_dpp`/`_sdwa` suffix forces differs from the base form,
+/// which leaves the choice to the assembler.
+pub fn variant_difference(variant: &EncodingVariant) -> Option<&'static str> {
+  match variant {
+    EncodingVariant::Native => None,
+    EncodingVariant::E32 => Some(
+      "Forces the 32-bit VOP1/VOP2/VOPC form: no source modifiers, clamp or omod, src1 must be a VGPR, and VOPC \
+       writes `vcc` implicitly.",
+    ),
+    EncodingVariant::E64 => Some(
+      "Forces the 64-bit VOP3 form even when the operands fit 32 bits: adds `abs`/`neg`, `clamp` and `omod`, lets \
+       every source be an SGPR or constant, and gives VOPC an explicit SGPR destination.",
+    ),
+    EncodingVariant::Dpp => Some(
+      "Adds a DPP dword: src0 is read from another lane (`quad_perm`, `row_shl`, `row_mirror`, `dpp8`, ...) under \
+       `row_mask`/`bank_mask`, with `bound_ctrl` or `fi` deciding what out-of-range lanes read.",
+    ),
+    EncodingVariant::Sdwa => Some(
+      "Adds an SDWA dword selecting a byte or word of each source (`src0_sel`, `src1_sel`) and of the destination \
+       (`dst_sel`, with `dst_unused` for the other bits).",
+    ),
+    EncodingVariant::E64Dpp => Some(
+      "VOP3 with a DPP dword: the VOP3 modifiers together with DPP's cross-lane read of src0.",
+    ),
+  }
+}
+
 pub fn find_matching_encoding(available_encodings: &[String], variant: &EncodingVariant) -> Option<String> {
   // Map LLVM suffix variants to potential encoding name patterns
   match variant {
@@ -145,3 +173,52 @@ pub fn find_matching_encoding(available_encodings: &[String], variant: &Encoding
     }
   }
 }
+
+/// The entry with its operand list replaced by the one of the encoding the typed suffix selects
+/// (`v_add_f32_e64` -> VOP3). VOP3 forms of float ops also get their `clamp`/`omod` slots.
+pub fn entry_for_variant<'a>(entry: &'a InstructionEntry, variant: &EncodingVariant) -> Cow<'a, InstructionEntry> {
+  if *variant == EncodingVariant::Native {
+    return Cow::Borrowed(entry);
+  }
+  let encoding_name = match find_matching_encoding(&entry.available_encodings, variant) {
+    Some(encoding_name) => encoding_name,
+    None => return Cow::Borrowed(entry),
+  };
+  let operands = entry
+    .encoding_operands
+    .iter()
+    .find(|operands| operands.encoding == encoding_name);
+  let is_vop3 = encoding_name.contains("VOP3") && !encoding_name.contains("VOP3P");
+  if operands.is_none() && !is_vop3 {
+    return Cow::Borrowed(entry);
+  }
+  let mut selected = entry.clone();
+  if let Some(operands) = operands {
+    selected.args = operands.args.clone();
+    selected.arg_types = operands.arg_types.clone();
+    selected.arg_data_types = operands.arg_data_types.clone();
+    selected.arg_sizes = operands.arg_sizes.clone();
+  }
+  let is_float = selected
+    .arg_data_types
+    .iter()
+    .map(|data_type| data_type.to_ascii_lowercase())
+    .any(|data_type| {
+      let data_type = data_type.trim_start_matches("fmt_num_");
+      data_type.starts_with('f') || data_type.starts_with("bf")
+    });
+  if is_vop3 && is_float {
+    for modifier in ["clamp", "omod"] {
+      if selected.args.iter().any(|arg| arg.eq_ignore_ascii_case(modifier)) {
+        continue;
+      }
+      selected.args.push(modifier.to_string());
+      selected.arg_types.push("modifier".to_string());
+      selected.arg_data_types.push(String::new());
+      if !selected.arg_sizes.is_empty() {
+        selected.arg_sizes.push(32);
+      }
+    }
+  }
+  Cow::Owned(selected)
+}
@@ -0,0 +1,48 @@
+use crate::architecture::arch_matches;
+use serde::Deserialize;
+
+/// A known hardware bug or required software workaround, from the data file's `errata` table.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Erratum {
+  /// Lowercase mnemonics the erratum affects.
+  pub instructions: Vec<String>,
+  pub architectures: Vec<String>,
+  pub note: String,
+}
+
+impl Erratum {
+  pub fn affects(&self, mnemonic: &str) -> bool {
+    self.instructions.iter().any(|name| name.eq_ignore_ascii_case(mnemonic))
+  }
+
+  /// Whether the erratum applies on the architecture; with no filter every architecture counts.
+  pub fn applies_on(&self, filter: Option<&str>) -> bool {
+    match filter {
+      Some(filter) => self.architectures.iter().any(|arch| arch_matches(arch, filter)),
+      None => true,
+    }
+  }
+}
+
+/// Errata for the (suffix-free) mnemonic on the architecture.
+pub fn errata_for<'a>(
+  table: &'a [Erratum],
+  mnemonic: &'a str,
+  filter: Option<&'a str>,
+) -> impl Iterator<Item = &'a Erratum> {
+  table
+    .iter()
+    .filter(move |erratum| erratum.affects(mnemonic) && erratum.applies_on(filter))
+}
+
+/// Hover banner for the errata, naming their architectures.
+pub fn errata_banner(errata: &[&Erratum]) -> Option<String> {
+  if errata.is_empty() {
+    return None;
+  }
+  let lines: Vec<String> = errata
+    .iter()
+    .map(|erratum| format!("⚠️ **Erratum** ({}): {}", erratum.architectures.join(", "), erratum.note))
+    .collect();
+  Some(lines.join("\n\n"))
+}
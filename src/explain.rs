@@ -0,0 +1,164 @@
+use crate::encoding::{entry_for_variant, find_matching_encoding, get_encoding_description, split_encoding_variant};
+use crate::expression::parse_integer_literal;
+use crate::index::find_entry;
+use crate::registers::{parse_register, RegisterClass};
+use crate::symbols::{evaluate_with_symbols, SymbolDefinition};
+use crate::tokenizer::InstructionLine;
+use crate::types::{InstructionEntry, SpecialRegister};
+use serde::Serialize;
+use std::collections::HashMap;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+/// Result of `amdgpu.explainLine`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LineExplanation {
+  pub line: u32,
+  pub text: String,
+  /// `None` when the line holds no instruction, or one the index does not know on the active
+  /// architecture.
+  pub instruction: Option<ExplainedInstruction>,
+  pub operands: Vec<ExplainedOperand>,
+  pub modifiers: Vec<String>,
+  pub diagnostics: Vec<ExplainedDiagnostic>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedInstruction {
+  /// Mnemonic as written, with any encoding suffix.
+  pub mnemonic: String,
+  pub name: String,
+  pub architectures: Vec<String>,
+  pub encoding: Option<String>,
+  pub encoding_description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedOperand {
+  pub text: String,
+  /// Operand field the operand fills (`VDST`, `SRC0`, ...), or `None` past the last slot.
+  pub slot: Option<String>,
+  pub slot_type: Option<String>,
+  pub data_type: Option<String>,
+  /// `vgpr`, `sgpr`, `agpr`, `ttmp`, `special_register`, `constant`, `expression` or `unknown`.
+  pub kind: &'static str,
+  /// Registers spanned, for register operands.
+  pub registers: Option<u32>,
+  /// Value of constants and of expressions whose symbols resolve.
+  pub value: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedDiagnostic {
+  pub severity: &'static str,
+  pub message: String,
+}
+
+fn severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+  match severity {
+    Some(DiagnosticSeverity::ERROR) => "error",
+    Some(DiagnosticSeverity::WARNING) => "warning",
+    Some(DiagnosticSeverity::HINT) => "hint",
+    _ => "information",
+  }
+}
+
+fn non_empty(values: &[String], index: usize) -> Option<String> {
+  values.get(index).filter(|value| !value.is_empty()).cloned()
+}
+
+fn explain_operand(
+  entry: Option<&InstructionEntry>,
+  index: usize,
+  text: &str,
+  special_registers: &[SpecialRegister],
+  definitions: &[SymbolDefinition],
+  line: u32,
+) -> ExplainedOperand {
+  // `-v1` and `|v1|` are source modifiers around a register.
+  let bare = text.trim_start_matches('-').trim_matches('|');
+  let register = parse_register(bare);
+  let kind = match register.map(|register| register.class) {
+    Some(RegisterClass::Vector) => "vgpr",
+    Some(RegisterClass::Scalar) => "sgpr",
+    Some(RegisterClass::Accumulator) => "agpr",
+    Some(RegisterClass::TrapTemp) => "ttmp",
+    None if special_registers.iter().any(|register| register.name.eq_ignore_ascii_case(bare)) => "special_register",
+    None => "unknown",
+  };
+  let value = register.is_none().then(|| evaluate_with_symbols(definitions, text, line)).flatten();
+  let is_literal = parse_integer_literal(text.trim_start_matches('-')).is_some() || text.parse::<f64>().is_ok();
+  let kind = match (kind, value) {
+    ("unknown", _) if is_literal => "constant",
+    ("unknown", Some(_)) => "expression",
+    (kind, _) => kind,
+  };
+  ExplainedOperand {
+    text: text.to_string(),
+    slot: entry.and_then(|entry| non_empty(&entry.args, index)),
+    slot_type: entry.and_then(|entry| non_empty(&entry.arg_types, index)),
+    data_type: entry.and_then(|entry| non_empty(&entry.arg_data_types, index)),
+    kind,
+    registers: register.map(|register| register.dwords()),
+    value,
+  }
+}
+
+/// Explains `parsed`, an instruction on `line`: the entry it resolves to on the active architecture, the
+/// encoding its suffix selects, what each operand is, and the line's diagnostics.
+#[allow(clippy::too_many_arguments)]
+pub fn explain_line(
+  line_idx: u32,
+  line: &str,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  filter: Option<&str>,
+  special_registers: &[SpecialRegister],
+  definitions: &[SymbolDefinition],
+  diagnostics: &[Diagnostic],
+  parsed: Option<InstructionLine<'_>>,
+) -> LineExplanation {
+  let diagnostics = diagnostics
+    .iter()
+    .filter(|diagnostic| diagnostic.range.start.line == line_idx)
+    .map(|diagnostic| ExplainedDiagnostic {
+      severity: severity_name(diagnostic.severity),
+      message: diagnostic.message.clone(),
+    })
+    .collect();
+  let mut explanation = LineExplanation {
+    line: line_idx,
+    text: line.to_string(),
+    instruction: None,
+    operands: Vec::new(),
+    modifiers: Vec::new(),
+    diagnostics,
+  };
+  let parsed = match parsed {
+    Some(parsed) => parsed,
+    None => return explanation,
+  };
+  let split = split_encoding_variant(parsed.mnemonic.text);
+  let entry = find_entry(index, &split.base, filter).map(|entry| entry_for_variant(entry, &split.variant));
+  if let Some(entry) = &entry {
+    let encoding = find_matching_encoding(&entry.available_encodings, &split.variant);
+    explanation.instruction = Some(ExplainedInstruction {
+      mnemonic: parsed.mnemonic.text.to_string(),
+      name: entry.name.clone(),
+      architectures: entry.architectures.clone(),
+      encoding_description: encoding
+        .as_deref()
+        .and_then(get_encoding_description)
+        .map(|description| description.to_string()),
+      encoding,
+    });
+  }
+  explanation.operands = parsed
+    .operands
+    .iter()
+    .enumerate()
+    .map(|(operand_index, operand)| {
+      explain_operand(entry.as_deref(), operand_index, operand.text, special_registers, definitions, line_idx)
+    })
+    .collect();
+  explanation.modifiers = parsed.modifiers.iter().map(|modifier| modifier.text.to_string()).collect();
+  explanation
+}
@@ -0,0 +1,170 @@
+use crate::tokenizer::{is_label_char, is_label_start};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExprToken<'a> {
+  Number(i64),
+  Symbol(&'a str),
+  Op(&'static str),
+  Open,
+  Close,
+}
+
+/// Integer literal in assembler syntax: decimal, `0x` hex, `0b` binary or `0o` octal.
+pub fn parse_integer_literal(text: &str) -> Option<i64> {
+  let lower = text.to_ascii_lowercase();
+  let (digits, radix) = if let Some(hex) = lower.strip_prefix("0x") {
+    (hex, 16)
+  } else if let Some(bin) = lower.strip_prefix("0b") {
+    (bin, 2)
+  } else if let Some(oct) = lower.strip_prefix("0o") {
+    (oct, 8)
+  } else {
+    (lower.as_str(), 10)
+  };
+  if digits.is_empty() {
+    return None;
+  }
+  u64::from_str_radix(digits, radix).ok().map(|value| value as i64)
+}
+
+// Longer operators first so `<<` is not read as `<`.
+const OPERATORS: &[&str] = &[
+  "<<", ">>", "<=", ">=", "==", "!=", "<>", "&&", "||", "<", ">", "+", "-", "*", "/", "%", "&", "|", "^", "~", "!",
+];
+
+fn tokenize(text: &str) -> Option<Vec<ExprToken<'_>>> {
+  let bytes = text.as_bytes();
+  let mut tokens = Vec::new();
+  let mut idx = 0;
+  while idx < bytes.len() {
+    let byte = bytes[idx];
+    if byte.is_ascii_whitespace() {
+      idx += 1;
+    } else if byte == b'(' {
+      tokens.push(ExprToken::Open);
+      idx += 1;
+    } else if byte == b')' {
+      tokens.push(ExprToken::Close);
+      idx += 1;
+    } else if byte.is_ascii_digit() {
+      let start = idx;
+      while idx < bytes.len() && bytes[idx].is_ascii_alphanumeric() {
+        idx += 1;
+      }
+      tokens.push(ExprToken::Number(parse_integer_literal(&text[start..idx])?));
+    } else if is_label_start(byte) {
+      let start = idx;
+      while idx < bytes.len() && is_label_char(bytes[idx]) {
+        idx += 1;
+      }
+      tokens.push(ExprToken::Symbol(&text[start..idx]));
+    } else {
+      let op = OPERATORS.iter().find(|op| text[idx..].starts_with(**op))?;
+      tokens.push(ExprToken::Op(op));
+      idx += op.len();
+    }
+  }
+  Some(tokens)
+}
+
+/// Binding strength of a binary operator, from GNU as's four levels: `* / % << >>` bind tightest,
+/// then `| & ^ !`, then `+ -` and the comparisons, then `&& ||`.
+fn precedence(op: &str) -> u8 {
+  match op {
+    "&&" | "||" => 1,
+    "+" | "-" | "==" | "!=" | "<>" | "<" | ">" | "<=" | ">=" => 2,
+    "|" | "&" | "^" => 3,
+    _ => 4,
+  }
+}
+
+/// GNU as represents a true comparison as -1.
+fn truth(value: bool) -> i64 {
+  if value { -1 } else { 0 }
+}
+
+struct Parser<'a, 'r> {
+  tokens: Vec<ExprToken<'a>>,
+  pos: usize,
+  resolve: &'r dyn Fn(&str) -> Option<i64>,
+}
+
+impl Parser<'_, '_> {
+  fn peek(&self) -> Option<&ExprToken<'_>> {
+    self.tokens.get(self.pos)
+  }
+
+  fn primary(&mut self) -> Option<i64> {
+    let token = self.tokens.get(self.pos)?.clone();
+    self.pos += 1;
+    match token {
+      ExprToken::Number(value) => Some(value),
+      ExprToken::Symbol(name) => (self.resolve)(name),
+      ExprToken::Open => {
+        let value = self.binary(0)?;
+        match self.peek() {
+          Some(ExprToken::Close) => {
+            self.pos += 1;
+            Some(value)
+          }
+          _ => None,
+        }
+      }
+      ExprToken::Op("-") => Some(self.primary()?.wrapping_neg()),
+      ExprToken::Op("+") => self.primary(),
+      ExprToken::Op("~") => Some(!self.primary()?),
+      ExprToken::Op("!") => Some(i64::from(self.primary()? == 0)),
+      _ => None,
+    }
+  }
+
+  fn binary(&mut self, min_precedence: u8) -> Option<i64> {
+    let mut lhs = self.primary()?;
+    loop {
+      let op = match self.peek() {
+        Some(ExprToken::Op(op)) if *op != "~" && *op != "!" && precedence(op) > min_precedence => *op,
+        _ => return Some(lhs),
+      };
+      self.pos += 1;
+      let rhs = self.binary(precedence(op))?;
+      lhs = match op {
+        "+" => lhs.wrapping_add(rhs),
+        "-" => lhs.wrapping_sub(rhs),
+        "*" => lhs.wrapping_mul(rhs),
+        "/" => lhs.checked_div(rhs)?,
+        "%" => lhs.checked_rem(rhs)?,
+        "<<" => lhs.wrapping_shl(rhs as u32),
+        ">>" => lhs.wrapping_shr(rhs as u32),
+        "&" => lhs & rhs,
+        "|" => lhs | rhs,
+        "^" => lhs ^ rhs,
+        "==" => truth(lhs == rhs),
+        "!=" | "<>" => truth(lhs != rhs),
+        "<" => truth(lhs < rhs),
+        ">" => truth(lhs > rhs),
+        "<=" => truth(lhs <= rhs),
+        ">=" => truth(lhs >= rhs),
+        "&&" => i64::from(lhs != 0 && rhs != 0),
+        "||" => i64::from(lhs != 0 || rhs != 0),
+        _ => return None,
+      };
+    }
+  }
+}
+
+/// Evaluates an integer expression (`label+8`, `(end-start)/4`, `1 << SHIFT`) with GNU-as style
+/// operators. Symbols are looked up through `resolve`; any unresolved symbol makes the result `None`.
+pub fn evaluate_expression(text: &str, resolve: &dyn Fn(&str) -> Option<i64>) -> Option<i64> {
+  let tokens = tokenize(text)?;
+  let mut parser = Parser {
+    tokens,
+    pos: 0,
+    resolve,
+  };
+  let value = parser.binary(0)?;
+  if parser.pos != parser.tokens.len() {
+    return None;
+  }
+  Some(value)
+}
+
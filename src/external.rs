@@ -0,0 +1,41 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Symbols the assembly uses but never defines (kernel argument labels, linker-provided symbols),
+/// from the `externalSymbols` option, with optional hover text.
+#[derive(Debug, Clone, Default)]
+pub struct ExternalSymbols {
+  descriptions: HashMap<String, Option<String>>,
+}
+
+impl ExternalSymbols {
+  /// Parses `{ "name": "description", ... }` or a list of names.
+  pub fn from_value(value: &Value) -> Self {
+    let descriptions = match value {
+      Value::Object(map) => map
+        .iter()
+        .map(|(name, description)| {
+          let description = description.as_str().filter(|text| !text.is_empty()).map(str::to_string);
+          (name.clone(), description)
+        })
+        .collect(),
+      Value::Array(names) => names
+        .iter()
+        .filter_map(|name| name.as_str())
+        .map(|name| (name.to_string(), None))
+        .collect(),
+      _ => HashMap::new(),
+    };
+    Self { descriptions }
+  }
+
+  /// Hover markdown for a configured symbol.
+  pub fn hover_markdown(&self, name: &str) -> Option<String> {
+    let description = self.descriptions.get(name)?;
+    let mut value = format!("**{name}** — external symbol");
+    if let Some(description) = description {
+      value.push_str(&format!("\n\n{description}"));
+    }
+    Some(value)
+  }
+}
@@ -0,0 +1,71 @@
+use crate::outline::document_outline;
+use crate::tokenizer::{line_comment_start, Dialect};
+use tower_lsp::lsp_types::{DocumentSymbol, FoldingRange, FoldingRangeKind, SymbolKind};
+
+fn folding_range(start_line: u32, end_line: u32, kind: Option<FoldingRangeKind>) -> FoldingRange {
+  FoldingRange {
+    start_line,
+    end_line,
+    kind,
+    ..FoldingRange::default()
+  }
+}
+
+/// Kernel, label and macro sections of the outline, without the blank lines that end them.
+fn outline_ranges(lines: &[&str], symbols: &[DocumentSymbol], out: &mut Vec<FoldingRange>) {
+  for symbol in symbols {
+    if symbol.kind == SymbolKind::CONSTANT {
+      continue;
+    }
+    let start = symbol.range.start.line;
+    let mut end = symbol.range.end.line;
+    while end > start && lines.get(end as usize).is_some_and(|line| line.trim().is_empty()) {
+      end -= 1;
+    }
+    if end > start {
+      out.push(folding_range(start, end, None));
+    }
+    if let Some(children) = &symbol.children {
+      outline_ranges(lines, children, out);
+    }
+  }
+}
+
+fn is_comment_line(line: &str, dialect: Dialect) -> bool {
+  let trimmed = line.trim_start();
+  !trimmed.is_empty() && line_comment_start(line, dialect) == Some(line.len() - trimmed.len())
+}
+
+/// Foldable regions besides conditional branches: kernels and the labels and macros inside them,
+/// `.amdhsa_kernel` descriptor blocks, and runs of two or more comment lines.
+pub fn folding_ranges(text: &str, dialect: Dialect) -> Vec<FoldingRange> {
+  let lines: Vec<&str> = text.lines().collect();
+  let mut out = Vec::new();
+  outline_ranges(&lines, &document_outline(text, dialect), &mut out);
+  let mut descriptor_start = None;
+  let mut comment_start = None;
+  for (line_idx, line) in lines.iter().enumerate() {
+    let line_idx = line_idx as u32;
+    match line.split_whitespace().next() {
+      Some(".amdhsa_kernel") => descriptor_start = Some(line_idx),
+      Some(".end_amdhsa_kernel") => {
+        if let Some(start) = descriptor_start.take() {
+          out.push(folding_range(start, line_idx, Some(FoldingRangeKind::Region)));
+        }
+      }
+      _ => {}
+    }
+    if is_comment_line(line, dialect) {
+      comment_start.get_or_insert(line_idx);
+    } else if let Some(start) = comment_start.take() && line_idx - start >= 2 {
+      out.push(folding_range(start, line_idx - 1, Some(FoldingRangeKind::Comment)));
+    }
+  }
+  if let Some(start) = comment_start {
+    let last = lines.len() as u32 - 1;
+    if last > start {
+      out.push(folding_range(start, last, Some(FoldingRangeKind::Comment)));
+    }
+  }
+  out
+}
@@ -1,5 +1,15 @@
-use crate::encoding::{find_matching_encoding, get_encoding_description};
+use crate::addressing::addressing_section;
+use crate::directives::Directive;
+use crate::encoding::{find_matching_encoding, get_encoding_description, split_encoding_variant, variant_difference};
+use crate::lds::KernelLds;
+use crate::matrix::{
+  is_wmma, matrix_operand_role, matrix_register_counts, parse_matrix_shape, wmma_layout_for_arch, wmma_layout_name,
+  wmma_register_counts, wmma_type_combinations, MatrixFamily, MatrixRegisterCounts, MatrixShape,
+  WmmaLayout, SPARSITY_INDEX_NOTE,
+};
+use crate::scratch::KernelScratch;
 use crate::types::{EncodingVariant, InstructionEntry, SpecialRegister};
+use serde_json::Value;
 use tower_lsp::lsp_types::{HoverContents, MarkupContent, MarkupKind};
 
 pub fn format_mnemonic(name: &str) -> String {
@@ -10,6 +20,9 @@ fn format_arg_type(arg_type: &str) -> Option<String> {
   match arg_type {
     "register" => Some("reg".to_string()),
     "register_or_inline" => Some("reg/inline".to_string()),
+    "accumulator" => Some("acc".to_string()),
+    "register_or_accumulator" => Some("reg/acc".to_string()),
+    "modifier" => Some("mod".to_string()),
     "immediate" => Some("imm".to_string()),
     "unknown" => None,
     _ => Some(arg_type.to_string()),
@@ -36,69 +49,630 @@ fn format_data_type(data_type: &str) -> Option<&'static str> {
   }
 }
 
-pub fn format_hover(entry: &InstructionEntry, variant: &EncodingVariant) -> HoverContents {
-  let mut lines = Vec::new();
-  lines.push(format!("**{}**", format_mnemonic(&entry.name)));
+/// Number of consecutive registers an operand spans, when the data records it as wider than a dword.
+pub fn operand_dwords(entry: &InstructionEntry, index: usize) -> Option<u32> {
+  let size = *entry.arg_sizes.get(index)?;
+  if size <= 32 {
+    return None;
+  }
+  Some(size.div_ceil(32))
+}
 
-  if !entry.args.is_empty() {
-    let args = entry
-      .args
-      .iter()
-      .enumerate()
-      .map(|(index, arg)| {
-        let arg_type = entry.arg_types.get(index).map(|value| value.as_str()).unwrap_or("unknown");
-        let arg_type = format_arg_type(arg_type);
-        let data_type = entry
+/// Register file an operand example should use, judged from the XML field name (`SDST`, `VSRC1`,
+/// ...) and, for generic `SRCn` fields, the instruction's own prefix.
+fn example_register_prefix(entry: &InstructionEntry, index: usize) -> char {
+  let arg_type = entry.arg_types.get(index).map(|value| value.as_str()).unwrap_or("unknown");
+  if arg_type == "accumulator" {
+    return 'a';
+  }
+  let field = entry.args.get(index).map(|value| value.to_ascii_uppercase()).unwrap_or_default();
+  if field.starts_with('V') {
+    return 'v';
+  }
+  if field.starts_with('S') && !field.starts_with("SRC") {
+    return 's';
+  }
+  if entry.name.to_ascii_lowercase().starts_with("s_") { 's' } else { 'v' }
+}
+
+/// One-line example invocation built from the operand kinds, e.g. `v_fmac_f32 v0, v1, v2`.
+fn format_usage_example(entry: &InstructionEntry) -> String {
+  let mut next_register = [0u32; 3];
+  let mut operands = Vec::new();
+  for index in 0..entry.args.len() {
+    let arg_type = entry.arg_types.get(index).map(|value| value.as_str()).unwrap_or("unknown");
+    let operand = match arg_type {
+      "modifier" => continue,
+      "immediate" => "0".to_string(),
+      "label" => "label".to_string(),
+      _ => {
+        let prefix = example_register_prefix(entry, index);
+        let slot = match prefix {
+          's' => 0,
+          'a' => 1,
+          _ => 2,
+        };
+        let is_64bit = entry
           .arg_data_types
           .get(index)
-          .map(|value| value.as_str())
-          .and_then(format_data_type);
-        let type_label = match (arg_type, data_type) {
-          (Some(arg_type), Some(data_type)) => format!("{arg_type} {data_type}"),
-          (Some(arg_type), None) => arg_type,
-          (None, Some(data_type)) => data_type.to_string(),
-          (None, None) => String::new(),
-        };
-        if type_label.is_empty() {
-          arg.to_string()
+          .is_some_and(|data_type| data_type.ends_with("64"));
+        let dwords = operand_dwords(entry, index).unwrap_or(if is_64bit { 2 } else { 1 });
+        // Multi-dword tuples start at an aligned register, as most instructions require.
+        let first = next_register[slot].next_multiple_of(dwords.min(4));
+        next_register[slot] = first + dwords;
+        if dwords == 1 {
+          format!("{prefix}{first}")
         } else {
-          format!("{arg}: {type_label}")
+          format!("{prefix}[{first}:{}]", first + dwords - 1)
         }
-      })
-      .collect::<Vec<_>>()
-      .join(", ");
-    lines.push(args);
+      }
+    };
+    operands.push(operand);
   }
-  if let Some(description) = &entry.description {
-    if !description.is_empty() {
-      lines.push(description.clone());
+  let mnemonic = format_mnemonic(&entry.name);
+  if operands.is_empty() {
+    mnemonic
+  } else {
+    format!("{mnemonic} {}", operands.join(", "))
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoverSection {
+  Args,
+  Semantics,
+  Description,
+  Notes,
+  /// Notes from the configured ISA overlay files, one block per overlay.
+  Overlays,
+  Encodings,
+  Architectures,
+}
+
+/// Order in which enabled sections appear below the instruction title (the usage example, when
+/// enabled, always leads above the title).
+const HOVER_SECTION_ORDER: &[HoverSection] = &[
+  HoverSection::Args,
+  HoverSection::Semantics,
+  HoverSection::Description,
+  HoverSection::Notes,
+  HoverSection::Overlays,
+  HoverSection::Encodings,
+  HoverSection::Architectures,
+];
+
+/// Which hover sections to render, from the `hoverSections` initialization option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoverSections {
+  pub example: bool,
+  pub args: bool,
+  /// Show `b32`/`f16`/... next to each operand in the args section.
+  pub data_types: bool,
+  /// Matrix shape, register counts and layouts for MFMA/WMMA instructions; address space and
+  /// address operands for flat/global/scratch ones.
+  pub semantics: bool,
+  pub description: bool,
+  pub notes: bool,
+  pub overlays: bool,
+  /// The encoding selected by a typed suffix (`_e64`, `_dpp`, ...).
+  pub encodings: bool,
+  pub architectures: bool,
+}
+
+impl Default for HoverSections {
+  fn default() -> Self {
+    Self {
+      example: true,
+      args: true,
+      data_types: true,
+      semantics: true,
+      description: true,
+      notes: true,
+      overlays: true,
+      encodings: true,
+      architectures: false,
     }
   }
+}
 
-  if *variant != EncodingVariant::Native {
-    if let Some(encoding_name) = find_matching_encoding(&entry.available_encodings, variant) {
-      if let Some(desc) = get_encoding_description(&encoding_name) {
-        lines.push(format!("Encoding: {}", desc));
-      } else {
-        lines.push(format!("Encoding: {}", encoding_name));
+impl HoverSections {
+  pub fn from_value(value: &Value) -> Self {
+    let defaults = Self::default();
+    let flag = |key: &str, default: bool| value.get(key).and_then(|value| value.as_bool()).unwrap_or(default);
+    Self {
+      example: flag("example", defaults.example),
+      args: flag("args", defaults.args),
+      data_types: flag("dataTypes", defaults.data_types),
+      semantics: flag("semantics", defaults.semantics),
+      description: flag("description", defaults.description),
+      notes: flag("notes", defaults.notes),
+      overlays: flag("overlays", defaults.overlays),
+      encodings: flag("encodings", defaults.encodings),
+      architectures: flag("architectures", defaults.architectures),
+    }
+  }
+
+  fn enabled(&self, section: HoverSection) -> bool {
+    match section {
+      HoverSection::Args => self.args,
+      HoverSection::Semantics => self.semantics,
+      HoverSection::Description => self.description,
+      HoverSection::Notes => self.notes,
+      HoverSection::Overlays => self.overlays,
+      HoverSection::Encodings => self.encodings,
+      HoverSection::Architectures => self.architectures,
+    }
+  }
+}
+
+/// Instruction hover, optionally with a line about this particular use (e.g. a decoded wait).
+pub fn format_hover(
+  entry: &InstructionEntry,
+  variant: &EncodingVariant,
+  sections: &HoverSections,
+  annotation: Option<String>,
+) -> HoverContents {
+  HoverContents::Markup(MarkupContent {
+    kind: MarkupKind::Markdown,
+    value: format_hover_markdown(entry, variant, sections, annotation),
+  })
+}
+
+/// Instruction hover for a cursor on one of its operands, naming the operand slot it fills.
+pub fn format_operand_hover(
+  entry: &InstructionEntry,
+  variant: &EncodingVariant,
+  sections: &HoverSections,
+  operand_index: usize,
+  operand_text: &str,
+  detail: Option<String>,
+) -> HoverContents {
+  let slot = match entry.args.get(operand_index) {
+    Some(_) => format!("`{}`", format_arg_label(entry, operand_index, sections.data_types)),
+    None => "no operand slot (extra operand)".to_string(),
+  };
+  let mut annotation = format!("Operand {} `{operand_text}` → {slot}", operand_index + 1);
+  if let Some(detail) = detail {
+    annotation.push_str(&format!("  \n{detail}"));
+  }
+  HoverContents::Markup(MarkupContent {
+    kind: MarkupKind::Markdown,
+    value: format_hover_markdown(entry, variant, sections, Some(annotation)),
+  })
+}
+
+/// Hover for one dword of an objdump encoding comment: its value and bits, followed by the
+/// instruction on the same line when it is known.
+pub fn format_encoding_dword_hover(
+  instruction: Option<(&InstructionEntry, &EncodingVariant)>,
+  sections: &HoverSections,
+  address: &str,
+  dwords: &[u32],
+  dword_index: usize,
+) -> HoverContents {
+  let value = dwords[dword_index];
+  let bits = (0..8)
+    .rev()
+    .map(|nibble| format!("{:04b}", (value >> (nibble * 4)) & 0xf))
+    .collect::<Vec<_>>()
+    .join("_");
+  let offset = u64::from_str_radix(address, 16).unwrap_or_default() + 4 * dword_index as u64;
+  let annotation = format!(
+    "Encoding dword {} of {} at `{offset:#x}`: `{value:#010X}`  \n`{bits}`",
+    dword_index + 1,
+    dwords.len(),
+  );
+  let value = match instruction {
+    Some((entry, variant)) => format_hover_markdown(entry, variant, sections, Some(annotation)),
+    None => annotation,
+  };
+  HoverContents::Markup(MarkupContent {
+    kind: MarkupKind::Markdown,
+    value,
+  })
+}
+
+/// Hover for the `_e64`/`_dpp`/... suffix of a mnemonic: the encoding it selects and how that
+/// differs from the base form's.
+pub fn format_encoding_suffix_hover(mnemonic: &str, base: &str, entry: Option<&InstructionEntry>) -> HoverContents {
+  let suffix = &mnemonic[base.len()..];
+  let variant = split_encoding_variant(mnemonic).variant;
+  let encoding = entry.and_then(|entry| find_matching_encoding(&entry.available_encodings, &variant));
+  let description = encoding.as_deref().and_then(get_encoding_description);
+  let mut lines = vec![match description {
+    Some(description) => format!("**`{suffix}`** — {description}"),
+    None => format!("**`{suffix}`** encoding suffix"),
+  }];
+  if let Some(difference) = variant_difference(&variant) {
+    lines.push(difference.to_string());
+  }
+  let base_encoding =
+    entry.and_then(|entry| find_matching_encoding(&entry.available_encodings, &EncodingVariant::Native));
+  match (&encoding, base_encoding) {
+    (Some(encoding), Some(base_encoding)) if *encoding == base_encoding => lines.push(format!(
+      "`{}` already encodes as `{encoding}`, so the suffix only makes the choice explicit.",
+      base.to_ascii_lowercase()
+    )),
+    (Some(encoding), Some(base_encoding)) => lines.push(format!(
+      "`{}` encodes as `{encoding}`; `{}` alone would use `{base_encoding}`.",
+      mnemonic.to_ascii_lowercase(),
+      base.to_ascii_lowercase()
+    )),
+    (None, _) if entry.is_some() => {
+      lines.push(format!("`{}` has no encoding for this suffix.", base.to_ascii_lowercase()));
+    }
+    _ => {}
+  }
+  HoverContents::Markup(MarkupContent {
+    kind: MarkupKind::Markdown,
+    value: lines.join("\n\n"),
+  })
+}
+
+/// Inlay label for one operand: `vdst:`, or `src0: reg f32` with types.
+pub fn format_operand_inlay(entry: &InstructionEntry, index: usize, types: bool) -> String {
+  let arg = entry.args.get(index).map(|value| value.as_str()).unwrap_or_default();
+  if !types {
+    return format!("{}:", arg.to_ascii_lowercase());
+  }
+  let label = format_arg_label(entry, index, true);
+  match label.strip_prefix(arg) {
+    Some("") | None => format!("{}:", arg.to_ascii_lowercase()),
+    Some(rest) => format!("{}{rest}", arg.to_ascii_lowercase()),
+  }
+}
+
+/// `NAME: type data_type ×dwords` for one operand.
+fn format_arg_label(entry: &InstructionEntry, index: usize, data_types: bool) -> String {
+  let arg = entry.args.get(index).map(|value| value.as_str()).unwrap_or_default();
+  let arg_type = entry.arg_types.get(index).map(|value| value.as_str()).unwrap_or("unknown");
+  let arg_type = format_arg_type(arg_type);
+  let data_type = entry
+    .arg_data_types
+    .get(index)
+    .filter(|_| data_types)
+    .map(|value| value.as_str())
+    .and_then(format_data_type);
+  let mut type_label = match (arg_type, data_type) {
+    (Some(arg_type), Some(data_type)) => format!("{arg_type} {data_type}"),
+    (Some(arg_type), None) => arg_type,
+    (None, Some(data_type)) => data_type.to_string(),
+    (None, None) => String::new(),
+  };
+  if let Some(dwords) = operand_dwords(entry, index) {
+    type_label.push_str(&format!(" ×{dwords}"));
+  }
+  if type_label.is_empty() {
+    arg.to_string()
+  } else {
+    format!("{arg}: {type_label}")
+  }
+}
+
+fn format_args_section(entry: &InstructionEntry, data_types: bool) -> Option<String> {
+  if entry.args.is_empty() {
+    return None;
+  }
+  let args = (0..entry.args.len())
+    .map(|index| format_arg_label(entry, index, data_types))
+    .collect::<Vec<_>>()
+    .join(", ");
+  Some(args)
+}
+
+fn format_hover_section(
+  entry: &InstructionEntry,
+  variant: &EncodingVariant,
+  section: HoverSection,
+  sections: &HoverSections,
+) -> Option<String> {
+  match section {
+    HoverSection::Args => format_args_section(entry, sections.data_types),
+    HoverSection::Semantics => parse_matrix_shape(&entry.name)
+      .map(|shape| format_matrix_shape(entry, &shape))
+      .or_else(|| addressing_section(entry)),
+    HoverSection::Description => entry.description.clone().filter(|description| !description.is_empty()),
+    HoverSection::Notes => {
+      if entry.notes.is_empty() {
+        return None;
+      }
+      let notes = entry
+        .notes
+        .iter()
+        .map(|note| format!("- {note}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+      Some(format!("**Notes**\n{notes}"))
+    }
+    HoverSection::Overlays => {
+      let mut sources: Vec<&str> = Vec::new();
+      for note in &entry.overlay_notes {
+        if !sources.contains(&note.source.as_str()) {
+          sources.push(&note.source);
+        }
       }
+      let blocks: Vec<String> = sources
+        .iter()
+        .map(|source| {
+          let notes = entry
+            .overlay_notes
+            .iter()
+            .filter(|note| note.source == *source)
+            .map(|note| format!("- {}", note.text))
+            .collect::<Vec<_>>()
+            .join("\n");
+          format!("**{source}**\n{notes}")
+        })
+        .collect();
+      (!blocks.is_empty()).then(|| blocks.join("\n\n"))
     }
+    HoverSection::Encodings => {
+      if *variant == EncodingVariant::Native {
+        return None;
+      }
+      let encoding_name = find_matching_encoding(&entry.available_encodings, variant)?;
+      match get_encoding_description(&encoding_name) {
+        Some(desc) => Some(format!("Encoding: {}", desc)),
+        None => Some(format!("Encoding: {}", encoding_name)),
+      }
+    }
+    HoverSection::Architectures => {
+      if entry.architectures.is_empty() {
+        return None;
+      }
+      Some(format!("**Architectures:** {}", entry.architectures.join(", ")))
+    }
+  }
+}
+
+fn format_hover_markdown(
+  entry: &InstructionEntry,
+  variant: &EncodingVariant,
+  sections: &HoverSections,
+  annotation: Option<String>,
+) -> String {
+  let mut lines = Vec::new();
+  if sections.example {
+    lines.push(format!("```asm\n{}\n```", format_usage_example(entry)));
+  }
+  match entry.execution_unit {
+    Some(unit) => lines.push(format!("**{}** · {}", format_mnemonic(&entry.name), unit.label())),
+    None => lines.push(format!("**{}**", format_mnemonic(&entry.name))),
+  }
+  lines.extend(annotation);
+  for &section in HOVER_SECTION_ORDER {
+    if !sections.enabled(section) {
+      continue;
+    }
+    if let Some(text) = format_hover_section(entry, variant, section, sections) {
+      lines.push(text);
+    }
+  }
+  lines.join("\n\n")
+}
+
+/// Full Markdown documentation for every entry sharing a mnemonic: every hover section plus the
+/// complete encoding list.
+pub fn format_full_documentation(entries: &[&InstructionEntry], variant: &EncodingVariant) -> String {
+  let sections = HoverSections {
+    architectures: false,
+    ..HoverSections::default()
+  };
+  let mut documents = Vec::new();
+  for entry in entries {
+    let mut lines = vec![format_hover_markdown(entry, variant, &sections, None)];
+    if !entry.available_encodings.is_empty() {
+      let encodings = entry
+        .available_encodings
+        .iter()
+        .map(|encoding| match get_encoding_description(encoding) {
+          Some(desc) => format!("- `{encoding}`: {desc}"),
+          None => format!("- `{encoding}`"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+      lines.push(format!("**Encodings**\n{encodings}"));
+    }
+    if !entry.architectures.is_empty() {
+      lines.push(format!("**Architectures:** {}", entry.architectures.join(", ")));
+    }
+    documents.push(lines.join("\n\n"));
+  }
+  documents.join("\n\n---\n\n")
+}
+
+fn format_register_counts(shape: &MatrixShape, counts: &MatrixRegisterCounts) -> String {
+  let sparse = matches!(shape.family, MatrixFamily::Smfmac | MatrixFamily::Swmmac);
+  if sparse {
+    format!("A {}, B {}, index {}, C/D {}", counts.a, counts.b, counts.c, counts.d)
+  } else {
+    format!("A {}, B {}, C {}, D {}", counts.a, counts.b, counts.c, counts.d)
+  }
+}
+
+/// Distinct WMMA layouts among the architectures an entry is documented for, oldest first.
+pub fn entry_wmma_layouts(entry: &InstructionEntry) -> Vec<WmmaLayout> {
+  let mut layouts = Vec::new();
+  for layout in [WmmaLayout::Replicated, WmmaLayout::Packed] {
+    if entry
+      .architectures
+      .iter()
+      .any(|arch| wmma_layout_for_arch(arch) == Some(layout))
+    {
+      layouts.push(layout);
+    }
+  }
+  layouts
+}
+
+/// Signature-help annotation for a matrix instruction operand: its role and per-lane register count
+/// for the active architecture (falling back to the oldest documented layout).
+pub fn format_matrix_operand(
+  entry: &InstructionEntry,
+  shape: &MatrixShape,
+  index: usize,
+  arch: Option<&str>,
+) -> Option<String> {
+  let role = matrix_operand_role(shape, index)?;
+  let counts = if is_wmma(shape) {
+    let layout = arch
+      .and_then(wmma_layout_for_arch)
+      .or_else(|| entry_wmma_layouts(entry).first().copied())?;
+    wmma_register_counts(shape, layout)
+  } else {
+    matrix_register_counts(shape)
+  };
+  let regs = match index {
+    0 => counts.d,
+    1 => counts.a,
+    2 => counts.b,
+    _ => counts.c,
+  };
+  let mut doc = format!("{role}, {regs} regs/lane");
+  if role == "sparsity index" {
+    doc.push_str(". ");
+    doc.push_str(SPARSITY_INDEX_NOTE);
+  }
+  Some(doc)
+}
+
+fn format_matrix_shape(entry: &InstructionEntry, shape: &MatrixShape) -> String {
+  let mut shape_line = format!("Shape: {}×{}×{} (M×N×K)", shape.m, shape.n, shape.k);
+  if shape.blocks > 1 {
+    shape_line.push_str(&format!(", {} blocks", shape.blocks));
+  }
+  if matches!(shape.family, MatrixFamily::Smfmac | MatrixFamily::Swmmac) {
+    shape_line.push_str(", 2:4 sparse A");
+  }
+  let types_line = if shape.a_type == shape.b_type {
+    format!("Types: A/B {} → C/D {}", shape.a_type, shape.out_type)
+  } else {
+    format!("Types: A {}, B {} → C/D {}", shape.a_type, shape.b_type, shape.out_type)
+  };
+  let mut out = vec![shape_line, types_line];
+  if is_wmma(shape) {
+    for layout in entry_wmma_layouts(entry) {
+      let counts = wmma_register_counts(shape, layout);
+      out.push(format!(
+        "Registers per lane (wave32, {}): {}",
+        wmma_layout_name(layout),
+        format_register_counts(shape, &counts)
+      ));
+    }
+    for layout in entry_wmma_layouts(entry) {
+      out.push(format!(
+        "{} type combinations: {}",
+        wmma_layout_name(layout),
+        wmma_type_combinations(layout).join(", ")
+      ));
+    }
+  } else {
+    let counts = matrix_register_counts(shape);
+    out.push(format!(
+      "Registers per lane (wave64): {}",
+      format_register_counts(shape, &counts)
+    ));
   }
+  if matches!(shape.family, MatrixFamily::Smfmac | MatrixFamily::Swmmac) {
+    out.push(SPARSITY_INDEX_NOTE.to_string());
+  }
+  out.join("  \n")
+}
+
+pub fn format_expression_hover(expression: &str, value: i64) -> HoverContents {
+  HoverContents::Markup(MarkupContent {
+    kind: MarkupKind::Markdown,
+    value: format!("`{expression}` = {value} ({:#x})", value as u64),
+  })
+}
+
+/// A label with the code it labels, so a branch target can be read without jumping to it.
+pub fn format_label_hover(label: &str, line: u32, preview: &[&str], target: Option<String>) -> HoverContents {
+  let mut value = format!("**{label}** (label, line {})", line + 1);
+  if let Some(target) = target {
+    value.push_str(&format!("\n\n{target}"));
+  }
+  if !preview.is_empty() {
+    value.push_str(&format!("\n\n```asm\n{}\n```", preview.join("\n")));
+  }
+  HoverContents::Markup(MarkupContent {
+    kind: MarkupKind::Markdown,
+    value,
+  })
+}
+
+pub fn format_relocation_hover(symbol: &str, specifier: &str, info: Option<(&str, &str)>) -> HoverContents {
+  let value = match info {
+    Some((relocation, description)) => format!("`{symbol}{specifier}` ({relocation})\n\n{description}"),
+    None => format!("`{symbol}{specifier}`: unknown relocation specifier"),
+  };
+  HoverContents::Markup(MarkupContent {
+    kind: MarkupKind::Markdown,
+    value,
+  })
+}
 
+pub fn format_kernel_hover(kernel: &KernelLds, module_lds_bytes: u64, scratch: Option<&KernelScratch>) -> HoverContents {
+  let mut lines = vec![format!("**{}** (kernel)", kernel.name)];
+  let mut lds = Vec::new();
+  match kernel.group_segment_size {
+    Some(size) => lds.push(format!("group segment {size} bytes")),
+    None if kernel.access_count > 0 => lds.push("no `.amdhsa_group_segment_fixed_size` declared".to_string()),
+    None => {}
+  }
+  if module_lds_bytes > 0 {
+    lds.push(format!("`.amdgpu_lds` {module_lds_bytes} bytes"));
+  }
+  if let Some(size) = kernel.group_segment_size {
+    lds.push(format!("total {} bytes", size + module_lds_bytes));
+  }
+  if !lds.is_empty() {
+    lines.push(format!("LDS: {}", lds.join(", ")));
+  }
+  if kernel.access_count > 0 {
+    let mut accesses = format!("{} `ds_*` accesses", kernel.access_count);
+    if let Some(end) = kernel.max_access_end {
+      accesses.push_str(&format!(", constant offsets reach byte {end}"));
+    }
+    lines.push(accesses);
+  }
+  if let Some(scratch) = scratch.filter(|scratch| scratch.private_segment_size.is_some() || scratch.access_count > 0) {
+    let mut line = match scratch.private_segment_size {
+      Some(size) => format!("Scratch: {size} bytes per lane"),
+      None => "Scratch: no `.amdhsa_private_segment_fixed_size` declared".to_string(),
+    };
+    if scratch.access_count > 0 {
+      line.push_str(&format!(", {} scratch accesses", scratch.access_count));
+      if let Some(end) = scratch.max_access_end {
+        line.push_str(&format!(", constant offsets reach byte {end}"));
+      }
+    }
+    lines.push(line);
+  }
   HoverContents::Markup(MarkupContent {
     kind: MarkupKind::Markdown,
     value: lines.join("\n\n"),
   })
 }
 
+pub fn format_directive_hover(directive: &Directive) -> HoverContents {
+  let signature = if directive.syntax.is_empty() {
+    directive.name.to_string()
+  } else {
+    format!("{} {}", directive.name, directive.syntax)
+  };
+  HoverContents::Markup(MarkupContent {
+    kind: MarkupKind::Markdown,
+    value: format!("```\n{signature}\n```\n\n{}", directive.description),
+  })
+}
+
 pub fn format_special_register_hover(register: &SpecialRegister) -> HoverContents {
   let mut lines = Vec::new();
   lines.push(format!("**{}**", register.name));
 
-  if let Some(description) = &register.description {
-    if !description.is_empty() {
-      lines.push(description.clone());
-    }
+  if let Some(description) = &register.description && !description.is_empty() {
+    lines.push(description.clone());
   }
 
   HoverContents::Markup(MarkupContent {
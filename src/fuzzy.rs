@@ -0,0 +1,61 @@
+/// Levenshtein distance over bytes (mnemonics are ASCII).
+pub fn edit_distance(a: &str, b: &str) -> usize {
+  let a = a.as_bytes();
+  let b = b.as_bytes();
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+  let mut current = vec![0; b.len() + 1];
+  for (i, &ca) in a.iter().enumerate() {
+    current[0] = i + 1;
+    for (j, &cb) in b.iter().enumerate() {
+      let substitution = previous[j] + usize::from(ca != cb);
+      current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+    }
+    std::mem::swap(&mut previous, &mut current);
+  }
+  previous[b.len()]
+}
+
+/// Up to `limit` candidates closest to `query`, nearest first. Candidates further away than a
+/// third of the query length (minimum 2 edits) are not considered plausible typos.
+pub fn closest_names<'a>(query: &str, candidates: impl Iterator<Item = &'a str>, limit: usize) -> Vec<&'a str> {
+  let query = query.to_ascii_lowercase();
+  let max_distance = (query.len() / 3).max(2);
+  let mut scored: Vec<(usize, &'a str)> = candidates
+    .filter(|candidate| candidate.len().abs_diff(query.len()) <= max_distance)
+    .map(|candidate| (edit_distance(&query, candidate), candidate))
+    .filter(|(distance, _)| *distance <= max_distance)
+    .collect();
+  scored.sort();
+  scored.into_iter().take(limit).map(|(_, name)| name).collect()
+}
+
+/// How well `query` matches `candidate` as a subsequence, ignoring case and the query's
+/// underscores (`vaddf32` matches `v_add_f32`), or `None` when it does not. The first character
+/// must start a `_`-separated part; characters matched at a part start or right after the
+/// previous match score higher, skipped characters and unmatched length lower.
+pub fn subsequence_score(query: &str, candidate: &str) -> Option<i64> {
+  let bytes = candidate.as_bytes();
+  let is_part_start = |index: usize| index == 0 || bytes[index - 1] == b'_';
+  let first = query.bytes().find(|&b| b != b'_')?.to_ascii_lowercase();
+  let mut position =
+    (0..bytes.len()).find(|&index| is_part_start(index) && bytes[index].to_ascii_lowercase() == first)?;
+  let mut score = 0;
+  let mut matched = 0;
+  let mut previous = None;
+  for wanted in query.bytes().filter(|&b| b != b'_').map(|b| b.to_ascii_lowercase()) {
+    let index = position + bytes[position..].iter().position(|b| b.to_ascii_lowercase() == wanted)?;
+    let skipped = bytes[position..index].iter().filter(|&&b| b != b'_').count() as i64;
+    score += 1 - skipped;
+    if is_part_start(index) {
+      score += 8;
+    }
+    // Runs continue across underscores, so `addf` reads `add_f` as one run.
+    if previous.is_some_and(|previous: usize| bytes[previous + 1..index].iter().all(|&b| b == b'_')) {
+      score += 5;
+    }
+    previous = Some(index);
+    position = index + 1;
+    matched += 1;
+  }
+  Some(score - (bytes.len() as i64 - matched) / 2)
+}
@@ -0,0 +1,42 @@
+use crate::index::DeferredShard;
+use crate::types::{InstructionEntry, IsaLoadInfo};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Result of the `amdgpu/health` request.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+  /// Whether the ISA data loaded; without it the server only offers text features.
+  pub data_loaded: bool,
+  pub data_path: String,
+  pub error: Option<String>,
+  /// Instruction entries loaded so far, and their distinct mnemonics.
+  pub instructions: usize,
+  pub unique_names: usize,
+  /// Architectures of per-architecture shards not loaded yet.
+  pub pending_architectures: Vec<String>,
+  /// How to fix a failed load.
+  pub hint: Option<String>,
+}
+
+pub fn health_report(
+  load_info: &IsaLoadInfo,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  deferred: &[DeferredShard],
+) -> HealthReport {
+  let data_loaded = load_info.load_error.is_none();
+  HealthReport {
+    data_loaded,
+    data_path: load_info.data_path.clone(),
+    error: load_info.load_error.clone(),
+    instructions: index.values().map(|entries| entries.len()).sum(),
+    unique_names: index.len(),
+    pending_architectures: deferred.iter().map(|shard| shard.architecture.clone()).collect(),
+    hint: (!data_loaded).then(|| {
+      "Set `amdgpuLsp.dataPath`, pass `--data` or set `AMDGPU_LSP_DATA` to an isa.json or a directory of \
+       per-architecture shards generated by parse_isa"
+        .to_string()
+    }),
+  }
+}
@@ -0,0 +1,46 @@
+use crate::registers::{parse_register, RegisterRange};
+use crate::text_utils::byte_offset_to_utf16_position;
+use crate::tokenizer::{line_comment_start, string_literal_ranges, Dialect};
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, Position, Range};
+
+fn is_word_byte(b: u8) -> bool {
+  b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Every register operand in the document that shares a register with `target`: `v5` matches
+/// `v[4:7]`, and `s[0:1]` matches `s1`.
+pub fn register_highlights(text: &str, target: &RegisterRange, dialect: Dialect) -> Vec<DocumentHighlight> {
+  let mut highlights = Vec::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+    let strings = string_literal_ranges(code, dialect);
+    let bytes = code.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+      if !is_word_byte(bytes[idx]) || (idx > 0 && is_word_byte(bytes[idx - 1])) {
+        idx += 1;
+        continue;
+      }
+      let start = idx;
+      while idx < bytes.len() && is_word_byte(bytes[idx]) {
+        idx += 1;
+      }
+      // `s[0:1]`: the bracketed range belongs to the register name before it.
+      if bytes.get(idx) == Some(&b'[') && let Some(close) = code[idx..].find(']') {
+        idx += close + 1;
+      }
+      if strings.iter().any(|&(string_start, string_end)| string_start <= start && start < string_end) {
+        continue;
+      }
+      if !parse_register(&code[start..idx]).is_some_and(|register| register.overlaps(target)) {
+        continue;
+      }
+      let position = |byte| Position::new(line_idx as u32, byte_offset_to_utf16_position(line, byte));
+      highlights.push(DocumentHighlight {
+        range: Range::new(position(start), position(idx)),
+        kind: Some(DocumentHighlightKind::TEXT),
+      });
+    }
+  }
+  highlights
+}
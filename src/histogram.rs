@@ -0,0 +1,64 @@
+use crate::conditional::analyze_conditionals;
+use crate::encoding::split_encoding_variant;
+use crate::index::find_entry;
+use crate::symbols::{collect_macro_names, collect_symbol_definitions};
+use crate::tokenizer::{parse_instruction_statements, Dialect};
+use crate::types::InstructionEntry;
+use crate::vopd::{dual_entry, statement_operations};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+/// Parameters of the `amdgpu/instructionHistogram` request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionHistogramParams {
+  pub text_document: TextDocumentIdentifier,
+}
+
+/// Result of `amdgpu/instructionHistogram`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InstructionHistogram {
+  pub total: usize,
+  /// Counts by lowercase mnemonic as written, encoding suffix included.
+  pub mnemonics: BTreeMap<String, usize>,
+  /// Counts by execution unit (`scalar ALU`, `vector ALU`, ...); `macro` for invocations of the
+  /// document's macros, `other` for instructions without a unit and `unknown` for mnemonics the
+  /// index lacks on the architecture.
+  pub categories: BTreeMap<String, usize>,
+}
+
+/// Counts the instructions of the assembled code, skipping branches that conditionals disable. Each
+/// half of a VOPD pair counts as an instruction of its own.
+pub fn instruction_histogram(
+  text: &str,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  filter: Option<&str>,
+  dialect: Dialect,
+) -> InstructionHistogram {
+  let macros = collect_macro_names(text, dialect);
+  let conditionals = analyze_conditionals(text, &collect_symbol_definitions(text, dialect), dialect);
+  let mut histogram = InstructionHistogram::default();
+  for (line_idx, line) in text.lines().enumerate() {
+    if conditionals.inactive_lines.contains(&(line_idx as u32)) {
+      continue;
+    }
+    let statements = parse_instruction_statements(line, dialect);
+    for parsed in statements.into_iter().flat_map(|parsed| statement_operations(line, parsed, dialect)) {
+      let mnemonic = parsed.mnemonic.text.to_ascii_lowercase();
+      let category = if macros.contains(&mnemonic) {
+        "macro"
+      } else {
+        let entry = dual_entry(index, &mnemonic, filter);
+        match entry.or_else(|| find_entry(index, &split_encoding_variant(&mnemonic).base, filter)) {
+          Some(entry) => entry.execution_unit.map_or("other", |unit| unit.label()),
+          None => "unknown",
+        }
+      };
+      histogram.total += 1;
+      *histogram.categories.entry(category.to_string()).or_default() += 1;
+      *histogram.mnemonics.entry(mnemonic).or_default() += 1;
+    }
+  }
+  histogram
+}
@@ -0,0 +1,52 @@
+use crate::types::EncodingVariant;
+use std::collections::HashMap;
+use tower_lsp::lsp_types::HoverContents;
+
+/// Rendered instruction hovers kept for repeated hovers over the same mnemonic.
+const HOVER_CACHE_CAPACITY: usize = 256;
+
+/// What an instruction hover depends on besides the loaded data and hover settings: the
+/// lowercase token, the architecture filter, the encoding its suffix selects and the line's
+/// annotation (wait, idiom and errata notes).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HoverKey {
+  pub token: String,
+  pub architecture: Option<String>,
+  pub variant: EncodingVariant,
+  pub annotation: Option<String>,
+}
+
+/// Least recently used cache of rendered instruction hovers. Cleared whenever the data, overlays
+/// or hover sections change.
+#[derive(Debug, Default)]
+pub struct HoverCache {
+  entries: HashMap<HoverKey, (u64, HoverContents)>,
+  clock: u64,
+}
+
+impl HoverCache {
+  pub fn get(&mut self, key: &HoverKey) -> Option<HoverContents> {
+    self.clock += 1;
+    let clock = self.clock;
+    self.entries.get_mut(key).map(|(used, contents)| {
+      *used = clock;
+      contents.clone()
+    })
+  }
+
+  /// Stores a hover, evicting the least recently used one when full.
+  pub fn insert(&mut self, key: HoverKey, contents: HoverContents) {
+    if self.entries.len() >= HOVER_CACHE_CAPACITY && !self.entries.contains_key(&key) {
+      let oldest = self.entries.iter().min_by_key(|(_, (used, _))| *used).map(|(key, _)| key.clone());
+      if let Some(oldest) = oldest {
+        self.entries.remove(&oldest);
+      }
+    }
+    self.clock += 1;
+    self.entries.insert(key, (self.clock, contents));
+  }
+
+  pub fn clear(&mut self) {
+    self.entries.clear();
+  }
+}
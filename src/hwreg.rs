@@ -0,0 +1,431 @@
+use crate::expression::parse_integer_literal;
+use crate::tokenizer::{InstructionLine, Token};
+
+/// A named bit field of a hardware register.
+pub struct HwregField {
+  pub name: &'static str,
+  pub offset: u32,
+  pub size: u32,
+  /// Generations or families that have the field; empty for every one the register exists on.
+  families: &'static [&'static str],
+  pub description: &'static str,
+}
+
+const fn field(name: &'static str, offset: u32, size: u32) -> HwregField {
+  HwregField { name, offset, size, families: &[], description: "" }
+}
+
+const fn mode_field(
+  name: &'static str,
+  offset: u32,
+  size: u32,
+  families: &'static [&'static str],
+  description: &'static str,
+) -> HwregField {
+  HwregField { name, offset, size, families, description }
+}
+
+/// A hardware register readable with `s_getreg_b32` and writable with `s_setreg_b32`.
+struct HardwareRegister {
+  /// Assembler names; RDNA4 renamed several (`HW_REG_MODE` is `HW_REG_WAVE_MODE`).
+  names: &'static [&'static str],
+  id: u32,
+  /// Families (`rdna`, `cdna`) whose layout this is.
+  families: &'static [&'static str],
+  fields: &'static [HwregField],
+}
+
+/// MODE, the per-wave floating point and trap configuration.
+const MODE_FIELDS: &[HwregField] = &[
+  mode_field(
+    "FP_ROUND",
+    0,
+    4,
+    &[],
+    "Rounding: bits [1:0] for f32, [3:2] for f64/f16; 0 nearest even, 1 toward +inf, 2 toward -inf, 3 toward zero",
+  ),
+  mode_field(
+    "FP_DENORM",
+    4,
+    4,
+    &[],
+    "Denormals: bits [5:4] for f32, [7:6] for f64/f16; 0 flush inputs and outputs, 1 allow inputs, 2 allow \
+     outputs, 3 allow both",
+  ),
+  mode_field(
+    "DX10_CLAMP",
+    8,
+    1,
+    &["rdna1", "rdna2", "rdna3", "cdna"],
+    "Clamping turns NaN results into zero",
+  ),
+  mode_field(
+    "IEEE",
+    9,
+    1,
+    &["rdna1", "rdna2", "rdna3", "cdna"],
+    "IEEE mode: min/max quiet signaling NaNs and follow IEEE 754-2008",
+  ),
+  mode_field("LOD_CLAMPED", 10, 1, &[], "Set by hardware when an image sample clamped its LOD"),
+  mode_field(
+    "EXCP_EN",
+    12,
+    9,
+    &[],
+    "Trap enables: invalid, input denormal, divide by zero, overflow, underflow, inexact, integer divide by zero, \
+     address watch, memory violation",
+  ),
+  mode_field("FP16_OVFL", 23, 1, &[], "f16 overflow saturates to the largest finite value instead of infinity"),
+  mode_field("GPR_IDX_EN", 27, 1, &["cdna"], "VGPR indexing (`s_set_gpr_idx_on`) is active"),
+  mode_field("VSKIP", 28, 1, &[], "Vector instructions are skipped (debugger use)"),
+  mode_field("CSP", 29, 3, &[], "Conditional branch stack pointer"),
+];
+
+const STATUS_FIELDS: &[HwregField] = &[
+  field("SCC", 0, 1),
+  field("SPI_PRIO", 1, 2),
+  field("USER_PRIO", 3, 2),
+  field("PRIV", 5, 1),
+  field("TRAP_EN", 6, 1),
+  field("TTRACE_EN", 7, 1),
+  field("EXPORT_RDY", 8, 1),
+  field("EXECZ", 9, 1),
+  field("VCCZ", 10, 1),
+  field("IN_TG", 11, 1),
+  field("IN_BARRIER", 12, 1),
+  field("HALT", 13, 1),
+  field("TRAP", 14, 1),
+  field("TTRACE_CU_EN", 15, 1),
+  field("VALID", 16, 1),
+  field("ECC_ERR", 17, 1),
+  field("SKIP_EXPORT", 18, 1),
+  field("PERF_EN", 19, 1),
+  field("COND_DBG_USER", 20, 1),
+  field("COND_DBG_SYS", 21, 1),
+  field("ALLOW_REPLAY", 22, 1),
+  field("FATAL_HALT", 23, 1),
+  field("MUST_EXPORT", 27, 1),
+];
+
+const TRAPSTS_FIELDS: &[HwregField] = &[
+  field("EXCP", 0, 9),
+  field("SAVECTX", 10, 1),
+  field("ILLEGAL_INST", 11, 1),
+  field("EXCP_HI", 12, 3),
+  field("EXCP_CYCLE", 16, 6),
+  field("DP_RATE", 29, 3),
+];
+
+const LDS_ALLOC_FIELDS: &[HwregField] = &[field("LDS_BASE", 0, 8), field("LDS_SIZE", 12, 9)];
+
+const IB_STS_FIELDS: &[HwregField] = &[
+  field("VM_CNT", 0, 4),
+  field("EXP_CNT", 4, 3),
+  field("LGKM_CNT", 8, 4),
+  field("VALU_CNT", 12, 3),
+];
+
+const HARDWARE_REGISTERS: &[HardwareRegister] = &[
+  HardwareRegister {
+    names: &["HW_REG_MODE", "HW_REG_WAVE_MODE"],
+    id: 1,
+    families: &["rdna", "cdna"],
+    fields: MODE_FIELDS,
+  },
+  HardwareRegister {
+    names: &["HW_REG_STATUS", "HW_REG_WAVE_STATUS"],
+    id: 2,
+    families: &["rdna", "cdna"],
+    fields: STATUS_FIELDS,
+  },
+  HardwareRegister {
+    names: &["HW_REG_TRAPSTS"],
+    id: 3,
+    families: &["rdna", "cdna"],
+    fields: TRAPSTS_FIELDS,
+  },
+  HardwareRegister {
+    names: &["HW_REG_HW_ID"],
+    id: 4,
+    families: &["cdna"],
+    fields: &[
+      field("WAVE_ID", 0, 4),
+      field("SIMD_ID", 4, 2),
+      field("PIPE_ID", 6, 2),
+      field("CU_ID", 8, 4),
+      field("SH_ID", 12, 1),
+      field("SE_ID", 13, 3),
+      field("TG_ID", 16, 4),
+      field("VM_ID", 20, 4),
+      field("QUEUE_ID", 24, 3),
+      field("STATE_ID", 27, 3),
+      field("ME_ID", 30, 2),
+    ],
+  },
+  HardwareRegister {
+    names: &["HW_REG_GPR_ALLOC"],
+    id: 5,
+    families: &["cdna"],
+    fields: &[
+      field("VGPR_BASE", 0, 6),
+      field("VGPR_SIZE", 8, 6),
+      field("SGPR_BASE", 16, 6),
+      field("SGPR_SIZE", 24, 4),
+    ],
+  },
+  HardwareRegister {
+    names: &["HW_REG_GPR_ALLOC", "HW_REG_WAVE_GPR_ALLOC"],
+    id: 5,
+    families: &["rdna"],
+    fields: &[field("VGPR_BASE", 0, 8), field("VGPR_SIZE", 8, 8)],
+  },
+  HardwareRegister {
+    names: &["HW_REG_LDS_ALLOC", "HW_REG_WAVE_LDS_ALLOC"],
+    id: 6,
+    families: &["rdna", "cdna"],
+    fields: LDS_ALLOC_FIELDS,
+  },
+  HardwareRegister {
+    names: &["HW_REG_IB_STS"],
+    id: 7,
+    families: &["rdna", "cdna"],
+    fields: IB_STS_FIELDS,
+  },
+  HardwareRegister {
+    names: &["HW_REG_FLAT_SCR_LO"],
+    id: 20,
+    families: &["rdna", "cdna"],
+    fields: &[],
+  },
+  HardwareRegister {
+    names: &["HW_REG_FLAT_SCR_HI"],
+    id: 21,
+    families: &["rdna", "cdna"],
+    fields: &[],
+  },
+  HardwareRegister {
+    names: &["HW_REG_XNACK_MASK"],
+    id: 22,
+    families: &["cdna"],
+    fields: &[],
+  },
+  HardwareRegister {
+    names: &["HW_REG_HW_ID1", "HW_REG_WAVE_HW_ID1"],
+    id: 23,
+    families: &["rdna"],
+    fields: &[
+      field("WAVE_ID", 0, 5),
+      field("SIMD_ID", 8, 2),
+      field("WGP_ID", 10, 4),
+      field("SA_ID", 16, 1),
+      field("SE_ID", 18, 3),
+      field("DP_RATE", 29, 3),
+    ],
+  },
+  HardwareRegister {
+    names: &["HW_REG_HW_ID2", "HW_REG_WAVE_HW_ID2"],
+    id: 24,
+    families: &["rdna"],
+    fields: &[
+      field("QUEUE_ID", 0, 4),
+      field("PIPE_ID", 4, 2),
+      field("ME_ID", 8, 2),
+      field("STATE_ID", 12, 3),
+      field("WG_ID", 16, 5),
+      field("VM_ID", 24, 4),
+    ],
+  },
+  HardwareRegister {
+    names: &["HW_REG_SHADER_CYCLES", "HW_REG_SHADER_CYCLES_LO"],
+    id: 29,
+    families: &["rdna"],
+    fields: &[field("CYCLES", 0, 20)],
+  },
+];
+
+/// Whether a filter and a family or generation name overlap: `rdna3.5` is in `rdna3`, and a bare
+/// `rdna` filter could be any generation.
+pub fn family_matches(filter: &str, family: &str) -> bool {
+  let filter = filter.trim_end_matches('+');
+  filter.starts_with(family) || family.starts_with(filter)
+}
+
+/// The register an `hwreg` id names, by name or number, preferring the layout of the filtered family.
+fn hardware_register(id: &str, filter: Option<&str>) -> Option<&'static HardwareRegister> {
+  let number = parse_integer_literal(id).map(|number| number as u32);
+  let mut candidates = HARDWARE_REGISTERS.iter().filter(|register| match number {
+    Some(number) => register.id == number,
+    None => register.names.iter().any(|name| name.eq_ignore_ascii_case(id)),
+  });
+  match filter {
+    Some(filter) => candidates.find(|register| register.families.iter().any(|family| family_matches(filter, family))),
+    None => candidates.next(),
+  }
+}
+
+/// Fields of the register on the filtered architecture.
+fn register_fields<'a>(
+  register: &'static HardwareRegister,
+  filter: Option<&'a str>,
+) -> impl Iterator<Item = &'static HwregField> + 'a {
+  register.fields.iter().filter(move |field| {
+    field.families.is_empty()
+      || filter.is_none_or(|filter| field.families.iter().any(|family| family_matches(filter, family)))
+  })
+}
+
+/// MODE fields on the filtered architecture, for completion and hover.
+pub fn mode_fields(filter: Option<&str>) -> Vec<&'static HwregField> {
+  let mode = &HARDWARE_REGISTERS[0];
+  register_fields(mode, filter).collect()
+}
+
+/// Register names `hwreg(...)` accepts on the filtered architecture, with their ids.
+pub fn hardware_register_names(filter: Option<&str>) -> Vec<(&'static str, u32)> {
+  let mut names: Vec<(&'static str, u32)> = HARDWARE_REGISTERS
+    .iter()
+    .filter(|register| {
+      filter.is_none_or(|filter| register.families.iter().any(|family| family_matches(filter, family)))
+    })
+    .flat_map(|register| register.names.iter().map(|name| (*name, register.id)))
+    .collect();
+  names.sort();
+  names.dedup();
+  names
+}
+
+/// Meaning of a whole FP_ROUND or FP_DENORM value, split into its f32 and f64/f16 halves.
+fn decode_mode_value(name: &str, value: u32) -> Option<String> {
+  let meanings: [&str; 4] = match name {
+    "FP_ROUND" => ["nearest even", "toward +inf", "toward -inf", "toward zero"],
+    "FP_DENORM" => ["flush all", "allow inputs", "allow outputs", "allow all"],
+    _ => return None,
+  };
+  Some(format!(
+    "f32 {}, f64/f16 {}",
+    meanings[(value & 3) as usize],
+    meanings[((value >> 2) & 3) as usize]
+  ))
+}
+
+fn format_bits(offset: u32, size: u32) -> String {
+  if size == 1 {
+    format!("bit {offset}")
+  } else {
+    format!("bits [{}:{offset}]", offset + size - 1)
+  }
+}
+
+/// Register id and bit range of an `hwreg(id)` or `hwreg(id, offset, size)` operand; the
+/// encoding holds a 5-bit offset and a size of 1 to 32 bits, so other ranges are rejected.
+struct HwregSpec<'a> {
+  id: &'a str,
+  offset: u32,
+  size: u32,
+}
+
+fn parse_hwreg(operand: &str) -> Option<HwregSpec<'_>> {
+  let trimmed = operand.trim();
+  if !trimmed.get(..6).is_some_and(|prefix| prefix.eq_ignore_ascii_case("hwreg(")) {
+    return None;
+  }
+  let inner = trimmed[6..].strip_suffix(')')?;
+  let arguments: Vec<&str> = inner.split(',').map(|argument| argument.trim()).collect();
+  let (offset, size) = match arguments.as_slice() {
+    [_] => (0, 32),
+    [_, offset, size] => (
+      u32::try_from(parse_integer_literal(offset)?).ok()?,
+      u32::try_from(parse_integer_literal(size)?).ok()?,
+    ),
+    _ => return None,
+  };
+  if offset > 31 || !(1..=32).contains(&size) {
+    return None;
+  }
+  Some(HwregSpec { id: arguments[0], offset, size })
+}
+
+/// `s_setreg_imm32_b32` immediates wider than the `size` bits the hwreg operand writes; the
+/// hardware silently drops the excess bits.
+pub fn setreg_issues<'a>(parsed: &InstructionLine<'a>) -> Vec<(Token<'a>, String)> {
+  if !parsed.mnemonic.text.to_ascii_lowercase().starts_with("s_setreg_imm32") {
+    return Vec::new();
+  }
+  let (spec, value) = match (parsed.operands.first(), parsed.operands.get(1)) {
+    (Some(spec), Some(value)) => (spec, value),
+    _ => return Vec::new(),
+  };
+  let (spec, number) = match (parse_hwreg(spec.text), parse_integer_literal(value.text)) {
+    (Some(spec), Some(number)) => (spec, number),
+    _ => return Vec::new(),
+  };
+  let fits = match spec.size {
+    32 => (i64::from(i32::MIN)..=i64::from(u32::MAX)).contains(&number),
+    size => (0..1i64 << size).contains(&number),
+  };
+  if fits {
+    return Vec::new();
+  }
+  let message = format!(
+    "`{}` does not fit the {} bit{} written to `{}`; only the low bits are kept",
+    value.text,
+    spec.size,
+    if spec.size == 1 { "" } else { "s" },
+    spec.id
+  );
+  vec![(*value, message)]
+}
+
+/// Which bits of which hardware register an `hwreg(id[, offset, size])` operand of
+/// `s_getreg`/`s_setreg` selects, with the named fields the range overlaps. The immediate of
+/// `s_setreg_imm32_b32` is decoded into the values it writes to each field.
+pub fn hwreg_note(parsed: &InstructionLine<'_>, operand: &str, filter: Option<&str>) -> Option<String> {
+  let mnemonic = parsed.mnemonic.text;
+  let HwregSpec { id, offset, size } = parse_hwreg(operand)?;
+  let lower = mnemonic.to_ascii_lowercase();
+  let verb = if lower.starts_with("s_getreg") {
+    "Reads"
+  } else if lower.starts_with("s_setreg") {
+    "Writes"
+  } else {
+    "Selects"
+  };
+  let register = match hardware_register(id, filter) {
+    Some(register) => register,
+    None => return Some(format!("{verb} {} of hardware register `{id}`", format_bits(offset, size))),
+  };
+  let mut note = format!("{verb} {} of `{}` (id {})", format_bits(offset, size), register.names[0], register.id);
+  if offset + size > 32 {
+    note.push_str("; the range runs past bit 31");
+  }
+  let end = offset + size;
+  let written = parsed
+    .operands
+    .get(1)
+    .filter(|_| lower.starts_with("s_setreg_imm32"))
+    .and_then(|value| parse_integer_literal(value.text))
+    .map(|value| (value as u64 & ((1u64 << size) - 1)) << offset);
+  let overlapping: Vec<String> = register_fields(register, filter)
+    .filter(|field| field.offset < end && offset < field.offset + field.size)
+    .map(|field| {
+      let partial = field.offset < offset || field.offset + field.size > end;
+      let bits = format_bits(field.offset, field.size);
+      let mut line = format!("- `{}` ({bits}{})", field.name, if partial { ", partially" } else { "" });
+      if let Some(written) = written {
+        let value = ((written >> field.offset) & ((1u64 << field.size) - 1)) as u32;
+        line.push_str(&format!(" = {value}"));
+        if let Some(meaning) = decode_mode_value(field.name, value).filter(|_| !partial) {
+          line.push_str(&format!(" ({meaning})"));
+        }
+      }
+      if !field.description.is_empty() {
+        line.push_str(&format!(": {}", field.description));
+      }
+      line
+    })
+    .collect();
+  if !overlapping.is_empty() {
+    note.push_str(&format!(":\n\n{}", overlapping.join("\n")));
+  }
+  Some(note)
+}
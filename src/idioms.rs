@@ -0,0 +1,94 @@
+use crate::registers::{parse_register, RegisterClass};
+use crate::tokenizer::InstructionLine;
+
+/// A recognizable instruction pattern and what it is for. `operands` match the leading operands:
+/// `*` matches anything, `sgpr`/`vgpr` a register of that class, anything else the text itself
+/// (case-insensitive).
+struct Idiom {
+  mnemonics: &'static [&'static str],
+  operands: &'static [&'static str],
+  explanation: &'static str,
+}
+
+const IDIOMS: &[Idiom] = &[
+  Idiom {
+    mnemonics: &["s_mov_b32"],
+    operands: &["m0", "-1"],
+    explanation: "Sets M0 to all ones before LDS access: GFX8 and older clamp DS addresses against M0, so -1 lifts \
+                  the limit.",
+  },
+  Idiom {
+    mnemonics: &["v_readfirstlane_b32"],
+    operands: &["sgpr", "vgpr"],
+    explanation: "Scalarization: copies the first active lane's value into an SGPR, for values known to be uniform or \
+                  one iteration of a waterfall loop.",
+  },
+  Idiom {
+    mnemonics: &["s_mov_b64", "s_mov_b32"],
+    operands: &["exec", "-1"],
+    explanation: "Enables every lane of the wave, e.g. for whole-wave operations.",
+  },
+  Idiom {
+    mnemonics: &["s_mov_b32"],
+    operands: &["exec_lo", "-1"],
+    explanation: "Enables every lane of a wave32 wave, e.g. for whole-wave operations.",
+  },
+  Idiom {
+    mnemonics: &["s_and_saveexec_b64", "s_and_saveexec_b32"],
+    operands: &["*", "*"],
+    explanation: "Opens a divergent `if`: saves EXEC to the destination and keeps only the lanes whose condition \
+                  is set.",
+  },
+  Idiom {
+    mnemonics: &["s_or_b64"],
+    operands: &["exec", "exec", "*"],
+    explanation: "Closes a divergent `if`: restores the lanes saved by `s_and_saveexec_b64`.",
+  },
+  Idiom {
+    mnemonics: &["s_or_b32"],
+    operands: &["exec_lo", "exec_lo", "*"],
+    explanation: "Closes a divergent `if`: restores the lanes saved by `s_and_saveexec_b32`.",
+  },
+  Idiom {
+    mnemonics: &["s_cbranch_execz"],
+    operands: &[],
+    explanation: "Skips the block when no lane is active, so an empty branch of divergent code costs nothing.",
+  },
+  Idiom {
+    mnemonics: &["v_mbcnt_lo_u32_b32"],
+    operands: &["vgpr", "-1", "0"],
+    explanation: "Lane ID: counts the set mask bits below this lane (follow with `v_mbcnt_hi_u32_b32` in wave64).",
+  },
+  Idiom {
+    mnemonics: &["s_getpc_b64"],
+    operands: &[],
+    explanation: "Start of PC-relative addressing: the address of the next instruction, usually adjusted with \
+                  `s_add_u32`/`s_addc_u32` and `symbol@rel32@lo`/`@hi`.",
+  },
+];
+
+fn operand_matches(pattern: &str, text: &str) -> bool {
+  match pattern {
+    "*" => true,
+    "sgpr" => parse_register(text).is_some_and(|register| register.class == RegisterClass::Scalar),
+    "vgpr" => parse_register(text).is_some_and(|register| register.class == RegisterClass::Vector),
+    _ => pattern.eq_ignore_ascii_case(text),
+  }
+}
+
+/// Explanation of the idiom the instruction forms, if it matches one in the pattern table.
+pub fn idiom_note(parsed: &InstructionLine<'_>) -> Option<&'static str> {
+  let mnemonic = parsed.mnemonic.text.to_ascii_lowercase();
+  IDIOMS
+    .iter()
+    .find(|idiom| {
+      idiom.mnemonics.contains(&mnemonic.as_str())
+        && parsed.operands.len() >= idiom.operands.len()
+        && idiom
+          .operands
+          .iter()
+          .zip(&parsed.operands)
+          .all(|(pattern, operand)| operand_matches(pattern, operand.text))
+    })
+    .map(|idiom| idiom.explanation)
+}
@@ -1,49 +1,331 @@
+use crate::architecture::{arch_matches, entry_matches_arch, ArchitectureAliases};
+use crate::errata::Erratum;
+use crate::logging::log_line;
+use crate::modifiers::MemoryModifier;
+use crate::search::SearchIndex;
 use crate::types::{InstructionEntry, IsaData, IsaLoadInfo, SpecialRegister, SpecialRegistersData};
-use std::collections::HashMap;
+use parse_isa::aliases::architecture_aliases as builtin_architecture_aliases;
+use parse_isa::generate::{build_isa_output, collect_xml_files};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
+use std::path::{Path, PathBuf};
 
-pub fn load_isa_index() -> (
-  HashMap<String, Vec<InstructionEntry>>,
-  Vec<SpecialRegister>,
-  IsaLoadInfo,
-) {
-  let data_path = env::var("AMDGPU_LSP_DATA").unwrap_or_else(|_| "data/isa.json".to_string());
-  let contents = match fs::read_to_string(&data_path) {
-    Ok(text) => text,
-    Err(error) => {
-      return (
-        HashMap::new(),
-        Vec::new(),
-        IsaLoadInfo {
-          data_path,
-          load_error: Some(format!("Failed to read isa.json: {error}")),
-        },
-      );
+/// `isa.json` generated from the XMLs at build time by the `embed-data` feature.
+#[cfg(embedded_isa_data)]
+const EMBEDDED_ISA_DATA: Option<&str> = Some(include_str!(concat!(env!("OUT_DIR"), "/isa.json")));
+#[cfg(not(embedded_isa_data))]
+const EMBEDDED_ISA_DATA: Option<&str> = None;
+
+/// Everything loaded from `isa.json`, with instructions indexed by lowercase mnemonic.
+pub struct IsaIndex {
+  pub instructions: HashMap<String, Vec<InstructionEntry>>,
+  /// Full-text index over the instruction names and descriptions.
+  pub search: SearchIndex,
+  pub special_registers: Vec<SpecialRegister>,
+  pub architecture_aliases: ArchitectureAliases,
+  pub memory_modifiers: Vec<MemoryModifier>,
+  pub errata: Vec<Erratum>,
+  pub load_info: IsaLoadInfo,
+  /// Per-architecture shards of a data directory whose instructions are not loaded yet.
+  pub deferred: Vec<DeferredShard>,
+}
+
+/// A `<architecture>.json` shard (`rdna3.json`, `cdna3.json`, ...) of a data directory, loaded once a
+/// document targets its architecture.
+#[derive(Debug, Clone)]
+pub struct DeferredShard {
+  pub architecture: String,
+  pub path: PathBuf,
+}
+
+impl IsaIndex {
+  /// Loads the deferred shards `filter` needs, all of them when `None`.
+  pub fn load_deferred(&mut self, filter: Option<&str>) {
+    if load_deferred_shards(&mut self.instructions, &mut self.deferred, filter) {
+      self.search = SearchIndex::build(&self.instructions);
     }
-  };
-  let isa_data: IsaData = match serde_json::from_str(&contents) {
-    Ok(parsed) => parsed,
-    Err(error) => {
-      return (
-        HashMap::new(),
-        Vec::new(),
-        IsaLoadInfo {
-          data_path,
-          load_error: Some(format!("Failed to parse isa.json: {error}")),
-        },
-      );
+  }
+}
+
+/// Merges the deferred shards matching `filter` (all of them when `None`) into `instructions` and
+/// drops them from `deferred`. Shards that cannot be read or parsed are logged and skipped. Returns
+/// whether any shard was loaded.
+pub fn load_deferred_shards(
+  instructions: &mut HashMap<String, Vec<InstructionEntry>>,
+  deferred: &mut Vec<DeferredShard>,
+  filter: Option<&str>,
+) -> bool {
+  let (wanted, rest): (Vec<DeferredShard>, Vec<DeferredShard>) = deferred
+    .drain(..)
+    .partition(|shard| filter.is_none_or(|filter| arch_matches(&shard.architecture, filter)));
+  *deferred = rest;
+  let shards = read_shard_instructions(&wanted);
+  let loaded = !shards.is_empty();
+  for entries in shards {
+    merge_instructions(instructions, entries);
+  }
+  loaded
+}
+
+/// The instructions of each of `shards`, in order; shards that cannot be read or parsed are logged
+/// and skipped.
+pub fn read_shard_instructions(shards: &[DeferredShard]) -> Vec<Vec<InstructionEntry>> {
+  shards
+    .iter()
+    .filter_map(|shard| match read_shard(&shard.path) {
+      Ok(isa_data) => Some(isa_data.instructions),
+      Err(error) => {
+        log_line(&format!("{error}; skipping it"));
+        None
+      }
+    })
+    .collect()
+}
+
+/// Just the names and architectures of a shard's instructions.
+#[derive(Deserialize)]
+struct ShardNames {
+  instructions: Vec<ShardName>,
+}
+
+#[derive(Deserialize)]
+struct ShardName {
+  name: String,
+  architectures: Vec<String>,
+}
+
+/// An entry carrying only a name and its architectures, which is all content detection reads.
+fn detection_entry(name: String, architectures: Vec<String>) -> InstructionEntry {
+  InstructionEntry {
+    name,
+    architectures,
+    description: None,
+    args: Vec::new(),
+    arg_types: Vec::new(),
+    arg_data_types: Vec::new(),
+    arg_sizes: Vec::new(),
+    available_encodings: Vec::new(),
+    encoding_operands: Vec::new(),
+    notes: Vec::new(),
+    execution_unit: None,
+    overlay_notes: Vec::new(),
+  }
+}
+
+/// Names and architectures of the loaded instructions and of those in `deferred` shards, which are
+/// read for nothing else: content detection can weigh every target without loading them all.
+pub fn detection_index(
+  instructions: &HashMap<String, Vec<InstructionEntry>>,
+  deferred: &[DeferredShard],
+) -> HashMap<String, Vec<InstructionEntry>> {
+  let mut index: HashMap<String, Vec<InstructionEntry>> = HashMap::new();
+  for (key, entries) in instructions {
+    let stubs = entries.iter().map(|entry| detection_entry(entry.name.clone(), entry.architectures.clone()));
+    index.entry(key.clone()).or_default().extend(stubs);
+  }
+  for shard in deferred {
+    let names = fs::read_to_string(&shard.path)
+      .map_err(|error| error.to_string())
+      .and_then(|contents| serde_json::from_str::<ShardNames>(&contents).map_err(|error| error.to_string()));
+    match names {
+      Ok(names) => {
+        for ShardName { name, architectures } in names.instructions {
+          index.entry(name.to_ascii_lowercase()).or_default().push(detection_entry(name, architectures));
+        }
+      }
+      Err(error) => log_line(&format!("Failed to read {}: {error}; skipping it", shard.path.display())),
+    }
+  }
+  index
+}
+
+/// The `deferred` shards a document with no detected architecture needs: for each of its `mnemonics`
+/// that no loaded instruction has, the first shard whose architecture has it.
+pub fn covering_shards<'a>(
+  mnemonics: impl IntoIterator<Item = &'a str>,
+  instructions: &HashMap<String, Vec<InstructionEntry>>,
+  detection: &HashMap<String, Vec<InstructionEntry>>,
+  deferred: &[DeferredShard],
+) -> Vec<DeferredShard> {
+  let mut wanted: Vec<DeferredShard> = Vec::new();
+  for mnemonic in mnemonics {
+    if instructions.contains_key(mnemonic) {
+      continue;
     }
+    let entries = match detection.get(mnemonic) {
+      Some(entries) => entries,
+      None => continue,
+    };
+    let has = |shard: &DeferredShard| entries.iter().any(|entry| entry_matches_arch(entry, &shard.architecture));
+    if wanted.iter().any(&has) {
+      continue;
+    }
+    if let Some(shard) = deferred.iter().find(|shard| has(shard)) {
+      wanted.push(shard.clone());
+    }
+  }
+  wanted
+}
+
+/// The built-in aliases with the data's on top, so data generated before a target was added to the
+/// table still resolves its names.
+fn architecture_aliases(data: &HashMap<String, Vec<String>>) -> ArchitectureAliases {
+  let builtin: HashMap<String, Vec<String>> = builtin_architecture_aliases().into_iter().collect();
+  let mut aliases = ArchitectureAliases::from_table(&builtin);
+  aliases.extend(data);
+  aliases
+}
+
+fn failed_index(data_path: String, error: String) -> IsaIndex {
+  IsaIndex {
+    instructions: HashMap::new(),
+    search: SearchIndex::default(),
+    special_registers: Vec::new(),
+    architecture_aliases: architecture_aliases(&HashMap::new()),
+    memory_modifiers: Vec::new(),
+    errata: Vec::new(),
+    load_info: IsaLoadInfo {
+      data_path,
+      load_error: Some(error),
+    },
+    deferred: Vec::new(),
+  }
+}
+
+/// Loads `data_path` when given, else `AMDGPU_LSP_DATA`, else the embedded data when built with
+/// `embed-data`, else `data/isa.json` relative to the working directory.
+pub fn load_configured_isa_index(data_path: Option<String>) -> IsaIndex {
+  let data_path = data_path.or_else(|| env::var("AMDGPU_LSP_DATA").ok().filter(|path| !path.is_empty()));
+  match (data_path, EMBEDDED_ISA_DATA) {
+    (Some(data_path), _) => load_isa_index(data_path),
+    (None, Some(contents)) => parse_isa_index(contents, "<embedded>".to_string()),
+    (None, None) => load_isa_index("data/isa.json".to_string()),
+  }
+}
+
+/// Loads an `isa.json`, or a directory of JSON shards and/or raw XML specs.
+pub fn load_isa_index(data_path: String) -> IsaIndex {
+  if Path::new(&data_path).is_dir() {
+    return load_isa_directory(data_path);
+  }
+  match fs::read_to_string(&data_path) {
+    Ok(contents) => parse_isa_index(&contents, data_path),
+    Err(error) => failed_index(data_path, format!("Failed to read isa.json: {error}")),
+  }
+}
+
+fn parse_isa_index(contents: &str, data_path: String) -> IsaIndex {
+  match serde_json::from_str(contents) {
+    Ok(isa_data) => build_index(vec![isa_data], data_path),
+    Err(error) => failed_index(data_path, format!("Failed to parse isa.json: {error}")),
+  }
+}
+
+/// Merges every `*.json` shard and the XMLs of a data directory. Instructions parsed from the XMLs
+/// come first, so a freshly dropped-in spec wins over a pre-generated shard for the same
+/// architecture. Shards named after an architecture (`rdna3.json`) are deferred until a document
+/// targets it; the shared tables come from the first readable one. JSON files that cannot be read
+/// or parsed are logged and skipped.
+fn load_isa_directory(data_path: String) -> IsaIndex {
+  let directory = PathBuf::from(&data_path);
+  let mut json_files: Vec<PathBuf> = match fs::read_dir(&directory) {
+    Ok(entries) => entries
+      .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+      .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+      .collect(),
+    Err(error) => return failed_index(data_path, format!("Failed to read data directory: {error}")),
   };
-  let mut index: HashMap<String, Vec<InstructionEntry>> = HashMap::new();
-  for entry in isa_data.instructions {
-    index
-      .entry(entry.name.to_ascii_lowercase())
-      .or_default()
-      .push(entry);
+  json_files.sort();
+  let mut xml_files = match collect_xml_files(&[directory]) {
+    Ok(files) => files,
+    Err(error) => return failed_index(data_path, format!("Failed to read data directory: {error}")),
+  };
+  xml_files.sort();
+  if json_files.is_empty() && xml_files.is_empty() {
+    return failed_index(data_path, "No .json or .xml files in the data directory".to_string());
   }
+  let (mut arch_files, json_files): (Vec<PathBuf>, Vec<PathBuf>) =
+    json_files.into_iter().partition(|path| shard_architecture(path).is_some());
 
-  let mut special_registers: Vec<SpecialRegister> = match isa_data.special_registers {
+  let mut shards = Vec::new();
+  if !xml_files.is_empty() {
+    let parsed = build_isa_output(&xml_files)
+      .map_err(|error| error.to_string())
+      .and_then(|output| serde_json::to_value(output).map_err(|error| error.to_string()))
+      .and_then(|value| serde_json::from_value::<IsaData>(value).map_err(|error| error.to_string()));
+    match parsed {
+      Ok(isa_data) => shards.push(isa_data),
+      Err(error) => return failed_index(data_path, format!("Failed to parse the XML specs: {error}")),
+    }
+  }
+  for path in &json_files {
+    match read_shard(path) {
+      Ok(isa_data) => shards.push(isa_data),
+      Err(error) => log_line(&format!("{error}; skipping it")),
+    }
+  }
+  while !arch_files.is_empty() {
+    match read_shard(&arch_files[0]) {
+      Ok(isa_data) => {
+        shards.push(IsaData {
+          instructions: Vec::new(),
+          ..isa_data
+        });
+        break;
+      }
+      Err(error) => {
+        log_line(&format!("{error}; skipping it"));
+        arch_files.remove(0);
+      }
+    }
+  }
+  if shards.is_empty() {
+    return failed_index(data_path, "No readable .json or .xml files in the data directory".to_string());
+  }
+  let mut index = build_index(shards, data_path);
+  index.deferred = arch_files
+    .into_iter()
+    .filter_map(|path| {
+      let architecture = shard_architecture(&path)?;
+      Some(DeferredShard { architecture, path })
+    })
+    .collect();
+  index
+}
+
+/// The architecture a shard is named after, for `rdna3.json`, `rdna3.5.json`, `cdna3.json`, ...
+fn shard_architecture(path: &Path) -> Option<String> {
+  let stem = path.file_stem()?.to_str()?.to_ascii_lowercase();
+  let version = stem.strip_prefix("rdna").or_else(|| stem.strip_prefix("cdna"))?;
+  let is_version = version.starts_with(|c: char| c.is_ascii_digit())
+    && version.chars().all(|c| c.is_ascii_digit() || c == '.');
+  is_version.then_some(stem)
+}
+
+fn read_shard(path: &Path) -> Result<IsaData, String> {
+  let name = path.display().to_string();
+  let contents = fs::read_to_string(path).map_err(|error| format!("Failed to read {name}: {error}"))?;
+  serde_json::from_str(&contents).map_err(|error| format!("Failed to parse {name}: {error}"))
+}
+
+/// Adds a per-architecture shard's instructions to the index, skipping ones an earlier shard
+/// already added for the same architectures (instructions shared across targets repeat in each).
+pub fn merge_instructions(index: &mut HashMap<String, Vec<InstructionEntry>>, entries: Vec<InstructionEntry>) {
+  let mut loaded: HashMap<String, usize> = HashMap::new();
+  for entry in entries {
+    let key = entry.name.to_ascii_lowercase();
+    let known = index.entry(key.clone()).or_default();
+    let earlier = *loaded.entry(key).or_insert(known.len());
+    if !known[..earlier].iter().any(|known| known.architectures == entry.architectures) {
+      known.push(entry);
+    }
+  }
+}
+
+fn expand_special_registers(data: SpecialRegistersData) -> Vec<SpecialRegister> {
+  match data {
     SpecialRegistersData::Flat(list) => list,
     SpecialRegistersData::Compressed(data) => {
       let mut expanded = data.singles;
@@ -52,16 +334,79 @@ pub fn load_isa_index() -> (
       }
       expanded
     }
-  };
+  }
+}
+
+/// Indexes one or more data sets. The hand-written tables (modifiers, errata) repeat in every
+/// generated file, so their entries are only taken once.
+fn build_index(shards: Vec<IsaData>, data_path: String) -> IsaIndex {
+  let mut index: HashMap<String, Vec<InstructionEntry>> = HashMap::new();
+  let mut special_registers: Vec<SpecialRegister> = Vec::new();
+  let mut register_names = HashSet::new();
+  let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+  let mut memory_modifiers: Vec<MemoryModifier> = Vec::new();
+  let mut errata: Vec<Erratum> = Vec::new();
+  for isa_data in shards {
+    for entry in isa_data.instructions {
+      index
+        .entry(entry.name.to_ascii_lowercase())
+        .or_default()
+        .push(entry);
+    }
+    for register in expand_special_registers(isa_data.special_registers) {
+      if register_names.insert(register.name.to_ascii_lowercase()) {
+        special_registers.push(register);
+      }
+    }
+    for (arch, names) in isa_data.architecture_aliases {
+      let merged = aliases.entry(arch).or_default();
+      for name in names {
+        if !merged.contains(&name) {
+          merged.push(name);
+        }
+      }
+    }
+    for modifier in isa_data.memory_modifiers {
+      let known = memory_modifiers
+        .iter()
+        .any(|known| known.name == modifier.name && known.architectures == modifier.architectures);
+      if !known {
+        memory_modifiers.push(modifier);
+      }
+    }
+    for erratum in isa_data.errata {
+      if !errata.iter().any(|known| known.note == erratum.note) {
+        errata.push(erratum);
+      }
+    }
+  }
   // Keep stable ordering for predictable output and lookups.
   special_registers.sort_by(|a, b| a.name.cmp(&b.name));
 
-  (
-    index,
+  IsaIndex {
+    search: SearchIndex::build(&index),
+    instructions: index,
     special_registers,
-    IsaLoadInfo {
+    architecture_aliases: architecture_aliases(&aliases),
+    memory_modifiers,
+    errata,
+    load_info: IsaLoadInfo {
       data_path,
       load_error: None,
     },
-  )
+    deferred: Vec::new(),
+  }
+}
+
+/// Looks up the entry for a (suffix-free) mnemonic, honoring the architecture filter when present.
+pub fn find_entry<'a>(
+  index: &'a HashMap<String, Vec<InstructionEntry>>,
+  mnemonic: &str,
+  filter: Option<&str>,
+) -> Option<&'a InstructionEntry> {
+  let entries = index.get(&mnemonic.to_ascii_lowercase())?;
+  match filter {
+    Some(filter) => entries.iter().find(|entry| entry_matches_arch(entry, filter)),
+    None => entries.first(),
+  }
 }
@@ -0,0 +1,93 @@
+use crate::encoding::{entry_for_variant, split_encoding_variant};
+use crate::formatting::format_operand_inlay;
+use crate::index::find_entry;
+use crate::text_utils::byte_offset_to_utf16_position;
+use crate::tokenizer::{parse_instruction_statements, Dialect, InstructionLine};
+use crate::types::InstructionEntry;
+use crate::vopd::{dual_entry, half_operands, parse_dual_line, DualHalf};
+use std::collections::HashMap;
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Position, Range};
+
+/// What operand inlay hints show, from the `operandInlayHints` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlayStyle {
+  /// The operand slot: `vdst:`.
+  Names,
+  /// The slot and its type: `src0: reg f32`.
+  Types,
+}
+
+impl InlayStyle {
+  /// Parses `operandInlayHints`; `off` or anything else disables the hints.
+  pub fn from_option(value: &str) -> Option<Self> {
+    match value.to_ascii_lowercase().as_str() {
+      "names" => Some(InlayStyle::Names),
+      "types" => Some(InlayStyle::Types),
+      _ => None,
+    }
+  }
+}
+
+fn push_hints(
+  line_idx: u32,
+  line: &str,
+  entry: &InstructionEntry,
+  parsed: &InstructionLine<'_>,
+  arg_indices: &[usize],
+  style: InlayStyle,
+  out: &mut Vec<InlayHint>,
+) {
+  for (operand, &arg_index) in parsed.operands.iter().zip(arg_indices) {
+    out.push(InlayHint {
+      position: Position::new(line_idx, byte_offset_to_utf16_position(line, operand.start)),
+      label: InlayHintLabel::String(format_operand_inlay(entry, arg_index, style == InlayStyle::Types)),
+      kind: Some(InlayHintKind::PARAMETER),
+      text_edits: None,
+      tooltip: None,
+      padding_left: None,
+      padding_right: Some(true),
+      data: None,
+    });
+  }
+}
+
+/// Operand slot hints for the instructions on the lines of `range`; VOPD lines get each half's
+/// slots.
+pub fn operand_inlay_hints(
+  text: &str,
+  range: Range,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  filter: Option<&str>,
+  style: InlayStyle,
+  dialect: Dialect,
+) -> Vec<InlayHint> {
+  let mut hints = Vec::new();
+  let first = range.start.line as usize;
+  let count = (range.end.line as usize + 1).saturating_sub(first);
+  for (line_idx, line) in text.lines().enumerate().skip(first).take(count) {
+    let line_idx = line_idx as u32;
+    if let Some(dual) = parse_dual_line(line, dialect) {
+      let halves = [(DualHalf::X, Some(&dual.x)), (DualHalf::Y, dual.y.as_ref())];
+      for (half, parsed) in halves {
+        let parsed = match parsed {
+          Some(parsed) => parsed,
+          None => continue,
+        };
+        if let Some(entry) = dual_entry(index, parsed.mnemonic.text, filter) {
+          push_hints(line_idx, line, entry, parsed, &half_operands(entry, half), style, &mut hints);
+        }
+      }
+      continue;
+    }
+    for parsed in parse_instruction_statements(line, dialect) {
+      let split = split_encoding_variant(parsed.mnemonic.text);
+      let entry = match find_entry(index, &split.base, filter) {
+        Some(entry) => entry_for_variant(entry, &split.variant),
+        None => continue,
+      };
+      let all: Vec<usize> = (0..entry.args.len()).collect();
+      push_hints(line_idx, line, &entry, &parsed, &all, style, &mut hints);
+    }
+  }
+  hints
+}
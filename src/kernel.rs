@@ -0,0 +1,90 @@
+use crate::expression::parse_integer_literal;
+use std::collections::HashMap;
+
+/// Fields of an `.amdhsa_kernel` descriptor block that the analyses care about.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KernelDescriptor {
+  pub group_segment_fixed_size: Option<u64>,
+  pub private_segment_fixed_size: Option<u64>,
+  /// `.amdhsa_enable_private_segment` (gfx10+) or the wave offset system SGPR (gfx9).
+  pub private_segment_enabled: bool,
+  /// `.amdhsa_user_sgpr_private_segment_buffer`: s[0:3] hold the scratch buffer resource.
+  pub private_segment_buffer: bool,
+  /// `.amdhsa_uses_dynamic_stack`: scratch is sized at launch, not by the fixed size.
+  pub uses_dynamic_stack: bool,
+}
+
+/// A problem found at a specific modifier or mnemonic of an instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentIssue {
+  pub line: u32,
+  /// Byte range within the line.
+  pub start: usize,
+  pub end: usize,
+  pub message: String,
+}
+
+pub fn parse_kernel_descriptors(text: &str) -> HashMap<String, KernelDescriptor> {
+  let mut descriptors: HashMap<String, KernelDescriptor> = HashMap::new();
+  let mut current: Option<String> = None;
+  for line in text.lines() {
+    let mut parts = line.split_whitespace();
+    let directive = match parts.next() {
+      Some(directive) => directive,
+      None => continue,
+    };
+    if directive == ".amdhsa_kernel" {
+      current = parts.next().map(|name| name.to_string());
+      if let Some(name) = &current {
+        descriptors.entry(name.clone()).or_default();
+      }
+      continue;
+    }
+    if directive == ".end_amdhsa_kernel" {
+      current = None;
+      continue;
+    }
+    let descriptor = match current.as_ref().and_then(|name| descriptors.get_mut(name)) {
+      Some(descriptor) => descriptor,
+      None => continue,
+    };
+    let value = match parts.next().and_then(parse_integer_literal) {
+      Some(value) => value as u64,
+      None => continue,
+    };
+    match directive {
+      ".amdhsa_group_segment_fixed_size" => descriptor.group_segment_fixed_size = Some(value),
+      ".amdhsa_private_segment_fixed_size" => descriptor.private_segment_fixed_size = Some(value),
+      ".amdhsa_enable_private_segment" | ".amdhsa_system_sgpr_private_segment_wavefront_offset" => {
+        descriptor.private_segment_enabled |= value != 0;
+      }
+      ".amdhsa_user_sgpr_private_segment_buffer" => descriptor.private_segment_buffer = value != 0,
+      ".amdhsa_uses_dynamic_stack" => descriptor.uses_dynamic_stack = value != 0,
+      _ => {}
+    }
+  }
+  descriptors
+}
+
+/// Bytes moved per element by a memory mnemonic, from either naming scheme
+/// (`_dwordx2`/`_ushort` or `_b64`/`_u16`).
+pub fn access_bytes(mnemonic: &str) -> Option<u64> {
+  // The type is the last recognizable part (`ds_read_u16_d16_hi` -> u16).
+  mnemonic.rsplit('_').find_map(|part| {
+    match part {
+      "dwordx4" => return Some(16),
+      "dwordx3" => return Some(12),
+      "dwordx2" => return Some(8),
+      "dword" => return Some(4),
+      "short" | "ushort" | "sshort" => return Some(2),
+      "byte" | "ubyte" | "sbyte" => return Some(1),
+      _ => {}
+    }
+    let digits = part.trim_start_matches(|ch: char| ch.is_ascii_alphabetic());
+    if digits.len() == part.len() || part.starts_with("d16") {
+      return None;
+    }
+    let bits: u64 = digits.parse().ok()?;
+    Some(bits.div_ceil(8))
+  })
+}
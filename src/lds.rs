@@ -0,0 +1,135 @@
+use crate::expression::parse_integer_literal;
+use crate::kernel::{access_bytes, parse_kernel_descriptors, SegmentIssue};
+use crate::outline::collect_kernel_names;
+use crate::tokenizer::{leading_label, line_comment_start, parse_instruction_statements, Dialect, InstructionLine};
+
+/// LDS footprint of one kernel, combining its descriptor with the `ds_*` accesses in its body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KernelLds {
+  pub name: String,
+  /// `.amdhsa_group_segment_fixed_size` from the kernel descriptor.
+  pub group_segment_size: Option<u64>,
+  /// End (offset + access size) of the furthest constant-offset `ds_*` access.
+  pub max_access_end: Option<u64>,
+  pub access_count: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LdsAnalysis {
+  pub kernels: Vec<KernelLds>,
+  /// Bytes allocated by module-level `.amdgpu_lds` symbols.
+  pub module_lds_bytes: u64,
+  pub issues: Vec<SegmentIssue>,
+}
+
+impl LdsAnalysis {
+  pub fn kernel(&self, name: &str) -> Option<&KernelLds> {
+    self.kernels.iter().find(|kernel| kernel.name == name)
+  }
+}
+
+/// `ds_*` instructions that do not address LDS memory through their offset.
+fn is_non_memory_ds(mnemonic: &str) -> bool {
+  ["permute", "swizzle", "append", "consume", "gws", "nop"]
+    .iter()
+    .any(|keyword| mnemonic.contains(keyword))
+}
+
+/// Furthest byte a constant-offset `ds_*` access touches relative to its address VGPR, along with
+/// the modifier that determines it.
+fn ds_access_end(parsed: &InstructionLine<'_>, mnemonic: &str) -> Option<(u64, usize, usize)> {
+  let element = access_bytes(mnemonic)?;
+  if mnemonic.contains("read2") || mnemonic.contains("write2") || mnemonic.contains("load_2addr") || mnemonic.contains("store_2addr") {
+    let stride = if mnemonic.contains("st64") { element * 64 } else { element };
+    let mut furthest = None;
+    for name in ["offset0", "offset1"] {
+      if let Some((token, value)) = parsed.modifier_value(name) {
+        let value = parse_integer_literal(value)? as u64;
+        let end = value * stride + element;
+        if furthest.is_none_or(|(current, _, _)| end > current) {
+          furthest = Some((end, token.start, token.end()));
+        }
+      }
+    }
+    return furthest;
+  }
+  let (token, value) = parsed.modifier_value("offset")?;
+  let value = parse_integer_literal(value)? as u64;
+  Some((value + element, token.start, token.end()))
+}
+
+fn module_lds_bytes(text: &str, dialect: Dialect) -> u64 {
+  let mut total = 0;
+  for line in text.lines() {
+    let code = match line_comment_start(line, dialect) {
+      Some(comment_start) => &line[..comment_start],
+      None => line,
+    };
+    let rest = match code.trim_start().strip_prefix(".amdgpu_lds") {
+      Some(rest) => rest,
+      None => continue,
+    };
+    // .amdgpu_lds symbol, size[, align]
+    if let Some(size) = rest.split(',').nth(1).and_then(|size| parse_integer_literal(size.trim())) {
+      total += size as u64;
+    }
+  }
+  total
+}
+
+/// Assigns `ds_*` accesses to the kernel whose label precedes them and compares their constant
+/// offsets with the kernel's declared group segment size.
+pub fn analyze_lds(text: &str, dialect: Dialect) -> LdsAnalysis {
+  let kernel_names = collect_kernel_names(text, dialect);
+  let descriptors = parse_kernel_descriptors(text);
+  let module_lds = module_lds_bytes(text, dialect);
+  let mut analysis = LdsAnalysis {
+    module_lds_bytes: module_lds,
+    ..LdsAnalysis::default()
+  };
+  let mut current: Option<usize> = None;
+  for (line_idx, line) in text.lines().enumerate() {
+    if let Some(label) = leading_label(line) && kernel_names.contains(label.text) {
+      analysis.kernels.push(KernelLds {
+        name: label.text.to_string(),
+        group_segment_size: descriptors
+          .get(label.text)
+          .and_then(|descriptor| descriptor.group_segment_fixed_size),
+        ..KernelLds::default()
+      });
+      current = Some(analysis.kernels.len() - 1);
+    }
+    for parsed in parse_instruction_statements(line, dialect) {
+      let mnemonic = parsed.mnemonic.text.to_ascii_lowercase();
+      if !mnemonic.starts_with("ds_") || is_non_memory_ds(&mnemonic) || parsed.has_modifier("gds") {
+        continue;
+      }
+      let kernel = match current {
+        Some(index) => &mut analysis.kernels[index],
+        None => continue,
+      };
+      kernel.access_count += 1;
+      let (end, start, modifier_end) = match ds_access_end(&parsed, &mnemonic) {
+        Some(access) => access,
+        None => continue,
+      };
+      kernel.max_access_end = Some(kernel.max_access_end.map_or(end, |current| current.max(end)));
+      let limit = match kernel.group_segment_size {
+        Some(size) => size + module_lds,
+        None => continue,
+      };
+      if end > limit {
+        analysis.issues.push(SegmentIssue {
+          line: line_idx as u32,
+          start,
+          end: modifier_end,
+          message: format!(
+            "LDS access reaches byte {end} but kernel `{}` only allocates {limit} bytes of LDS",
+            kernel.name
+          ),
+        });
+      }
+    }
+  }
+  analysis
+}
@@ -0,0 +1,166 @@
+mod addressing;
+mod architecture;
+mod branches;
+mod code_size;
+mod colors;
+mod conditional;
+mod config;
+mod detection;
+mod diagnostics;
+mod directives;
+mod discovery;
+mod encoding;
+mod errata;
+mod explain;
+mod expression;
+mod external;
+mod folding;
+mod formatting;
+mod fuzzy;
+mod health;
+mod highlight;
+mod histogram;
+mod hover_cache;
+mod hwreg;
+mod idioms;
+mod index;
+mod inlay;
+mod kernel;
+mod lds;
+mod lint;
+mod literals;
+mod logging;
+mod matrix;
+mod modifiers;
+mod op_sel;
+mod outline;
+mod overlay;
+mod packed;
+mod recovery;
+mod reformat;
+mod register_usage;
+mod registers;
+mod relocations;
+mod rename;
+mod runtime;
+mod sarif;
+mod scheduler;
+mod scratch;
+mod search;
+mod semantic_tokens;
+mod sendmsg;
+mod server;
+mod smem;
+mod special_operands;
+mod suffixes;
+mod symbols;
+mod telemetry;
+mod text_utils;
+mod tokenizer;
+mod types;
+mod vopd;
+mod waits;
+mod workspace;
+
+use index::{load_configured_isa_index, IsaIndex};
+pub use lint::LintReport;
+pub use logging::set_log_file;
+pub use sarif::SarifReport;
+use recovery::{install_panic_hook, GuardRequests};
+use server::IsaServer;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use telemetry::TimingSettings;
+use tokenizer::Dialect;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tower_lsp::{LspService, Server};
+
+/// Settings for an embedded server.
+#[derive(Debug, Clone, Default)]
+pub struct RunConfig {
+  /// Path to `isa.json`. Defaults to `AMDGPU_LSP_DATA`, then the data embedded by the `embed-data`
+  /// feature, then `data/isa.json`.
+  pub data_path: Option<String>,
+  /// Default architecture (`rdna3`, `gfx1100`, ...) for documents whose language id names none,
+  /// used when target discovery finds nothing; takes precedence over `AMDGPU_LSP_ARCH`.
+  pub default_architecture: Option<String>,
+  /// Assembler dialect: `llvm` (llvm-mc's AMDGPU syntax, where `;` starts a comment) unless `gnu`,
+  /// where `;` separates statements, or `sp3` for AMD's SP3 dumps. A client's `assemblerDialect`
+  /// setting replaces it for that server.
+  pub dialect: Option<String>,
+}
+
+/// Loads the instruction data and serves LSP over `reader`/`writer` until the client exits, so the
+/// server can run over stdio, a socket or in-process pipes.
+pub async fn run_server<R, W>(reader: R, writer: W, config: RunConfig)
+where
+  R: AsyncRead + Unpin,
+  W: AsyncWrite,
+{
+  install_panic_hook();
+  let dialect = Dialect::from_option(config.dialect.as_deref().unwrap_or_default());
+  let isa = load_configured_isa_index(config.data_path);
+  let timing = Arc::new(TimingSettings::default());
+  let mut server_client = None;
+  let (service, socket) = LspService::build(|client| {
+    server_client = Some(client.clone());
+    IsaServer::new(client, isa, timing.clone(), config.default_architecture, dialect)
+  })
+  .custom_method("amdgpu/health", IsaServer::health)
+  .custom_method("amdgpu/instructionHistogram", IsaServer::instruction_histogram)
+  .custom_method("amdgpu/rawEntry", IsaServer::raw_entry)
+  .custom_method("amdgpu/registerUsage", IsaServer::register_usage)
+  .finish();
+  Server::new(reader, writer, socket).serve(GuardRequests::new(service, timing, server_client)).await;
+}
+
+/// Loads the instruction data for every architecture, including per-architecture shards the server
+/// would only load on demand.
+fn load_all_architectures(data_path: Option<String>) -> Result<IsaIndex, String> {
+  let mut isa = load_configured_isa_index(data_path);
+  if let Some(error) = isa.load_info.load_error {
+    return Err(format!("{error} (path: {})", isa.load_info.data_path));
+  }
+  isa.load_deferred(None);
+  Ok(isa)
+}
+
+/// Loads the instruction data the way the server would and summarizes it (`--validate-data`).
+pub fn validate_data(data_path: Option<String>) -> Result<String, String> {
+  let isa = load_all_architectures(data_path)?;
+  let mut per_architecture: BTreeMap<&str, usize> = BTreeMap::new();
+  for entry in isa.instructions.values().flatten() {
+    for arch in &entry.architectures {
+      *per_architecture.entry(arch.as_str()).or_default() += 1;
+    }
+  }
+  let total_entries: usize = isa.instructions.values().map(|entries| entries.len()).sum();
+  let mut lines = vec![
+    format!("Data: {}", isa.load_info.data_path),
+    format!("Instructions: {total_entries} entries ({} unique names)", isa.instructions.len()),
+  ];
+  for (arch, count) in per_architecture {
+    lines.push(format!("  {arch}: {count}"));
+  }
+  lines.push(format!("Special registers: {}", isa.special_registers.len()));
+  lines.push(format!("Architecture aliases: {} architectures", isa.architecture_aliases.architecture_count()));
+  lines.push(format!("Memory modifiers: {}", isa.memory_modifiers.len()));
+  lines.push(format!("Errata: {}", isa.errata.len()));
+  Ok(lines.join("\n"))
+}
+
+/// Runs the document diagnostics over `files` and returns them as a SARIF log (`--sarif`), so the
+/// interactive checks can gate CI.
+pub fn sarif_report(config: RunConfig, files: &[PathBuf]) -> Result<SarifReport, String> {
+  let dialect = Dialect::from_option(config.dialect.as_deref().unwrap_or_default());
+  let isa = load_all_architectures(config.data_path)?;
+  sarif::build_report(&lint::analyze_files(&isa, files, config.default_architecture, dialect)?)
+}
+
+/// Runs the document diagnostics over `files` without a client (`lint`).
+pub fn lint_files(config: RunConfig, files: &[PathBuf]) -> Result<LintReport, String> {
+  let dialect = Dialect::from_option(config.dialect.as_deref().unwrap_or_default());
+  let isa = load_all_architectures(config.data_path)?;
+  Ok(lint::lint_report(&lint::analyze_files(&isa, files, config.default_architecture, dialect)?))
+}
@@ -0,0 +1,97 @@
+use crate::architecture::{architecture_filter, environment_architecture, normalize_architecture_hint};
+use crate::detection::detect_architecture;
+use crate::diagnostics::document_diagnostics;
+use crate::index::IsaIndex;
+use crate::tokenizer::Dialect;
+use std::fs;
+use std::path::PathBuf;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+/// The diagnostics of one file, as the server would publish them.
+pub struct FileDiagnostics {
+  pub path: PathBuf,
+  pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Findings of `lint`, one `path:line:column: severity: message [code]` line each.
+pub struct LintReport {
+  pub output: String,
+  pub errors: usize,
+  pub warnings: usize,
+}
+
+/// Runs the document diagnostics over each file, with the architecture its extension names, else
+/// `default_architecture` or the environment's, else the one its instructions suggest.
+pub fn analyze_files(
+  isa: &IsaIndex,
+  files: &[PathBuf],
+  default_architecture: Option<String>,
+  dialect: Dialect,
+) -> Result<Vec<FileDiagnostics>, String> {
+  // `--arch` takes target names (`gfx1100`) as well as the data's architecture names, like the server's.
+  let default_architecture = default_architecture
+    .map(|arch| normalize_architecture_hint(&arch, &isa.architecture_aliases))
+    .or_else(|| environment_architecture(&isa.architecture_aliases));
+  files
+    .iter()
+    .map(|path| {
+      let text = fs::read_to_string(path).map_err(|error| format!("Failed to read {}: {error}", path.display()))?;
+      let language_id = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_ascii_lowercase();
+      let filter = architecture_filter(&language_id, None, default_architecture.as_ref(), &isa.architecture_aliases)
+        .or_else(|| detect_architecture(&text, &isa.instructions, dialect).map(|guess| guess.architecture));
+      let diagnostics = document_diagnostics(
+        &text,
+        &isa.instructions,
+        &isa.memory_modifiers,
+        &isa.errata,
+        filter.as_deref(),
+        dialect,
+      );
+      Ok(FileDiagnostics {
+        path: path.clone(),
+        diagnostics,
+      })
+    })
+    .collect()
+}
+
+fn severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+  match severity {
+    Some(DiagnosticSeverity::ERROR) => "error",
+    Some(DiagnosticSeverity::WARNING) => "warning",
+    Some(DiagnosticSeverity::HINT) => "hint",
+    _ => "info",
+  }
+}
+
+/// Formats the findings like a compiler would, with one-based lines and columns.
+pub fn lint_report(files: &[FileDiagnostics]) -> LintReport {
+  let mut report = LintReport {
+    output: String::new(),
+    errors: 0,
+    warnings: 0,
+  };
+  for file in files {
+    for diagnostic in &file.diagnostics {
+      match diagnostic.severity {
+        Some(DiagnosticSeverity::ERROR) => report.errors += 1,
+        Some(DiagnosticSeverity::WARNING) => report.warnings += 1,
+        _ => {}
+      }
+      let start = diagnostic.range.start;
+      report.output.push_str(&format!(
+        "{}:{}:{}: {}: {}",
+        file.path.display(),
+        start.line + 1,
+        start.character + 1,
+        severity_name(diagnostic.severity),
+        diagnostic.message
+      ));
+      if let Some(NumberOrString::String(code)) = &diagnostic.code {
+        report.output.push_str(&format!(" [{code}]"));
+      }
+      report.output.push('\n');
+    }
+  }
+  report
+}
@@ -0,0 +1,196 @@
+use crate::code_size::{selected_encoding, takes_literal};
+use crate::encoding::{entry_for_variant, split_encoding_variant};
+use crate::expression::parse_integer_literal;
+use crate::packed::f16_to_f32;
+use crate::tokenizer::{InstructionLine, Token};
+use crate::types::InstructionEntry;
+
+/// Float values the hardware encodes inline, besides their negations.
+const INLINE_FLOATS: &[f64] = &[0.0, 0.5, 1.0, 2.0, 4.0, 0.15915494];
+/// Bit patterns of the inline floats (±0.5, ±1, ±2, ±4 and 1/(2π)), which an integer literal on a
+/// float operand of that width also encodes inline.
+const INLINE_F16_BITS: &[u64] = &[0x3800, 0xb800, 0x3c00, 0xbc00, 0x4000, 0xc000, 0x4400, 0xc400, 0x3118];
+const INLINE_F32_BITS: &[u64] =
+  &[0x3f000000, 0xbf000000, 0x3f800000, 0xbf800000, 0x40000000, 0xc0000000, 0x40800000, 0xc0800000, 0x3e22f983];
+const INLINE_F64_BITS: &[u64] = &[
+  0x3fe0000000000000,
+  0xbfe0000000000000,
+  0x3ff0000000000000,
+  0xbff0000000000000,
+  0x4000000000000000,
+  0xc000000000000000,
+  0x4010000000000000,
+  0xc010000000000000,
+  0x3fc45f306dc9c882,
+];
+
+/// Width of a float operand type (`f32`, `FMT_NUM_F16`, `pk2_f16`); `None` for other types.
+fn float_width(data_type: Option<&str>) -> Option<u32> {
+  let data_type = data_type?.to_ascii_lowercase();
+  match data_type.trim_start_matches("fmt_num_").trim_start_matches("pk2_") {
+    "f16" => Some(16),
+    "f32" => Some(32),
+    "f64" => Some(64),
+    _ => None,
+  }
+}
+
+/// Signed value of an integer literal, `-` included.
+fn parse_signed_integer(text: &str) -> Option<i64> {
+  match text.strip_prefix('-') {
+    Some(digits) => parse_integer_literal(digits)?.checked_neg(),
+    None => parse_integer_literal(text),
+  }
+}
+
+fn parse_float_literal(text: &str) -> Option<f64> {
+  let digits = text.strip_prefix('-').unwrap_or(text);
+  if !digits.contains('.') || !digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+    return None;
+  }
+  text.parse().ok()
+}
+
+/// Whether an operand of type `data_type` needs a trailing literal dword: an integer outside the
+/// inline constants (-16..=64) that is not, on a float operand, the bit pattern of an inline float;
+/// a float other than the inline ones; or a relocated symbol (`table@abs32@lo`).
+pub fn needs_literal(operand: &str, data_type: Option<&str>) -> bool {
+  let text = operand.trim();
+  if let Some(value) = parse_signed_integer(text) {
+    if (-16..=64).contains(&value) {
+      return false;
+    }
+    let inline_bits: &[&[u64]] = match float_width(data_type) {
+      Some(16) => &[INLINE_F16_BITS, INLINE_F32_BITS],
+      Some(32) => &[INLINE_F32_BITS],
+      Some(64) => &[INLINE_F64_BITS],
+      _ => &[],
+    };
+    let bits = if float_width(data_type) == Some(64) { value as u64 } else { value as u32 as u64 };
+    return !inline_bits.iter().any(|table| table.contains(&bits));
+  }
+  if let Some(value) = parse_float_literal(text) {
+    return !INLINE_FLOATS.iter().any(|inline| (value.abs() - inline).abs() < 1e-7);
+  }
+  text.contains('@')
+}
+
+/// The dword a literal operand encodes, so `100` and `0x64` (or `1.5` and `0x3fc00000`) count as one
+/// literal; symbols compare by name.
+fn literal_value(operand: &str, data_type: Option<&str>) -> String {
+  let text = operand.trim();
+  let bits = match (parse_signed_integer(text), parse_float_literal(text)) {
+    (Some(value), _) => Some(value as u32),
+    (None, Some(value)) if float_width(data_type) == Some(64) => Some((value.to_bits() >> 32) as u32),
+    (None, Some(value)) => Some((value as f32).to_bits()),
+    (None, None) => None,
+  };
+  match bits {
+    Some(bits) => format!("0x{bits:08x}"),
+    None => text.to_ascii_lowercase(),
+  }
+}
+
+/// `0b0011_1111_...`, grouped by nibble.
+fn grouped_binary(bits: u64, width: u32) -> String {
+  let digits = format!("{bits:0width$b}", width = width as usize);
+  let groups: Vec<&str> = digits.as_bytes().chunks(4).map(|chunk| std::str::from_utf8(chunk).unwrap_or("")).collect();
+  format!("0b{}", groups.join("_"))
+}
+
+/// The line of a literal note saying how the operand is encoded.
+fn encoding_line(text: &str, data_type: Option<&str>) -> &'static str {
+  if needs_literal(text, data_type) {
+    "- uses a 32-bit literal: the instruction grows by one dword"
+  } else {
+    "- inline constant"
+  }
+}
+
+/// Decimal, hex and binary forms of an integer immediate, with the float values of its bit
+/// pattern, and the f32 bits of a float immediate. Inline constants (-16..=64) are read as
+/// integers by the hardware, so their bits are not decoded as floats. `data_type` is the operand's
+/// type, which decides whether the value is inline.
+pub fn numeric_literal_note(text: &str, data_type: Option<&str>) -> Option<String> {
+  let text = text.trim();
+  if let Some(value) = parse_float_literal(text) {
+    let bits = (value as f32).to_bits();
+    return Some(format!("`{text}`: f32 `0x{bits:08x}`\n{}", encoding_line(text, data_type)));
+  }
+  let value = parse_signed_integer(text)?;
+  let wide = value < i32::MIN as i64 || value > u32::MAX as i64;
+  let width = if wide { 64 } else { 32 };
+  let bits = if wide { value as u64 } else { value as u32 as u64 };
+  let mut decimal = value.to_string();
+  if value < 0 {
+    decimal.push_str(&format!(" (unsigned {bits})"));
+  } else if !wide && value > i32::MAX as i64 {
+    decimal.push_str(&format!(" (signed {})", value as u32 as i32));
+  }
+  let mut lines = vec![
+    format!("`{text}`: {decimal}"),
+    format!("- hex: `0x{bits:0width$x}`", width = width as usize / 4),
+    format!("- binary: `{}`", grouped_binary(bits, width)),
+  ];
+  if !(-16..=64).contains(&value) {
+    if wide {
+      lines.push(format!("- f64: {:?}", f64::from_bits(bits)));
+    } else {
+      lines.push(format!("- f32: {:?}", f32::from_bits(bits as u32)));
+      lines.push(format!("- f16 (low half `0x{:04x}`): {:?}", bits as u16, f16_to_f32(bits as u16)));
+    }
+  }
+  lines.push(encoding_line(text, data_type).to_string());
+  Some(lines.join("\n"))
+}
+
+/// Literal operands the selected encoding cannot hold: a second distinct literal (an instruction
+/// carries one), or any literal in a VOP3/VOP3P encoding on CDNA, which predates VOP3 literals.
+pub fn literal_issues<'a>(
+  entry: &InstructionEntry,
+  parsed: &InstructionLine<'a>,
+  filter: Option<&str>,
+) -> Vec<(Token<'a>, String)> {
+  let split = split_encoding_variant(parsed.mnemonic.text);
+  let encoding = match selected_encoding(entry, parsed, &split.variant) {
+    Some(encoding) if takes_literal(&encoding) => encoding,
+    _ => return Vec::new(),
+  };
+  let entry = entry_for_variant(entry, &split.variant);
+  let data_type = |index: usize| entry.arg_data_types.get(index).map(String::as_str);
+  let literals: Vec<(Token<'a>, String)> = parsed
+    .operands
+    .iter()
+    .enumerate()
+    .filter(|(index, operand)| needs_literal(operand.text, data_type(*index)))
+    .map(|(index, operand)| (*operand, literal_value(operand.text, data_type(index))))
+    .collect();
+  let is_vop3 = encoding.trim_start_matches("ENC_").starts_with("VOP3");
+  if let Some(filter) = filter.filter(|filter| filter.starts_with("cdna") && is_vop3) {
+    return literals
+      .into_iter()
+      .map(|(literal, _)| {
+        let message = format!(
+          "`{}` needs a 32-bit literal, which VOP3 encodings cannot carry on {filter}; move it into a register first",
+          literal.text
+        );
+        (literal, message)
+      })
+      .collect();
+  }
+  let first = match literals.first() {
+    Some((_, value)) => value.clone(),
+    None => return Vec::new(),
+  };
+  literals
+    .into_iter()
+    .filter(|(_, value)| *value != first)
+    .map(|(literal, _)| {
+      let message = format!(
+        "`{}` would be a second 32-bit literal; an instruction carries only one, so move one value into a register",
+        literal.text
+      );
+      (literal, message)
+    })
+    .collect()
+}
@@ -0,0 +1,23 @@
+use crate::recovery::lock;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Where server-side log lines go instead of stderr, when `--log-file` is given.
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Appends server log lines (panics, slow requests) to `path` instead of stderr.
+pub fn set_log_file(path: &Path) -> io::Result<()> {
+  let file = OpenOptions::new().create(true).append(true).open(path)?;
+  *lock(&LOG_FILE) = Some(file);
+  Ok(())
+}
+
+/// Writes one `amdgpu-lsp: ...` line to the log file, or stderr when there is none.
+pub fn log_line(message: &str) {
+  if let Some(file) = lock(&LOG_FILE).as_mut() && writeln!(file, "amdgpu-lsp: {message}").is_ok() {
+    return;
+  }
+  eprintln!("amdgpu-lsp: {message}");
+}
@@ -1,21 +1,168 @@
-mod architecture;
-mod encoding;
-mod formatting;
-mod index;
-mod server;
-mod text_utils;
-mod types;
+use amdgpu_lsp::{lint_files, run_server, sarif_report, set_log_file, validate_data, RunConfig};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-use index::load_isa_index;
-use server::IsaServer;
-use tower_lsp::{LspService, Server};
+/// Language server for AMDGPU (RDNA/CDNA) assembly. Speaks LSP over stdio unless `--tcp` or
+/// `--pipe` is given.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+  #[command(subcommand)]
+  command: Option<Command>,
+  /// Path to isa.json (default: AMDGPU_LSP_DATA, then the embedded data, then data/isa.json)
+  #[arg(long, value_name = "PATH", global = true)]
+  data: Option<String>,
+  /// Default architecture for files whose extension names none (rdna3, gfx90a, MI300X, ...)
+  #[arg(long, value_name = "ARCH", global = true)]
+  arch: Option<String>,
+  /// Assembler dialect: `llvm` (default, `;` starts a comment), `gnu` (`;` separates statements) or `sp3`
+  #[arg(long, value_name = "DIALECT", global = true)]
+  dialect: Option<String>,
+  /// Append server logs (panics, slow requests) to this file instead of stderr
+  #[arg(long, value_name = "PATH")]
+  log_file: Option<PathBuf>,
+  /// Listen on this address (e.g. 127.0.0.1:9257) and serve the first client that connects
+  #[arg(long, value_name = "ADDR", conflicts_with = "pipe")]
+  tcp: Option<String>,
+  /// Connect to the client's named pipe (a Unix socket path on Unix)
+  #[arg(long, value_name = "NAME")]
+  pipe: Option<String>,
+  /// Worker threads. Above 1 the server runs on a multi-threaded runtime and analyzes documents and
+  /// the workspace in the background while answering hovers and completions
+  #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+  threads: u16,
+  /// Load the data, print statistics and exit
+  #[arg(long)]
+  validate_data: bool,
+  /// Run the diagnostics over these files, print them as SARIF and exit (non-zero if any is an error)
+  #[arg(long, value_name = "FILE", num_args = 1..)]
+  sarif: Vec<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// Run the diagnostics over files and print the findings. Exits with 1 if any is an error, 2 if
+  /// the files or the data cannot be loaded
+  Lint {
+    #[arg(value_name = "FILE", required = true)]
+    files: Vec<PathBuf>,
+  },
+}
+
+fn main() -> ExitCode {
+  let cli = Cli::parse();
+  let runtime = match cli.threads {
+    1 => tokio::runtime::Builder::new_current_thread().enable_all().build(),
+    threads => tokio::runtime::Builder::new_multi_thread()
+      .worker_threads(threads as usize)
+      .enable_all()
+      .build(),
+  };
+  match runtime {
+    Ok(runtime) => runtime.block_on(serve(cli)),
+    Err(error) => {
+      eprintln!("amdgpu-lsp: failed to start the runtime: {error}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+async fn serve(cli: Cli) -> ExitCode {
+  if cli.validate_data {
+    return match validate_data(cli.data) {
+      Ok(report) => {
+        println!("{report}");
+        ExitCode::SUCCESS
+      }
+      Err(error) => {
+        eprintln!("amdgpu-lsp: {error}");
+        ExitCode::FAILURE
+      }
+    };
+  }
+  if let Some(Command::Lint { files }) = &cli.command {
+    let config = RunConfig {
+      data_path: cli.data,
+      default_architecture: cli.arch,
+      dialect: cli.dialect,
+    };
+    return match lint_files(config, files) {
+      Ok(report) => {
+        print!("{}", report.output);
+        eprintln!("{} error(s), {} warning(s)", report.errors, report.warnings);
+        if report.errors == 0 { ExitCode::SUCCESS } else { ExitCode::from(1) }
+      }
+      Err(error) => {
+        eprintln!("amdgpu-lsp: {error}");
+        ExitCode::from(2)
+      }
+    };
+  }
+  if !cli.sarif.is_empty() {
+    let config = RunConfig {
+      data_path: cli.data,
+      default_architecture: cli.arch,
+      dialect: cli.dialect,
+    };
+    return match sarif_report(config, &cli.sarif) {
+      Ok(report) => {
+        println!("{}", report.json);
+        if report.errors == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+      }
+      Err(error) => {
+        eprintln!("amdgpu-lsp: {error}");
+        ExitCode::FAILURE
+      }
+    };
+  }
+  if let Some(path) = &cli.log_file && let Err(error) = set_log_file(path) {
+    eprintln!("amdgpu-lsp: failed to open log file {}: {error}", path.display());
+    return ExitCode::FAILURE;
+  }
+  let config = RunConfig {
+    data_path: cli.data,
+    default_architecture: cli.arch,
+    dialect: cli.dialect,
+  };
+  let result = match (cli.tcp, cli.pipe) {
+    (Some(address), _) => serve_tcp(&address, config).await,
+    (None, Some(name)) => serve_pipe(&name, config).await,
+    (None, None) => {
+      run_server(tokio::io::stdin(), tokio::io::stdout(), config).await;
+      Ok(())
+    }
+  };
+  match result {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(error) => {
+      eprintln!("amdgpu-lsp: {error}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+async fn serve_tcp(address: &str, config: RunConfig) -> std::io::Result<()> {
+  let listener = tokio::net::TcpListener::bind(address).await?;
+  eprintln!("amdgpu-lsp: listening on {}", listener.local_addr()?);
+  let (stream, _) = listener.accept().await?;
+  let (reader, writer) = stream.into_split();
+  run_server(reader, writer, config).await;
+  Ok(())
+}
+
+#[cfg(unix)]
+async fn serve_pipe(name: &str, config: RunConfig) -> std::io::Result<()> {
+  let stream = tokio::net::UnixStream::connect(name).await?;
+  let (reader, writer) = stream.into_split();
+  run_server(reader, writer, config).await;
+  Ok(())
+}
 
-#[tokio::main(flavor = "current_thread")]
-async fn main() {
-  let (index, special_registers, load_info) = load_isa_index();
-  let stdin = tokio::io::stdin();
-  let stdout = tokio::io::stdout();
-  let (service, socket) =
-    LspService::new(|client| IsaServer::new(client, index, special_registers, load_info));
-  Server::new(stdin, stdout, socket).serve(service).await;
+#[cfg(windows)]
+async fn serve_pipe(name: &str, config: RunConfig) -> std::io::Result<()> {
+  let pipe = tokio::net::windows::named_pipe::ClientOptions::new().open(name)?;
+  let (reader, writer) = tokio::io::split(pipe);
+  run_server(reader, writer, config).await;
+  Ok(())
 }
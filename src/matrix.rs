@@ -0,0 +1,237 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixFamily {
+  Mfma,
+  Smfmac,
+  Wmma,
+  Swmmac,
+}
+
+/// How WMMA fragments are spread across a wave32: RDNA3 replicates A/B across both half-waves,
+/// RDNA4 packs them without replication (and packs 16-bit accumulators).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WmmaLayout {
+  Replicated,
+  Packed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixShape {
+  pub family: MatrixFamily,
+  pub m: u32,
+  pub n: u32,
+  pub k: u32,
+  /// Number of independent blocks computed by one instruction (e.g. `_4b_` / legacy 4x4 forms).
+  pub blocks: u32,
+  pub a_type: String,
+  pub b_type: String,
+  pub out_type: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatrixRegisterCounts {
+  pub a: u32,
+  pub b: u32,
+  pub c: u32,
+  pub d: u32,
+}
+
+fn parse_leading_number(text: &str) -> Option<(u32, &str)> {
+  let digits = text.bytes().take_while(|b| b.is_ascii_digit()).count();
+  if digits == 0 {
+    return None;
+  }
+  Some((text[..digits].parse().ok()?, &text[digits..]))
+}
+
+pub fn element_bits(data_type: &str) -> u32 {
+  match data_type {
+    "f64" => 64,
+    "f32" | "i32" | "xf32" => 32,
+    "f16" | "bf16" => 16,
+    "i8" | "iu8" | "fp8" | "bf8" | "f8f6f4" => 8,
+    "iu4" => 4,
+    _ => 32,
+  }
+}
+
+/// Legacy (pre-gfx940) MFMA names do not spell out the block count; the multi-block variants are
+/// the ones using the smallest K for their input type.
+fn implicit_mfma_blocks(m: u32, k: u32, in_type: &str, is_1k: bool) -> u32 {
+  if m == 4 {
+    return if in_type == "f64" { 4 } else { 16 };
+  }
+  let small_k = match in_type {
+    "f32" => 1,
+    "f16" | "i8" => 4,
+    "bf16" if is_1k => 4,
+    "bf16" => 2,
+    _ => return 1,
+  };
+  if k != small_k {
+    return 1;
+  }
+  match m {
+    32 => 2,
+    16 => 4,
+    _ => 1,
+  }
+}
+
+/// Decodes the shape and types of a matrix-core instruction from its mnemonic
+/// (`v_mfma_f32_32x32x8f16`, `v_mfma_f32_32x32x1_2b_f32`, ...). The XML does not spell these out.
+pub fn parse_matrix_shape(name: &str) -> Option<MatrixShape> {
+  let lower = name.to_ascii_lowercase();
+  let (family, rest) = if let Some(rest) = lower.strip_prefix("v_mfma_") {
+    (MatrixFamily::Mfma, rest)
+  } else if let Some(rest) = lower.strip_prefix("v_smfmac_") {
+    (MatrixFamily::Smfmac, rest)
+  } else if let Some(rest) = lower.strip_prefix("v_wmma_") {
+    (MatrixFamily::Wmma, rest)
+  } else if let Some(rest) = lower.strip_prefix("v_swmmac_") {
+    (MatrixFamily::Swmmac, rest)
+  } else {
+    return None;
+  };
+  let (out_type, dims) = rest.split_once('_')?;
+  let (m, dims) = parse_leading_number(dims)?;
+  let (n, dims) = parse_leading_number(dims.strip_prefix('x')?)?;
+  let (k, suffix) = parse_leading_number(dims.strip_prefix('x')?)?;
+
+  let mut suffix = suffix.trim_start_matches('_');
+  let mut explicit_blocks = None;
+  if let Some((blocks, remainder)) = parse_leading_number(suffix)
+    && let Some(remainder) = remainder.strip_prefix("b_")
+  {
+    explicit_blocks = Some(blocks);
+    suffix = remainder;
+  }
+  let is_1k = suffix.ends_with("_1k");
+  let in_types = suffix.trim_end_matches("_1k");
+  if in_types.is_empty() {
+    return None;
+  }
+  let (a_type, b_type) = match in_types.split_once('_') {
+    Some((a, b)) => (a.to_string(), b.to_string()),
+    None => (in_types.to_string(), in_types.to_string()),
+  };
+  let blocks = match family {
+    MatrixFamily::Mfma | MatrixFamily::Smfmac => {
+      explicit_blocks.unwrap_or_else(|| implicit_mfma_blocks(m, k, &a_type, is_1k))
+    }
+    MatrixFamily::Wmma | MatrixFamily::Swmmac => 1,
+  };
+  Some(MatrixShape {
+    family,
+    m,
+    n,
+    k,
+    blocks,
+    a_type,
+    b_type,
+    out_type: out_type.to_string(),
+  })
+}
+
+fn lane_dwords(elements: u32, bits: u32, lanes: u32) -> u32 {
+  (elements * bits).div_ceil(lanes * 32).max(1)
+}
+
+pub fn is_wmma(shape: &MatrixShape) -> bool {
+  matches!(shape.family, MatrixFamily::Wmma | MatrixFamily::Swmmac)
+}
+
+pub fn wmma_layout_for_arch(arch: &str) -> Option<WmmaLayout> {
+  if arch.starts_with("rdna4") {
+    return Some(WmmaLayout::Packed);
+  }
+  if arch.starts_with("rdna3") {
+    return Some(WmmaLayout::Replicated);
+  }
+  None
+}
+
+pub fn wmma_layout_name(layout: WmmaLayout) -> &'static str {
+  match layout {
+    WmmaLayout::Replicated => "RDNA3",
+    WmmaLayout::Packed => "RDNA4",
+  }
+}
+
+/// Result ← input type combinations accepted by the WMMA family of each generation.
+pub fn wmma_type_combinations(layout: WmmaLayout) -> &'static [&'static str] {
+  match layout {
+    WmmaLayout::Replicated => &["f32 ← f16", "f32 ← bf16", "f16 ← f16", "bf16 ← bf16", "i32 ← iu8", "i32 ← iu4"],
+    WmmaLayout::Packed => &[
+      "f32 ← f16",
+      "f32 ← bf16",
+      "f16 ← f16",
+      "bf16 ← bf16",
+      "i32 ← iu8",
+      "i32 ← iu4",
+      "f32 ← fp8/bf8 (any A/B pairing)",
+    ],
+  }
+}
+
+/// Wave32 register counts for a WMMA/SWMMAC fragment layout. For SWMMAC the `c` slot is the
+/// sparsity index VGPR; the accumulator is tied to the destination.
+pub fn wmma_register_counts(shape: &MatrixShape, layout: WmmaLayout) -> MatrixRegisterCounts {
+  let lanes = 32;
+  // RDNA3 replicates A/B across both half-waves, so only 16 lanes carry distinct data.
+  let ab_lanes = match layout {
+    WmmaLayout::Replicated => 16,
+    WmmaLayout::Packed => lanes,
+  };
+  let mut a_elements = shape.m * shape.k;
+  if shape.family == MatrixFamily::Swmmac {
+    a_elements /= 2;
+  }
+  let b_elements = shape.n * shape.k;
+  let acc_elements = shape.m * shape.n;
+  let acc_bits = match layout {
+    // RDNA3 keeps 16-bit results in one half of each VGPR (selected by op_sel).
+    WmmaLayout::Replicated => 32,
+    WmmaLayout::Packed => element_bits(&shape.out_type),
+  };
+  let d = lane_dwords(acc_elements, acc_bits, lanes);
+  MatrixRegisterCounts {
+    a: lane_dwords(a_elements, element_bits(&shape.a_type), ab_lanes),
+    b: lane_dwords(b_elements, element_bits(&shape.b_type), ab_lanes),
+    c: if shape.family == MatrixFamily::Swmmac { 1 } else { d },
+    d,
+  }
+}
+
+/// Role of the Nth explicit operand (vdst, src0, src1, src2) of a matrix instruction.
+pub fn matrix_operand_role(shape: &MatrixShape, index: usize) -> Option<&'static str> {
+  match (index, shape.family) {
+    (0, MatrixFamily::Swmmac | MatrixFamily::Smfmac) => Some("D (accumulator, read and written)"),
+    (0, _) => Some("D (result)"),
+    (1, _) => Some("A matrix"),
+    (2, _) => Some("B matrix"),
+    (3, MatrixFamily::Swmmac | MatrixFamily::Smfmac) => Some("sparsity index"),
+    (3, _) => Some("C (accumulator input)"),
+    _ => None,
+  }
+}
+
+pub const SPARSITY_INDEX_NOTE: &str = "The index VGPR holds 2-bit positions of the two non-zero values in every group of four A elements (2:4 sparsity); `index_key:N` selects which slice of the VGPR is used.";
+
+/// Registers each lane contributes per operand, assuming wave64 (the only MFMA wave size).
+pub fn matrix_register_counts(shape: &MatrixShape) -> MatrixRegisterCounts {
+  let lanes = 64;
+  let mut a_elements = shape.m * shape.k * shape.blocks;
+  if shape.family == MatrixFamily::Smfmac {
+    // 2:4 structured sparsity: only half of A is stored, the rest is described by the index operand.
+    a_elements /= 2;
+  }
+  let b_elements = shape.n * shape.k * shape.blocks;
+  let acc_elements = shape.m * shape.n * shape.blocks;
+  let acc = lane_dwords(acc_elements, element_bits(&shape.out_type), lanes);
+  MatrixRegisterCounts {
+    a: lane_dwords(a_elements, element_bits(&shape.a_type), lanes),
+    b: lane_dwords(b_elements, element_bits(&shape.b_type), lanes),
+    c: acc,
+    d: acc,
+  }
+}
@@ -0,0 +1,75 @@
+use crate::architecture::arch_matches;
+use serde::Deserialize;
+
+/// Which keyword modifiers a memory instruction accepts depends on its kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryClass {
+  Ds,
+  /// Two-address DS operations (`ds_read2_b32`, `ds_store_2addr_b64`, ...), which take
+  /// `offset0:`/`offset1:` instead of `offset:`.
+  #[serde(rename = "ds_2addr")]
+  Ds2Addr,
+  Buffer,
+  /// `flat_*`, `global_*` and `scratch_*`.
+  Flat,
+  Image,
+}
+
+pub fn memory_class(mnemonic: &str) -> Option<MemoryClass> {
+  let lower = mnemonic.to_ascii_lowercase();
+  if let Some(op) = lower.strip_prefix("ds_") {
+    let two_address = op.contains("2addr")
+      || op.split('_').any(|segment| {
+        let stem = segment.strip_suffix("st64").unwrap_or(segment);
+        stem.len() > 1 && stem.ends_with('2') && stem[..stem.len() - 1].bytes().all(|b| b.is_ascii_alphabetic())
+      });
+    return Some(if two_address { MemoryClass::Ds2Addr } else { MemoryClass::Ds });
+  }
+  if lower.starts_with("buffer_") || lower.starts_with("tbuffer_") {
+    return Some(MemoryClass::Buffer);
+  }
+  if ["flat_", "global_", "scratch_"].iter().any(|prefix| lower.starts_with(prefix)) {
+    return Some(MemoryClass::Flat);
+  }
+  if lower.starts_with("image_") {
+    return Some(MemoryClass::Image);
+  }
+  None
+}
+
+/// A keyword modifier of memory instructions, from the data file's `memory_modifiers` table.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemoryModifier {
+  pub name: String,
+  /// Written as `name:value` rather than a bare flag.
+  #[serde(default)]
+  pub takes_value: bool,
+  pub classes: Vec<MemoryClass>,
+  pub architectures: Vec<String>,
+  /// Older modifiers this one supersedes on its architectures (`th:` replaces `glc`).
+  #[serde(default)]
+  pub replaces: Vec<String>,
+  pub description: String,
+}
+
+impl MemoryModifier {
+  /// Whether the modifier exists on the architecture; with no filter every architecture counts.
+  pub fn available_on(&self, filter: Option<&str>) -> bool {
+    match filter {
+      Some(filter) => self.architectures.iter().any(|arch| arch_matches(arch, filter)),
+      None => true,
+    }
+  }
+}
+
+/// Modifiers an instruction of `class` accepts on the architecture.
+pub fn memory_modifiers<'a>(
+  table: &'a [MemoryModifier],
+  class: MemoryClass,
+  filter: Option<&'a str>,
+) -> impl Iterator<Item = &'a MemoryModifier> {
+  table
+    .iter()
+    .filter(move |modifier| modifier.classes.contains(&class) && modifier.available_on(filter))
+}
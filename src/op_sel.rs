@@ -0,0 +1,129 @@
+use crate::tokenizer::{InstructionLine, Token};
+use crate::types::InstructionEntry;
+
+/// Which half selection a modifier sets: `op_sel` feeds the low half of the result, `op_sel_hi`
+/// the high half.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpSelKind {
+  Low,
+  High,
+}
+
+impl OpSelKind {
+  fn from_name(name: &str) -> Option<Self> {
+    if name.eq_ignore_ascii_case("op_sel") {
+      Some(OpSelKind::Low)
+    } else if name.eq_ignore_ascii_case("op_sel_hi") {
+      Some(OpSelKind::High)
+    } else {
+      None
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      OpSelKind::Low => "op_sel",
+      OpSelKind::High => "op_sel_hi",
+    }
+  }
+
+  /// Bit every source gets when the modifier is omitted.
+  fn default_bit(self) -> bool {
+    self == OpSelKind::High
+  }
+}
+
+/// An `op_sel:[...]` or `op_sel_hi:[...]` modifier. `bits` is `None` where an entry is not `0` or
+/// `1`.
+#[derive(Debug, Clone)]
+pub struct OpSel<'a> {
+  pub kind: OpSelKind,
+  pub token: Token<'a>,
+  pub bits: Vec<Option<bool>>,
+}
+
+pub fn parse_op_sel<'a>(token: &Token<'a>) -> Option<OpSel<'a>> {
+  let (name, value) = token.text.split_once(':')?;
+  let kind = OpSelKind::from_name(name)?;
+  let inner = value.trim().strip_prefix('[')?.strip_suffix(']')?;
+  let bits = inner
+    .split(',')
+    .map(|bit| match bit.trim() {
+      "0" => Some(false),
+      "1" => Some(true),
+      _ => None,
+    })
+    .collect();
+  Some(OpSel {
+    kind,
+    token: *token,
+    bits,
+  })
+}
+
+pub fn is_vop3p(entry: &InstructionEntry) -> bool {
+  entry.available_encodings.iter().any(|encoding| encoding == "ENC_VOP3P")
+}
+
+/// Source operands of the line: everything after the destination.
+fn source_count(parsed: &InstructionLine<'_>) -> usize {
+  parsed.operands.len().saturating_sub(1)
+}
+
+/// Problems with the `op_sel`/`op_sel_hi` modifiers of a VOP3P instruction: a vector longer than
+/// the number of sources, or entries other than `0`/`1`. Shorter vectors are fine; the assembler
+/// fills the missing entries with zeros.
+pub fn op_sel_issues<'a>(parsed: &InstructionLine<'a>) -> Vec<(Token<'a>, String)> {
+  let sources = source_count(parsed);
+  let mut issues = Vec::new();
+  for op_sel in parsed.modifiers.iter().filter_map(parse_op_sel) {
+    let name = op_sel.kind.name();
+    if op_sel.bits.iter().any(|bit| bit.is_none()) {
+      issues.push((op_sel.token, format!("`{name}` entries must be 0 or 1")));
+    } else if op_sel.bits.len() > sources {
+      issues.push((
+        op_sel.token,
+        format!(
+          "`{name}` has {} entries but `{}` has {sources} source operands",
+          op_sel.bits.len(),
+          parsed.mnemonic.text
+        ),
+      ));
+    }
+  }
+  issues
+}
+
+fn half_name(high: bool) -> &'static str {
+  if high { "high" } else { "low" }
+}
+
+/// Which half of each source feeds each half of the result, for hover on an `op_sel`/`op_sel_hi`
+/// modifier. The other modifier of the pair is read from the line or takes its default.
+pub fn op_sel_note(entry: &InstructionEntry, parsed: &InstructionLine<'_>, modifier: &Token<'_>) -> Option<String> {
+  let hovered = parse_op_sel(modifier)?;
+  let selections: Vec<OpSel<'_>> = parsed.modifiers.iter().filter_map(parse_op_sel).collect();
+  let bits_of = |kind: OpSelKind, index: usize| {
+    selections
+      .iter()
+      .find(|op_sel| op_sel.kind == kind)
+      .map_or(Some(kind.default_bit()), |op_sel| op_sel.bits.get(index).copied().unwrap_or(Some(false)))
+  };
+  let mut lines = vec![format!(
+    "`{}`: {} picks the source half that feeds the {} half of each packed result",
+    modifier.text,
+    hovered.kind.name(),
+    half_name(hovered.kind == OpSelKind::High)
+  )];
+  for (index, operand) in parsed.operands.iter().skip(1).enumerate() {
+    let slot = entry.args.get(index + 1).map(|slot| slot.as_str()).unwrap_or("source");
+    let describe = |bit: Option<bool>| bit.map_or("?", half_name);
+    lines.push(format!(
+      "- {slot} `{}`: result low ← {} half, result high ← {} half",
+      operand.text,
+      describe(bits_of(OpSelKind::Low, index)),
+      describe(bits_of(OpSelKind::High, index))
+    ));
+  }
+  Some(lines.join("\n"))
+}
@@ -0,0 +1,199 @@
+use crate::text_utils::byte_offset_to_utf16_position;
+use crate::tokenizer::{leading_label, line_comment_start, strip_leading_label, Dialect, Token, DATA_DIRECTIVES};
+use std::collections::HashSet;
+use tower_lsp::lsp_types::{DocumentSymbol, Position, Range, SymbolKind};
+
+fn code_part(line: &str, dialect: Dialect) -> &str {
+  match line_comment_start(line, dialect) {
+    Some(comment_start) => &line[..comment_start],
+    None => line,
+  }
+}
+
+/// Names declared as kernels via `.amdhsa_kernel`, `.amdgpu_hsa_kernel` or `.type name, @function`.
+pub fn collect_kernel_names(text: &str, dialect: Dialect) -> HashSet<String> {
+  let mut names = HashSet::new();
+  for line in text.lines() {
+    let mut parts = code_part(line, dialect).split_whitespace();
+    match parts.next() {
+      Some(".amdhsa_kernel") | Some(".amdgpu_hsa_kernel") => {
+        if let Some(name) = parts.next() {
+          names.insert(name.to_string());
+        }
+      }
+      Some(".type") => {
+        let rest = parts.collect::<Vec<_>>().join(" ");
+        if let Some((name, kind)) = rest.split_once(',') {
+          let kind = kind.trim();
+          if kind == "@function" || kind == "STT_AMDGPU_HSA_KERNEL" {
+            names.insert(name.trim().to_string());
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+  names
+}
+
+fn line_end(lines: &[&str], line: u32) -> Position {
+  let text = lines.get(line as usize).copied().unwrap_or_default();
+  Position {
+    line,
+    character: byte_offset_to_utf16_position(text, text.len()),
+  }
+}
+
+fn token_selection(line_idx: u32, line: &str, token: &Token<'_>) -> Range {
+  Range {
+    start: Position {
+      line: line_idx,
+      character: byte_offset_to_utf16_position(line, token.start),
+    },
+    end: Position {
+      line: line_idx,
+      character: byte_offset_to_utf16_position(line, token.end()),
+    },
+  }
+}
+
+#[allow(deprecated)]
+fn symbol(name: &str, detail: Option<String>, kind: SymbolKind, selection: Range) -> DocumentSymbol {
+  DocumentSymbol {
+    name: name.to_string(),
+    detail,
+    kind,
+    tags: None,
+    deprecated: None,
+    range: selection,
+    selection_range: selection,
+    children: None,
+  }
+}
+
+fn push_child(parent: &mut DocumentSymbol, child: DocumentSymbol) {
+  parent.children.get_or_insert_with(Vec::new).push(child);
+}
+
+struct OutlineBuilder<'a> {
+  lines: Vec<&'a str>,
+  top: Vec<DocumentSymbol>,
+  kernel: Option<DocumentSymbol>,
+  label: Option<DocumentSymbol>,
+}
+
+impl OutlineBuilder<'_> {
+  fn close_label(&mut self, end_line: u32) {
+    if let Some(mut label) = self.label.take() {
+      label.range.end = line_end(&self.lines, end_line);
+      match &mut self.kernel {
+        Some(kernel) => push_child(kernel, label),
+        None => self.top.push(label),
+      }
+    }
+  }
+
+  fn close_kernel(&mut self, end_line: u32) {
+    self.close_label(end_line);
+    if let Some(mut kernel) = self.kernel.take() {
+      kernel.range.end = line_end(&self.lines, end_line);
+      self.top.push(kernel);
+    }
+  }
+
+  /// Attaches a leaf to the innermost open label or kernel.
+  fn push_leaf(&mut self, leaf: DocumentSymbol) {
+    if let Some(label) = &mut self.label {
+      push_child(label, leaf);
+    } else if let Some(kernel) = &mut self.kernel {
+      push_child(kernel, leaf);
+    } else {
+      self.top.push(leaf);
+    }
+  }
+}
+
+/// Nested outline: kernels at the top level, their labels and macros as children, and data
+/// directives as leaves under the label they follow. Symbols before the first kernel are top level.
+pub fn document_outline(text: &str, dialect: Dialect) -> Vec<DocumentSymbol> {
+  let kernels = collect_kernel_names(text, dialect);
+  let mut builder = OutlineBuilder {
+    lines: text.lines().collect(),
+    top: Vec::new(),
+    kernel: None,
+    label: None,
+  };
+  let mut open_macro: Option<DocumentSymbol> = None;
+  let line_count = builder.lines.len();
+  for line_idx in 0..line_count {
+    let line = builder.lines[line_idx];
+    let line_idx = line_idx as u32;
+    let code = code_part(line, dialect);
+    let (directive_start, trimmed) = strip_leading_label(code);
+    let directive = trimmed.split_whitespace().next().unwrap_or_default();
+
+    if let Some(mut macro_symbol) = open_macro.take() {
+      if directive == ".endm" || directive == ".endmacro" {
+        macro_symbol.range.end = line_end(&builder.lines, line_idx);
+        builder.push_leaf(macro_symbol);
+      } else {
+        open_macro = Some(macro_symbol);
+      }
+      continue;
+    }
+
+    if directive == ".macro" {
+      let rest = trimmed[directive.len()..].trim_start();
+      let name_len = rest
+        .find(|ch: char| ch.is_whitespace() || ch == ',')
+        .unwrap_or(rest.len());
+      if name_len == 0 {
+        continue;
+      }
+      let name_start = directive_start + (trimmed.len() - rest.len());
+      let token = Token {
+        text: &rest[..name_len],
+        start: name_start,
+      };
+      let params = rest[name_len..].trim_start_matches(',').trim();
+      let detail = (!params.is_empty()).then(|| params.to_string());
+      let mut macro_symbol = symbol(token.text, detail, SymbolKind::METHOD, token_selection(line_idx, line, &token));
+      macro_symbol.range.start.character = 0;
+      // Macros sit next to labels rather than inside the section of the label before them.
+      builder.close_label(line_idx.saturating_sub(1));
+      open_macro = Some(macro_symbol);
+      continue;
+    }
+
+    if let Some(label) = leading_label(code) {
+      let selection = token_selection(line_idx, line, &label);
+      let previous_line = line_idx.saturating_sub(1);
+      if kernels.contains(label.text) {
+        builder.close_kernel(previous_line);
+        builder.kernel = Some(symbol(label.text, Some("kernel".to_string()), SymbolKind::FUNCTION, selection));
+      } else {
+        builder.close_label(previous_line);
+        builder.label = Some(symbol(label.text, None, SymbolKind::KEY, selection));
+      }
+    }
+
+    if DATA_DIRECTIVES.contains(&directive) {
+      let token = Token {
+        text: directive,
+        start: directive_start,
+      };
+      let args = trimmed[directive.len()..].trim();
+      let detail = (!args.is_empty()).then(|| args.to_string());
+      let mut leaf = symbol(directive, detail, SymbolKind::CONSTANT, token_selection(line_idx, line, &token));
+      leaf.range.end = line_end(&builder.lines, line_idx);
+      builder.push_leaf(leaf);
+    }
+  }
+  let last_line = line_count.saturating_sub(1) as u32;
+  if let Some(mut macro_symbol) = open_macro {
+    macro_symbol.range.end = line_end(&builder.lines, last_line);
+    builder.push_leaf(macro_symbol);
+  }
+  builder.close_kernel(last_line);
+  builder.top
+}
@@ -0,0 +1,100 @@
+use crate::architecture::arch_matches;
+use crate::types::{InstructionEntry, OverlayNote};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// An overlay file: notes to add to instructions, shown under `name` (the file stem by default).
+#[derive(Debug, Clone, Deserialize)]
+struct OverlayFile {
+  #[serde(default)]
+  name: Option<String>,
+  instructions: Vec<OverlayInstruction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OverlayInstruction {
+  name: String,
+  /// Architectures the notes apply to; all of them when empty.
+  #[serde(default)]
+  architectures: Vec<String>,
+  notes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct OverlayEntry {
+  architectures: Vec<String>,
+  note: OverlayNote,
+}
+
+/// Notes from the `isaOverlays` files, by lowercase mnemonic.
+#[derive(Debug, Clone, Default)]
+pub struct IsaOverlays {
+  notes: HashMap<String, Vec<OverlayEntry>>,
+}
+
+impl IsaOverlays {
+  /// Reads every overlay in `paths`, in order. Files that cannot be read or parsed are skipped and
+  /// reported in the returned errors.
+  pub fn load(paths: &[String]) -> (Self, Vec<String>) {
+    let mut overlays = Self::default();
+    let mut errors = Vec::new();
+    for path in paths {
+      let file: OverlayFile = match fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|error| error.to_string()))
+      {
+        Ok(file) => file,
+        Err(error) => {
+          errors.push(format!("Failed to load ISA overlay {path}: {error}"));
+          continue;
+        }
+      };
+      let source = file.name.unwrap_or_else(|| {
+        Path::new(path)
+          .file_stem()
+          .map(|stem| stem.to_string_lossy().into_owned())
+          .unwrap_or_else(|| path.clone())
+      });
+      for instruction in file.instructions {
+        let entries = overlays.notes.entry(instruction.name.to_ascii_lowercase()).or_default();
+        for text in instruction.notes {
+          entries.push(OverlayEntry {
+            architectures: instruction.architectures.iter().map(|arch| arch.to_ascii_lowercase()).collect(),
+            note: OverlayNote {
+              source: source.clone(),
+              text,
+            },
+          });
+        }
+      }
+    }
+    (overlays, errors)
+  }
+
+  /// `entry` with the overlay notes for its mnemonic merged in: those whose architectures (which may
+  /// be families or ranges like `rdna3+`) match the document's filter, or without a filter, any of
+  /// the entry's architectures.
+  pub fn apply<'a>(&self, entry: &'a InstructionEntry, filter: Option<&str>) -> Cow<'a, InstructionEntry> {
+    let applies = |arch: &String| match filter {
+      Some(filter) => arch_matches(arch, filter),
+      None => entry.architectures.iter().any(|entry_arch| arch_matches(arch, entry_arch)),
+    };
+    let notes: Vec<OverlayNote> = self
+      .notes
+      .get(&entry.name.to_ascii_lowercase())
+      .into_iter()
+      .flatten()
+      .filter(|overlay| overlay.architectures.is_empty() || overlay.architectures.iter().any(applies))
+      .map(|overlay| overlay.note.clone())
+      .collect();
+    if notes.is_empty() {
+      return Cow::Borrowed(entry);
+    }
+    let mut merged = entry.clone();
+    merged.overlay_notes.extend(notes);
+    Cow::Owned(merged)
+  }
+}
@@ -0,0 +1,84 @@
+use crate::expression::parse_integer_literal;
+use crate::types::InstructionEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HalfFormat {
+  F16,
+  Bf16,
+}
+
+impl HalfFormat {
+  fn name(self) -> &'static str {
+    match self {
+      HalfFormat::F16 => "f16",
+      HalfFormat::Bf16 => "bf16",
+    }
+  }
+
+  fn decode(self, bits: u16) -> f32 {
+    match self {
+      HalfFormat::F16 => f16_to_f32(bits),
+      HalfFormat::Bf16 => f32::from_bits((bits as u32) << 16),
+    }
+  }
+}
+
+pub fn f16_to_f32(bits: u16) -> f32 {
+  let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+  let exponent = ((bits >> 10) & 0x1f) as i32;
+  let mantissa = (bits & 0x3ff) as f32;
+  match exponent {
+    0 => sign * mantissa * 2f32.powi(-24),
+    0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+    0x1f => f32::NAN,
+    _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+  }
+}
+
+/// Half format of a packed operand, from its data type (`FMT_NUM_PK2_F16`) or, when the data does
+/// not say, from a `v_pk_*_f16`/`v_pk_*_bf16` mnemonic.
+fn packed_format(entry: &InstructionEntry, operand_index: usize) -> Option<HalfFormat> {
+  let data_type = entry.arg_data_types.get(operand_index).map(|data_type| data_type.to_ascii_lowercase());
+  match data_type.as_deref().map(|data_type| data_type.trim_start_matches("fmt_num_")) {
+    Some("pk2_f16") => return Some(HalfFormat::F16),
+    Some("pk2_bf16") => return Some(HalfFormat::Bf16),
+    Some("" | "b32" | "any") | None => {}
+    Some(_) => return None,
+  }
+  let name = entry.name.to_ascii_lowercase();
+  if !name.starts_with("v_pk_") {
+    return None;
+  }
+  if name.ends_with("_bf16") {
+    Some(HalfFormat::Bf16)
+  } else if name.ends_with("_f16") {
+    Some(HalfFormat::F16)
+  } else {
+    None
+  }
+}
+
+/// Both halves of a 32-bit literal used by a packed f16/bf16 operand, for hover. Integers in the
+/// inline constant range (-16..=64) are not literals and are left alone.
+pub fn packed_literal_note(entry: &InstructionEntry, operand_index: usize, text: &str) -> Option<String> {
+  let format = packed_format(entry, operand_index)?;
+  let (negative, digits) = match text.trim().strip_prefix('-') {
+    Some(digits) => (true, digits),
+    None => (false, text.trim()),
+  };
+  let magnitude = parse_integer_literal(digits)?;
+  let value = if negative { -magnitude } else { magnitude };
+  if (-16..=64).contains(&value) || value < i32::MIN as i64 || value > u32::MAX as i64 {
+    return None;
+  }
+  let bits = value as u32;
+  let low = bits as u16;
+  let high = (bits >> 16) as u16;
+  Some(format!(
+    "`{}`: packed {} — low half `0x{low:04x}` = {:?}, high half `0x{high:04x}` = {:?}",
+    text.trim(),
+    format.name(),
+    format.decode(low),
+    format.decode(high)
+  ))
+}
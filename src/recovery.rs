@@ -0,0 +1,113 @@
+use crate::logging::log_line;
+use crate::telemetry::{RequestTimer, TimingSettings};
+use futures::FutureExt;
+use serde_json::json;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, Once, PoisonError};
+use std::task::{Context, Poll};
+use tower::Service;
+use tower_lsp::jsonrpc::{Error, ErrorCode, Id, Request, Response};
+use tower_lsp::Client;
+
+thread_local! {
+  /// Backtrace of the most recent panic on this thread, picked up by `GuardRequests`.
+  static LAST_BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Logs panics with a backtrace to stderr (the client's output channel) or `--log-file`, and keeps
+/// the backtrace so the failed request's error can carry it.
+pub fn install_panic_hook() {
+  INSTALL_HOOK.call_once(|| {
+    panic::set_hook(Box::new(|info| {
+      let backtrace = Backtrace::force_capture().to_string();
+      log_line(&format!("{info}\n{backtrace}"));
+      LAST_BACKTRACE.with(|last| *last.borrow_mut() = Some(backtrace));
+    }));
+  });
+}
+
+/// Locks a mutex even if a panicking handler poisoned it; the state behind every server mutex is
+/// replaced wholesale, so a half-finished update is not a concern.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+  mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+  if let Some(message) = payload.downcast_ref::<&str>() {
+    return message.to_string();
+  }
+  if let Some(message) = payload.downcast_ref::<String>() {
+    return message.clone();
+  }
+  "unknown panic".to_string()
+}
+
+fn panic_response(request_id: Option<Id>, method: &str, payload: &(dyn std::any::Any + Send)) -> Option<Response> {
+  let backtrace = LAST_BACKTRACE.with(|last| last.borrow_mut().take());
+  let error = Error {
+    code: ErrorCode::InternalError,
+    message: format!("{method} panicked: {}", panic_message(payload)).into(),
+    data: backtrace.map(|backtrace| json!({ "backtrace": backtrace })),
+  };
+  // Notifications have no id; the panic is only logged.
+  request_id.map(|id| Response::from_error(id, error))
+}
+
+/// Service wrapper around every message: a panic in one handler becomes a JSON-RPC internal error
+/// for that request instead of taking the whole server down, and slow messages are reported.
+pub struct GuardRequests<S> {
+  inner: S,
+  timing: Arc<TimingSettings>,
+  client: Option<Client>,
+}
+
+impl<S> GuardRequests<S> {
+  pub fn new(inner: S, timing: Arc<TimingSettings>, client: Option<Client>) -> Self {
+    Self { inner, timing, client }
+  }
+}
+
+impl<S> Service<Request> for GuardRequests<S>
+where
+  S: Service<Request, Response = Option<Response>>,
+  S::Future: Send + 'static,
+  S::Error: Send + 'static,
+{
+  type Response = Option<Response>;
+  type Error = S::Error;
+  type Future = Pin<Box<dyn Future<Output = Result<Option<Response>, S::Error>> + Send>>;
+
+  fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx)
+  }
+
+  fn call(&mut self, request: Request) -> Self::Future {
+    let request_id = request.id().cloned();
+    let method = request.method().to_string();
+    let timer = RequestTimer::start(&request, self.timing.clone(), self.client.clone());
+    let future = match panic::catch_unwind(AssertUnwindSafe(|| self.inner.call(request))) {
+      Ok(future) => future,
+      Err(payload) => {
+        let response = panic_response(request_id, &method, payload.as_ref());
+        return Box::pin(async move {
+          timer.finish().await;
+          Ok(response)
+        });
+      }
+    };
+    Box::pin(async move {
+      let result = match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result,
+        Err(payload) => Ok(panic_response(request_id, &method, payload.as_ref())),
+      };
+      timer.finish().await;
+      result
+    })
+  }
+}
@@ -0,0 +1,303 @@
+use crate::tokenizer::{
+  line_comment_start, sp3_keyword, statement_ranges, strip_leading_disasm_prefix, strip_leading_label, Dialect,
+};
+use crate::vopd::parse_dual_line;
+use serde_json::Value;
+use tower_lsp::lsp_types::{Position, Range, TextEdit};
+
+/// Layout applied by document formatting, from the `formatting` initialization option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatSettings {
+  /// Column of mnemonics and directives; labels stay at column 0.
+  pub indent: usize,
+  /// Column operands start at, or one space after a longer mnemonic.
+  pub operand_column: usize,
+  /// Column trailing comments start at, or one space after longer code.
+  pub comment_column: usize,
+  pub lowercase_mnemonics: bool,
+}
+
+impl Default for FormatSettings {
+  fn default() -> Self {
+    Self {
+      indent: 4,
+      operand_column: 24,
+      comment_column: 60,
+      lowercase_mnemonics: false,
+    }
+  }
+}
+
+impl FormatSettings {
+  pub fn from_value(value: &Value) -> Self {
+    let defaults = Self::default();
+    let column = |key: &str, default: usize| {
+      value.get(key).and_then(|value| value.as_u64()).map_or(default, |value| value as usize)
+    };
+    Self {
+      indent: column("indent", defaults.indent),
+      operand_column: column("operandColumn", defaults.operand_column),
+      comment_column: column("commentColumn", defaults.comment_column),
+      lowercase_mnemonics: value
+        .get("lowercaseMnemonics")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(defaults.lowercase_mnemonics),
+    }
+  }
+}
+
+/// Pads `out` with spaces to `column`, or adds one space when it already reaches it.
+fn pad_to(out: &mut String, column: usize) {
+  let width = out.chars().count();
+  let spaces = if width < column { column - width } else { 1 };
+  out.extend(std::iter::repeat_n(' ', spaces));
+}
+
+/// Operands with no space before commas and exactly one after. Commas inside brackets or parentheses
+/// (`op_sel:[0,1]`, `hwreg(HW_REG_MODE, 0, 4)`) belong to one operand and are left alone.
+fn normalize_commas(operands: &str) -> String {
+  let mut out = String::with_capacity(operands.len());
+  let mut depth = 0usize;
+  let mut start = 0;
+  let mut split = Vec::new();
+  for (index, c) in operands.char_indices() {
+    match c {
+      '[' | '(' => depth += 1,
+      ']' | ')' => depth = depth.saturating_sub(1),
+      ',' if depth == 0 => {
+        split.push(&operands[start..index]);
+        start = index + 1;
+      }
+      _ => {}
+    }
+  }
+  split.push(&operands[start..]);
+  let mut parts = split.into_iter().map(str::trim).peekable();
+  while let Some(part) = parts.next() {
+    out.push_str(part);
+    if parts.peek().is_some() {
+      out.push(',');
+      if parts.peek().is_some_and(|next| !next.is_empty()) {
+        out.push(' ');
+      }
+    }
+  }
+  out
+}
+
+/// Appends a statement's mnemonic and operands. Operands start at the operand column when `aligned`, else one space
+/// after the mnemonic.
+fn push_statement(out: &mut String, statement: &str, settings: &FormatSettings, dialect: Dialect, aligned: bool) {
+  let mnemonic_len = statement.find(char::is_whitespace).unwrap_or(statement.len());
+  let (mnemonic, operands) = statement.split_at(mnemonic_len);
+  let operands = operands.trim();
+  // Directives, `name = value` assignments and VOPD pairs keep their spacing; only instructions
+  // get an operand column.
+  let is_instruction = !mnemonic.starts_with('.')
+    && !operands.starts_with('=')
+    && sp3_keyword(statement, dialect).is_none()
+    && parse_dual_line(statement, dialect).is_none();
+  if is_instruction && settings.lowercase_mnemonics {
+    out.push_str(&mnemonic.to_ascii_lowercase());
+  } else {
+    out.push_str(mnemonic);
+  }
+  if !operands.is_empty() {
+    if is_instruction {
+      if aligned {
+        pad_to(out, settings.operand_column);
+      } else {
+        out.push(' ');
+      }
+      out.push_str(&normalize_commas(operands));
+    } else {
+      out.push(' ');
+      out.push_str(operands);
+    }
+  }
+}
+
+/// The line laid out by `settings`, or `None` to leave it alone: disassembly listings (address
+/// and encoding prefixes) and block comments keep their own layout. With the GNU dialect, the
+/// statements after the first follow it as `; statement`.
+fn format_line(line: &str, settings: &FormatSettings, dialect: Dialect) -> Option<String> {
+  let code_end = line_comment_start(line, dialect).unwrap_or(line.len());
+  let (code, comment) = line.split_at(code_end);
+  let comment = comment.trim_end();
+  if code.contains("/*") || comment.contains("/*") {
+    return None;
+  }
+  if code.trim().is_empty() {
+    // A comment-only line keeps column 0 when it has it, and follows the code indent otherwise.
+    if comment.is_empty() || !line.starts_with(char::is_whitespace) {
+      return Some(comment.to_string());
+    }
+    return Some(format!("{}{comment}", " ".repeat(settings.indent)));
+  }
+  let mut statements = statement_ranges(code, dialect)
+    .into_iter()
+    .map(|(start, end)| &code[start..end])
+    .filter(|statement| !statement.trim().is_empty());
+  let first = statements.next().unwrap_or_default();
+  let (label_end, statement) = strip_leading_label(first);
+  let (prefix_len, _) = strip_leading_disasm_prefix(statement);
+  if prefix_len != statement.len() - statement.trim_start().len() {
+    return None;
+  }
+  let mut out = String::new();
+  let label = first[..label_end].trim();
+  if !label.is_empty() {
+    out.push_str(label);
+  }
+  let statement = statement.trim();
+  if !statement.is_empty() {
+    // SP3 `shader`/`end` open and close a block, so they stay at column 0 like labels.
+    if !matches!(sp3_keyword(statement, dialect), Some("shader" | "end")) {
+      pad_to(&mut out, settings.indent);
+    }
+    push_statement(&mut out, statement, settings, dialect, true);
+  }
+  for statement in statements {
+    out.push_str("; ");
+    let (label_end, instruction) = strip_leading_label(statement);
+    let label = statement[..label_end].trim();
+    let instruction = instruction.trim();
+    out.push_str(label);
+    if !label.is_empty() && !instruction.is_empty() {
+      out.push(' ');
+    }
+    push_statement(&mut out, instruction, settings, dialect, false);
+  }
+  if !comment.is_empty() {
+    if out.is_empty() {
+      out.push_str(comment);
+    } else {
+      pad_to(&mut out, settings.comment_column);
+      out.push_str(comment);
+    }
+  }
+  Some(out)
+}
+
+/// Edits laying out the lines of `range` (the whole document when `None`), one per changed line.
+pub fn format_document(text: &str, range: Option<Range>, settings: &FormatSettings, dialect: Dialect) -> Vec<TextEdit> {
+  let (first, last) = match range {
+    Some(range) => (range.start.line as usize, range.end.line as usize),
+    None => (0, usize::MAX),
+  };
+  let mut edits = Vec::new();
+  for (line_idx, line) in text.lines().enumerate().skip(first) {
+    if line_idx > last {
+      break;
+    }
+    let formatted = match format_line(line, settings, dialect) {
+      Some(formatted) if formatted != line => formatted,
+      _ => continue,
+    };
+    let end = line.encode_utf16().count() as u32;
+    edits.push(TextEdit::new(
+      Range::new(Position::new(line_idx as u32, 0), Position::new(line_idx as u32, end)),
+      formatted,
+    ));
+  }
+  edits
+}
+
+fn utf16_len(text: &str) -> u32 {
+  text.encode_utf16().count() as u32
+}
+
+/// Edits turning `line` into `formatted` that only touch the whitespace runs and characters that
+/// differ, so a cursor in the line stays put. Falls back to replacing the whole line.
+fn minimal_edits(line_idx: u32, line: &str, formatted: &str) -> Vec<TextEdit> {
+  let position = |text: &str, byte: usize| Position::new(line_idx, utf16_len(&text[..byte]));
+  let whitespace_end = |text: &str, from: usize| {
+    text[from..].find(|c: char| !c.is_whitespace()).map_or(text.len(), |offset| from + offset)
+  };
+  let mut edits: Vec<TextEdit> = Vec::new();
+  let mut push = |start: usize, end: usize, new_text: &str| {
+    let range = Range::new(position(line, start), position(line, end));
+    match edits.last_mut() {
+      Some(last) if last.range.end == range.start => {
+        last.range.end = range.end;
+        last.new_text.push_str(new_text);
+      }
+      _ => edits.push(TextEdit::new(range, new_text.to_string())),
+    }
+  };
+  let (mut old, mut new) = (0, 0);
+  loop {
+    let (old_end, new_end) = (whitespace_end(line, old), whitespace_end(formatted, new));
+    if line[old..old_end] != formatted[new..new_end] {
+      push(old, old_end, &formatted[new..new_end]);
+    }
+    (old, new) = (old_end, new_end);
+    match (line[old..].chars().next(), formatted[new..].chars().next()) {
+      (None, None) => break,
+      (Some(a), Some(b)) if a.eq_ignore_ascii_case(&b) => {
+        if a != b {
+          push(old, old + a.len_utf8(), &b.to_string());
+        }
+        old += a.len_utf8();
+        new += b.len_utf8();
+      }
+      _ => {
+        let range = Range::new(Position::new(line_idx, 0), position(line, line.len()));
+        return vec![TextEdit::new(range, formatted.to_string())];
+      }
+    }
+  }
+  edits
+}
+
+/// Edits for typing `ch` at `position`: after a `,` the line's commas get their canonical spacing
+/// (and a space after the new one), and after a newline the finished line is laid out and the new
+/// one indented.
+pub fn format_on_type(
+  text: &str,
+  position: Position,
+  ch: &str,
+  settings: &FormatSettings,
+  dialect: Dialect,
+) -> Vec<TextEdit> {
+  let lines: Vec<&str> = text.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line)).collect();
+  let line_idx = position.line as usize;
+  match ch {
+    "," => {
+      let line = match lines.get(line_idx) {
+        Some(line) => *line,
+        None => return Vec::new(),
+      };
+      // Casing is left to the newline, so typing a comma never rewrites the mnemonic under the cursor.
+      let settings = FormatSettings {
+        lowercase_mnemonics: false,
+        ..settings.clone()
+      };
+      let mut formatted = match format_line(line, &settings, dialect) {
+        Some(formatted) => formatted,
+        None => return Vec::new(),
+      };
+      if formatted.ends_with(',') && line_comment_start(&formatted, dialect).is_none() {
+        formatted.push(' ');
+      }
+      minimal_edits(position.line, line, &formatted)
+    }
+    "\n" if line_idx > 0 => {
+      let mut edits = match lines.get(line_idx - 1) {
+        Some(previous) => match format_line(previous, settings, dialect) {
+          Some(formatted) => minimal_edits(position.line - 1, previous, &formatted),
+          None => Vec::new(),
+        },
+        None => Vec::new(),
+      };
+      let current = lines.get(line_idx).copied().unwrap_or_default();
+      let indent = " ".repeat(settings.indent);
+      if current.trim().is_empty() && current != indent {
+        let end = Position::new(position.line, utf16_len(current));
+        edits.push(TextEdit::new(Range::new(Position::new(position.line, 0), end), indent));
+      }
+      edits
+    }
+    _ => Vec::new(),
+  }
+}
@@ -0,0 +1,183 @@
+use crate::code_size::instruction_bytes;
+use crate::outline::collect_kernel_names;
+use crate::registers::{parse_register, RegisterClass, RegisterRange};
+use crate::symbols::collect_macro_names;
+use crate::tokenizer::{leading_label, parse_instruction_statements, Dialect};
+use crate::types::InstructionEntry;
+use crate::vopd::statement_operations;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+/// Parameters of the `amdgpu/registerUsage` request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterUsageParams {
+  pub text_document: TextDocumentIdentifier,
+}
+
+/// Registers one kernel touches, from the operands of the instructions between its label and the
+/// next kernel's, and the size of that code.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KernelRegisterUsage {
+  pub name: String,
+  pub line: u32,
+  /// Byte range of the kernel label within its line.
+  #[serde(skip)]
+  pub start: usize,
+  #[serde(skip)]
+  pub end: usize,
+  /// Highest VGPR index used plus one.
+  pub vgprs: u32,
+  pub sgprs: u32,
+  pub agprs: u32,
+  /// Instructions in the kernel, macro invocations excluded.
+  pub instructions: u32,
+  /// Estimated code size from the encodings the instructions select.
+  pub bytes: u32,
+}
+
+/// One kernel of the `amdgpu/registerUsage` result.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KernelUsageReport {
+  #[serde(flatten)]
+  pub usage: KernelRegisterUsage,
+  pub estimated_waves: Option<u32>,
+}
+
+/// Register file limits of an architecture family, per SIMD.
+struct OccupancyModel {
+  vgpr_file: u32,
+  vgpr_granule: u32,
+  max_waves: u32,
+  /// SGPRs only limit occupancy on GCN/CDNA; RDNA gives every wave its full set.
+  sgpr_file: Option<u32>,
+  sgpr_granule: u32,
+  /// CDNA allocates AGPRs from the same file, after the VGPRs.
+  unified_agprs: bool,
+}
+
+fn occupancy_model(filter: &str) -> Option<OccupancyModel> {
+  let filter = filter.trim_end_matches('+');
+  if filter.starts_with("cdna") {
+    return Some(OccupancyModel {
+      vgpr_file: 512,
+      vgpr_granule: 8,
+      max_waves: 8,
+      sgpr_file: Some(800),
+      sgpr_granule: 16,
+      unified_agprs: true,
+    });
+  }
+  // Wave32 allocation; RDNA3 and later have the larger register file on their big dies.
+  let large_file = ["rdna3", "rdna4"].iter().any(|prefix| filter.starts_with(prefix));
+  if filter.starts_with("rdna") {
+    return Some(OccupancyModel {
+      vgpr_file: if large_file { 1536 } else { 1024 },
+      vgpr_granule: if large_file { 24 } else { 8 },
+      max_waves: if large_file { 16 } else { 20 },
+      sgpr_file: None,
+      sgpr_granule: 0,
+      unified_agprs: false,
+    });
+  }
+  None
+}
+
+/// Estimated waves per SIMD the kernel's register use allows on the filtered architecture.
+pub fn estimated_waves(usage: &KernelRegisterUsage, filter: Option<&str>) -> Option<u32> {
+  let model = occupancy_model(filter?)?;
+  let vgprs = if model.unified_agprs && usage.agprs > 0 {
+    usage.vgprs.next_multiple_of(4) + usage.agprs
+  } else {
+    usage.vgprs
+  };
+  let mut waves = model.max_waves;
+  if vgprs > 0 {
+    waves = waves.min(model.vgpr_file / vgprs.next_multiple_of(model.vgpr_granule));
+  }
+  if let Some(sgpr_file) = model.sgpr_file {
+    // VCC and the other trailing system SGPRs are allocated with the kernel's own.
+    let sgprs = (usage.sgprs + 6).next_multiple_of(model.sgpr_granule);
+    waves = waves.min(sgpr_file / sgprs);
+  }
+  Some(waves)
+}
+
+/// Code lens text such as `vgprs: 84, sgprs: 32, est. occupancy: 6 waves`.
+pub fn usage_summary(usage: &KernelRegisterUsage, filter: Option<&str>) -> String {
+  let mut parts = vec![format!("vgprs: {}", usage.vgprs), format!("sgprs: {}", usage.sgprs)];
+  if usage.agprs > 0 {
+    parts.push(format!("agprs: {}", usage.agprs));
+  }
+  if let Some(waves) = estimated_waves(usage, filter) {
+    parts.push(format!("est. occupancy: {waves} {}", if waves == 1 { "wave" } else { "waves" }));
+  }
+  parts.join(", ")
+}
+
+/// Code lens text such as `212 instructions, ~1240 bytes`.
+pub fn size_summary(usage: &KernelRegisterUsage) -> String {
+  let noun = if usage.instructions == 1 { "instruction" } else { "instructions" };
+  format!("{} {noun}, ~{} bytes", usage.instructions, usage.bytes)
+}
+
+/// Register named by an operand, looking through `-`, `|v1|`, `abs()`/`neg()` and `.l`/`.h` halves.
+fn operand_register(operand: &str) -> Option<RegisterRange> {
+  let mut text = operand.trim().trim_start_matches('-');
+  for wrapper in ["abs(", "neg(", "sext("] {
+    if let Some(inner) = text.strip_prefix(wrapper).and_then(|inner| inner.strip_suffix(')')) {
+      text = inner;
+    }
+  }
+  let text = text.trim_matches('|');
+  let text = text.strip_suffix(".l").or_else(|| text.strip_suffix(".h")).unwrap_or(text);
+  parse_register(text)
+}
+
+/// Highest register of each class used by every kernel of the document, and its code size.
+pub fn analyze_register_usage(
+  text: &str,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  filter: Option<&str>,
+  dialect: Dialect,
+) -> Vec<KernelRegisterUsage> {
+  let kernel_names = collect_kernel_names(text, dialect);
+  let macros = collect_macro_names(text, dialect);
+  let mut kernels: Vec<KernelRegisterUsage> = Vec::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    if let Some(label) = leading_label(line) && kernel_names.contains(label.text) {
+      kernels.push(KernelRegisterUsage {
+        name: label.text.to_string(),
+        line: line_idx as u32,
+        start: label.start,
+        end: label.end(),
+        ..KernelRegisterUsage::default()
+      });
+    }
+    let kernel = match kernels.last_mut() {
+      Some(kernel) => kernel,
+      None => continue,
+    };
+    for parsed in parse_instruction_statements(line, dialect) {
+      if !macros.contains(&parsed.mnemonic.text.to_ascii_lowercase()) {
+        kernel.instructions += 1;
+        kernel.bytes += instruction_bytes(line, &parsed, index, filter, dialect);
+      }
+      let operations = statement_operations(line, parsed, dialect);
+      let operands = operations.iter().flat_map(|operation| &operation.operands);
+      for register in operands.filter_map(|operand| operand_register(operand.text)) {
+        let count = match register.class {
+          RegisterClass::Vector => &mut kernel.vgprs,
+          RegisterClass::Scalar => &mut kernel.sgprs,
+          RegisterClass::Accumulator => &mut kernel.agprs,
+          RegisterClass::TrapTemp => continue,
+        };
+        *count = (*count).max(register.last + 1);
+      }
+    }
+  }
+  kernels
+}
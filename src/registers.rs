@@ -0,0 +1,246 @@
+use crate::architecture::arch_matches;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterClass {
+  Vector,
+  Scalar,
+  Accumulator,
+  TrapTemp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterRange {
+  pub class: RegisterClass,
+  pub first: u32,
+  pub last: u32,
+}
+
+/// Parses `v12`, `s[0:1]`, `a[4]`, `acc[0:3]`, `ttmp[0:1]` style register operands.
+pub fn parse_register(text: &str) -> Option<RegisterRange> {
+  let lower = text.trim().to_ascii_lowercase();
+  let (class, rest) = if let Some(rest) = lower.strip_prefix("ttmp") {
+    (RegisterClass::TrapTemp, rest)
+  } else if let Some(rest) = lower.strip_prefix('v') {
+    (RegisterClass::Vector, rest)
+  } else if let Some(rest) = lower.strip_prefix('s') {
+    (RegisterClass::Scalar, rest)
+  } else if let Some(rest) = lower.strip_prefix("acc").or_else(|| lower.strip_prefix('a')) {
+    (RegisterClass::Accumulator, rest)
+  } else {
+    return None;
+  };
+  if let Some(inner) = rest.strip_prefix('[').and_then(|inner| inner.strip_suffix(']')) {
+    let (first, last) = match inner.split_once(':') {
+      Some((first, last)) => (first.trim().parse().ok()?, last.trim().parse().ok()?),
+      None => {
+        let single = inner.trim().parse().ok()?;
+        (single, single)
+      }
+    };
+    if last < first {
+      return None;
+    }
+    return Some(RegisterRange { class, first, last });
+  }
+  if rest.is_empty() || !rest.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+  let index = rest.parse().ok()?;
+  Some(RegisterRange {
+    class,
+    first: index,
+    last: index,
+  })
+}
+
+impl RegisterClass {
+  /// Number of registers of the class a wave can address.
+  fn count(self) -> u32 {
+    match self {
+      RegisterClass::Vector | RegisterClass::Accumulator => 256,
+      RegisterClass::Scalar => 106,
+      RegisterClass::TrapTemp => 16,
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      RegisterClass::Vector => "VGPR",
+      RegisterClass::Scalar => "SGPR",
+      RegisterClass::Accumulator => "AGPR",
+      RegisterClass::TrapTemp => "trap temporary",
+    }
+  }
+}
+
+impl RegisterRange {
+  pub fn dwords(&self) -> u32 {
+    self.last - self.first + 1
+  }
+
+  /// Whether the two ranges share a register.
+  pub fn overlaps(&self, other: &RegisterRange) -> bool {
+    self.class == other.class && self.first <= other.last && other.first <= self.last
+  }
+}
+
+/// Problem with a register operand: an index past the end of its register file, or an
+/// accumulator register on an architecture without AGPRs.
+pub fn register_issue(register: &RegisterRange, filter: Option<&str>) -> Option<String> {
+  if register.class == RegisterClass::Accumulator
+    && let Some(filter) = filter.filter(|filter| !filter.starts_with("cdna"))
+  {
+    return Some(format!("accumulator registers (AGPRs) do not exist on {filter}; they are CDNA only"));
+  }
+  let count = register.class.count();
+  (register.last >= count).then(|| {
+    format!(
+      "{} index {} is out of range; the last {} is {}",
+      register.class.name(),
+      register.last,
+      register.class.name(),
+      count - 1
+    )
+  })
+}
+
+/// Register the first of a tuple must be a multiple of: SGPR and trap temporary tuples are
+/// even-aligned, and quad-aligned from four dwords; VGPR and AGPR tuples are even-aligned on CDNA2
+/// and later. `None` when that depends on an unknown architecture.
+pub fn tuple_alignment(register: &RegisterRange, filter: Option<&str>) -> Option<u32> {
+  if register.dwords() == 1 {
+    return Some(1);
+  }
+  match register.class {
+    RegisterClass::Scalar | RegisterClass::TrapTemp => Some(if register.dwords() >= 4 { 4 } else { 2 }),
+    RegisterClass::Vector | RegisterClass::Accumulator => {
+      Some(if arch_matches(filter?, "cdna2+") { 2 } else { 1 })
+    }
+  }
+}
+
+/// Width and alignment of a bracketed register tuple (`v[4:7]`, `s[0:1]`).
+fn tuple_note(text: &str, register: &RegisterRange, filter: Option<&str>) -> String {
+  let prefix = match register.class {
+    RegisterClass::Vector => "v",
+    RegisterClass::Scalar => "s",
+    RegisterClass::Accumulator => "a",
+    RegisterClass::TrapTemp => "ttmp",
+  };
+  let dwords = register.dwords();
+  let registers = if dwords == 1 {
+    format!("{prefix}{}", register.first)
+  } else {
+    format!("tuple {prefix}{}–{prefix}{}", register.first, register.last)
+  };
+  let mut note = format!(
+    "`{text}`: {} {registers}, {dwords} {} ({} bits).",
+    register.class.name(),
+    if dwords == 1 { "dword" } else { "dwords" },
+    dwords * 32
+  );
+  match tuple_alignment(register, filter) {
+    Some(1) if dwords > 1 => note.push_str(&format!(
+      " {} tuples need no alignment on {}.",
+      register.class.name(),
+      filter.unwrap_or_default()
+    )),
+    Some(1) => {}
+    Some(alignment) if register.first.is_multiple_of(alignment) => {
+      note.push_str(&format!(" Aligned: must start at a multiple of {alignment}."))
+    }
+    Some(alignment) => note.push_str(&format!(
+      " **Misaligned**: must start at a multiple of {alignment}, not {prefix}{}.",
+      register.first
+    )),
+    None => note.push_str(" VGPR and AGPR tuples are even-aligned on CDNA2 and later."),
+  }
+  note
+}
+
+/// What a register operand is, for hover: accumulator registers are explained, and bracketed
+/// tuples get their width and alignment. Other single registers need no explanation.
+pub fn register_note(text: &str, filter: Option<&str>) -> Option<String> {
+  let register = parse_register(text)?;
+  let mut parts = Vec::new();
+  if register.class == RegisterClass::Accumulator {
+    let registers = if register.first == register.last {
+      format!("a{}", register.first)
+    } else {
+      format!("a{}–a{} ({} dwords)", register.first, register.last, register.dwords())
+    };
+    parts.push(format!(
+      "`{text}`: accumulator VGPRs {registers}. CDNA matrix instructions take sources and accumulate \
+       results here; `v_accvgpr_read_b32`/`v_accvgpr_write_b32` move values to and from VGPRs"
+    ));
+  }
+  if text.contains('[') {
+    parts.push(tuple_note(text.trim(), &register, filter));
+  }
+  (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// Register file an operand slot takes, from its field name: `VDST`/`VADDR` are vector, `SDST`/
+/// `SBASE` scalar, and the generic `SRCn` of vector ALU ops take either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotFile {
+  Vector,
+  Scalar,
+  Either,
+}
+
+pub fn slot_file(slot: &str, arg_type: &str) -> SlotFile {
+  let slot = slot.to_ascii_uppercase();
+  if arg_type == "register_or_inline" || slot.starts_with('V') {
+    SlotFile::Vector
+  } else if slot.starts_with('S') && !slot.starts_with("SRC") {
+    SlotFile::Scalar
+  } else {
+    SlotFile::Either
+  }
+}
+
+/// Named scalar registers and their width in dwords.
+const SCALAR_SPECIAL_REGISTERS: &[(&str, u32)] =
+  &[("vcc", 2), ("exec", 2), ("vcc_lo", 1), ("vcc_hi", 1), ("exec_lo", 1), ("exec_hi", 1), ("m0", 1), ("null", 1)];
+
+/// Registers `dwords` wide that can fill an operand of the given file, aligned as the
+/// architecture requires, each with a short detail: VGPRs, then SGPRs, then named scalar registers.
+/// `null` is offered on RDNA only, at any width.
+pub fn register_candidates(file: SlotFile, dwords: u32, filter: Option<&str>) -> Vec<(String, String)> {
+  let dwords = dwords.max(1);
+  let mut candidates = Vec::new();
+  let detail = |class: &str| {
+    if dwords == 1 {
+      class.to_string()
+    } else {
+      format!("{class}, {dwords} dwords")
+    }
+  };
+  let mut push_file = |class: RegisterClass, prefix: &str, count: u32| {
+    let range = RegisterRange { class, first: 0, last: dwords - 1 };
+    let alignment = tuple_alignment(&range, filter).unwrap_or(1) as usize;
+    for first in (0..count.saturating_sub(dwords - 1)).step_by(alignment) {
+      let label = if dwords == 1 {
+        format!("{prefix}{first}")
+      } else {
+        format!("{prefix}[{first}:{}]", first + dwords - 1)
+      };
+      candidates.push((label, detail(class.name())));
+    }
+  };
+  if file != SlotFile::Scalar {
+    push_file(RegisterClass::Vector, "v", 256);
+  }
+  if file != SlotFile::Vector {
+    push_file(RegisterClass::Scalar, "s", 106);
+    let rdna = filter.is_none_or(|filter| !filter.starts_with("cdna"));
+    for &(name, width) in SCALAR_SPECIAL_REGISTERS {
+      let fits = if name == "null" { rdna } else { width == dwords };
+      if fits {
+        candidates.push((name.to_string(), detail("special register")));
+      }
+    }
+  }
+  candidates
+}
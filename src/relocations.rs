@@ -0,0 +1,216 @@
+use crate::tokenizer::{is_label_char, is_label_start, line_comment_start, string_literal_ranges, Dialect, Token};
+use std::collections::HashSet;
+
+/// Relocation specifiers the assembler accepts after a symbol, with the ELF relocation each emits.
+pub const RELOCATION_SPECIFIERS: &[(&str, &str, &str)] = &[
+  (
+    "@rel32@lo",
+    "R_AMDGPU_REL32_LO",
+    "Low 32 bits of the PC-relative offset `symbol + addend - P`, where P is the address of the 32-bit literal \
+     being patched. In the `s_getpc_b64` idiom the `+4` addend makes it relative to the PC that `s_getpc_b64` \
+     returned; pair it with `@rel32@hi` on the following `s_addc_u32`.",
+  ),
+  (
+    "@rel32@hi",
+    "R_AMDGPU_REL32_HI",
+    "High 32 bits of the PC-relative offset `symbol + addend - P`, added with carry after the matching \
+     `@rel32@lo`. Its literal sits 8 bytes later, hence the usual `+12` addend.",
+  ),
+  (
+    "@gotpcrel",
+    "R_AMDGPU_GOTPCREL",
+    "PC-relative offset of the symbol's GOT entry; load the symbol address from the GOT with `s_load_dwordx2`.",
+  ),
+  (
+    "@gotpcrel32@lo",
+    "R_AMDGPU_GOTPCREL32_LO",
+    "Low 32 bits of the PC-relative offset of the symbol's GOT entry; pair it with `@gotpcrel32@hi`.",
+  ),
+  (
+    "@gotpcrel32@hi",
+    "R_AMDGPU_GOTPCREL32_HI",
+    "High 32 bits of the PC-relative offset of the symbol's GOT entry, after the matching `@gotpcrel32@lo`.",
+  ),
+  (
+    "@abs32@lo",
+    "R_AMDGPU_ABS32_LO",
+    "Low 32 bits of the symbol's absolute address; pair it with `@abs32@hi`.",
+  ),
+  (
+    "@abs32@hi",
+    "R_AMDGPU_ABS32_HI",
+    "High 32 bits of the symbol's absolute address.",
+  ),
+  ("@rel64", "R_AMDGPU_REL64", "64-bit PC-relative offset of the symbol, for data directives."),
+  ("@abs64", "R_AMDGPU_ABS64", "64-bit absolute address of the symbol, for data directives."),
+];
+
+/// A `symbol@specifier` operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelocationOperand<'a> {
+  pub symbol: Token<'a>,
+  /// The specifier including its leading `@` (`@rel32@lo`).
+  pub specifier: Token<'a>,
+}
+
+impl RelocationOperand<'_> {
+  /// Relocation type and description of a known specifier.
+  pub fn info(&self) -> Option<(&'static str, &'static str)> {
+    RELOCATION_SPECIFIERS
+      .iter()
+      .find(|(name, _, _)| name.eq_ignore_ascii_case(self.specifier.text))
+      .map(|&(_, relocation, description)| (relocation, description))
+  }
+}
+
+/// `symbol@specifier` operands on a line, outside comments and string literals.
+pub fn line_relocations(line: &str, dialect: Dialect) -> Vec<RelocationOperand<'_>> {
+  let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+  let strings = string_literal_ranges(code, dialect);
+  let bytes = code.as_bytes();
+  let mut out = Vec::new();
+  let mut idx = 0;
+  while idx < bytes.len() {
+    let starts_word = is_label_start(bytes[idx]) && (idx == 0 || !is_label_char(bytes[idx - 1]));
+    if !starts_word {
+      idx += 1;
+      continue;
+    }
+    let start = idx;
+    while idx < bytes.len() && is_label_char(bytes[idx]) {
+      idx += 1;
+    }
+    if idx >= bytes.len() || bytes[idx] != b'@' {
+      continue;
+    }
+    let symbol_end = idx;
+    while idx < bytes.len() && (bytes[idx] == b'@' || bytes[idx].is_ascii_alphanumeric()) {
+      idx += 1;
+    }
+    if strings.iter().any(|&(string_start, string_end)| string_start <= start && start < string_end) {
+      continue;
+    }
+    out.push(RelocationOperand {
+      symbol: Token {
+        text: &code[start..symbol_end],
+        start,
+      },
+      specifier: Token {
+        text: &code[symbol_end..idx],
+        start: symbol_end,
+      },
+    });
+  }
+  out
+}
+
+/// The relocation operand whose specifier contains the byte.
+pub fn relocation_at(line: &str, byte: usize, dialect: Dialect) -> Option<RelocationOperand<'_>> {
+  line_relocations(line, dialect)
+    .into_iter()
+    .find(|relocation| relocation.specifier.start <= byte && byte <= relocation.specifier.end())
+}
+
+/// Lines after a `@lo` half within which its `@hi` half is expected.
+const PAIR_WINDOW: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelocationIssueKind {
+  UnknownSpecifier,
+  /// A `@lo`/`@hi` half without its partner, or paired with a different symbol or relocation.
+  Unpaired,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelocationIssue {
+  pub line: u32,
+  /// Byte range of the whole `symbol@specifier` within its line.
+  pub start: usize,
+  pub end: usize,
+  pub kind: RelocationIssueKind,
+  pub message: String,
+}
+
+/// The relocation family and half of a split 32-bit specifier: `@rel32@lo` is (`@rel32`, lo).
+fn split_half(specifier: &str) -> Option<(String, bool)> {
+  let lower = specifier.to_ascii_lowercase();
+  if let Some(family) = lower.strip_suffix("@lo") {
+    return Some((family.to_string(), true));
+  }
+  lower.strip_suffix("@hi").map(|family| (family.to_string(), false))
+}
+
+/// Unknown specifiers, and `@lo`/`@hi` halves (`@rel32`, `@gotpcrel32`, `@abs32`) whose partner
+/// for the same symbol is missing from the nearby lines. Lines in `skip` are not checked.
+pub fn analyze_relocations(text: &str, skip: &HashSet<u32>, dialect: Dialect) -> Vec<RelocationIssue> {
+  let mut halves = Vec::new();
+  let mut issues = Vec::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    let line_idx = line_idx as u32;
+    if skip.contains(&line_idx) {
+      continue;
+    }
+    for relocation in line_relocations(line, dialect) {
+      let operand = format!("{}{}", relocation.symbol.text, relocation.specifier.text);
+      if relocation.info().is_none() {
+        issues.push(RelocationIssue {
+          line: line_idx,
+          start: relocation.symbol.start,
+          end: relocation.specifier.end(),
+          kind: RelocationIssueKind::UnknownSpecifier,
+          message: format!("Unknown relocation specifier `{}` in `{operand}`", relocation.specifier.text),
+        });
+        continue;
+      }
+      if let Some((family, low)) = split_half(relocation.specifier.text) {
+        halves.push((line_idx, relocation, family, low));
+      }
+    }
+  }
+
+  for (position, (line, relocation, family, low)) in halves.iter().enumerate() {
+    // The other halves within the window, nearest first.
+    let nearby: Vec<_> = if *low {
+      halves[position + 1..]
+        .iter()
+        .take_while(|(other_line, ..)| other_line - line <= PAIR_WINDOW)
+        .filter(|(.., other_low)| !other_low)
+        .collect()
+    } else {
+      halves[..position]
+        .iter()
+        .rev()
+        .take_while(|(other_line, ..)| line - other_line <= PAIR_WINDOW)
+        .filter(|(.., other_low)| *other_low)
+        .collect()
+    };
+    if nearby
+      .iter()
+      .any(|(_, other, other_family, _)| other.symbol.text == relocation.symbol.text && other_family == family)
+    {
+      continue;
+    }
+    let other_half = if *low { "hi" } else { "lo" };
+    let mut message = format!(
+      "`{}{}` has no matching `{}{family}@{other_half}` nearby; both halves are needed to form the 64-bit value",
+      relocation.symbol.text, relocation.specifier.text, relocation.symbol.text
+    );
+    if let Some((other_line, other, ..)) = nearby.first() {
+      message.push_str(&format!(
+        " (the nearest {other_half} half is `{}{}` on line {})",
+        other.symbol.text,
+        other.specifier.text,
+        other_line + 1
+      ));
+    }
+    issues.push(RelocationIssue {
+      line: *line,
+      start: relocation.symbol.start,
+      end: relocation.specifier.end(),
+      kind: RelocationIssueKind::Unpaired,
+      message,
+    });
+  }
+  issues.sort_by_key(|issue| (issue.line, issue.start));
+  issues
+}
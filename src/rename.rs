@@ -0,0 +1,115 @@
+use crate::symbols::collect_symbol_definitions;
+use crate::tokenizer::{
+  is_label_char, is_label_start, leading_label, line_comment_start, string_literal_ranges, strip_leading_label, Dialect,
+};
+
+/// Why an occurrence of a symbol changes in a rename, shown as the edit's change annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OccurrenceKind {
+  /// The `name:` label or `.set`/`.equ`/`=` assignment.
+  Definition,
+  /// The target operand of a branch or call.
+  BranchReference,
+  /// Any other use: instruction operands, expressions and directives (`.quad`, `.globl`, `.size`).
+  DataReference,
+}
+
+impl OccurrenceKind {
+  pub const ALL: [OccurrenceKind; 3] = [
+    OccurrenceKind::Definition,
+    OccurrenceKind::BranchReference,
+    OccurrenceKind::DataReference,
+  ];
+
+  pub fn annotation_id(self) -> &'static str {
+    match self {
+      OccurrenceKind::Definition => "definition",
+      OccurrenceKind::BranchReference => "branch reference",
+      OccurrenceKind::DataReference => "data reference",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolOccurrence {
+  pub line: u32,
+  /// Byte range of the name within its line.
+  pub start: usize,
+  pub end: usize,
+  pub kind: OccurrenceKind,
+}
+
+pub fn is_branch_mnemonic(mnemonic: &str) -> bool {
+  let lower = mnemonic.to_ascii_lowercase();
+  ["s_branch", "s_cbranch_", "s_call_"].iter().any(|prefix| lower.starts_with(prefix))
+}
+
+pub fn is_symbol_name(name: &str) -> bool {
+  let bytes = name.as_bytes();
+  !bytes.is_empty() && is_label_start(bytes[0]) && bytes.iter().all(|&b| is_label_char(b))
+}
+
+/// Every occurrence of a label or constant in the document, outside comments and string literals.
+pub fn symbol_occurrences(text: &str, name: &str, dialect: Dialect) -> Vec<SymbolOccurrence> {
+  let definitions = collect_symbol_definitions(text, dialect);
+  let mut occurrences = Vec::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    let line_idx = line_idx as u32;
+    let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+    let mut definition_start = None;
+    if let Some(label) = leading_label(code).filter(|label| label.text == name) {
+      definition_start = Some(label.start);
+    }
+    if let Some(definition) = definitions
+      .iter()
+      .find(|definition| definition.line == line_idx && definition.name == name)
+    {
+      definition_start = Some(definition.start);
+    }
+    if let Some(start) = definition_start {
+      occurrences.push(SymbolOccurrence {
+        line: line_idx,
+        start,
+        end: start + name.len(),
+        kind: OccurrenceKind::Definition,
+      });
+    }
+
+    let (statement_start, statement) = strip_leading_label(code);
+    let first_word_len = statement
+      .find(|ch: char| ch.is_whitespace() || ch == ',')
+      .unwrap_or(statement.len());
+    let kind = if is_branch_mnemonic(&statement[..first_word_len]) {
+      OccurrenceKind::BranchReference
+    } else {
+      OccurrenceKind::DataReference
+    };
+    let strings = string_literal_ranges(code, dialect);
+    let bytes = code.as_bytes();
+    let mut idx = statement_start + first_word_len;
+    while idx < bytes.len() {
+      let starts_word = is_label_start(bytes[idx]) && (idx == 0 || !is_label_char(bytes[idx - 1]));
+      if !starts_word {
+        idx += 1;
+        continue;
+      }
+      let start = idx;
+      while idx < bytes.len() && is_label_char(bytes[idx]) {
+        idx += 1;
+      }
+      if &code[start..idx] != name || Some(start) == definition_start {
+        continue;
+      }
+      if strings.iter().any(|&(string_start, string_end)| string_start <= start && start < string_end) {
+        continue;
+      }
+      occurrences.push(SymbolOccurrence {
+        line: line_idx,
+        start,
+        end: idx,
+        kind,
+      });
+    }
+  }
+  occurrences
+}
@@ -0,0 +1,23 @@
+use crate::logging::log_line;
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+/// Runs CPU-heavy analysis (diagnostics, workspace scans). On a multi-threaded runtime it moves to
+/// the blocking pool so interactive requests keep being answered meanwhile; on a current-thread
+/// runtime it runs inline, as every handler does. `None` if the analysis panicked.
+pub async fn run_analysis<T, F>(analysis: F) -> Option<T>
+where
+  T: Send + 'static,
+  F: FnOnce() -> T + Send + 'static,
+{
+  if Handle::current().runtime_flavor() != RuntimeFlavor::MultiThread {
+    return Some(analysis());
+  }
+  match tokio::task::spawn_blocking(analysis).await {
+    Ok(value) => Some(value),
+    Err(error) => {
+      // The panic hook has already logged the panic itself.
+      log_line(&format!("background analysis failed: {error}"));
+      None
+    }
+  }
+}
@@ -0,0 +1,87 @@
+use crate::diagnostics::DIAGNOSTIC_SOURCE;
+use crate::lint::FileDiagnostics;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+/// Rule id of diagnostics that carry no code.
+const GENERIC_RULE: &str = "diagnostic";
+
+/// SARIF log of the diagnostics of a set of files.
+pub struct SarifReport {
+  pub json: String,
+  /// Results at `error` level, for failing CI runs.
+  pub errors: usize,
+}
+
+fn rule_id(diagnostic: &Diagnostic) -> String {
+  match &diagnostic.code {
+    Some(NumberOrString::String(code)) => code.clone(),
+    Some(NumberOrString::Number(code)) => code.to_string(),
+    None => GENERIC_RULE.to_string(),
+  }
+}
+
+fn level(severity: Option<DiagnosticSeverity>) -> &'static str {
+  match severity {
+    Some(DiagnosticSeverity::ERROR) => "error",
+    Some(DiagnosticSeverity::WARNING) => "warning",
+    _ => "note",
+  }
+}
+
+/// A SARIF result. LSP positions are zero-based UTF-16 offsets; SARIF regions are one-based in the
+/// same unit, its default `columnKind`.
+fn sarif_result(uri: &str, diagnostic: &Diagnostic) -> Value {
+  let range = diagnostic.range;
+  json!({
+    "ruleId": rule_id(diagnostic),
+    "level": level(diagnostic.severity),
+    "message": { "text": diagnostic.message },
+    "locations": [{
+      "physicalLocation": {
+        "artifactLocation": { "uri": uri },
+        "region": {
+          "startLine": range.start.line + 1,
+          "startColumn": range.start.character + 1,
+          "endLine": range.end.line + 1,
+          "endColumn": range.end.character + 1,
+        },
+      },
+    }],
+  })
+}
+
+/// SARIF log of the diagnostics of each file.
+pub fn build_report(files: &[FileDiagnostics]) -> Result<SarifReport, String> {
+  let mut results = Vec::new();
+  let mut rules = BTreeSet::new();
+  let mut errors = 0;
+  for file in files {
+    let uri = file.path.to_string_lossy().replace('\\', "/");
+    for diagnostic in &file.diagnostics {
+      if diagnostic.severity == Some(DiagnosticSeverity::ERROR) {
+        errors += 1;
+      }
+      rules.insert(rule_id(diagnostic));
+      results.push(sarif_result(&uri, diagnostic));
+    }
+  }
+  let rules: Vec<Value> = rules.into_iter().map(|id| json!({ "id": id })).collect();
+  let log = json!({
+    "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+    "version": "2.1.0",
+    "runs": [{
+      "tool": {
+        "driver": {
+          "name": DIAGNOSTIC_SOURCE,
+          "version": env!("CARGO_PKG_VERSION"),
+          "rules": rules,
+        },
+      },
+      "results": results,
+    }],
+  });
+  let json = serde_json::to_string_pretty(&log).map_err(|error| error.to_string())?;
+  Ok(SarifReport { json, errors })
+}
@@ -0,0 +1,99 @@
+use crate::logging::log_line;
+use crate::recovery::lock;
+use futures::FutureExt;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+type Job = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+#[derive(Default)]
+struct Shared {
+  /// Pending background jobs in submission order, each under a key (`diagnostics <uri>`).
+  queue: Mutex<VecDeque<(String, Job)>>,
+  work: Notify,
+  /// Interactive requests being handled; background jobs wait until there are none.
+  interactive: AtomicUsize,
+  idle: Notify,
+}
+
+/// Runs background analyses one at a time on a worker task. Interactive requests (hover,
+/// completion, signature help) hold an [`InteractiveGuard`]; the worker does not start a job while
+/// any is held, so a burst of typing never queues behind diagnostics. A job submitted under the key
+/// of a pending one replaces it, since only the latest document state matters.
+#[derive(Clone)]
+pub struct Scheduler {
+  shared: Arc<Shared>,
+}
+
+impl Scheduler {
+  /// Spawns the worker on the current tokio runtime.
+  pub fn start() -> Self {
+    let shared = Arc::new(Shared::default());
+    tokio::spawn(worker(shared.clone()));
+    Self { shared }
+  }
+
+  pub fn submit(&self, key: String, job: impl Future<Output = ()> + Send + 'static) {
+    let mut queue = lock(&self.shared.queue);
+    queue.retain(|(pending, _)| *pending != key);
+    queue.push_back((key, Box::pin(job)));
+    drop(queue);
+    self.shared.work.notify_one();
+  }
+
+  /// Marks an interactive request as in flight until the guard is dropped.
+  pub fn interactive(&self) -> InteractiveGuard {
+    self.shared.interactive.fetch_add(1, Ordering::SeqCst);
+    InteractiveGuard {
+      shared: self.shared.clone(),
+    }
+  }
+}
+
+pub struct InteractiveGuard {
+  shared: Arc<Shared>,
+}
+
+impl Drop for InteractiveGuard {
+  fn drop(&mut self) {
+    if self.shared.interactive.fetch_sub(1, Ordering::SeqCst) == 1 {
+      self.shared.idle.notify_waiters();
+    }
+  }
+}
+
+async fn wait_for_idle(shared: &Shared) {
+  loop {
+    let idle = shared.idle.notified();
+    tokio::pin!(idle);
+    // Register before checking so a guard dropped in between still wakes us.
+    idle.as_mut().enable();
+    if shared.interactive.load(Ordering::SeqCst) == 0 {
+      return;
+    }
+    idle.await;
+  }
+}
+
+async fn worker(shared: Arc<Shared>) {
+  loop {
+    let next = lock(&shared.queue).pop_front();
+    let (key, job) = match next {
+      Some(next) => next,
+      None => {
+        shared.work.notified().await;
+        continue;
+      }
+    };
+    wait_for_idle(&shared).await;
+    if AssertUnwindSafe(job).catch_unwind().await.is_err() {
+      // The panic hook has already logged the panic itself.
+      log_line(&format!("background job `{key}` panicked"));
+    }
+  }
+}
@@ -0,0 +1,124 @@
+use crate::expression::parse_integer_literal;
+use crate::kernel::{access_bytes, parse_kernel_descriptors, KernelDescriptor, SegmentIssue};
+use crate::outline::collect_kernel_names;
+use crate::registers::{parse_register, RegisterClass};
+use crate::tokenizer::{leading_label, parse_instruction_statements, Dialect, InstructionLine};
+
+/// Per-lane scratch (private segment) usage of one kernel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KernelScratch {
+  pub name: String,
+  /// `.amdhsa_private_segment_fixed_size` from the kernel descriptor.
+  pub private_segment_size: Option<u64>,
+  /// End (offset + access size) of the furthest constant-offset scratch access.
+  pub max_access_end: Option<u64>,
+  pub access_count: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScratchAnalysis {
+  pub kernels: Vec<KernelScratch>,
+  pub issues: Vec<SegmentIssue>,
+}
+
+impl ScratchAnalysis {
+  pub fn kernel(&self, name: &str) -> Option<&KernelScratch> {
+    self.kernels.iter().find(|kernel| kernel.name == name)
+  }
+}
+
+/// Whether the instruction accesses scratch: `scratch_*`, or a `buffer_*` access through the
+/// private segment buffer resource in s[0:3].
+fn is_scratch_access(parsed: &InstructionLine<'_>, mnemonic: &str, descriptor: Option<&KernelDescriptor>) -> bool {
+  if mnemonic.starts_with("scratch_") {
+    return true;
+  }
+  if !mnemonic.starts_with("buffer_") || !descriptor.is_some_and(|descriptor| descriptor.private_segment_buffer) {
+    return false;
+  }
+  parsed
+    .operands
+    .get(2)
+    .and_then(|operand| parse_register(operand.text))
+    .is_some_and(|register| register.class == RegisterClass::Scalar && register.first == 0 && register.last == 3)
+}
+
+/// Reserved per-lane scratch bytes, or `None` when the descriptor does not say.
+fn reserved_scratch(descriptor: &KernelDescriptor) -> Option<u64> {
+  if descriptor.uses_dynamic_stack {
+    return None;
+  }
+  match descriptor.private_segment_fixed_size {
+    Some(size) => Some(size),
+    None if descriptor.private_segment_enabled => None,
+    None => Some(0),
+  }
+}
+
+/// Compares constant scratch offsets with each kernel's declared private segment size and flags
+/// scratch accesses in kernels whose descriptor reserves no scratch.
+pub fn analyze_scratch(text: &str, dialect: Dialect) -> ScratchAnalysis {
+  let kernel_names = collect_kernel_names(text, dialect);
+  let descriptors = parse_kernel_descriptors(text);
+  let mut analysis = ScratchAnalysis::default();
+  let mut current: Option<usize> = None;
+  for (line_idx, line) in text.lines().enumerate() {
+    if let Some(label) = leading_label(line) && kernel_names.contains(label.text) {
+      analysis.kernels.push(KernelScratch {
+        name: label.text.to_string(),
+        private_segment_size: descriptors
+          .get(label.text)
+          .and_then(|descriptor| descriptor.private_segment_fixed_size),
+        ..KernelScratch::default()
+      });
+      current = Some(analysis.kernels.len() - 1);
+    }
+    for parsed in parse_instruction_statements(line, dialect) {
+      let kernel = match current {
+        Some(index) => &mut analysis.kernels[index],
+        None => continue,
+      };
+      let descriptor = descriptors.get(&kernel.name);
+      let mnemonic = parsed.mnemonic.text.to_ascii_lowercase();
+      if !is_scratch_access(&parsed, &mnemonic, descriptor) {
+        continue;
+      }
+      kernel.access_count += 1;
+      let reserved = descriptor.and_then(reserved_scratch);
+      if reserved == Some(0) {
+        analysis.issues.push(SegmentIssue {
+          line: line_idx as u32,
+          start: parsed.mnemonic.start,
+          end: parsed.mnemonic.end(),
+          message: format!(
+            "Scratch access in kernel `{}`, but its descriptor reserves no private segment",
+            kernel.name
+          ),
+        });
+        continue;
+      }
+      let (token, value) = match parsed.modifier_value("offset") {
+        Some(offset) => offset,
+        None => continue,
+      };
+      let (offset, element) = match (parse_integer_literal(value), access_bytes(&mnemonic)) {
+        (Some(offset), Some(element)) => (offset as u64, element),
+        _ => continue,
+      };
+      let end = offset + element;
+      kernel.max_access_end = Some(kernel.max_access_end.map_or(end, |current| current.max(end)));
+      if let Some(size) = reserved && end > size {
+        analysis.issues.push(SegmentIssue {
+          line: line_idx as u32,
+          start: token.start,
+          end: token.end(),
+          message: format!(
+            "Scratch access reaches byte {end} but kernel `{}` only reserves {size} bytes per lane",
+            kernel.name
+          ),
+        });
+      }
+    }
+  }
+  analysis
+}
@@ -0,0 +1,238 @@
+use crate::types::InstructionEntry;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Words too common in descriptions to say anything about a match.
+const STOP_WORDS: &[&str] = &[
+  "a", "an", "and", "as", "by", "for", "from", "in", "into", "is", "it", "of", "on", "or", "the", "to", "with",
+];
+
+/// Characters of description shown around the first match.
+const SNIPPET_LENGTH: usize = 160;
+
+/// One hit of `amdgpu.searchInstructions`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+  pub name: String,
+  pub architectures: Vec<String>,
+  pub score: u32,
+  /// Part of the description around the best matching sentence, or empty when only the name matched.
+  pub snippet: String,
+}
+
+/// Lowercase alphanumeric words of `text`, without stop words.
+pub fn search_terms(text: &str) -> Vec<String> {
+  text
+    .split(|c: char| !c.is_ascii_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(|word| word.to_ascii_lowercase())
+    .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+    .collect()
+}
+
+/// Score of one description word against a query term: whole words beat prefixes (`bit` in
+/// `bits`), and short terms only match whole words.
+fn word_score(word: &str, term: &str) -> u32 {
+  if word == term {
+    3
+  } else if term.len() >= 3 && word.starts_with(term) {
+    1
+  } else {
+    0
+  }
+}
+
+/// Score of a mnemonic part (`ff1` in `s_ff1_i32`) against a query term. Every name match counts
+/// more than any description match.
+fn name_part_score(part: &str, term: &str) -> u32 {
+  if part == term {
+    12
+  } else if term.len() >= 3 && part.starts_with(term) {
+    6
+  } else {
+    0
+  }
+}
+
+/// Words and the documents containing them, with the words kept sorted for prefix lookups.
+#[derive(Default)]
+struct Postings {
+  words: Vec<String>,
+  documents: Vec<Vec<u32>>,
+}
+
+impl Postings {
+  fn build(map: HashMap<String, Vec<u32>>) -> Self {
+    let mut pairs: Vec<(String, Vec<u32>)> = map.into_iter().collect();
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    let (words, documents) = pairs.into_iter().unzip();
+    Self { words, documents }
+  }
+
+  /// Words starting with `term` (including `term` itself) and their documents.
+  fn with_prefix<'a>(&'a self, term: &'a str) -> impl Iterator<Item = (&'a str, &'a [u32])> {
+    let first = self.words.partition_point(|word| word.as_str() < term);
+    self.words[first..]
+      .iter()
+      .zip(&self.documents[first..])
+      .take_while(move |(word, _)| word.starts_with(term))
+      .map(|(word, documents)| (word.as_str(), documents.as_slice()))
+  }
+}
+
+/// Inverted index over instruction names and descriptions, built once at load time so a query
+/// only touches the documents sharing a word with it.
+#[derive(Default)]
+pub struct SearchIndex {
+  /// Lowercase mnemonics, indexed by document id.
+  mnemonics: Vec<String>,
+  description_words: Postings,
+  name_parts: Postings,
+}
+
+impl SearchIndex {
+  pub fn build(index: &HashMap<String, Vec<InstructionEntry>>) -> Self {
+    let mut mnemonics: Vec<&String> = index.keys().collect();
+    mnemonics.sort();
+    let mut description_words: HashMap<String, Vec<u32>> = HashMap::new();
+    let mut name_parts: HashMap<String, Vec<u32>> = HashMap::new();
+    for (document, mnemonic) in mnemonics.iter().enumerate() {
+      let document = document as u32;
+      let mut words: Vec<String> = index[*mnemonic]
+        .iter()
+        .filter_map(|entry| entry.description.as_deref())
+        .flat_map(search_terms)
+        .collect();
+      words.sort();
+      words.dedup();
+      for word in words {
+        description_words.entry(word).or_default().push(document);
+      }
+      let mut parts: Vec<&str> = mnemonic.split('_').filter(|part| !part.is_empty()).collect();
+      parts.sort();
+      parts.dedup();
+      for part in parts {
+        name_parts.entry(part.to_string()).or_default().push(document);
+      }
+    }
+    Self {
+      mnemonics: mnemonics.into_iter().cloned().collect(),
+      description_words: Postings::build(description_words),
+      name_parts: Postings::build(name_parts),
+    }
+  }
+
+  /// Instructions whose names or descriptions match the free-text `query`, best first. Entries are
+  /// restricted to architectures accepted by `matches_filter`.
+  pub fn search(
+    &self,
+    index: &HashMap<String, Vec<InstructionEntry>>,
+    query: &str,
+    matches_filter: impl Fn(&InstructionEntry) -> bool,
+    limit: usize,
+  ) -> Vec<SearchResult> {
+    let terms = search_terms(query);
+    if terms.is_empty() {
+      return Vec::new();
+    }
+    // Per document: name score, description score and number of terms its description matched.
+    let mut scores: HashMap<u32, (u32, u32, u32)> = HashMap::new();
+    for term in &terms {
+      let mut name_best: HashMap<u32, u32> = HashMap::new();
+      for (part, documents) in self.name_parts.with_prefix(term) {
+        let score = name_part_score(part, term);
+        for &document in documents {
+          let best = name_best.entry(document).or_default();
+          *best = (*best).max(score);
+        }
+      }
+      let mut description_best: HashMap<u32, u32> = HashMap::new();
+      for (word, documents) in self.description_words.with_prefix(term) {
+        let score = word_score(word, term);
+        for &document in documents {
+          let best = description_best.entry(document).or_default();
+          *best = (*best).max(score);
+        }
+      }
+      for (document, score) in name_best {
+        scores.entry(document).or_default().0 += score;
+      }
+      for (document, score) in description_best.into_iter().filter(|(_, score)| *score > 0) {
+        let entry = scores.entry(document).or_default();
+        entry.1 += score;
+        entry.2 += 1;
+      }
+    }
+    let whole_name = query.trim().to_ascii_lowercase();
+    if let Ok(document) = self.mnemonics.binary_search(&whole_name) {
+      // The whole query naming the instruction ranks it first.
+      scores.entry(document as u32).or_default().0 = 100;
+    }
+
+    let mut ranked: Vec<(u32, &str, Vec<&InstructionEntry>)> = scores
+      .into_iter()
+      .filter(|(_, (name_score, _, matched_terms))| *name_score > 0 || *matched_terms > 0)
+      .filter_map(|(document, (name_score, description_score, matched_terms))| {
+        let mnemonic = self.mnemonics[document as usize].as_str();
+        let entries: Vec<&InstructionEntry> =
+          index.get(mnemonic)?.iter().filter(|entry| matches_filter(entry)).collect();
+        if entries.is_empty() {
+          return None;
+        }
+        // Matching more of the query outweighs matching some of it repeatedly.
+        let score = name_score + description_score + 4 * matched_terms * matched_terms;
+        Some((score, mnemonic, entries))
+      })
+      .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    ranked.truncate(limit);
+    ranked
+      .into_iter()
+      .map(|(score, _, entries)| {
+        let description = entries.iter().find_map(|entry| entry.description.as_deref()).unwrap_or_default();
+        let mut architectures: Vec<String> = entries.iter().flat_map(|entry| entry.architectures.clone()).collect();
+        architectures.sort();
+        architectures.dedup();
+        SearchResult {
+          name: entries[0].name.clone(),
+          architectures,
+          score,
+          snippet: snippet(description, &terms),
+        }
+      })
+      .collect()
+  }
+}
+
+/// The sentence of `description` matching the most terms, cut to `SNIPPET_LENGTH` characters
+/// around its first match; empty when no term matches.
+fn snippet(description: &str, terms: &[String]) -> String {
+  let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+  let sentences: Vec<&str> = description.split_inclusive(". ").collect();
+  // `max_by_key` keeps the last of equal sentences; walk backwards so the earliest one wins.
+  let sentence = sentences
+    .into_iter()
+    .rev()
+    .max_by_key(|sentence| {
+      let words = search_terms(sentence);
+      terms.iter().filter(|term| words.iter().any(|word| word_score(word, term) > 0)).count()
+    })
+    .unwrap_or_default()
+    .trim();
+  let words = search_terms(sentence);
+  if !terms.iter().any(|term| words.iter().any(|word| word_score(word, term) > 0)) {
+    return String::new();
+  }
+  if sentence.chars().count() <= SNIPPET_LENGTH {
+    return sentence.to_string();
+  }
+  let lower = sentence.to_ascii_lowercase();
+  let first_match = terms.iter().filter_map(|term| lower.find(term.as_str())).min().unwrap_or(0);
+  let mut start = first_match.saturating_sub(SNIPPET_LENGTH / 4);
+  while !sentence.is_char_boundary(start) {
+    start -= 1;
+  }
+  let excerpt: String = sentence[start..].chars().take(SNIPPET_LENGTH).collect();
+  let prefix = if start > 0 { "…" } else { "" };
+  format!("{prefix}{}…", excerpt.trim())
+}
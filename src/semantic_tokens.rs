@@ -0,0 +1,131 @@
+use crate::symbols::collect_symbol_definitions;
+use crate::text_utils::byte_offset_to_utf16_position;
+use crate::tokenizer::{
+  is_label_char, is_label_start, leading_label, line_comment_start, string_literal_ranges, strip_leading_label, Dialect,
+};
+use std::collections::HashSet;
+use tower_lsp::lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
+
+// Indices into the legend below.
+const LABEL: u32 = 0;
+const VARIABLE: u32 = 1;
+const DECLARATION: u32 = 1 << 0;
+const READONLY: u32 = 1 << 1;
+
+/// Only symbols get semantic tokens; mnemonics, registers and directives are left to the grammar.
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+  SemanticTokensLegend {
+    token_types: vec![SemanticTokenType::new("label"), SemanticTokenType::VARIABLE],
+    token_modifiers: vec![SemanticTokenModifier::DECLARATION, SemanticTokenModifier::READONLY],
+  }
+}
+
+struct RawToken {
+  line: u32,
+  start: usize,
+  end: usize,
+  token_type: u32,
+  modifiers: u32,
+}
+
+/// Labels (`declaration` where defined) and `.set`/`.equ`/`=` constants (`readonly`, plus
+/// `declaration` where assigned), delta-encoded for `textDocument/semanticTokens/full`.
+pub fn document_semantic_tokens(text: &str, dialect: Dialect) -> Vec<SemanticToken> {
+  let definitions = collect_symbol_definitions(text, dialect);
+  let constants: HashSet<&str> = definitions.iter().map(|definition| definition.name.as_str()).collect();
+  let lines: Vec<&str> = text.lines().collect();
+  let labels: HashSet<&str> = lines
+    .iter()
+    .filter_map(|line| leading_label(&line[..line_comment_start(line, dialect).unwrap_or(line.len())]))
+    .map(|label| label.text)
+    .collect();
+
+  let mut raw = Vec::new();
+  for (line_idx, line) in lines.iter().enumerate() {
+    let line_idx = line_idx as u32;
+    let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+    let mut line_tokens = Vec::new();
+    if let Some(label) = leading_label(code) {
+      line_tokens.push(RawToken {
+        line: line_idx,
+        start: label.start,
+        end: label.end(),
+        token_type: LABEL,
+        modifiers: DECLARATION,
+      });
+    }
+    for definition in definitions.iter().filter(|definition| definition.line == line_idx) {
+      line_tokens.push(RawToken {
+        line: line_idx,
+        start: definition.start,
+        end: definition.end,
+        token_type: VARIABLE,
+        modifiers: DECLARATION | READONLY,
+      });
+    }
+
+    // References: identifiers after the mnemonic or directive, outside string literals.
+    let (statement_start, statement) = strip_leading_label(code);
+    let first_word_len = statement
+      .find(|ch: char| ch.is_whitespace() || ch == ',')
+      .unwrap_or(statement.len());
+    let strings = string_literal_ranges(code, dialect);
+    let bytes = code.as_bytes();
+    let mut idx = statement_start + first_word_len;
+    while idx < bytes.len() {
+      let starts_word = is_label_start(bytes[idx]) && (idx == 0 || !is_label_char(bytes[idx - 1]));
+      if !starts_word {
+        idx += 1;
+        continue;
+      }
+      let start = idx;
+      while idx < bytes.len() && is_label_char(bytes[idx]) {
+        idx += 1;
+      }
+      if strings.iter().any(|&(string_start, string_end)| string_start <= start && start < string_end) {
+        continue;
+      }
+      if line_tokens.iter().any(|token| token.start <= start && start < token.end) {
+        continue;
+      }
+      let word = &code[start..idx];
+      let (token_type, modifiers) = if labels.contains(word) {
+        (LABEL, 0)
+      } else if constants.contains(word) {
+        (VARIABLE, READONLY)
+      } else {
+        continue;
+      };
+      line_tokens.push(RawToken {
+        line: line_idx,
+        start,
+        end: idx,
+        token_type,
+        modifiers,
+      });
+    }
+    line_tokens.sort_by_key(|token| token.start);
+    raw.extend(line_tokens);
+  }
+
+  let mut out = Vec::with_capacity(raw.len());
+  let mut previous_line = 0;
+  let mut previous_start = 0;
+  for token in raw {
+    let line = lines[token.line as usize];
+    let start = byte_offset_to_utf16_position(line, token.start);
+    let end = byte_offset_to_utf16_position(line, token.end);
+    let delta_line = token.line - previous_line;
+    let delta_start = if delta_line == 0 { start - previous_start } else { start };
+    out.push(SemanticToken {
+      delta_line,
+      delta_start,
+      length: end - start,
+      token_type: token.token_type,
+      token_modifiers_bitset: token.modifiers,
+    });
+    previous_line = token.line;
+    previous_start = start;
+  }
+  out
+}
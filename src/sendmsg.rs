@@ -0,0 +1,197 @@
+use crate::expression::parse_integer_literal;
+use crate::hwreg::family_matches;
+use crate::tokenizer::InstructionLine;
+
+/// An operation of a message that takes one (`GS_OP_EMIT` of `MSG_GS`).
+struct MessageOp {
+  name: &'static str,
+  id: u32,
+  description: &'static str,
+}
+
+/// A message `s_sendmsg`/`s_sendmsg_rtn` can send.
+struct Message {
+  name: &'static str,
+  id: u32,
+  /// Generations or families that have the message.
+  families: &'static [&'static str],
+  ops: &'static [MessageOp],
+  /// Whether the message takes a GS stream id.
+  stream: bool,
+  description: &'static str,
+}
+
+const fn op(name: &'static str, id: u32, description: &'static str) -> MessageOp {
+  MessageOp { name, id, description }
+}
+
+const fn message(name: &'static str, id: u32, families: &'static [&'static str], description: &'static str) -> Message {
+  Message { name, id, families, ops: &[], stream: false, description }
+}
+
+const PRE_GFX11: &[&str] = &["cdna", "rdna1", "rdna2"];
+const GFX11_PLUS: &[&str] = &["rdna3", "rdna4"];
+
+const GS_OPS: &[MessageOp] = &[
+  op("GS_OP_NOP", 0, "no vertex operation (only with `MSG_GS_DONE`)"),
+  op("GS_OP_CUT", 1, "end the current primitive strip"),
+  op("GS_OP_EMIT", 2, "emit a vertex"),
+  op("GS_OP_EMIT_CUT", 3, "emit a vertex and end the strip"),
+];
+
+const SYSMSG_OPS: &[MessageOp] = &[
+  op("SYSMSG_OP_ECC_ERR_INTERRUPT", 1, "raise an ECC error interrupt"),
+  op("SYSMSG_OP_REG_RD", 2, "read a register"),
+  op("SYSMSG_OP_HOST_TRAP_ACK", 3, "acknowledge a host trap"),
+  op("SYSMSG_OP_TTRACE_PUT", 4, "write a token to the thread trace"),
+];
+
+const MESSAGES: &[Message] = &[
+  message("MSG_INTERRUPT", 1, &["rdna", "cdna"], "Raise a host interrupt; M0[23:0] carries user data"),
+  Message {
+    name: "MSG_GS",
+    id: 2,
+    families: PRE_GFX11,
+    ops: GS_OPS,
+    stream: true,
+    description: "Geometry shader vertex emit or cut; M0 holds the GS wave id",
+  },
+  Message {
+    name: "MSG_GS_DONE",
+    id: 3,
+    families: PRE_GFX11,
+    ops: GS_OPS,
+    stream: true,
+    description: "Geometry shader wave is done, optionally with a last emit or cut",
+  },
+  message("MSG_HS_TESSFACTOR", 2, GFX11_PLUS, "Hull shader tessellation factors are all zero or one"),
+  message(
+    "MSG_DEALLOC_VGPRS",
+    3,
+    GFX11_PLUS,
+    "Release the wave's VGPRs before it ends, once its last stores no longer read them",
+  ),
+  message("MSG_SAVEWAVE", 4, PRE_GFX11, "Context save: the wave reached a save point"),
+  message(
+    "MSG_STALL_WAVE_GEN",
+    5,
+    &["cdna", "rdna1", "rdna2", "rdna3"],
+    "Stop the SPI from launching new waves",
+  ),
+  message("MSG_HALT_WAVES", 6, &["cdna", "rdna1", "rdna2", "rdna3"], "Halt every wave of the queue"),
+  message("MSG_ORDERED_PS_DONE", 7, PRE_GFX11, "Pixel shader ordered section is done"),
+  message("MSG_EARLY_PRIM_DEALLOC", 8, PRE_GFX11, "Release primitive resources before the wave ends"),
+  message(
+    "MSG_GS_ALLOC_REQ",
+    9,
+    &["rdna", "cdna"],
+    "Request GS output space; M0 holds the vertex and primitive counts",
+  ),
+  message("MSG_GET_DOORBELL", 10, PRE_GFX11, "Read the queue's doorbell id into M0"),
+  message("MSG_GET_DDID", 11, &["rdna1", "rdna2"], "Read the draw or dispatch id into M0"),
+  Message {
+    name: "MSG_SYSMSG",
+    id: 15,
+    families: PRE_GFX11,
+    ops: SYSMSG_OPS,
+    stream: false,
+    description: "System message for trap handlers and debuggers",
+  },
+  message("MSG_RTN_GET_DOORBELL", 128, GFX11_PLUS, "Return the queue's doorbell id"),
+  message("MSG_RTN_GET_DDID", 129, GFX11_PLUS, "Return the draw or dispatch id"),
+  message("MSG_RTN_GET_TMA", 130, GFX11_PLUS, "Return the trap memory address (TMA)"),
+  message("MSG_RTN_GET_REALTIME", 131, GFX11_PLUS, "Return the 64-bit constant-rate real time counter"),
+  message("MSG_RTN_SAVE_WAVE", 132, GFX11_PLUS, "Context save: return when the wave may be saved"),
+  message("MSG_RTN_GET_TBA", 133, GFX11_PLUS, "Return the trap base address (TBA)"),
+  message("MSG_RTN_GET_TBA_TO_PC", 134, &["rdna4"], "Return the trap base address relative to the PC"),
+  message("MSG_RTN_GET_SE_AID_ID", 135, &["rdna4"], "Return the shader engine and die ids"),
+];
+
+/// Message id bits of the immediate: 8 from RDNA3 on, 4 before.
+fn id_mask(filter: Option<&str>) -> u32 {
+  match filter {
+    Some(filter) if GFX11_PLUS.iter().any(|family| family_matches(filter, family)) => 0xff,
+    Some(_) => 0xf,
+    None => 0xff,
+  }
+}
+
+/// The message a `sendmsg` id names, by name or number, on the filtered architecture.
+fn find_message(id: &str, filter: Option<&str>) -> Option<&'static Message> {
+  let number = parse_integer_literal(id).map(|number| number as u32);
+  MESSAGES.iter().find(|message| {
+    let named = match number {
+      Some(number) => message.id == number,
+      None => message.name.eq_ignore_ascii_case(id),
+    };
+    named && filter.is_none_or(|filter| message.families.iter().any(|family| family_matches(filter, family)))
+  })
+}
+
+fn is_sendmsg(mnemonic: &str) -> bool {
+  mnemonic.to_ascii_lowercase().starts_with("s_sendmsg")
+}
+
+/// Message, operation and stream of a `sendmsg(msg[, op[, stream]])` operand, or of the raw
+/// immediate of `s_sendmsg` split by the target's field layout.
+fn parse_sendmsg(operand: &str, filter: Option<&str>) -> Option<(String, Option<String>, Option<String>)> {
+  let trimmed = operand.trim();
+  let lower = trimmed.to_ascii_lowercase();
+  let inner = match trimmed.find('(') {
+    Some(open) if lower.starts_with("sendmsg(") || lower.starts_with("sendmsg_rtn(") => {
+      trimmed[open + 1..].strip_suffix(')')?
+    }
+    _ => {
+      let value = parse_integer_literal(trimmed)? as u32;
+      let id = value & id_mask(filter);
+      let message = find_message(&id.to_string(), filter);
+      let op = message.filter(|message| !message.ops.is_empty()).map(|_| ((value >> 4) & 7).to_string());
+      let stream = message.filter(|message| message.stream).map(|_| ((value >> 8) & 3).to_string());
+      return Some((id.to_string(), op, stream));
+    }
+  };
+  let mut arguments = inner.split(',').map(|argument| argument.trim().to_string());
+  let id = arguments.next().filter(|id| !id.is_empty())?;
+  Some((id, arguments.next(), arguments.next()))
+}
+
+/// What a `sendmsg(...)` operand (or the plain immediate) of `s_sendmsg`/`s_sendmsghalt`/
+/// `s_sendmsg_rtn_*` sends: the message, its operation and GS stream, and the encoded immediate.
+pub fn sendmsg_note(parsed: &InstructionLine<'_>, operand: &str, filter: Option<&str>) -> Option<String> {
+  if !is_sendmsg(parsed.mnemonic.text) {
+    return None;
+  }
+  let (id, op, stream) = parse_sendmsg(operand, filter)?;
+  let message = match find_message(&id, filter) {
+    Some(message) => message,
+    None => {
+      let target = filter.map(|filter| format!(" on {filter}")).unwrap_or_default();
+      return Some(format!("Unknown message `{id}`{target}"));
+    }
+  };
+  let mut lines = vec![format!("`{}` (id {}): {}", message.name, message.id, message.description)];
+  let mut encoded = message.id;
+  if let Some(op) = op {
+    let number = parse_integer_literal(&op).map(|number| number as u32);
+    let found = message.ops.iter().find(|candidate| match number {
+      Some(number) => candidate.id == number,
+      None => candidate.name.eq_ignore_ascii_case(&op),
+    });
+    match found {
+      Some(found) => {
+        encoded |= found.id << 4;
+        lines.push(format!("- operation `{}` ({}): {}", found.name, found.id, found.description));
+      }
+      None => lines.push(format!("- operation `{op}` is not one of {}'s", message.name)),
+    }
+  }
+  if let Some(stream) = stream.as_deref().and_then(parse_integer_literal) {
+    encoded |= (stream as u32 & 3) << 8;
+    lines.push(format!("- GS stream {}", stream & 3));
+  }
+  if message.id >= 128 {
+    lines.push("- returns its result in the destination SGPR(s) of `s_sendmsg_rtn_b32`/`_b64`".to_string());
+  }
+  lines.push(format!("- encoded immediate `{encoded:#x}`"));
+  Some(lines.join("\n"))
+}
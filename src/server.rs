@@ -1,73 +1,1268 @@
-use crate::architecture::{architecture_filter, entry_matches_arch, normalize_architecture_hint};
-use crate::encoding::split_encoding_variant;
-use crate::formatting::{format_hover, format_mnemonic, format_special_register_hover};
+use crate::architecture::{
+  arch_matches, architecture_filter, entry_matches_arch, environment_architecture, normalize_architecture_hint,
+  ArchitectureAliases, ArchitectureChanged, ArchitectureChangedParams,
+};
+use crate::branches::{branch_target_note, label_target_note};
+use crate::colors::{color_literal_text, document_color_literals};
+use crate::conditional::analyze_conditionals;
+use crate::config::ServerConfig;
+use crate::detection::{
+  detect_architecture, document_mnemonics, ArchitectureDetected, ArchitectureDetectedParams, ArchitectureGuess,
+};
+use crate::diagnostics::{document_diagnostics, missing_data_diagnostic, mnemonic_quick_fixes};
+use crate::directives::{directives_with_prefix, find_directive};
+use crate::discovery::discover_target_architecture;
+use crate::encoding::{entry_for_variant, split_encoding_variant};
+use crate::errata::{errata_banner, errata_for, Erratum};
+use crate::explain::explain_line;
+use crate::external::ExternalSymbols;
+use crate::expression::parse_integer_literal;
+use crate::folding::folding_ranges;
+use crate::formatting::{
+  format_directive_hover, format_encoding_dword_hover, format_encoding_suffix_hover, format_expression_hover,
+  format_full_documentation, format_hover, format_kernel_hover, format_label_hover, format_matrix_operand,
+  format_mnemonic, format_operand_hover, format_relocation_hover, format_special_register_hover, operand_dwords, HoverSections,
+};
+use crate::fuzzy::subsequence_score;
+use crate::health::{health_report, HealthReport};
+use crate::highlight::register_highlights;
+use crate::histogram::{instruction_histogram, InstructionHistogram, InstructionHistogramParams};
+use crate::hover_cache::{HoverCache, HoverKey};
+use crate::hwreg::{hardware_register_names, hwreg_note, mode_fields};
+use crate::idioms::idiom_note;
+use crate::index::{
+  covering_shards, detection_index, find_entry, merge_instructions, read_shard_instructions, DeferredShard, IsaIndex,
+};
+use crate::inlay::{operand_inlay_hints, InlayStyle};
+use crate::lds::{analyze_lds, LdsAnalysis};
+use crate::literals::numeric_literal_note;
+use crate::logging::log_line;
+use crate::matrix::{parse_matrix_shape, MatrixFamily};
+use crate::modifiers::{memory_class, memory_modifiers, MemoryModifier};
+use crate::op_sel::{is_vop3p, op_sel_note};
+use crate::outline::document_outline;
+use crate::overlay::IsaOverlays;
+use crate::packed::packed_literal_note;
+use crate::recovery::lock;
+use crate::runtime::run_analysis;
+use crate::scheduler::Scheduler;
+use crate::reformat::{format_document, format_on_type, FormatSettings};
+use crate::register_usage::{
+  analyze_register_usage, estimated_waves, size_summary, usage_summary, KernelUsageReport, RegisterUsageParams,
+};
+use crate::registers::{parse_register, register_candidates, register_note, slot_file};
+use crate::relocations::relocation_at;
+use crate::rename::{is_symbol_name, symbol_occurrences, OccurrenceKind, SymbolOccurrence};
+use crate::scratch::{analyze_scratch, ScratchAnalysis};
+use crate::search::SearchIndex;
+use crate::semantic_tokens::{document_semantic_tokens, semantic_tokens_legend};
+use crate::sendmsg::sendmsg_note;
+use crate::smem::smem_offset_note;
+use crate::special_operands::special_operand_note;
+use crate::suffixes::{suffix_edits, SuffixStyle};
+use crate::symbols::{
+  collect_macro_signatures, collect_symbol_definitions, evaluate_with_symbols, find_global_declaration, MacroSignature,
+};
+use crate::telemetry::TimingSettings;
 use crate::text_utils::{
   byte_offset_to_utf16_position, extract_word_at_position, extract_word_prefix_at_position,
-  utf16_position_to_byte_offset,
+  utf16_position_to_byte_offset, WordKind,
+};
+use crate::tokenizer::{
+  data_directive, encoding_comment, in_string_literal, instruction_at, is_label_char, is_label_start,
+  line_comment_start, operand_index_at, parse_instruction_statements, statement_ranges,
+  strip_leading_disasm_prefix, strip_leading_label, Dialect, InstructionLine, Token,
+};
+use crate::waits::{describe_wait, statement_wait};
+use crate::types::{
+  DocumentState, DocumentStore, EncodingVariant, InstructionEntry, IsaLoadInfo, RawEntryParams, SpecialRegister,
+};
+use crate::vopd::{dual_entry, dual_half_note, half_operands, pairing_rules, parse_dual_line, DualHalf, DualLine};
+use crate::workspace::{
+  collect_assembly_files, file_instruction_uses, file_symbols, filter_symbols, instruction_locations,
+  workspace_cache_path, PartialSymbolResults, PartialSymbolResultsParams, WorkspaceIndexCache,
 };
-use crate::types::{DocumentState, DocumentStore, InstructionEntry, IsaLoadInfo, SpecialRegister};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use tower_lsp::jsonrpc::Result;
+use tokio::sync::RwLock;
+use serde_json::Value;
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::{
-  CompletionItem, CompletionItemKind, CompletionList, CompletionOptions, CompletionParams,
-  CompletionResponse, CompletionTextEdit, Hover, HoverParams,
-  GotoDefinitionParams, GotoDefinitionResponse, HoverProviderCapability, InitializeParams,
-  InitializeResult, Location, MessageType, OneOf, ParameterInformation, ParameterLabel, Position,
-  Range, ServerCapabilities, SignatureHelp, SignatureHelpOptions, SignatureHelpParams,
-  SignatureInformation, TextDocumentContentChangeEvent, TextDocumentItem, TextDocumentSyncCapability,
-  TextDocumentSyncKind, TextEdit, Url,
+  AnnotatedTextEdit, ChangeAnnotation, CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+  CodeActionProviderCapability, CodeActionResponse, CodeLens, CodeLensOptions, CodeLensParams, Command,
+  ColorInformation, ColorPresentation, ColorPresentationParams,
+  ColorProviderCapability, CompletionItem, CompletionItemKind, CompletionList, CompletionOptions, CompletionParams,
+  CompletionResponse, CompletionTextEdit, DeclarationCapability, DidChangeConfigurationParams, DidChangeWorkspaceFoldersParams, DocumentChanges, DidCloseTextDocumentParams, DocumentColorParams, DocumentFormattingParams,
+  DocumentOnTypeFormattingOptions, DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
+  DocumentSymbolParams, DocumentSymbolResponse,
+  DocumentHighlight, DocumentHighlightParams, Documentation, ExecuteCommandOptions,
+  ExecuteCommandParams, FoldingRange, FoldingRangeKind, FoldingRangeParams,
+  FoldingRangeProviderCapability, Hover, HoverContents, HoverParams,
+  GotoDefinitionParams, GotoDefinitionResponse, HoverProviderCapability, InitializeParams, InlayHint, InlayHintParams,
+  InitializeResult, Location, OptionalVersionedTextDocumentIdentifier, PrepareRenameResponse, RenameOptions, RenameParams, MarkupContent, MarkupKind, MessageType, OneOf, ParameterInformation, ParameterLabel, Position,
+  Range, ReferenceParams, SemanticTokens, SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensParams,
+  SemanticTokensResult, SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelp,
+  SignatureHelpOptions, SignatureHelpParams,
+  SignatureInformation, SymbolInformation, TextDocumentContentChangeEvent, TextDocumentEdit, TextDocumentItem,
+  TextDocumentPositionParams, TextDocumentSyncCapability,
+  TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit, WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
+  WorkspaceSymbolParams,
 };
+use tower_lsp::lsp_types::request::{GotoDeclarationParams, GotoDeclarationResponse};
 use tower_lsp::{Client, LanguageServer};
 
+const SHOW_DOCS_COMMAND: &str = "amdgpu.showDocs";
+const SEARCH_INSTRUCTIONS_COMMAND: &str = "amdgpu.searchInstructions";
+const EXPLAIN_LINE_COMMAND: &str = "amdgpu.explainLine";
+const FIND_INSTRUCTION_USAGES_COMMAND: &str = "amdgpu.findInstructionUsages";
+const EXECUTE_COMMANDS: &[&str] =
+  &[SHOW_DOCS_COMMAND, SEARCH_INSTRUCTIONS_COMMAND, EXPLAIN_LINE_COMMAND, FIND_INSTRUCTION_USAGES_COMMAND];
+/// Results returned by `amdgpu.searchInstructions`.
+const SEARCH_RESULT_LIMIT: usize = 20;
+
+type InstructionIndex = Arc<HashMap<String, Vec<InstructionEntry>>>;
+type KernelSegments = Arc<(LdsAnalysis, ScratchAnalysis)>;
+
 pub struct IsaServer {
   client: Client,
-  docs: Arc<Mutex<DocumentStore>>,
-  index: HashMap<String, Vec<InstructionEntry>>,
+  docs: Arc<RwLock<DocumentStore>>,
+  index: Arc<Mutex<InstructionIndex>>,
+  /// Full-text index over instruction names and descriptions, for `amdgpu.searchInstructions`.
+  search_index: Arc<Mutex<Arc<SearchIndex>>>,
+  /// Per-architecture shards not loaded yet; loaded once an open document targets them.
+  deferred: Arc<Mutex<Vec<DeferredShard>>>,
+  /// Names and architectures of every instruction, loaded or not, for detecting the architecture of
+  /// documents without one while shards are still deferred.
+  detection_index: Arc<Mutex<Option<InstructionIndex>>>,
   special_registers: Vec<SpecialRegister>,
+  architecture_aliases: ArchitectureAliases,
+  memory_modifiers: Arc<Vec<MemoryModifier>>,
+  errata: Arc<Vec<Erratum>>,
   architecture_override: Arc<Mutex<Option<String>>>,
+  hover_sections: Arc<Mutex<HoverSections>>,
+  hover_cache: Arc<Mutex<HoverCache>>,
+  /// LDS and scratch analyses shown in kernel hovers, per document and the version they describe.
+  segment_cache: Arc<Mutex<HashMap<Url, (i32, KernelSegments)>>>,
+  /// Notes from the `isaOverlays` files, merged into entries when they are shown.
+  overlays: Arc<Mutex<Arc<IsaOverlays>>>,
+  /// Symbols from the `externalSymbols` option, defined outside the assembly.
+  external_symbols: Arc<Mutex<Arc<ExternalSymbols>>>,
+  /// Architecture for documents whose language id names none: discovered from the workspace's
+  /// build files, else `--arch`, else taken from the environment.
+  default_architecture: Arc<Mutex<Option<String>>>,
+  /// Raw `--arch` value, resolved against the aliases at initialize.
+  command_line_architecture: Option<String>,
+  workspace_roots: Arc<Mutex<Vec<PathBuf>>>,
+  /// Symbols of workspace files on disk, loaded from the on-disk cache by the first workspace
+  /// symbol request.
+  workspace_index: Arc<Mutex<Option<WorkspaceIndexCache>>>,
+  /// Whether the workspace index is persisted between sessions.
+  persist_workspace_index: AtomicBool,
+  /// Whether packed `0xAARRGGBB` literals are reported as document colors.
+  color_decorators: AtomicBool,
+  /// Whether instruction hover explains recognized idioms (`explainIdioms`).
+  explain_idioms: AtomicBool,
+  /// Spelling of VOP1/VOP2 encoding suffixes offered by the normalize code action; `None` offers nothing.
+  suffix_style: Arc<Mutex<Option<SuffixStyle>>>,
+  /// Operand inlay hints shown before each operand (`operandInlayHints`); `None` shows none.
+  inlay_style: Arc<Mutex<Option<InlayStyle>>>,
+  /// Columns and casing used by document formatting (`formatting`).
+  format_settings: Arc<Mutex<FormatSettings>>,
+  /// Assembler dialect the documents are parsed in: `--dialect`, replaced by `assemblerDialect`.
+  dialect: Arc<Mutex<Dialect>>,
+  /// Invalid `initializationOptions`, shown once the client is initialized.
+  initialization_warning: Arc<Mutex<Option<String>>>,
+  scheduler: Scheduler,
+  /// Whether the client accepts annotated edits in `documentChanges`, for rename.
+  change_annotations: AtomicBool,
+  timing: Arc<TimingSettings>,
   load_info: IsaLoadInfo,
 }
 
 impl IsaServer {
   pub fn new(
     client: Client,
-    index: HashMap<String, Vec<InstructionEntry>>,
-    special_registers: Vec<SpecialRegister>,
-    load_info: IsaLoadInfo,
+    isa: IsaIndex,
+    timing: Arc<TimingSettings>,
+    command_line_architecture: Option<String>,
+    dialect: Dialect,
   ) -> Self {
     Self {
       client,
-      docs: Arc::new(Mutex::new(DocumentStore::default())),
-      index,
-      special_registers,
+      docs: Arc::new(RwLock::new(DocumentStore::default())),
+      index: Arc::new(Mutex::new(Arc::new(isa.instructions))),
+      search_index: Arc::new(Mutex::new(Arc::new(isa.search))),
+      deferred: Arc::new(Mutex::new(isa.deferred)),
+      detection_index: Arc::new(Mutex::new(None)),
+      special_registers: isa.special_registers,
+      architecture_aliases: isa.architecture_aliases,
+      memory_modifiers: Arc::new(isa.memory_modifiers),
+      errata: Arc::new(isa.errata),
       architecture_override: Arc::new(Mutex::new(None)),
-      load_info,
+      hover_sections: Arc::new(Mutex::new(HoverSections::default())),
+      hover_cache: Arc::new(Mutex::new(HoverCache::default())),
+      segment_cache: Arc::new(Mutex::new(HashMap::new())),
+      overlays: Arc::new(Mutex::new(Arc::new(IsaOverlays::default()))),
+      external_symbols: Arc::new(Mutex::new(Arc::new(ExternalSymbols::default()))),
+      default_architecture: Arc::new(Mutex::new(None)),
+      command_line_architecture,
+      workspace_roots: Arc::new(Mutex::new(Vec::new())),
+      workspace_index: Arc::new(Mutex::new(None)),
+      persist_workspace_index: AtomicBool::new(true),
+      color_decorators: AtomicBool::new(false),
+      explain_idioms: AtomicBool::new(false),
+      suffix_style: Arc::new(Mutex::new(None)),
+      inlay_style: Arc::new(Mutex::new(None)),
+      format_settings: Arc::new(Mutex::new(FormatSettings::default())),
+      dialect: Arc::new(Mutex::new(dialect)),
+      initialization_warning: Arc::new(Mutex::new(None)),
+      scheduler: Scheduler::start(),
+      change_annotations: AtomicBool::new(false),
+      timing,
+      load_info: isa.load_info,
     }
   }
 
-  fn get_document(&self, uri: &Url) -> Option<DocumentState> {
-    self.docs.lock().ok()?.docs.get(uri).cloned()
+  fn dialect(&self) -> Dialect {
+    *lock(&self.dialect)
+  }
+
+  async fn get_document(&self, uri: &Url) -> Option<DocumentState> {
+    self.docs.read().await.docs.get(uri).cloned()
+  }
+
+  /// LDS and scratch analyses of a document, computed once per version.
+  fn kernel_segments(&self, uri: &Url, doc: &DocumentState) -> KernelSegments {
+    if let Some((version, analyses)) = lock(&self.segment_cache).get(uri)
+      && *version == doc.version
+    {
+      return analyses.clone();
+    }
+    let dialect = self.dialect();
+    let analyses = Arc::new((analyze_lds(&doc.text, dialect), analyze_scratch(&doc.text, dialect)));
+    lock(&self.segment_cache).insert(uri.clone(), (doc.version, analyses.clone()));
+    analyses
+  }
+
+  /// Filter from the override, language id or default architecture, ignoring content detection.
+  fn configured_architecture_filter(&self, doc: &DocumentState) -> Option<String> {
+    let override_arch = lock(&self.architecture_override).clone();
+    let default_arch = lock(&self.default_architecture).clone();
+    architecture_filter(
+      &doc.language_id,
+      override_arch.as_ref(),
+      default_arch.as_ref(),
+      &self.architecture_aliases,
+    )
+  }
+
+  fn document_architecture_filter(&self, doc: &DocumentState) -> Option<String> {
+    self
+      .configured_architecture_filter(doc)
+      .or_else(|| doc.detected_architecture.clone())
+  }
+
+  /// `annotation` preceded by the banner for the instruction's errata on the architecture.
+  fn with_errata_banner(&self, mnemonic: &str, filter: Option<&str>, annotation: Option<String>) -> Option<String> {
+    let errata: Vec<&Erratum> = errata_for(&self.errata, mnemonic, filter).collect();
+    match (errata_banner(&errata), annotation) {
+      (Some(banner), Some(annotation)) => Some(format!("{banner}\n\n{annotation}")),
+      (banner, annotation) => banner.or(annotation),
+    }
+  }
+
+  /// Signature help for a VOPD line: the X operation's operands followed by the Y operation's, with
+  /// the active parameter following the cursor across the `::`.
+  fn dual_signature_help(
+    &self,
+    dual: &DualLine<'_>,
+    line: &str,
+    cursor_byte: usize,
+    filter: Option<&str>,
+  ) -> Option<SignatureHelp> {
+    let index = self.index();
+    let lookup = |parsed: &InstructionLine<'_>| dual_entry(&index, parsed.mnemonic.text, filter);
+    let x_entry = lookup(&dual.x)?;
+    let x_operands = half_operands(x_entry, DualHalf::X);
+    let mut label = format_mnemonic(&x_entry.name);
+    let mut parameters = push_operand_parameters(&mut label, x_entry, &x_operands, filter);
+    let mut descriptions: Vec<String> = x_entry.description.iter().cloned().collect();
+    let y = dual.y.as_ref().and_then(|y| Some((y, lookup(y)?)));
+    let y_operands = y.map(|(_, y_entry)| half_operands(y_entry, DualHalf::Y)).unwrap_or_default();
+    if let Some((_, y_entry)) = y {
+      label.push_str(&format!(" :: {}", format_mnemonic(&y_entry.name)));
+      parameters.extend(push_operand_parameters(&mut label, y_entry, &y_operands, filter));
+      descriptions.extend(y_entry.description.iter().cloned());
+    }
+    let in_y = dual.separator.is_some_and(|separator| cursor_byte >= separator);
+    let active_parameter = match (in_y, y) {
+      (false, _) if cursor_byte > dual.x.mnemonic.end() => {
+        let last_index = x_operands.len().checked_sub(1)?;
+        Some(operand_index_at(line, &dual.x.mnemonic, cursor_byte).min(last_index))
+      }
+      (true, Some((y, _))) if cursor_byte > y.mnemonic.end() => {
+        let last_index = y_operands.len().checked_sub(1)?;
+        Some(x_operands.len() + operand_index_at(line, &y.mnemonic, cursor_byte).min(last_index))
+      }
+      _ => None,
+    }
+    .map(|index| index as u32);
+    let signature = SignatureInformation {
+      label,
+      documentation: (!descriptions.is_empty())
+        .then(|| tower_lsp::lsp_types::Documentation::String(descriptions.join("\n\n"))),
+      parameters: Some(parameters),
+      active_parameter,
+    };
+    Some(SignatureHelp {
+      signatures: vec![signature],
+      active_signature: Some(0),
+      active_parameter,
+    })
+  }
+
+  /// Hover on a VOPD line: the pairing rules on the `::`, and on either half that operation's
+  /// documentation (annotated with its operand under the cursor) plus its role in the pair.
+  fn dual_hover(&self, doc: &DocumentState, dual: &DualLine<'_>, cursor_byte: usize) -> Option<Hover> {
+    let index = self.index();
+    let markdown = |value: String| Hover {
+      contents: HoverContents::Markup(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+      }),
+      range: None,
+    };
+    if dual.separator.is_some_and(|separator| separator <= cursor_byte && cursor_byte <= separator + 2) {
+      return Some(markdown(pairing_rules()));
+    }
+    let in_y = dual.separator.is_some_and(|separator| cursor_byte > separator);
+    let (half, parsed, partner) = match (in_y, &dual.y) {
+      (false, y) => (DualHalf::X, &dual.x, y.as_ref().map(|y| y.mnemonic.text)),
+      (true, Some(y)) => (DualHalf::Y, y, Some(dual.x.mnemonic.text)),
+      (true, None) => return None,
+    };
+    if cursor_byte < parsed.mnemonic.start {
+      return None;
+    }
+    let filter = self.document_architecture_filter(doc);
+    let entry = dual_entry(&index, parsed.mnemonic.text, filter.as_deref())?;
+    let entry = lock(&self.overlays).clone().apply(entry, filter.as_deref()).into_owned();
+    let sections = lock(&self.hover_sections).clone();
+    let note = dual_half_note(half, parsed.mnemonic.text, partner);
+    let operand = parsed
+      .operands
+      .iter()
+      .enumerate()
+      .find(|(_, operand)| operand.start <= cursor_byte && cursor_byte <= operand.end());
+    let arg_index = operand.and_then(|(operand_index, _)| half_operands(&entry, half).get(operand_index).copied());
+    let contents = match (operand, arg_index) {
+      (Some((_, operand)), Some(arg_index)) => {
+        format_operand_hover(&entry, &EncodingVariant::Native, &sections, arg_index, operand.text, Some(note))
+      }
+      _ => {
+        let annotation = self.with_errata_banner(&entry.name, filter.as_deref(), Some(note));
+        format_hover(&entry, &EncodingVariant::Native, &sections, annotation)
+      }
+    };
+    Some(Hover { contents, range: None })
+  }
+
+  /// `annotation` followed by the explanation of the idiom the instruction under the cursor forms,
+  /// when `explainIdioms` is on.
+  fn with_idiom_note(&self, line: &str, cursor_byte: usize, annotation: Option<String>) -> Option<String> {
+    let note = instruction_at(line, cursor_byte, self.dialect())
+      .filter(|_| self.explain_idioms.load(Ordering::Relaxed))
+      .and_then(|parsed| idiom_note(&parsed))
+      .map(|note| format!("💡 {note}"));
+    match (annotation, note) {
+      (Some(annotation), Some(note)) => Some(format!("{annotation}\n\n{note}")),
+      (annotation, note) => annotation.or(note),
+    }
+  }
+
+  /// Hover on a dword of a trailing objdump `// <address>: <dwords>` comment, tied back to the
+  /// instruction it encodes.
+  fn encoding_comment_hover(&self, doc: &DocumentState, line: &str, cursor_byte: usize) -> Option<Hover> {
+    let dialect = self.dialect();
+    let index = self.index();
+    let comment = encoding_comment(line, dialect)?;
+    let dword_index = comment
+      .dwords
+      .iter()
+      .position(|dword| dword.start <= cursor_byte && cursor_byte < dword.end())?;
+    let filter = self.document_architecture_filter(doc);
+    let sections = lock(&self.hover_sections).clone();
+    let overlays = lock(&self.overlays).clone();
+    // The comment encodes the last statement of the line, the one it trails.
+    let instruction = parse_instruction_statements(line, dialect).pop().and_then(|parsed| {
+      let split = split_encoding_variant(parsed.mnemonic.text);
+      let entry = overlays.apply(find_entry(&index, &split.base, filter.as_deref())?, filter.as_deref());
+      Some((entry_for_variant(&entry, &split.variant).into_owned(), split.variant))
+    });
+    let contents = format_encoding_dword_hover(
+      instruction.as_ref().map(|(entry, variant)| (entry, variant)),
+      &sections,
+      comment.address.text,
+      &comment.values(),
+      dword_index,
+    );
+    Some(Hover { contents, range: None })
+  }
+
+  /// Keyword modifiers (`offset:`, `glc`, `th:`, ...) for a memory instruction, offered once the cursor
+  /// is past its operands; `None` anywhere else so mnemonic completion applies.
+  fn modifier_completions(&self, doc: &DocumentState, position: Position) -> Option<Vec<CompletionItem>> {
+    let dialect = self.dialect();
+    let line = doc.text.lines().nth(position.line as usize)?;
+    let cursor = utf16_position_to_byte_offset(line, position);
+    if line_comment_start(line, dialect).is_some_and(|comment_start| cursor > comment_start) {
+      return None;
+    }
+    let parsed = instruction_at(line, cursor, dialect)?;
+    if cursor <= parsed.mnemonic.end() {
+      return None;
+    }
+    let class = memory_class(parsed.mnemonic.text)?;
+    let bytes = line.as_bytes();
+    let mut word_start = cursor.min(bytes.len());
+    while word_start > 0 && (bytes[word_start - 1].is_ascii_alphanumeric() || bytes[word_start - 1] == b'_') {
+      word_start -= 1;
+    }
+    // Still typing operands, or a modifier's value.
+    let before = line[parsed.mnemonic.end()..word_start].trim_end();
+    if before.is_empty() || before.ends_with(',') || before.ends_with(':') {
+      return None;
+    }
+    let used: Vec<&str> = parsed
+      .modifiers
+      .iter()
+      .filter(|modifier| modifier.start != word_start)
+      .map(|modifier| modifier.text.split(':').next().unwrap_or(modifier.text))
+      .collect();
+    let filter = self.document_architecture_filter(doc);
+    let range = Range {
+      start: Position {
+        line: position.line,
+        character: byte_offset_to_utf16_position(line, word_start),
+      },
+      end: position,
+    };
+    let items = memory_modifiers(&self.memory_modifiers, class, filter.as_deref())
+      .filter(|modifier| !used.iter().any(|name| name.eq_ignore_ascii_case(&modifier.name)))
+      .map(|modifier| {
+        let label = if modifier.takes_value {
+          format!("{}:", modifier.name)
+        } else {
+          modifier.name.clone()
+        };
+        CompletionItem {
+          label: label.clone(),
+          kind: Some(CompletionItemKind::PROPERTY),
+          detail: Some("memory modifier".to_string()),
+          documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: modifier.description.clone(),
+          })),
+          text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: label })),
+          ..CompletionItem::default()
+        }
+      })
+      .collect();
+    Some(items)
+  }
+
+  /// Register names for the first argument of `hwreg(`, plus each MODE field as the
+  /// `HW_REG_MODE, offset, size` that selects it.
+  fn hwreg_completions(&self, doc: &DocumentState, position: Position) -> Option<Vec<CompletionItem>> {
+    let line = doc.text.lines().nth(position.line as usize)?;
+    let cursor = utf16_position_to_byte_offset(line, position);
+    if line_comment_start(line, self.dialect()).is_some_and(|comment_start| cursor > comment_start) {
+      return None;
+    }
+    let bytes = line.as_bytes();
+    let mut word_start = cursor.min(bytes.len());
+    while word_start > 0 && (bytes[word_start - 1].is_ascii_alphanumeric() || bytes[word_start - 1] == b'_') {
+      word_start -= 1;
+    }
+    let before = line[..word_start].trim_end();
+    if !before.to_ascii_lowercase().ends_with("hwreg(") {
+      return None;
+    }
+    let filter = self.document_architecture_filter(doc);
+    let range = Range {
+      start: Position {
+        line: position.line,
+        character: byte_offset_to_utf16_position(line, word_start),
+      },
+      end: position,
+    };
+    let mut items: Vec<CompletionItem> = hardware_register_names(filter.as_deref())
+      .into_iter()
+      .map(|(name, id)| CompletionItem {
+        label: name.to_string(),
+        kind: Some(CompletionItemKind::VARIABLE),
+        detail: Some(format!("hardware register {id}")),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+          range,
+          new_text: name.to_string(),
+        })),
+        ..CompletionItem::default()
+      })
+      .collect();
+    items.extend(mode_fields(filter.as_deref()).into_iter().map(|field| {
+      let selector = format!("HW_REG_MODE, {}, {}", field.offset, field.size);
+      CompletionItem {
+        label: field.name.to_string(),
+        kind: Some(CompletionItemKind::FIELD),
+        detail: Some(selector.clone()),
+        documentation: Some(Documentation::String(field.description.to_string())),
+        filter_text: Some(field.name.to_string()),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: selector })),
+        ..CompletionItem::default()
+      }
+    }));
+    Some(items)
+  }
+
+  /// Directives for a word starting with `.` at the start of a statement (after any label).
+  fn directive_completions(&self, doc: &DocumentState, position: Position) -> Option<Vec<CompletionItem>> {
+    let line = doc.text.lines().nth(position.line as usize)?;
+    let cursor = utf16_position_to_byte_offset(line, position).min(line.len());
+    if line_comment_start(line, self.dialect()).is_some_and(|comment_start| cursor > comment_start) {
+      return None;
+    }
+    let (label_end, statement) = strip_leading_label(&line[..cursor]);
+    let start = label_end + statement.len() - statement.trim_start().len();
+    let word = &line[start..cursor];
+    if !word.starts_with('.') || word.contains(char::is_whitespace) {
+      return None;
+    }
+    let range = Range {
+      start: Position {
+        line: position.line,
+        character: byte_offset_to_utf16_position(line, start),
+      },
+      end: position,
+    };
+    let items = directives_with_prefix(word)
+      .map(|directive| CompletionItem {
+        label: directive.name.to_string(),
+        kind: Some(CompletionItemKind::KEYWORD),
+        detail: Some(format!("{} {}", directive.name, directive.syntax).trim_end().to_string()),
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+          kind: MarkupKind::Markdown,
+          value: directive.description.to_string(),
+        })),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+          range,
+          new_text: directive.name.to_string(),
+        })),
+        ..CompletionItem::default()
+      })
+      .collect();
+    Some(items)
+  }
+
+  /// Accumulator register ranges sized for the MFMA operand under the cursor, aligned to their
+  /// size; `None` outside accumulator operand slots.
+  fn agpr_completions(&self, doc: &DocumentState, position: Position) -> Option<Vec<CompletionItem>> {
+    let dialect = self.dialect();
+    let index = self.index();
+    let line = doc.text.lines().nth(position.line as usize)?;
+    let cursor = utf16_position_to_byte_offset(line, position);
+    if line_comment_start(line, dialect).is_some_and(|comment_start| cursor > comment_start) {
+      return None;
+    }
+    let parsed = instruction_at(line, cursor, dialect)?;
+    if cursor <= parsed.mnemonic.end() {
+      return None;
+    }
+    let filter = self.document_architecture_filter(doc);
+    let split = split_encoding_variant(parsed.mnemonic.text);
+    let entry = entry_for_variant(find_entry(&index, &split.base, filter.as_deref())?, &split.variant);
+    let family = parse_matrix_shape(&entry.name)?.family;
+    if !matches!(family, MatrixFamily::Mfma | MatrixFamily::Smfmac) {
+      return None;
+    }
+    let bytes = line.as_bytes();
+    let mut word_start = cursor.min(bytes.len());
+    while word_start > 0 && (bytes[word_start - 1].is_ascii_alphanumeric() || b"_[:]".contains(&bytes[word_start - 1])) {
+      word_start -= 1;
+    }
+    let operand_index = operand_index_at(line, &parsed.mnemonic, word_start);
+    let arg_type = entry.arg_types.get(operand_index)?;
+    if arg_type != "accumulator" && arg_type != "register_or_accumulator" {
+      return None;
+    }
+    let dwords = operand_dwords(&entry, operand_index).unwrap_or(1);
+    let range = Range {
+      start: Position {
+        line: position.line,
+        character: byte_offset_to_utf16_position(line, word_start),
+      },
+      end: position,
+    };
+    let items = (0..256)
+      .step_by(dwords as usize)
+      .map(|first| {
+        let label = if dwords == 1 {
+          format!("a{first}")
+        } else {
+          format!("a[{first}:{}]", first + dwords - 1)
+        };
+        CompletionItem {
+          label: label.clone(),
+          kind: Some(CompletionItemKind::VARIABLE),
+          detail: Some(format!("AGPR, {dwords} dword{}", if dwords == 1 { "" } else { "s" })),
+          sort_text: Some(format!("{first:03}")),
+          text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: label })),
+          ..CompletionItem::default()
+        }
+      })
+      .collect();
+    Some(items)
+  }
+
+  /// Registers for the operand slot under the cursor: VGPRs, SGPRs and named scalar registers as
+  /// the slot's field name allows, sized for its width. `None` outside register operand slots.
+  fn register_completions(&self, doc: &DocumentState, position: Position) -> Option<Vec<CompletionItem>> {
+    let dialect = self.dialect();
+    let index = self.index();
+    let line = doc.text.lines().nth(position.line as usize)?;
+    let cursor = utf16_position_to_byte_offset(line, position);
+    if line_comment_start(line, dialect).is_some_and(|comment_start| cursor > comment_start) {
+      return None;
+    }
+    let parsed = instruction_at(line, cursor, dialect)?;
+    if cursor <= parsed.mnemonic.end() {
+      return None;
+    }
+    let bytes = line.as_bytes();
+    let mut word_start = cursor.min(bytes.len());
+    while word_start > 0 && (bytes[word_start - 1].is_ascii_alphanumeric() || b"_[:]".contains(&bytes[word_start - 1])) {
+      word_start -= 1;
+    }
+    // Only at the start of an operand, not inside a modifier or an expression.
+    let before = line[parsed.mnemonic.end()..word_start].trim_end();
+    if !before.is_empty() && !before.ends_with(',') {
+      return None;
+    }
+    let filter = self.document_architecture_filter(doc);
+    let split = split_encoding_variant(parsed.mnemonic.text);
+    let entry = entry_for_variant(find_entry(&index, &split.base, filter.as_deref())?, &split.variant);
+    let operand_index = operand_index_at(line, &parsed.mnemonic, word_start);
+    let (slot, arg_type) = (entry.args.get(operand_index)?, entry.arg_types.get(operand_index)?);
+    if !matches!(arg_type.as_str(), "register" | "register_or_inline") {
+      return None;
+    }
+    let is_64bit = entry.arg_data_types.get(operand_index).is_some_and(|data_type| data_type.ends_with("64"));
+    let dwords = operand_dwords(&entry, operand_index).unwrap_or(if is_64bit { 2 } else { 1 });
+    let range = Range {
+      start: Position {
+        line: position.line,
+        character: byte_offset_to_utf16_position(line, word_start),
+      },
+      end: position,
+    };
+    let items = register_candidates(slot_file(slot, arg_type), dwords, filter.as_deref())
+      .into_iter()
+      .enumerate()
+      .map(|(order, (label, detail))| CompletionItem {
+        label: label.clone(),
+        kind: Some(CompletionItemKind::VARIABLE),
+        detail: Some(format!("{detail} for {slot}")),
+        sort_text: Some(format!("{order:04}")),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: label })),
+        ..CompletionItem::default()
+      })
+      .collect();
+    Some(items)
+  }
+
+  /// Snapshot of the instruction index; per-architecture shards loaded later replace it.
+  fn index(&self) -> InstructionIndex {
+    lock(&self.index).clone()
+  }
+
+  /// Loads the deferred per-architecture shards a document needs off the async runtime, and returns
+  /// the architecture detected for it when none is configured. Detection reads only the names of
+  /// unloaded shards; a document it places loads that target's shards, one it cannot place just
+  /// enough shards to cover the instructions it uses.
+  async fn load_document_architectures(&self, text: String, configured: Option<String>) -> Option<ArchitectureGuess> {
+    let dialect = self.dialect();
+    let index = self.index.clone();
+    let search_index = self.search_index.clone();
+    let deferred = self.deferred.clone();
+    let detection = self.detection_index.clone();
+    let hover_cache = self.hover_cache.clone();
+    let analysis = move || {
+      let pending = lock(&deferred).clone();
+      let loaded = lock(&index).clone();
+      let (guess, wanted) = match configured {
+        Some(filter) => {
+          let wanted = pending.iter().filter(|shard| arch_matches(&shard.architecture, &filter)).cloned().collect();
+          (None, wanted)
+        }
+        None => {
+          let cached = lock(&detection).clone();
+          let names = match cached {
+            Some(names) => names,
+            None if pending.is_empty() => loaded.clone(),
+            None => {
+              let names = Arc::new(detection_index(&loaded, &pending));
+              *lock(&detection) = Some(names.clone());
+              names
+            }
+          };
+          let guess = detect_architecture(&text, &names, dialect);
+          let wanted = match &guess {
+            Some(guess) => {
+              let matches = |shard: &&DeferredShard| arch_matches(&shard.architecture, &guess.architecture);
+              pending.iter().filter(matches).cloned().collect()
+            }
+            None => {
+              let mnemonics = document_mnemonics(&text, dialect);
+              covering_shards(mnemonics.iter().map(String::as_str), &loaded, &names, &pending)
+            }
+          };
+          (guess, wanted)
+        }
+      };
+      // Only the shards still pending: another document may have loaded some meanwhile.
+      let taken: Vec<DeferredShard> = {
+        let mut deferred = lock(&deferred);
+        let (taken, rest) = deferred.drain(..).partition(|shard| wanted.iter().any(|wanted| wanted.path == shard.path));
+        *deferred = rest;
+        taken
+      };
+      let shards = read_shard_instructions(&taken);
+      if !shards.is_empty() {
+        let mut index = lock(&index);
+        let mut instructions = (**index).clone();
+        for entries in shards {
+          merge_instructions(&mut instructions, entries);
+        }
+        *lock(&search_index) = Arc::new(SearchIndex::build(&instructions));
+        *index = Arc::new(instructions);
+        lock(&hover_cache).clear();
+      }
+      guess
+    };
+    run_analysis(analysis).await.flatten()
+  }
+
+  /// Loads the deferred shards of an architecture a command names, so lookups filtered to it find
+  /// its instructions.
+  async fn load_architecture(&self, filter: &str) {
+    // A configured filter skips detection, so no document text is needed.
+    self.load_document_architectures(String::new(), Some(filter.to_string())).await;
+  }
+
+  /// Re-runs content detection for documents without a configured filter and tells the client
+  /// when the guess changes.
+  async fn update_detected_architecture(&self, uri: &Url) {
+    let doc = match self.get_document(uri).await {
+      Some(doc) => doc,
+      None => return,
+    };
+    let configured = self.configured_architecture_filter(&doc);
+    let guess = self.load_document_architectures(doc.text.clone(), configured).await;
+    let architecture = guess.as_ref().map(|guess| guess.architecture.clone());
+    if architecture == doc.detected_architecture {
+      return;
+    }
+    if let Some(stored) = self.docs.write().await.docs.get_mut(uri) {
+      stored.detected_architecture = architecture;
+    }
+    if let Some(guess) = guess {
+      self
+        .client
+        .send_notification::<ArchitectureDetected>(ArchitectureDetectedParams {
+          uri: uri.clone(),
+          architecture: guess.architecture,
+          evidence: guess.evidence,
+        })
+        .await;
+    }
+  }
+
+  /// `amdgpu.showDocs <instruction> [architecture]`: full Markdown documentation for a mnemonic.
+  async fn show_docs(&self, arguments: &[Value]) -> Result<Option<Value>> {
+    let instruction = match arguments.first().and_then(|value| value.as_str()) {
+      Some(instruction) => instruction,
+      None => return Err(Error::invalid_params("Expected an instruction name argument")),
+    };
+    let filter = arguments
+      .get(1)
+      .and_then(|value| value.as_str())
+      .map(|value| normalize_architecture_hint(value, &self.architecture_aliases));
+    if let Some(filter) = &filter {
+      self.load_architecture(filter).await;
+    }
+    let index = self.index();
+    let split = split_encoding_variant(instruction.trim());
+    let entries = match index.get(&split.base.to_ascii_lowercase()) {
+      Some(entries) => entries,
+      None => return Err(Error::invalid_params(format!("Unknown instruction: {instruction}"))),
+    };
+    let matching: Vec<&InstructionEntry> = entries
+      .iter()
+      .filter(|entry| filter.as_ref().is_none_or(|filter| entry_matches_arch(entry, filter)))
+      .collect();
+    if matching.is_empty() {
+      return Err(Error::invalid_params(format!(
+        "{instruction} is not available on {}",
+        filter.unwrap_or_default()
+      )));
+    }
+    Ok(Some(Value::String(format_full_documentation(&matching, &split.variant))))
+  }
+
+  /// `amdgpu.searchInstructions <query> [architecture]`: instructions whose names or descriptions
+  /// match free text, best first, with a description snippet for each.
+  async fn search_instructions(&self, arguments: &[Value]) -> Result<Option<Value>> {
+    let query = match arguments.first().and_then(|value| value.as_str()) {
+      Some(query) => query,
+      None => return Err(Error::invalid_params("Expected a search query argument")),
+    };
+    let filter = arguments
+      .get(1)
+      .and_then(|value| value.as_str())
+      .map(|value| normalize_architecture_hint(value, &self.architecture_aliases));
+    if let Some(filter) = &filter {
+      self.load_architecture(filter).await;
+    }
+    let search_index = lock(&self.search_index).clone();
+    let results = search_index.search(
+      &self.index(),
+      query,
+      |entry| filter.as_ref().is_none_or(|filter| entry_matches_arch(entry, filter)),
+      SEARCH_RESULT_LIMIT,
+    );
+    serde_json::to_value(results).map(Some).map_err(|_| Error::internal_error())
+  }
+
+  /// `amdgpu.explainLine <uri> <line> [character]`: the instruction at a position of an open document
+  /// (the line's first statement without a character), its encoding, what each operand is and the line's
+  /// diagnostics.
+  async fn explain_line(&self, arguments: &[Value]) -> Result<Option<Value>> {
+    let dialect = self.dialect();
+    let index = self.index();
+    let uri = arguments
+      .first()
+      .and_then(|value| value.as_str())
+      .and_then(|value| Url::parse(value).ok());
+    let (uri, line_idx) = match (uri, arguments.get(1).and_then(|value| value.as_u64())) {
+      (Some(uri), Some(line_idx)) => (uri, line_idx as u32),
+      _ => return Err(Error::invalid_params("Expected a document URI and a line number")),
+    };
+    let doc = match self.get_document(&uri).await {
+      Some(doc) => doc,
+      None => return Err(Error::invalid_params(format!("Document is not open: {uri}"))),
+    };
+    let line = match doc.text.lines().nth(line_idx as usize) {
+      Some(line) => line,
+      None => return Err(Error::invalid_params(format!("Line {line_idx} is past the end of {uri}"))),
+    };
+    let character = arguments.get(2).and_then(|value| value.as_u64()).unwrap_or(0) as u32;
+    let cursor = utf16_position_to_byte_offset(line, Position::new(line_idx, character));
+    let filter = self.document_architecture_filter(&doc);
+    let diagnostics =
+      document_diagnostics(&doc.text, &index, &self.memory_modifiers, &self.errata, filter.as_deref(), dialect);
+    let explanation = explain_line(
+      line_idx,
+      line,
+      &index,
+      filter.as_deref(),
+      &self.special_registers,
+      &collect_symbol_definitions(&doc.text, dialect),
+      &diagnostics,
+      instruction_at(line, cursor, dialect),
+    );
+    serde_json::to_value(explanation).map(Some).map_err(|_| Error::internal_error())
+  }
+
+  /// `amdgpu.findInstructionUsages <instruction> [architecture]`: every line of the workspace using
+  /// an instruction, with any encoding suffix. With an architecture, files whose own architecture
+  /// is known and different are skipped.
+  async fn find_instruction_usages(&self, arguments: &[Value]) -> Result<Option<Value>> {
+    let dialect = self.dialect();
+    let instruction = match arguments.first().and_then(|value| value.as_str()) {
+      Some(instruction) => instruction,
+      None => return Err(Error::invalid_params("Expected an instruction name argument")),
+    };
+    let filter = arguments
+      .get(1)
+      .and_then(|value| value.as_str())
+      .map(|value| normalize_architecture_hint(value, &self.architecture_aliases));
+    let mnemonic = split_encoding_variant(instruction.trim()).base.to_ascii_lowercase();
+    let roots = lock(&self.workspace_roots).clone();
+    let persist = self.persist_workspace_index.load(Ordering::Relaxed);
+    let cache_path = if persist { workspace_cache_path(&roots) } else { None };
+    let files = self.scan_workspace_files(cache_path.clone()).await;
+    let mut locations = Vec::new();
+    for path in &files {
+      let uri = match Url::from_file_path(path) {
+        Ok(uri) => uri,
+        Err(_) => continue,
+      };
+      // Open documents may have unsaved edits and a detected architecture; prefer them.
+      let open = self.get_document(&uri).await;
+      let file_architecture = match &open {
+        Some(doc) => self.document_architecture_filter(doc),
+        None => {
+          let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+          let override_arch = lock(&self.architecture_override).clone();
+          let default_arch = lock(&self.default_architecture).clone();
+          architecture_filter(extension, override_arch.as_ref(), default_arch.as_ref(), &self.architecture_aliases)
+        }
+      };
+      if let (Some(filter), Some(file_architecture)) = (&filter, &file_architecture)
+        && !arch_matches(file_architecture, filter)
+      {
+        continue;
+      }
+      let mnemonic = mnemonic.clone();
+      let path = path.clone();
+      let workspace_index = self.workspace_index.clone();
+      let found = run_analysis(move || match open {
+        Some(doc) => instruction_locations(&uri, &file_instruction_uses(&doc.text, dialect), &mnemonic),
+        None => lock(&workspace_index)
+          .as_mut()
+          .and_then(|index| index.instruction_locations(&uri, &path, &mnemonic))
+          .unwrap_or_default(),
+      })
+      .await
+      .unwrap_or_default();
+      locations.extend(found);
+    }
+    self.save_workspace_index(cache_path, files).await;
+    serde_json::to_value(locations).map(Some).map_err(|_| Error::internal_error())
+  }
+
+  /// Assembly files under the workspace roots, with the workspace index opened for them. Both read
+  /// the disk, so they run as background analysis.
+  async fn scan_workspace_files(&self, cache_path: Option<PathBuf>) -> Vec<PathBuf> {
+    let roots = lock(&self.workspace_roots).clone();
+    let workspace_index = self.workspace_index.clone();
+    let dialect = self.dialect();
+    run_analysis(move || {
+      lock(&workspace_index).get_or_insert_with(|| match cache_path {
+        Some(path) => WorkspaceIndexCache::load(&path, dialect),
+        None => WorkspaceIndexCache::new(dialect),
+      });
+      collect_assembly_files(&roots)
+    })
+    .await
+    .unwrap_or_default()
+  }
+
+  /// Replaces the workspace roots. The workspace index belongs to one set of roots (and its cache
+  /// file is named after them), so it is dropped when they change.
+  fn set_workspace_roots(&self, roots: Vec<PathBuf>) {
+    let mut current = lock(&self.workspace_roots);
+    if *current != roots {
+      *current = roots;
+      *lock(&self.workspace_index) = None;
+    }
+  }
+
+  /// Persists the workspace index for `files` when it is backed by a cache file.
+  async fn save_workspace_index(&self, cache_path: Option<PathBuf>, files: Vec<PathBuf>) {
+    let path = match cache_path {
+      Some(path) => path,
+      None => return,
+    };
+    let workspace_index = self.workspace_index.clone();
+    let saved = run_analysis(move || match lock(&workspace_index).as_mut() {
+      Some(index) => {
+        index.retain_files(&files);
+        index.save(&path)
+      }
+      None => Ok(()),
+    })
+    .await;
+    if let Some(Err(err)) = saved {
+      self
+        .client
+        .log_message(MessageType::WARNING, format!("failed to write the workspace index cache: {err}"))
+        .await;
+    }
+  }
+
+  /// `amdgpu/health`: whether the ISA data loaded, from where, and how much of it.
+  pub async fn health(&self) -> Result<HealthReport> {
+    let deferred = lock(&self.deferred).clone();
+    Ok(health_report(&self.load_info, &self.index(), &deferred))
+  }
+
+  /// `amdgpu/instructionHistogram`: instruction counts of an open document by mnemonic and category.
+  pub async fn instruction_histogram(&self, params: InstructionHistogramParams) -> Result<InstructionHistogram> {
+    let dialect = self.dialect();
+    let uri = params.text_document.uri;
+    let doc = match self.get_document(&uri).await {
+      Some(doc) => doc,
+      None => return Err(Error::invalid_params(format!("Document is not open: {uri}"))),
+    };
+    let filter = self.document_architecture_filter(&doc);
+    let index = self.index();
+    run_analysis(move || instruction_histogram(&doc.text, &index, filter.as_deref(), dialect))
+      .await
+      .ok_or_else(Error::internal_error)
+  }
+
+  /// `amdgpu/registerUsage`: register counts, estimated occupancy and code size of each kernel of
+  /// an open document.
+  pub async fn register_usage(&self, params: RegisterUsageParams) -> Result<Vec<KernelUsageReport>> {
+    let uri = params.text_document.uri;
+    let doc = match self.get_document(&uri).await {
+      Some(doc) => doc,
+      None => return Err(Error::invalid_params(format!("Document is not open: {uri}"))),
+    };
+    let filter = self.document_architecture_filter(&doc);
+    let index = self.index();
+    let reports = analyze_register_usage(&doc.text, &index, filter.as_deref(), self.dialect())
+      .into_iter()
+      .map(|usage| KernelUsageReport {
+        estimated_waves: estimated_waves(&usage, filter.as_deref()),
+        usage,
+      })
+      .collect();
+    Ok(reports)
+  }
+
+  /// `amdgpu/rawEntry`: the `isa.json` records for an instruction name, unformatted, for clients
+  /// that render instructions themselves.
+  pub async fn raw_entry(&self, params: RawEntryParams) -> Result<Vec<InstructionEntry>> {
+    let filter = params
+      .architecture
+      .as_deref()
+      .map(|value| normalize_architecture_hint(value, &self.architecture_aliases));
+    if let Some(filter) = &filter {
+      self.load_architecture(filter).await;
+    }
+    let index = self.index();
+    let split = split_encoding_variant(params.name.trim());
+    let entries = match index.get(&split.base.to_ascii_lowercase()) {
+      Some(entries) => entries,
+      None => return Err(Error::invalid_params(format!("Unknown instruction: {}", params.name))),
+    };
+    let matching: Vec<InstructionEntry> = entries
+      .iter()
+      .filter(|entry| filter.as_ref().is_none_or(|filter| entry_matches_arch(entry, filter)))
+      .cloned()
+      .collect();
+    if matching.is_empty() {
+      return Err(Error::invalid_params(format!(
+        "{} is not available on {}",
+        params.name,
+        filter.unwrap_or_default()
+      )));
+    }
+    Ok(matching)
+  }
+
+  /// Sends `amdgpu/architectureChanged` when the document's effective architecture differs from
+  /// the one last reported.
+  async fn report_architecture(&self, uri: &Url) {
+    let doc = match self.get_document(uri).await {
+      Some(doc) => doc,
+      None => return,
+    };
+    let architecture = self.document_architecture_filter(&doc);
+    if doc.reported_architecture.as_ref() == Some(&architecture) {
+      return;
+    }
+    if let Some(stored) = self.docs.write().await.docs.get_mut(uri) {
+      stored.reported_architecture = Some(architecture.clone());
+    }
+    self
+      .client
+      .send_notification::<ArchitectureChanged>(ArchitectureChangedParams {
+        uri: uri.clone(),
+        architecture,
+      })
+      .await;
+  }
+
+  /// Parses settings from `initializationOptions` or `workspace/didChangeConfiguration`, applies the
+  /// valid ones and returns the warning to show the user about the rest.
+  fn apply_settings(&self, options: &Value) -> (ServerConfig, Option<String>) {
+    let (config, errors) = ServerConfig::parse(options);
+    let warning =
+      (!errors.is_empty()).then(|| format!("AMDGPU language server: ignoring invalid settings: {}", errors.join("; ")));
+    if let Some(warning) = &warning {
+      log_line(warning);
+    }
+    self.apply_options(&config);
+    (config, warning)
+  }
+
+  /// Applies the settings shared by `initializationOptions` and `workspace/didChangeConfiguration`.
+  fn apply_options(&self, config: &ServerConfig) {
+    if let Some(override_arch) = config.architecture_override() {
+      *lock(&self.architecture_override) = (!override_arch.trim().is_empty())
+        .then(|| normalize_architecture_hint(override_arch, &self.architecture_aliases));
+    }
+    if let Some(sections) = &config.hover_sections {
+      *lock(&self.hover_sections) = HoverSections::from_value(&Value::Object(sections.clone()));
+      lock(&self.hover_cache).clear();
+    }
+    if let Some(paths) = &config.isa_overlays {
+      let (overlays, errors) = IsaOverlays::load(paths);
+      for error in errors {
+        log_line(&error);
+      }
+      *lock(&self.overlays) = Arc::new(overlays);
+      lock(&self.hover_cache).clear();
+    }
+    if let Some(symbols) = &config.external_symbols {
+      *lock(&self.external_symbols) = Arc::new(ExternalSymbols::from_value(&symbols.to_value()));
+    }
+    if let Some(threshold_ms) = config.slow_request_ms {
+      self.timing.set_threshold_ms(threshold_ms);
+    }
+    if let Some(log_to_client) = config.log_slow_requests {
+      self.timing.set_log_to_client(log_to_client);
+    }
+    if let Some(persist) = config.workspace_index_cache
+      && self.persist_workspace_index.swap(persist, Ordering::Relaxed) != persist
+    {
+      // The loaded index is backed by the cache file (or not); start over on next use.
+      *lock(&self.workspace_index) = None;
+    }
+    if let Some(colors) = config.color_decorators {
+      self.color_decorators.store(colors, Ordering::Relaxed);
+    }
+    if let Some(explain) = config.explain_idioms {
+      self.explain_idioms.store(explain, Ordering::Relaxed);
+    }
+    if let Some(style) = &config.encoding_suffix_style {
+      *lock(&self.suffix_style) = SuffixStyle::from_option(style);
+    }
+    if let Some(style) = &config.operand_inlay_hints {
+      *lock(&self.inlay_style) = InlayStyle::from_option(style);
+    }
+    if let Some(formatting) = &config.formatting {
+      *lock(&self.format_settings) = FormatSettings::from_value(&Value::Object(formatting.clone()));
+    }
+    if let Some(dialect) = &config.assembler_dialect {
+      let dialect = Dialect::from_option(dialect);
+      if std::mem::replace(&mut *lock(&self.dialect), dialect) != dialect {
+        // Everything parsed under the old dialect is stale: hovers, kernel analyses and the workspace
+        // index, which reloads and discards a cache file built under another dialect.
+        lock(&self.hover_cache).clear();
+        lock(&self.segment_cache).clear();
+        *lock(&self.workspace_index) = None;
+      }
+    }
+  }
+
+  /// Queues diagnostics for the document on the background scheduler; they are published when
+  /// ready unless the document changed or closed in the meantime.
+  async fn publish_diagnostics(&self, uri: Url) {
+    let dialect = self.dialect();
+    let doc = match self.get_document(&uri).await {
+      Some(doc) => doc,
+      None => return,
+    };
+    let banner = missing_data_diagnostic(&self.load_info);
+    let filter = self.document_architecture_filter(&doc);
+    let index = self.index();
+    let modifiers = self.memory_modifiers.clone();
+    let errata = self.errata.clone();
+    let docs = self.docs.clone();
+    let client = self.client.clone();
+    self.scheduler.submit(format!("diagnostics {uri}"), async move {
+      let text = doc.text.clone();
+      let analysis = move || {
+        // Without data only the text checks (conditionals, relocations, segments) run; the banner says why.
+        let diagnostics = document_diagnostics(&text, &index, &modifiers, &errata, filter.as_deref(), dialect);
+        banner.into_iter().chain(diagnostics).collect()
+      };
+      let diagnostics = match run_analysis(analysis).await {
+        Some(diagnostics) => diagnostics,
+        None => return,
+      };
+      // An edit that arrived during the analysis publishes its own, newer diagnostics.
+      let current = docs.read().await.docs.get(&uri).map(|current| current.text == doc.text);
+      if current != Some(true) {
+        return;
+      }
+      client.publish_diagnostics(uri, diagnostics, None).await;
+    });
   }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for IsaServer {
   async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+    let mut discover_target = false;
     if let Some(options) = params.initialization_options {
-      if let Some(override_arch) = options.get("architectureOverride").and_then(|value| value.as_str()) {
-        if let Ok(mut stored) = self.architecture_override.lock() {
-          *stored = Some(normalize_architecture_hint(override_arch));
-        }
+      let (config, warning) = self.apply_settings(&options);
+      discover_target = config.discover_target.unwrap_or(false);
+      // Shown from `initialized`: messages sent while initializing may never reach the client.
+      *lock(&self.initialization_warning) = warning;
+    }
+    let workspace_edit = params
+      .capabilities
+      .workspace
+      .as_ref()
+      .and_then(|workspace| workspace.workspace_edit.as_ref());
+    let change_annotations = workspace_edit
+      .is_some_and(|edit| edit.document_changes == Some(true) && edit.change_annotation_support.is_some());
+    self.change_annotations.store(change_annotations, Ordering::Relaxed);
+    let mut roots: Vec<PathBuf> = params
+      .workspace_folders
+      .unwrap_or_default()
+      .iter()
+      .filter_map(|folder| folder.uri.to_file_path().ok())
+      .collect();
+    #[allow(deprecated)]
+    let root_uri = params.root_uri;
+    if roots.is_empty() && let Some(root) = root_uri.and_then(|uri| uri.to_file_path().ok()) {
+      roots.push(root);
+    }
+    let mut default_arch = None;
+    if discover_target && let Some((arch, source)) = discover_target_architecture(&roots, &self.architecture_aliases) {
+      self
+        .client
+        .log_message(
+          MessageType::INFO,
+          format!("Using target architecture {arch} from {}", source.display()),
+        )
+        .await;
+      default_arch = Some(arch);
+    }
+    if default_arch.is_none() && let Some(raw) = &self.command_line_architecture {
+      let arch = normalize_architecture_hint(raw, &self.architecture_aliases);
+      self
+        .client
+        .log_message(MessageType::INFO, format!("Using target architecture {arch} from --arch"))
+        .await;
+      default_arch = Some(arch);
+    }
+    if default_arch.is_none() {
+      default_arch = environment_architecture(&self.architecture_aliases);
+      if let Some(arch) = &default_arch {
+        self
+          .client
+          .log_message(MessageType::INFO, format!("Using target architecture {arch} from the environment"))
+          .await;
       }
     }
+    *lock(&self.default_architecture) = default_arch;
+    self.set_workspace_roots(roots);
     if let Some(error) = &self.load_info.load_error {
       self
         .client
         .log_message(MessageType::ERROR, format!("{error} (path: {})", self.load_info.data_path))
         .await;
     } else {
-      let total_entries: usize = self.index.values().map(|entries| entries.len()).sum();
+      let index = self.index();
+      let total_entries: usize = index.values().map(|entries| entries.len()).sum();
       self
         .client
         .log_message(
@@ -75,7 +1270,7 @@ impl LanguageServer for IsaServer {
           format!(
             "Loaded {} ISA entries ({} unique names) from {}",
             total_entries,
-            self.index.len(),
+            index.len(),
             self.load_info.data_path
           ),
         )
@@ -91,57 +1286,145 @@ impl LanguageServer for IsaServer {
           work_done_progress_options: Default::default(),
         }),
         definition_provider: Some(OneOf::Left(true)),
+        declaration_provider: Some(DeclarationCapability::Simple(true)),
+        references_provider: Some(OneOf::Left(true)),
+        document_highlight_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+          prepare_provider: Some(true),
+          work_done_progress_options: Default::default(),
+        })),
         completion_provider: Some(CompletionOptions {
           trigger_characters: Some(vec!["_".to_string(), ".".to_string()]),
-          resolve_provider: Some(false),
+          resolve_provider: Some(true),
           work_done_progress_options: Default::default(),
           all_commit_characters: None,
           completion_item: None,
         }),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        workspace: Some(WorkspaceServerCapabilities {
+          workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+            supported: Some(true),
+            change_notifications: Some(OneOf::Left(true)),
+          }),
+          file_operations: None,
+        }),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        color_provider: Some(ColorProviderCapability::Simple(true)),
+        code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(false) }),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+          first_trigger_character: ",".to_string(),
+          more_trigger_character: Some(vec!["\n".to_string()]),
+        }),
+        inlay_hint_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+          SemanticTokensOptions {
+            legend: semantic_tokens_legend(),
+            full: Some(SemanticTokensFullOptions::Bool(true)),
+            ..Default::default()
+          },
+        )),
+        execute_command_provider: Some(ExecuteCommandOptions {
+          commands: EXECUTE_COMMANDS.iter().map(|command| command.to_string()).collect(),
+          work_done_progress_options: Default::default(),
+        }),
         ..ServerCapabilities::default()
       },
       ..InitializeResult::default()
     })
   }
 
+  async fn initialized(&self, _: tower_lsp::lsp_types::InitializedParams) {
+    let warning = lock(&self.initialization_warning).take();
+    if let Some(warning) = warning {
+      self.client.show_message(MessageType::WARNING, warning).await;
+    }
+  }
+
   async fn did_open(&self, params: tower_lsp::lsp_types::DidOpenTextDocumentParams) {
     let TextDocumentItem {
       uri,
       text,
       language_id,
+      version,
       ..
     } = params.text_document;
-    if let Ok(mut store) = self.docs.lock() {
-      store.docs.insert(
-        uri,
-        DocumentState {
-          text,
-          language_id,
-        },
-      );
-    }
+    self.docs.write().await.docs.insert(
+      uri.clone(),
+      DocumentState {
+        text,
+        version,
+        language_id,
+        ..DocumentState::default()
+      },
+    );
+    self.update_detected_architecture(&uri).await;
+    self.report_architecture(&uri).await;
+    self.publish_diagnostics(uri).await;
   }
 
   async fn did_change(&self, params: tower_lsp::lsp_types::DidChangeTextDocumentParams) {
     if let Some(TextDocumentContentChangeEvent { text, .. }) = params.content_changes.into_iter().last() {
       let uri = params.text_document.uri.clone();
-      let mut new_len = None;
-      if let Ok(mut store) = self.docs.lock() {
-        let entry = store.docs.entry(uri.clone()).or_insert(DocumentState {
-          text: String::new(),
-          language_id: String::new(),
-        });
-        entry.text = text;
-        new_len = Some(entry.text.len());
+      {
+        let mut docs = self.docs.write().await;
+        let doc = docs.docs.entry(uri.clone()).or_default();
+        doc.text = text;
+        doc.version = params.text_document.version;
       }
-      let _ = new_len;
+      self.update_detected_architecture(&uri).await;
+      self.report_architecture(&uri).await;
+      self.publish_diagnostics(uri).await;
     }
   }
 
+  /// Accepts the initialization options again, either directly or under an `amdgpuLsp` section as
+  /// sent by clients that synchronize their settings, and re-resolves every open document.
+  async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+    let options = params.settings.get("amdgpuLsp").unwrap_or(&params.settings);
+    if let (_, Some(warning)) = self.apply_settings(options) {
+      self.client.show_message(MessageType::WARNING, warning).await;
+    }
+    let uris: Vec<Url> = self.docs.read().await.docs.keys().cloned().collect();
+    for uri in uris {
+      self.update_detected_architecture(&uri).await;
+      self.report_architecture(&uri).await;
+      self.publish_diagnostics(uri).await;
+    }
+  }
+
+  async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+    let removed: Vec<PathBuf> =
+      params.event.removed.iter().filter_map(|folder| folder.uri.to_file_path().ok()).collect();
+    let mut roots = lock(&self.workspace_roots).clone();
+    roots.retain(|root| !removed.contains(root));
+    for folder in params.event.added {
+      if let Ok(root) = folder.uri.to_file_path()
+        && !roots.contains(&root)
+      {
+        roots.push(root);
+      }
+    }
+    self.set_workspace_roots(roots);
+  }
+
+  async fn did_close(&self, params: DidCloseTextDocumentParams) {
+    let uri = params.text_document.uri;
+    self.docs.write().await.docs.remove(&uri);
+    lock(&self.segment_cache).remove(&uri);
+    self.client.publish_diagnostics(uri, Vec::new(), None).await;
+  }
+
   async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+    let dialect = self.dialect();
+    let index = self.index();
+    let _interactive = self.scheduler.interactive();
     let uri = params.text_document_position_params.text_document.uri;
     let position = params.text_document_position_params.position;
-    let doc = match self.get_document(&uri) {
+    let doc = match self.get_document(&uri).await {
       Some(doc) => doc,
       None => {
         return Ok(None);
@@ -152,54 +1435,186 @@ impl LanguageServer for IsaServer {
       None => return Ok(None),
     };
     let cursor_byte = utf16_position_to_byte_offset(line, position);
-    if let Some(comment_start) = line_comment_start(line) {
-      if cursor_byte >= comment_start {
-        return Ok(None);
+    if let Some(comment_start) = line_comment_start(line, dialect) && cursor_byte >= comment_start {
+      return Ok(self.encoding_comment_hover(&doc, line, cursor_byte));
+    }
+    // Words inside string literals and data directives (`.asciz "exec"`, `.byte 1, 2`) are not code.
+    if in_string_literal(line, cursor_byte, dialect) || data_directive(line, dialect).is_some() {
+      return Ok(None);
+    }
+    if let Some(relocation) = relocation_at(line, cursor_byte, dialect) {
+      return Ok(Some(Hover {
+        contents: format_relocation_hover(relocation.symbol.text, relocation.specifier.text, relocation.info()),
+        range: None,
+      }));
+    }
+    if let Some(contents) = directive_hover(line, cursor_byte, dialect) {
+      return Ok(Some(Hover { contents, range: None }));
+    }
+    if let Some(contents) = expression_hover(&doc.text, position.line, line, cursor_byte, dialect) {
+      return Ok(Some(Hover { contents, range: None }));
+    }
+    if let Some(dual) = parse_dual_line(line, dialect) {
+      return Ok(self.dual_hover(&doc, &dual, cursor_byte));
+    }
+    let word = extract_word_at_position(&doc.text, position);
+    // Registers, attributes and `hwreg(...)` cannot name a kernel, label or instruction.
+    let name = word.as_ref().filter(|word| word.kind == WordKind::Plain).map(|word| word.text.as_str());
+    if let Some(word) = name {
+      let segments = self.kernel_segments(&uri, &doc);
+      let (lds, scratch) = segments.as_ref();
+      if let Some(kernel) = lds.kernel(word) {
+        return Ok(Some(Hover {
+          contents: format_kernel_hover(kernel, lds.module_lds_bytes, scratch.kernel(word)),
+          range: None,
+        }));
+      }
+      if let Some(contents) = label_hover(&doc.text, line, position, dialect) {
+        return Ok(Some(Hover { contents, range: None }));
+      }
+      if let Some(value) = lock(&self.external_symbols).hover_markdown(word) {
+        return Ok(Some(Hover {
+          contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+          }),
+          range: None,
+        }));
+      }
+      // `null` as an instruction operand gets the operand hover, which explains it in context.
+      let is_null_operand = word.eq_ignore_ascii_case("null")
+        && instruction_at(line, cursor_byte, dialect).is_some_and(|parsed| cursor_byte > parsed.mnemonic.end());
+      if let Some(register) = self
+        .special_registers
+        .iter()
+        .filter(|_| !is_null_operand)
+        .find(|register| register.name.eq_ignore_ascii_case(word))
+      {
+        return Ok(Some(Hover {
+          contents: format_special_register_hover(register),
+          range: None,
+        }));
       }
     }
-    let word = match extract_word_at_position(&doc.text, position) {
-      Some(word) => word,
-      None => {
+    let filter = self.document_architecture_filter(&doc);
+    let sections = lock(&self.hover_sections).clone();
+    let overlays = lock(&self.overlays).clone();
+    let wait = instruction_at(line, cursor_byte, dialect)
+      .and_then(|parsed| Some((parsed.mnemonic, statement_wait(line, &parsed, filter.as_deref())?)));
+    // On the `_e64`/`_dpp`/... suffix itself: the encoding it selects rather than the instruction.
+    if let Some(mnemonic) = instruction_at(line, cursor_byte, dialect).map(|parsed| parsed.mnemonic) {
+      let split = split_encoding_variant(mnemonic.text);
+      let on_suffix = split.variant != EncodingVariant::Native
+        && mnemonic.start + split.base.len() < cursor_byte
+        && cursor_byte <= mnemonic.end();
+      if on_suffix && index.contains_key(&split.base.to_ascii_lowercase()) {
+        let entry = find_entry(&index, &split.base, filter.as_deref());
+        let entry = entry.map(|entry| overlays.apply(entry, filter.as_deref()));
+        let contents = format_encoding_suffix_hover(mnemonic.text, &split.base, entry.as_deref());
+        return Ok(Some(Hover { contents, range: None }));
+      }
+    }
+    if let Some(word) = name {
+      let split = split_encoding_variant(word);
+      if let Some(entry) = find_entry(&index, &split.base, filter.as_deref()) {
+        let annotation = wait
+          .as_ref()
+          .filter(|(mnemonic, _)| mnemonic.start <= cursor_byte && cursor_byte <= mnemonic.end())
+          .map(|(_, requirements)| describe_wait(requirements));
+        let annotation = self.with_idiom_note(line, cursor_byte, annotation);
+        let annotation = self.with_errata_banner(&split.base, filter.as_deref(), annotation);
+        let key = HoverKey {
+          token: split.base.to_ascii_lowercase(),
+          architecture: filter.clone(),
+          variant: split.variant.clone(),
+          annotation,
+        };
+        let cached = lock(&self.hover_cache).get(&key);
+        let contents = match cached {
+          Some(contents) => contents,
+          None => {
+            let entry = overlays.apply(entry, filter.as_deref());
+            let contents = format_hover(&entry, &split.variant, &sections, key.annotation.clone());
+            lock(&self.hover_cache).insert(key, contents.clone());
+            contents
+          }
+        };
+        return Ok(Some(Hover { contents, range: None }));
+      }
+      if index.contains_key(&split.base.to_ascii_lowercase()) {
+        // A real mnemonic that the active architecture lacks.
         return Ok(None);
       }
-    };
-    if let Some(register) = self
-      .special_registers
-      .iter()
-      .find(|register| register.name.eq_ignore_ascii_case(&word))
-    {
-      return Ok(Some(Hover {
-        contents: format_special_register_hover(register),
-        range: None,
-      }));
     }
-    // Split encoding variant from instruction name
-    let split = split_encoding_variant(&word);
-    let key = split.base.to_ascii_lowercase();
-    let entries = match self.index.get(&key) {
-      Some(entries) => entries,
-      None => return Ok(None),
+
+    // Anywhere else on an instruction line: the line's instruction, annotated with the operand
+    // under the cursor.
+    // A register tuple outside a known instruction (a macro argument, a directive) still gets its
+    // width and alignment.
+    let register_hover = || {
+      let word = word.as_ref().filter(|word| matches!(word.kind, WordKind::Register(_)))?;
+      let value = register_note(&word.text, filter.as_deref())?;
+      Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+          kind: MarkupKind::Markdown,
+          value,
+        }),
+        range: None,
+      })
     };
-    let override_arch = self.architecture_override.lock().ok().and_then(|value| value.clone());
-    if let Some(filter) = architecture_filter(&doc.language_id, override_arch.as_ref()) {
-      if let Some(entry) = entries.iter().find(|entry| entry_matches_arch(entry, &filter)) {
-        return Ok(Some(Hover {
-          contents: format_hover(entry, &split.variant),
-          range: None,
-        }));
-      }
+    let parsed = match instruction_at(line, cursor_byte, dialect) {
+      Some(parsed) => parsed,
+      None => return Ok(register_hover()),
+    };
+    if cursor_byte <= parsed.mnemonic.end() {
       return Ok(None);
     }
-    Ok(Some(Hover {
-      contents: format_hover(&entries[0], &split.variant),
-      range: None,
-    }))
+    let split = split_encoding_variant(parsed.mnemonic.text);
+    let entry = match find_entry(&index, &split.base, filter.as_deref()) {
+      Some(entry) => overlays.apply(entry, filter.as_deref()),
+      None => return Ok(register_hover()),
+    };
+    let entry = entry_for_variant(&entry, &split.variant);
+    let operand = parsed
+      .operands
+      .iter()
+      .enumerate()
+      .find(|(_, operand)| operand.start <= cursor_byte && cursor_byte <= operand.end());
+    let wait = wait.map(|(_, requirements)| describe_wait(&requirements));
+    let contents = match operand {
+      Some((operand_index, operand)) => {
+        let slot = entry.args.get(operand_index).map(|slot| slot.as_str());
+        let detail = special_operand_note(parsed.mnemonic.text, slot, operand.text)
+          .or_else(|| branch_target_note(&doc.text, line, &parsed, operand, dialect))
+          .or_else(|| smem_offset_note(&parsed, operand, filter.as_deref()))
+          .or_else(|| register_note(operand.text, filter.as_deref()))
+          .or_else(|| hwreg_note(&parsed, operand.text, filter.as_deref()))
+          .or_else(|| sendmsg_note(&parsed, operand.text, filter.as_deref()))
+          .or_else(|| packed_literal_note(&entry, operand_index, operand.text))
+          .or_else(|| numeric_literal_note(operand.text, entry.arg_data_types.get(operand_index).map(String::as_str)))
+          .or(wait);
+        format_operand_hover(&entry, &split.variant, &sections, operand_index, operand.text, detail)
+      }
+      None => {
+        let modifier =
+          parsed.modifiers.iter().find(|modifier| modifier.start <= cursor_byte && cursor_byte <= modifier.end());
+        let op_sel = modifier.filter(|_| is_vop3p(&entry)).and_then(|modifier| op_sel_note(&entry, &parsed, modifier));
+        let smem = modifier.and_then(|modifier| smem_offset_note(&parsed, modifier, filter.as_deref()));
+        let annotation = self.with_idiom_note(line, cursor_byte, op_sel.or(smem).or(wait));
+        let annotation = self.with_errata_banner(&split.base, filter.as_deref(), annotation);
+        format_hover(&entry, &split.variant, &sections, annotation)
+      }
+    };
+    Ok(Some(Hover { contents, range: None }))
   }
 
   async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+    let dialect = self.dialect();
+    let index = self.index();
+    let _interactive = self.scheduler.interactive();
     let uri = params.text_document_position_params.text_document.uri;
     let position = params.text_document_position_params.position;
-    let doc = match self.get_document(&uri) {
+    let doc = match self.get_document(&uri).await {
       Some(doc) => doc,
       None => {
         return Ok(None);
@@ -212,31 +1627,38 @@ impl LanguageServer for IsaServer {
       None => return Ok(None),
     };
     let cursor_byte = utf16_position_to_byte_offset(line, position);
-    if let Some(comment_start) = line_comment_start(line) {
-      if cursor_byte >= comment_start {
-        return Ok(None);
-      }
+    if let Some(comment_start) = line_comment_start(line, dialect) && cursor_byte >= comment_start {
+      return Ok(None);
+    }
+    if in_string_literal(line, cursor_byte, dialect) {
+      return Ok(None);
     }
 
-    let (label_offset, line_after_label) = strip_leading_label(line);
-    if cursor_byte < label_offset {
+    let parsed = match instruction_at(line, cursor_byte, dialect) {
+      Some(parsed) => parsed,
+      None => return Ok(None),
+    };
+    // Signatures only show once the cursor is past the mnemonic and its separating whitespace.
+    if cursor_byte <= parsed.mnemonic.end() {
       return Ok(None);
     }
+    let instruction = parsed.mnemonic.text;
 
-    // Find the instruction at the start of the line (before any spaces/commas)
-    let instruction = line_after_label
-      .split(|c: char| c.is_whitespace() || c == ',')
-      .next()
-      .unwrap_or("");
+    // A user macro shadows an instruction of the same name.
+    let macros = collect_macro_signatures(&doc.text, dialect);
+    if let Some(signature) = macros.iter().find(|signature| signature.name.eq_ignore_ascii_case(instruction)) {
+      return Ok(macro_signature_help(signature, line, &parsed.mnemonic, cursor_byte));
+    }
 
-    if instruction.is_empty() {
-      return Ok(None);
+    if let Some(dual) = parse_dual_line(line, dialect) {
+      let filter = self.document_architecture_filter(&doc);
+      return Ok(self.dual_signature_help(&dual, line, cursor_byte, filter.as_deref()));
     }
 
     // Split encoding variant from instruction name
     let split = split_encoding_variant(instruction);
     let key = split.base.to_ascii_lowercase();
-    let entries = match self.index.get(&key) {
+    let entries = match index.get(&key) {
       Some(entries) => entries,
       None => {
         return Ok(None);
@@ -244,69 +1666,34 @@ impl LanguageServer for IsaServer {
     };
 
     // Filter by architecture if needed
-    let override_arch = self.architecture_override.lock().ok().and_then(|value| value.clone());
-    let entry = if let Some(filter) = architecture_filter(&doc.language_id, override_arch.as_ref()) {
-      match entries.iter().find(|entry| entry_matches_arch(entry, &filter)) {
+    let filter = self.document_architecture_filter(&doc);
+    let entry = if let Some(filter) = &filter {
+      match entries.iter().find(|entry| entry_matches_arch(entry, filter)) {
         Some(entry) => entry,
         None => return Ok(None),
       }
     } else {
       &entries[0]
     };
+    let entry = entry_for_variant(entry, &split.variant);
+    let entry = entry.as_ref();
 
     if entry.args.is_empty() {
       return Ok(None);
     }
 
-    let line_before_cursor = &line[..cursor_byte.min(line.len())];
-    let (_, line_before_cursor) = strip_leading_label(line_before_cursor);
-    let trimmed_before_cursor = line_before_cursor.trim_start();
-    let args_section = match trimmed_before_cursor
-      .splitn(2, |c: char| c.is_whitespace())
-      .nth(1)
-    {
-      Some(args_section) => args_section,
-      None => return Ok(None),
-    };
-    let commas_before_cursor = args_section.chars().filter(|&c| c == ',').count();
+    let operand_index = operand_index_at(line, &parsed.mnemonic, cursor_byte);
     let active_parameter = if entry.args.is_empty() {
       None
     } else {
       let last_index = entry.args.len().saturating_sub(1);
-      Some(commas_before_cursor.min(last_index) as u32)
-    };
-
-    // Build signature with parameter information
-    let mut label = format_mnemonic(&entry.name);
-    let mut parameters = Vec::new();
-
-    if !entry.args.is_empty() {
-      label.push(' ');
-      let args_str = entry.args.join(", ");
-      let base_len = label.len();
-      label.push_str(&args_str);
-
-      // Create parameter information for each argument
-      let mut current_offset = base_len;
-      for (i, arg) in entry.args.iter().enumerate() {
-        let arg_type = entry.arg_types.get(i).map(|s| s.as_str()).unwrap_or("");
-        let compact_type = arg_type.replace("register", "reg");
-
-        parameters.push(ParameterInformation {
-          label: ParameterLabel::LabelOffsets([current_offset as u32, (current_offset + arg.len()) as u32]),
-          documentation: if !compact_type.is_empty() {
-            Some(tower_lsp::lsp_types::Documentation::String(compact_type))
-          } else {
-            None
-          },
-        });
+      Some(operand_index.min(last_index) as u32)
+    };
 
-        current_offset += arg.len();
-        if i < entry.args.len() - 1 {
-          current_offset += 2; // ", "
-        }
-      }
-    }
+    // Keep the typed suffix so the label names the encoding whose operands are listed.
+    let mut label = format_mnemonic(&format!("{}{}", entry.name, &instruction[split.base.len()..]));
+    let all_operands: Vec<usize> = (0..entry.args.len()).collect();
+    let parameters = push_operand_parameters(&mut label, entry, &all_operands, filter.as_deref());
 
     let signature = SignatureInformation {
       label,
@@ -328,55 +1715,235 @@ impl LanguageServer for IsaServer {
     &self,
     params: GotoDefinitionParams,
   ) -> Result<Option<GotoDefinitionResponse>> {
+    let dialect = self.dialect();
     let uri = params.text_document_position_params.text_document.uri;
     let position = params.text_document_position_params.position;
-    let doc = match self.get_document(&uri) {
+    let doc = match self.get_document(&uri).await {
       Some(doc) => doc,
       None => return Ok(None),
     };
-    let line = match doc.text.lines().nth(position.line as usize) {
-      Some(line) => line,
+    let line = doc.text.lines().nth(position.line as usize);
+    let label = match line.and_then(|line| symbol_at_position(line, position, dialect)) {
+      Some(label) => label,
       None => return Ok(None),
     };
-    let cursor_byte = utf16_position_to_byte_offset(line, position);
-    if let Some(comment_start) = line_comment_start(line) {
-      if cursor_byte >= comment_start {
-        return Ok(None);
-      }
-    }
-    let (label, _) = match extract_label_at_position(line, position) {
+    let (def_line, def_start, def_end) = match find_label_definition(&doc.text, &label, dialect) {
       Some(value) => value,
+      None => match collect_symbol_definitions(&doc.text, dialect)
+        .into_iter()
+        .find(|definition| definition.name == label)
+      {
+        Some(definition) => (definition.line, definition.start, definition.end),
+        None => return Ok(None),
+      },
+    };
+    Ok(symbol_location(uri, &doc.text, def_line, def_start, def_end).map(GotoDefinitionResponse::Scalar))
+  }
+
+  /// The `.globl` directive for a symbol, as opposed to the `name:` label that defines it.
+  async fn goto_declaration(&self, params: GotoDeclarationParams) -> Result<Option<GotoDeclarationResponse>> {
+    let dialect = self.dialect();
+    let uri = params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+    let doc = match self.get_document(&uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let line = doc.text.lines().nth(position.line as usize);
+    let label = match line.and_then(|line| symbol_at_position(line, position, dialect)) {
+      Some(label) => label,
       None => return Ok(None),
     };
-    let (def_line, def_start, def_end) = match find_label_definition(&doc.text, &label) {
+    let (line, start, end) = match find_global_declaration(&doc.text, &label, dialect) {
       Some(value) => value,
       None => return Ok(None),
     };
-    let def_text = match doc.text.lines().nth(def_line as usize) {
+    Ok(symbol_location(uri, &doc.text, line, start, end).map(GotoDeclarationResponse::Scalar))
+  }
+
+  /// Every reference to a label or constant defined in the document: branches to it and other
+  /// uses, plus the definition when the client asks for declarations.
+  async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+    let dialect = self.dialect();
+    let uri = params.text_document_position.text_document.uri;
+    let position = params.text_document_position.position;
+    let doc = match self.get_document(&uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let line = doc.text.lines().nth(position.line as usize);
+    let name = match line.and_then(|line| symbol_at_position(line, position, dialect)) {
+      Some(name) => name,
+      None => return Ok(None),
+    };
+    if !defines_symbol(&doc.text, &name, dialect) {
+      return Ok(None);
+    }
+    let include_declaration = params.context.include_declaration;
+    let locations = symbol_occurrences(&doc.text, &name, dialect)
+      .into_iter()
+      .filter(|occurrence| include_declaration || occurrence.kind != OccurrenceKind::Definition)
+      .filter_map(|occurrence| {
+        symbol_location(uri.clone(), &doc.text, occurrence.line, occurrence.start, occurrence.end)
+      })
+      .collect();
+    Ok(Some(locations))
+  }
+
+  /// Highlights every occurrence of the register under the cursor, counting ranges that include it.
+  async fn document_highlight(&self, params: DocumentHighlightParams) -> Result<Option<Vec<DocumentHighlight>>> {
+    let position = params.text_document_position_params.position;
+    let doc = match self.get_document(&params.text_document_position_params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let word = match extract_word_at_position(&doc.text, position) {
+      Some(word) => word,
+      None => return Ok(None),
+    };
+    let register = match word.kind {
+      WordKind::Register(range) => Some(range),
+      WordKind::Plain => parse_register(&word.text),
+      _ => None,
+    };
+    Ok(register.map(|register| register_highlights(&doc.text, &register, self.dialect())))
+  }
+
+  async fn prepare_rename(&self, params: TextDocumentPositionParams) -> Result<Option<PrepareRenameResponse>> {
+    let dialect = self.dialect();
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let line = match doc.text.lines().nth(params.position.line as usize) {
       Some(line) => line,
       None => return Ok(None),
     };
-    let start = Position {
-      line: def_line,
-      character: byte_offset_to_utf16_position(def_text, def_start),
+    let name = match symbol_at_position(line, params.position, dialect) {
+      Some(name) => name,
+      None => return Ok(None),
     };
-    let end = Position {
-      line: def_line,
-      character: byte_offset_to_utf16_position(def_text, def_end),
+    let position_byte = utf16_position_to_byte_offset(line, params.position);
+    // Only symbols defined in this document can be renamed; registers and mnemonics cannot.
+    if !defines_symbol(&doc.text, &name, dialect) {
+      return Ok(None);
+    }
+    let occurrence = match symbol_occurrences(&doc.text, &name, dialect).into_iter().find(|occurrence| {
+      occurrence.line == params.position.line && occurrence.start <= position_byte && position_byte <= occurrence.end
+    }) {
+      Some(occurrence) => occurrence,
+      None => return Ok(None),
     };
-    Ok(Some(GotoDefinitionResponse::Scalar(Location {
-      uri,
-      range: Range { start, end },
-    })))
+    Ok(
+      symbol_location(params.text_document.uri, &doc.text, occurrence.line, occurrence.start, occurrence.end)
+        .map(|location| PrepareRenameResponse::RangeWithPlaceholder {
+          range: location.range,
+          placeholder: name,
+        }),
+    )
+  }
+
+  /// Renames a label or constant throughout the document. Clients that support change annotations
+  /// get each edit tagged as a definition, branch reference or data reference.
+  async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+    let dialect = self.dialect();
+    let uri = params.text_document_position.text_document.uri;
+    let position = params.text_document_position.position;
+    let doc = match self.get_document(&uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let line = doc.text.lines().nth(position.line as usize);
+    let name = match line.and_then(|line| symbol_at_position(line, position, dialect)) {
+      Some(name) => name,
+      None => return Ok(None),
+    };
+    if !defines_symbol(&doc.text, &name, dialect) {
+      return Ok(None);
+    }
+    if !is_symbol_name(&params.new_name) {
+      return Err(Error::invalid_params(format!("`{}` is not a valid symbol name", params.new_name)));
+    }
+    let occurrences = symbol_occurrences(&doc.text, &name, dialect);
+    let lines: Vec<&str> = doc.text.lines().collect();
+    let edit_for = |occurrence: &SymbolOccurrence| {
+      let line = lines[occurrence.line as usize];
+      TextEdit {
+        range: Range {
+          start: Position {
+            line: occurrence.line,
+            character: byte_offset_to_utf16_position(line, occurrence.start),
+          },
+          end: Position {
+            line: occurrence.line,
+            character: byte_offset_to_utf16_position(line, occurrence.end),
+          },
+        },
+        new_text: params.new_name.clone(),
+      }
+    };
+
+    if !self.change_annotations.load(Ordering::Relaxed) {
+      let edits = occurrences.iter().map(edit_for).collect();
+      return Ok(Some(WorkspaceEdit {
+        changes: Some(HashMap::from([(uri, edits)])),
+        ..Default::default()
+      }));
+    }
+    let edits = occurrences
+      .iter()
+      .map(|occurrence| {
+        OneOf::Right(AnnotatedTextEdit {
+          text_edit: edit_for(occurrence),
+          annotation_id: occurrence.kind.annotation_id().to_string(),
+        })
+      })
+      .collect();
+    let annotations = OccurrenceKind::ALL
+      .into_iter()
+      .filter(|kind| occurrences.iter().any(|occurrence| occurrence.kind == *kind))
+      .map(|kind| {
+        let count = occurrences.iter().filter(|occurrence| occurrence.kind == kind).count();
+        let annotation = ChangeAnnotation {
+          label: kind.annotation_id().to_string(),
+          needs_confirmation: None,
+          description: Some(format!("{count} × `{name}`")),
+        };
+        (kind.annotation_id().to_string(), annotation)
+      })
+      .collect();
+    Ok(Some(WorkspaceEdit {
+      document_changes: Some(DocumentChanges::Edits(vec![TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+        edits,
+      }])),
+      change_annotations: Some(annotations),
+      ..Default::default()
+    }))
   }
 
   async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+    let dialect = self.dialect();
+    let index = self.index();
+    let _interactive = self.scheduler.interactive();
     let uri = params.text_document_position.text_document.uri;
     let position = params.text_document_position.position;
-    let doc = match self.get_document(&uri) {
+    let doc = match self.get_document(&uri).await {
       Some(doc) => doc,
       None => return Ok(None),
     };
+    if let Some(items) = self
+      .directive_completions(&doc, position)
+      .or_else(|| self.modifier_completions(&doc, position))
+      .or_else(|| self.hwreg_completions(&doc, position))
+      .or_else(|| self.agpr_completions(&doc, position))
+      .or_else(|| self.register_completions(&doc, position))
+    {
+      return Ok(Some(CompletionResponse::List(CompletionList {
+        is_incomplete: false,
+        items,
+      })));
+    }
 
     let (prefix, prefix_start) = match extract_word_prefix_at_position(&doc.text, position) {
       Some((prefix, prefix_start)) => (prefix, prefix_start),
@@ -392,16 +1959,20 @@ impl LanguageServer for IsaServer {
       Some(line) => line,
       None => return Ok(None),
     };
-    if let Some(comment_start) = line_comment_start(line) {
-      if prefix_start >= comment_start {
-        return Ok(None);
-      }
+    if let Some(comment_start) = line_comment_start(line, dialect) && prefix_start >= comment_start {
+      return Ok(None);
     }
 
-    // Only show completions for the first word on a line (the instruction)
-    let line_before_prefix = &line[..prefix_start];
+    // Only show completions for the first word of a statement (the instruction)
+    let statement_start = statement_ranges(line, dialect)
+      .into_iter()
+      .map(|(start, _)| start)
+      .take_while(|&start| start <= prefix_start)
+      .last()
+      .unwrap_or(0);
+    let line_before_prefix = &line[statement_start..prefix_start];
     let (label_offset, line_before_prefix) = strip_leading_label(line_before_prefix);
-    if prefix_start < label_offset {
+    if prefix_start < statement_start + label_offset {
       return Ok(None);
     }
     let (_, line_before_prefix) = strip_leading_disasm_prefix(line_before_prefix);
@@ -415,12 +1986,12 @@ impl LanguageServer for IsaServer {
 
     // If the prefix exactly matches a no-arg instruction, don't show completions
     // (the instruction is complete, nothing more to type)
-    if let Some(entries) = self.index.get(&prefix_lower) {
-      if let Some(entry) = entries.first() {
-        if entry.name.eq_ignore_ascii_case(trimmed_prefix) && entry.args.is_empty() {
-          return Ok(None);
-        }
-      }
+    if let Some(entries) = index.get(&prefix_lower)
+      && let Some(entry) = entries.first()
+      && entry.name.eq_ignore_ascii_case(trimmed_prefix)
+      && entry.args.is_empty()
+    {
+      return Ok(None);
     }
 
     let start_char = byte_offset_to_utf16_position(line, prefix_start);
@@ -430,29 +2001,45 @@ impl LanguageServer for IsaServer {
     };
     let range = Range { start, end: position };
 
+    // Descriptions are filled in by `completionItem/resolve`; `data` carries the architecture to
+    // resolve against, keeping broad prefixes (`v_`) cheap to send.
+    let data = self.document_architecture_filter(&doc).map(Value::String);
     let mut seen = std::collections::HashSet::new();
-    let mut items = Vec::new();
-    for (name, entries) in &self.index {
-      if !name.contains(&prefix_lower) {
-        continue;
-      }
+    let mut scored = Vec::new();
+    for (name, entries) in index.iter() {
+      let score = match subsequence_score(&prefix_lower, name) {
+        Some(score) => score,
+        None => continue,
+      };
       if let Some(entry) = entries.first() {
         let label = format_mnemonic(&entry.name);
         if seen.insert(label.clone()) {
-          items.push(CompletionItem {
-            label: label.clone(),
-            kind: Some(CompletionItemKind::KEYWORD),
-            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
-              range: range.clone(),
-              new_text: label,
-            })),
-            ..CompletionItem::default()
-          });
+          scored.push((score, label));
         }
       }
     }
 
-    items.sort_by(|a, b| a.label.cmp(&b.label));
+    // Best matches first; `sortText` keeps the ranking and `filterText` the typed prefix, so
+    // clients that filter by prefix do not drop `v_add_f32` for `vaddf32`.
+    scored.sort_by(|(a_score, a_label), (b_score, b_label)| {
+      b_score.cmp(a_score).then_with(|| a_label.cmp(b_label))
+    });
+    let items: Vec<CompletionItem> = scored
+      .into_iter()
+      .enumerate()
+      .map(|(rank, (_, label))| CompletionItem {
+        label: label.clone(),
+        kind: Some(CompletionItemKind::KEYWORD),
+        sort_text: Some(format!("{rank:05}")),
+        filter_text: Some(trimmed_prefix.to_string()),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+          range,
+          new_text: label,
+        })),
+        data: data.clone(),
+        ..CompletionItem::default()
+      })
+      .collect();
 
     Ok(Some(CompletionResponse::List(CompletionList {
       is_incomplete: true,
@@ -460,93 +2047,471 @@ impl LanguageServer for IsaServer {
     })))
   }
 
+  async fn completion_resolve(&self, mut item: CompletionItem) -> Result<CompletionItem> {
+    if item.kind != Some(CompletionItemKind::KEYWORD) || item.documentation.is_some() {
+      return Ok(item);
+    }
+    let index = self.index();
+    let filter = item.data.as_ref().and_then(Value::as_str);
+    let entry = match find_entry(&index, &item.label, filter) {
+      Some(entry) => lock(&self.overlays).apply(entry, filter),
+      None => return Ok(item),
+    };
+    let sections = lock(&self.hover_sections).clone();
+    item.detail = entry.description.as_deref().and_then(|description| description.lines().next()).map(str::to_string);
+    if let HoverContents::Markup(markup) = format_hover(&entry, &EncodingVariant::Native, &sections, None) {
+      item.documentation = Some(Documentation::MarkupContent(markup));
+    }
+    Ok(item)
+  }
+
+  async fn document_symbol(&self, params: DocumentSymbolParams) -> Result<Option<DocumentSymbolResponse>> {
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    Ok(Some(DocumentSymbolResponse::Nested(document_outline(&doc.text, self.dialect()))))
+  }
+
+  async fn symbol(&self, params: WorkspaceSymbolParams) -> Result<Option<Vec<SymbolInformation>>> {
+    let dialect = self.dialect();
+    let roots = lock(&self.workspace_roots).clone();
+    let token = params.partial_result_params.partial_result_token;
+    let persist = self.persist_workspace_index.load(Ordering::Relaxed);
+    let cache_path = if persist { workspace_cache_path(&roots) } else { None };
+    let files = self.scan_workspace_files(cache_path.clone()).await;
+    let mut results = Vec::new();
+    for path in &files {
+      let uri = match Url::from_file_path(path) {
+        Ok(uri) => uri,
+        Err(_) => continue,
+      };
+      // Open documents may have unsaved edits; prefer them over the file on disk.
+      let open_text = self.get_document(&uri).await.map(|doc| doc.text);
+      let query = params.query.clone();
+      let path = path.clone();
+      let workspace_index = self.workspace_index.clone();
+      let symbols = run_analysis(move || match open_text {
+        Some(text) => file_symbols(&uri, &text, &query, dialect),
+        None => {
+          let symbols = lock(&workspace_index).as_mut().and_then(|index| index.file_symbols(&uri, &path));
+          filter_symbols(symbols.unwrap_or_default(), &query)
+        }
+      })
+      .await
+      .unwrap_or_default();
+      if symbols.is_empty() {
+        continue;
+      }
+      match &token {
+        Some(token) => {
+          self
+            .client
+            .send_notification::<PartialSymbolResults>(PartialSymbolResultsParams {
+              token: token.clone(),
+              value: symbols,
+            })
+            .await;
+          // Let the transport flush this batch before scanning the next file.
+          tokio::task::yield_now().await;
+        }
+        None => results.extend(symbols),
+      }
+    }
+    self.save_workspace_index(cache_path, files).await;
+    Ok(Some(results))
+  }
+
+  async fn semantic_tokens_full(&self, params: SemanticTokensParams) -> Result<Option<SemanticTokensResult>> {
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+      result_id: None,
+      data: document_semantic_tokens(&doc.text, self.dialect()),
+    })))
+  }
+
+  async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+    let dialect = self.dialect();
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let definitions = collect_symbol_definitions(&doc.text, dialect);
+    let mut ranges: Vec<FoldingRange> = analyze_conditionals(&doc.text, &definitions, dialect)
+      .branches
+      .into_iter()
+      .map(|branch| FoldingRange {
+        start_line: branch.start_line,
+        end_line: branch.end_line,
+        kind: Some(FoldingRangeKind::Region),
+        ..FoldingRange::default()
+      })
+      .collect();
+    ranges.extend(folding_ranges(&doc.text, dialect));
+    Ok(Some(ranges))
+  }
+
+  /// "Did you mean" quick fixes for unknown mnemonics, and a source action rewriting every VOP1/VOP2
+  /// mnemonic of the document in the configured suffix style.
+  async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+    let index = self.index();
+    let requested = |kind: &CodeActionKind| {
+      params
+        .context
+        .only
+        .as_ref()
+        .is_none_or(|only| only.iter().any(|only| kind.as_str().starts_with(only.as_str())))
+    };
+    let uri = params.text_document.uri;
+    let doc = match self.get_document(&uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let mut actions = Vec::new();
+    if requested(&CodeActionKind::QUICKFIX) {
+      actions.extend(mnemonic_quick_fixes(&doc.text, &uri, &params.context.diagnostics));
+    }
+    let style = *lock(&self.suffix_style);
+    if let Some(style) = style.filter(|_| requested(&CodeActionKind::SOURCE)) {
+      let filter = self.document_architecture_filter(&doc);
+      let edits = suffix_edits(&doc.text, &index, filter.as_deref(), style, self.dialect());
+      if !edits.is_empty() {
+        let title = match style {
+          SuffixStyle::Suffixed => "Add `_e32`/`_e64` to every VOP1/VOP2 instruction",
+          SuffixStyle::Bare => "Remove redundant `_e32`/`_e64` suffixes",
+        };
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+          title: title.to_string(),
+          kind: Some(CodeActionKind::SOURCE),
+          edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri, edits)])),
+            ..Default::default()
+          }),
+          ..Default::default()
+        }));
+      }
+    }
+    Ok((!actions.is_empty()).then_some(actions))
+  }
+
+  async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let settings = lock(&self.format_settings).clone();
+    Ok(Some(format_document(&doc.text, None, &settings, self.dialect())))
+  }
+
+  async fn range_formatting(&self, params: DocumentRangeFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let settings = lock(&self.format_settings).clone();
+    Ok(Some(format_document(&doc.text, Some(params.range), &settings, self.dialect())))
+  }
+
+  async fn on_type_formatting(&self, params: DocumentOnTypeFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+    let position = params.text_document_position;
+    let doc = match self.get_document(&position.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let settings = lock(&self.format_settings).clone();
+    Ok(Some(format_on_type(&doc.text, position.position, &params.ch, &settings, self.dialect())))
+  }
+
+  /// Register usage and estimated occupancy above each kernel label, and its code size.
+  async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let filter = self.document_architecture_filter(&doc);
+    let lines: Vec<&str> = doc.text.lines().collect();
+    let lenses = analyze_register_usage(&doc.text, &self.index(), filter.as_deref(), self.dialect())
+      .into_iter()
+      .flat_map(|usage| {
+        let line = lines[usage.line as usize];
+        let position = |byte| Position::new(usage.line, byte_offset_to_utf16_position(line, byte));
+        let range = Range::new(position(usage.start), position(usage.end));
+        let lens = |title: String| CodeLens {
+          range,
+          command: Some(Command::new(title, String::new(), None)),
+          data: None,
+        };
+        [lens(usage_summary(&usage, filter.as_deref())), lens(size_summary(&usage))]
+      })
+      .collect();
+    Ok(Some(lenses))
+  }
+
+  async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+    let index = self.index();
+    let style = match *lock(&self.inlay_style) {
+      Some(style) => style,
+      None => return Ok(None),
+    };
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(None),
+    };
+    let filter = self.document_architecture_filter(&doc);
+    Ok(Some(operand_inlay_hints(&doc.text, params.range, &index, filter.as_deref(), style, self.dialect())))
+  }
+
+  async fn document_color(&self, params: DocumentColorParams) -> Result<Vec<ColorInformation>> {
+    if !self.color_decorators.load(Ordering::Relaxed) {
+      return Ok(Vec::new());
+    }
+    let doc = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc,
+      None => return Ok(Vec::new()),
+    };
+    let lines: Vec<&str> = doc.text.lines().collect();
+    let colors = document_color_literals(&doc.text, self.dialect())
+      .into_iter()
+      .map(|literal| {
+        let line = lines[literal.line as usize];
+        ColorInformation {
+          range: Range::new(
+            Position::new(literal.line, byte_offset_to_utf16_position(line, literal.start)),
+            Position::new(literal.line, byte_offset_to_utf16_position(line, literal.end)),
+          ),
+          color: literal.color(),
+        }
+      })
+      .collect();
+    Ok(colors)
+  }
+
+  async fn color_presentation(&self, params: ColorPresentationParams) -> Result<Vec<ColorPresentation>> {
+    let original = match self.get_document(&params.text_document.uri).await {
+      Some(doc) => doc.text.lines().nth(params.range.start.line as usize).map(|line| {
+        let start = utf16_position_to_byte_offset(line, params.range.start);
+        let end = utf16_position_to_byte_offset(line, params.range.end);
+        line[start..end.max(start)].to_string()
+      }),
+      None => None,
+    };
+    let label = color_literal_text(&params.color, original.as_deref().unwrap_or_default());
+    Ok(vec![ColorPresentation {
+      text_edit: Some(TextEdit::new(params.range, label.clone())),
+      label,
+      additional_text_edits: None,
+    }])
+  }
+
+  async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+    match params.command.as_str() {
+      SHOW_DOCS_COMMAND => self.show_docs(&params.arguments).await,
+      SEARCH_INSTRUCTIONS_COMMAND => self.search_instructions(&params.arguments).await,
+      EXPLAIN_LINE_COMMAND => self.explain_line(&params.arguments).await,
+      FIND_INSTRUCTION_USAGES_COMMAND => self.find_instruction_usages(&params.arguments).await,
+      other => Err(Error::invalid_params(format!("Unknown command: {other}"))),
+    }
+  }
+
   async fn shutdown(&self) -> Result<()> {
     Ok(())
   }
 }
 
-fn is_label_start(b: u8) -> bool {
-  (b as char).is_ascii_alphabetic() || b == b'_' || b == b'.' || b == b'$'
+/// Appends ` ARG, ARG, ...` for the entry's operands at `indices` to `label`, returning their
+/// parameters.
+fn push_operand_parameters(
+  label: &mut String,
+  entry: &InstructionEntry,
+  indices: &[usize],
+  filter: Option<&str>,
+) -> Vec<ParameterInformation> {
+  let matrix_shape = parse_matrix_shape(&entry.name);
+  let mut parameters = Vec::new();
+  for (position, &i) in indices.iter().enumerate() {
+    label.push_str(if position == 0 { " " } else { ", " });
+    let start = label.len();
+    label.push_str(&entry.args[i]);
+    let arg_type = entry.arg_types.get(i).map(|s| s.as_str()).unwrap_or("");
+    let mut compact_type = arg_type.replace("register", "reg").replace("accumulator", "acc");
+    if let Some(dwords) = operand_dwords(entry, i) {
+      compact_type.push_str(&format!(" ×{dwords}"));
+    }
+    if let Some(shape) = &matrix_shape && let Some(matrix_doc) = format_matrix_operand(entry, shape, i, filter) {
+      compact_type.push_str(&format!(" — {matrix_doc}"));
+    }
+    parameters.push(ParameterInformation {
+      label: ParameterLabel::LabelOffsets([start as u32, label.len() as u32]),
+      documentation: if !compact_type.is_empty() {
+        Some(tower_lsp::lsp_types::Documentation::String(compact_type))
+      } else {
+        None
+      },
+    });
+  }
+  parameters
 }
 
-fn is_label_char(b: u8) -> bool {
-  is_label_start(b) || (b as char).is_ascii_digit()
-}
+/// Signature help for an invocation of a document `.macro`. Arguments may be separated by commas
+/// or, as gas also accepts, by whitespace alone.
+fn macro_signature_help(
+  signature: &MacroSignature,
+  line: &str,
+  mnemonic: &Token<'_>,
+  cursor_byte: usize,
+) -> Option<SignatureHelp> {
+  if signature.parameters.is_empty() {
+    return None;
+  }
+  let args_section = &line[mnemonic.end()..cursor_byte.min(line.len())];
+  let argument_index = if args_section.contains(',') {
+    operand_index_at(line, mnemonic, cursor_byte)
+  } else {
+    let complete = args_section.split_whitespace().count();
+    if args_section.ends_with(char::is_whitespace) { complete } else { complete.saturating_sub(1) }
+  };
+  let active_parameter = Some(argument_index.min(signature.parameters.len() - 1) as u32);
+
+  let mut label = signature.name.clone();
+  let mut parameters = Vec::new();
+  for (i, parameter) in signature.parameters.iter().enumerate() {
+    label.push_str(if i == 0 { " " } else { ", " });
+    let start = label.len();
+    label.push_str(&parameter.label());
+    let documentation = if parameter.required {
+      Some("required".to_string())
+    } else if parameter.vararg {
+      Some("takes the remaining arguments".to_string())
+    } else {
+      parameter.default.as_ref().map(|default| format!("defaults to `{default}`"))
+    };
+    parameters.push(ParameterInformation {
+      label: ParameterLabel::LabelOffsets([start as u32, label.len() as u32]),
+      documentation: documentation.map(tower_lsp::lsp_types::Documentation::String),
+    });
+  }
 
-fn is_hex_digit(b: u8) -> bool {
-  (b as char).is_ascii_hexdigit()
+  Some(SignatureHelp {
+    signatures: vec![SignatureInformation {
+      label,
+      documentation: Some(tower_lsp::lsp_types::Documentation::String(format!(
+        "Macro defined on line {}",
+        signature.line + 1
+      ))),
+      parameters: Some(parameters),
+      active_parameter,
+    }],
+    active_signature: Some(0),
+    active_parameter,
+  })
 }
 
-fn strip_leading_label(line: &str) -> (usize, &str) {
-  let trimmed = line.trim_start();
-  let trimmed_offset = line.len() - trimmed.len();
-  let bytes = trimmed.as_bytes();
-  if bytes.is_empty() {
-    return (line.len(), "");
-  }
-  if !is_label_start(bytes[0]) {
-    return (trimmed_offset, trimmed);
-  }
-  let mut idx = 1;
-  while idx < bytes.len() && is_label_char(bytes[idx]) {
-    idx += 1;
-  }
-  if idx < bytes.len() && bytes[idx] == b':' {
-    let after_colon = &trimmed[idx + 1..];
-    let after_ws = after_colon.trim_start();
-    let after_ws_offset = trimmed_offset + idx + 1 + (after_colon.len() - after_ws.len());
-    return (after_ws_offset, after_ws);
+/// Documentation of the directive a line starts with, when the cursor is on its name.
+fn directive_hover(line: &str, cursor_byte: usize, dialect: Dialect) -> Option<HoverContents> {
+  let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+  let (label_end, statement) = strip_leading_label(code);
+  let start = label_end + statement.len() - statement.trim_start().len();
+  let name = statement.split_whitespace().next().filter(|name| name.starts_with('.'))?;
+  if cursor_byte < start || cursor_byte > start + name.len() {
+    return None;
   }
-  (trimmed_offset, trimmed)
+  find_directive(name).map(format_directive_hover)
 }
 
-fn line_comment_start(line: &str) -> Option<usize> {
-  match (line.find(';'), line.find("//")) {
-    (Some(semi), Some(slash)) => Some(semi.min(slash)),
-    (Some(semi), None) => Some(semi),
-    (None, Some(slash)) => Some(slash),
-    (None, None) => None,
+/// Value of the operand expression under the cursor (`label+8`, `(end-start)/4`, `NUM_WAVES`) when
+/// every symbol in it resolves through the document's `.set`/`=` assignments.
+fn expression_hover(
+  text: &str,
+  line_idx: u32,
+  line: &str,
+  cursor_byte: usize,
+  dialect: Dialect,
+) -> Option<HoverContents> {
+  let parsed = instruction_at(line, cursor_byte, dialect)?;
+  let operand = parsed
+    .operands
+    .iter()
+    .find(|operand| operand.start <= cursor_byte && cursor_byte <= operand.end())?;
+  if parse_integer_literal(operand.text.trim_start_matches('-')).is_some() {
+    return None;
   }
+  let definitions = collect_symbol_definitions(text, dialect);
+  let value = evaluate_with_symbols(&definitions, operand.text, line_idx)?;
+  Some(format_expression_hover(operand.text, value))
 }
 
-fn strip_leading_disasm_prefix(line: &str) -> (usize, &str) {
-  let trimmed = line.trim_start();
-  let trimmed_offset = line.len() - trimmed.len();
-  let bytes = trimmed.as_bytes();
-  if bytes.is_empty() {
-    return (line.len(), "");
-  }
+/// Code lines shown after a label in its hover.
+const LABEL_PREVIEW_LINES: usize = 5;
 
-  let mut idx = 0;
-  let mut hex_len = 0;
-  while idx < bytes.len() && is_hex_digit(bytes[idx]) {
-    idx += 1;
-    hex_len += 1;
+/// Preview of the code at a label referenced on this line (`s_cbranch_scc1 .LBB0_2`). Hovering the
+/// definition itself shows nothing; the code is right there.
+fn label_hover(text: &str, line: &str, position: Position, dialect: Dialect) -> Option<HoverContents> {
+  let (label, start) = extract_label_at_position(line, position)?;
+  let (def_line, _, _) = find_label_definition(text, &label, dialect)?;
+  if def_line == position.line && strip_leading_label(line).0 > start {
+    return None;
   }
-  if hex_len >= 4 && idx < bytes.len() && bytes[idx] == b':' {
-    idx += 1;
-    while idx < bytes.len() && (bytes[idx] as char).is_ascii_whitespace() {
-      idx += 1;
+  let mut preview = Vec::new();
+  for (line_idx, code_line) in text.lines().enumerate().skip(def_line as usize) {
+    let code = match line_comment_start(code_line, dialect) {
+      Some(comment_start) => &code_line[..comment_start],
+      None => code_line,
+    };
+    // The definition line only contributes an instruction written after the colon.
+    let code = if line_idx == def_line as usize { &code[strip_leading_label(code).0..] } else { code };
+    if code.trim().is_empty() {
+      continue;
+    }
+    preview.push(code.trim_end());
+    if preview.len() == LABEL_PREVIEW_LINES {
+      break;
     }
-  } else {
-    idx = 0;
   }
+  let target = instruction_at(line, start, dialect)
+    .and_then(|parsed| label_target_note(text, line, &parsed, &label, def_line, dialect));
+  Some(format_label_hover(&label, def_line, &preview, target))
+}
 
-  loop {
-    if idx + 8 <= bytes.len() && bytes[idx..idx + 8].iter().all(|&b| is_hex_digit(b)) {
-      let mut next = idx + 8;
-      if next < bytes.len() && (bytes[next] as char).is_ascii_whitespace() {
-        while next < bytes.len() && (bytes[next] as char).is_ascii_whitespace() {
-          next += 1;
-        }
-        idx = next;
-        continue;
-      }
-    }
-    break;
+/// The label or symbol name under the cursor, outside comments and string literals. Labels in data
+/// directives (`.quad target`) are references too.
+fn symbol_at_position(line: &str, position: Position, dialect: Dialect) -> Option<String> {
+  let cursor_byte = utf16_position_to_byte_offset(line, position);
+  if line_comment_start(line, dialect).is_some_and(|comment_start| cursor_byte >= comment_start) {
+    return None;
+  }
+  if in_string_literal(line, cursor_byte, dialect) {
+    return None;
   }
+  // `label@rel32@lo`: the relocation specifier stands for its symbol.
+  if let Some(relocation) = relocation_at(line, cursor_byte, dialect) {
+    return Some(relocation.symbol.text.to_string());
+  }
+  extract_label_at_position(line, position).map(|(label, _)| label)
+}
+
+/// Whether the document defines `name` as a label or `.set`/`.equ`/`=` constant.
+fn defines_symbol(text: &str, name: &str, dialect: Dialect) -> bool {
+  find_label_definition(text, name, dialect).is_some()
+    || collect_symbol_definitions(text, dialect).iter().any(|definition| definition.name == name)
+}
 
-  (trimmed_offset + idx, &trimmed[idx..])
+fn symbol_location(uri: Url, text: &str, line: u32, start: usize, end: usize) -> Option<Location> {
+  let line_text = text.lines().nth(line as usize)?;
+  let start = Position {
+    line,
+    character: byte_offset_to_utf16_position(line_text, start),
+  };
+  let end = Position {
+    line,
+    character: byte_offset_to_utf16_position(line_text, end),
+  };
+  Some(Location {
+    uri,
+    range: Range { start, end },
+  })
 }
 
 fn extract_label_at_position(line: &str, position: Position) -> Option<(String, usize)> {
@@ -569,9 +2534,9 @@ fn extract_label_at_position(line: &str, position: Position) -> Option<(String,
   Some((line[start..end].to_string(), start))
 }
 
-fn find_label_definition(text: &str, label: &str) -> Option<(u32, usize, usize)> {
+fn find_label_definition(text: &str, label: &str, dialect: Dialect) -> Option<(u32, usize, usize)> {
   for (line_idx, line) in text.lines().enumerate() {
-    let line_before_comment = match line_comment_start(line) {
+    let line_before_comment = match line_comment_start(line, dialect) {
       Some(comment_start) => &line[..comment_start],
       None => line,
     };
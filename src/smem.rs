@@ -0,0 +1,131 @@
+use crate::architecture::arch_matches;
+use crate::expression::parse_integer_literal;
+use crate::registers::parse_register;
+use crate::tokenizer::{InstructionLine, Token};
+
+/// Scalar memory loads and stores, whose third operand is the offset.
+const SMEM_PREFIXES: &[&str] =
+  &["s_load_", "s_buffer_load_", "s_store_", "s_buffer_store_", "s_scratch_load_", "s_scratch_store_"];
+
+fn is_smem(mnemonic: &str) -> bool {
+  let lower = mnemonic.to_ascii_lowercase();
+  SMEM_PREFIXES.iter().any(|prefix| lower.starts_with(prefix))
+}
+
+fn is_buffer(mnemonic: &str) -> bool {
+  mnemonic.to_ascii_lowercase().starts_with("s_buffer_")
+}
+
+fn parse_signed(text: &str) -> Option<i64> {
+  let text = text.trim();
+  match text.strip_prefix('-') {
+    Some(digits) => parse_integer_literal(digits)?.checked_neg(),
+    None => parse_integer_literal(text),
+  }
+}
+
+/// The offsets an SMEM line adds to its base: an SGPR (soffset) and an immediate, either of
+/// which may be the third operand; the immediate may also come from `offset:`.
+struct SmemOffset<'a> {
+  sgpr: Option<Token<'a>>,
+  immediates: Vec<(Token<'a>, Option<i64>)>,
+}
+
+fn smem_offset<'a>(parsed: &InstructionLine<'a>) -> Option<SmemOffset<'a>> {
+  if !is_smem(parsed.mnemonic.text) {
+    return None;
+  }
+  let operand = parsed.operands.get(2).copied()?;
+  let is_sgpr = operand.text.eq_ignore_ascii_case("null")
+    || operand.text.eq_ignore_ascii_case("m0")
+    || parse_register(operand.text).is_some();
+  let mut offset = SmemOffset {
+    sgpr: None,
+    immediates: Vec::new(),
+  };
+  if is_sgpr {
+    offset.sgpr = Some(operand);
+  } else {
+    offset.immediates.push((operand, parse_signed(operand.text)));
+  }
+  for modifier in &parsed.modifiers {
+    match modifier.text.split_once(':') {
+      Some((key, value)) if key.eq_ignore_ascii_case("offset") => offset.immediates.push((*modifier, parse_signed(value))),
+      _ => {}
+    }
+  }
+  Some(offset)
+}
+
+/// Inclusive byte range of the immediate offset on an architecture, with its field width: 24-bit
+/// signed on RDNA4, otherwise 21-bit signed, or 20-bit unsigned for `s_buffer_*`.
+fn immediate_range(filter: &str, buffer: bool) -> (i64, i64, &'static str) {
+  if arch_matches(filter, "rdna4+") {
+    (-(1 << 23), (1 << 23) - 1, "24-bit signed")
+  } else if buffer {
+    (0, (1 << 20) - 1, "20-bit unsigned")
+  } else {
+    (-(1 << 20), (1 << 20) - 1, "21-bit signed")
+  }
+}
+
+/// Which offset form an `s_load_*`/`s_store_*` line uses and the immediate range of the target,
+/// for hover on the offset operand or its `offset:` modifier.
+pub fn smem_offset_note(parsed: &InstructionLine<'_>, hovered: &Token<'_>, filter: Option<&str>) -> Option<String> {
+  let offset = smem_offset(parsed)?;
+  let mut tokens = offset.sgpr.iter().chain(offset.immediates.iter().map(|(token, _)| token));
+  if !tokens.any(|token| token == hovered) {
+    return None;
+  }
+  let base = parsed.operands.get(1).map(|base| base.text).unwrap_or("base");
+  let immediate = offset.immediates.first().map(|(token, _)| token.text.rsplit(':').next().unwrap_or(token.text));
+  let (form, address) = match (offset.sgpr, immediate) {
+    (Some(sgpr), Some(immediate)) => {
+      ("SGPR + immediate", format!("`{base}` + `{}` + `{immediate}` bytes", sgpr.text))
+    }
+    (Some(sgpr), None) => ("SGPR (soffset)", format!("`{base}` + `{}` bytes", sgpr.text)),
+    (None, Some(immediate)) => ("immediate", format!("`{base}` + `{immediate}` bytes")),
+    (None, None) => return None,
+  };
+  let buffer = is_buffer(parsed.mnemonic.text);
+  let range = match filter {
+    Some(filter) => {
+      let (min, max, width) = immediate_range(filter, buffer);
+      format!("- immediate range on {filter}: {min}..={max} ({width})")
+    }
+    None => "- immediate range: 21-bit signed on CDNA and RDNA1–3 (20-bit unsigned for `s_buffer_*`), \
+             24-bit signed on RDNA4"
+      .to_string(),
+  };
+  Some(format!(
+    "SMEM offset: {form}\n- address = {address}\n- forms: immediate, SGPR, or SGPR with `offset:` immediate\n{range}"
+  ))
+}
+
+/// Immediate SMEM offsets the target cannot encode: values outside its offset field, or an
+/// immediate third operand combined with `offset:`.
+pub fn smem_offset_issues<'a>(parsed: &InstructionLine<'a>, filter: Option<&str>) -> Vec<(Token<'a>, String)> {
+  let offset = match smem_offset(parsed) {
+    Some(offset) => offset,
+    None => return Vec::new(),
+  };
+  let mut issues = Vec::new();
+  if offset.sgpr.is_none() && let Some((modifier, _)) = offset.immediates.get(1) {
+    issues.push((
+      *modifier,
+      "SMEM takes one immediate offset; use an SGPR as the third operand to combine it with `offset:`".to_string(),
+    ));
+  }
+  if let Some(filter) = filter {
+    let (min, max, width) = immediate_range(filter, is_buffer(parsed.mnemonic.text));
+    for (token, value) in &offset.immediates {
+      if let Some(value) = value.filter(|value| !(min..=max).contains(value)) {
+        issues.push((
+          *token,
+          format!("SMEM offset {value} is outside the {width} immediate range on {filter} ({min}..={max})"),
+        ));
+      }
+    }
+  }
+  issues
+}
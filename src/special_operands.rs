@@ -0,0 +1,101 @@
+fn is_scratch(mnemonic: &str) -> bool {
+  mnemonic.to_ascii_lowercase().starts_with("scratch_")
+}
+
+/// MUBUF/MTBUF, whose VADDR is unused without `offen`/`idxen`.
+fn is_buffer(mnemonic: &str) -> bool {
+  let lower = mnemonic.to_ascii_lowercase();
+  lower.starts_with("buffer_") || lower.starts_with("tbuffer_")
+}
+
+fn is_export(mnemonic: &str) -> bool {
+  let lower = mnemonic.to_ascii_lowercase();
+  lower == "exp" || lower.starts_with("exp_") || lower == "export"
+}
+
+/// Whether `off` may fill the slot: an unused SADDR on global/scratch, an unused VADDR on
+/// scratch or buffer instructions, or an unused export source.
+pub fn off_allowed(mnemonic: &str, slot: Option<&str>) -> bool {
+  if is_export(mnemonic) {
+    return true;
+  }
+  match slot.map(|slot| slot.to_ascii_uppercase()) {
+    Some(slot) if slot == "SADDR" => true,
+    Some(slot) if slot == "VADDR" => is_scratch(mnemonic) || is_buffer(mnemonic),
+    _ => false,
+  }
+}
+
+/// What `null`/`off` means in a slot, for hover.
+pub fn special_operand_note(mnemonic: &str, slot: Option<&str>, text: &str) -> Option<String> {
+  match text.to_ascii_lowercase().as_str() {
+    "null" => Some(
+      "`null`: the null scalar register; reads return zero and writes are discarded (RDNA only)".to_string(),
+    ),
+    "off" => {
+      let slot = slot.map(|slot| slot.to_ascii_uppercase());
+      let note = if is_export(mnemonic) {
+        "`off`: this export source is not written"
+      } else if slot.as_deref() == Some("SADDR") && is_scratch(mnemonic) {
+        "`off`: no scalar base; the address comes from VADDR alone"
+      } else if slot.as_deref() == Some("SADDR") {
+        "`off`: no scalar base; VADDR holds the full 64-bit address"
+      } else if slot.as_deref() == Some("VADDR") && is_scratch(mnemonic) {
+        "`off`: no per-lane offset; the address comes from SADDR and the immediate offset"
+      } else if slot.as_deref() == Some("VADDR") && is_buffer(mnemonic) {
+        "`off`: no per-lane index or offset (neither `idxen` nor `offen`); the address comes from SOFFSET and the \
+         immediate offset"
+      } else {
+        "`off`: only valid for an unused SADDR (global/scratch), VADDR (scratch/buffer) or export source"
+      };
+      Some(note.to_string())
+    }
+    _ => None,
+  }
+}
+
+/// Problem with a `null`/`off` operand in the given slot, if any: `null` on CDNA or in a vector
+/// slot, `off` outside the slots that may be unused. `slots` are the instruction's operand field
+/// names and `operands` the typed operand texts, in order.
+pub fn special_operand_issue(
+  mnemonic: &str,
+  slots: &[String],
+  operands: &[&str],
+  index: usize,
+  filter: Option<&str>,
+) -> Option<String> {
+  let text = operands.get(index)?.to_ascii_lowercase();
+  let slot = slots.get(index).map(|slot| slot.as_str());
+  match text.as_str() {
+    "null" => {
+      if let Some(filter) = filter.filter(|filter| filter.starts_with("cdna")) {
+        return Some(format!("`null` is not a register on {filter}; it was introduced with RDNA"));
+      }
+      let slot = slot?;
+      // Vector slots (VDST, VADDR, VSRC1, ...) cannot take a scalar register.
+      slot
+        .to_ascii_uppercase()
+        .starts_with('V')
+        .then(|| format!("`null` cannot be used for the vector operand {slot}"))
+    }
+    "off" => {
+      if !off_allowed(mnemonic, slot) {
+        return Some(match slot {
+          Some(slot) => format!(
+            "`off` cannot be used for {slot}; it only marks an unused SADDR (global/scratch), VADDR \
+             (scratch/buffer) or export source"
+          ),
+          None => "`off` has no operand slot here".to_string(),
+        });
+      }
+      if is_scratch(mnemonic) && slot.is_some_and(|slot| slot.eq_ignore_ascii_case("VADDR")) {
+        let saddr = slots.iter().position(|slot| slot.eq_ignore_ascii_case("SADDR"))?;
+        if operands.get(saddr).is_some_and(|operand| operand.eq_ignore_ascii_case("off")) {
+          return Some("scratch needs at least one of VADDR and SADDR; both are `off`".to_string());
+        }
+      }
+      None
+    }
+    _ => None,
+  }
+}
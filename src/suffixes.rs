@@ -0,0 +1,108 @@
+use crate::conditional::analyze_conditionals;
+use crate::encoding::split_encoding_variant;
+use crate::index::find_entry;
+use crate::registers::{parse_register, RegisterClass};
+use crate::symbols::collect_symbol_definitions;
+use crate::text_utils::byte_offset_to_utf16_position;
+use crate::tokenizer::{parse_instruction_statements, Dialect, InstructionLine};
+use crate::types::{EncodingVariant, InstructionEntry};
+use std::collections::HashMap;
+use tower_lsp::lsp_types::{Position, Range, TextEdit};
+
+/// How VOP1/VOP2 mnemonics are spelled: always with `_e32`/`_e64`, as llvm-objdump prints them,
+/// or without, leaving the encoding to the assembler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuffixStyle {
+  Suffixed,
+  Bare,
+}
+
+impl SuffixStyle {
+  /// Parses the `encodingSuffixStyle` option; anything else disables normalization.
+  pub fn from_option(value: &str) -> Option<Self> {
+    match value.to_ascii_lowercase().as_str() {
+      "suffixed" => Some(SuffixStyle::Suffixed),
+      "bare" => Some(SuffixStyle::Bare),
+      _ => None,
+    }
+  }
+}
+
+fn has_short_encoding(entry: &InstructionEntry) -> bool {
+  entry
+    .available_encodings
+    .iter()
+    .any(|encoding| encoding == "ENC_VOP1" || encoding == "ENC_VOP2")
+}
+
+fn is_vgpr(text: &str) -> bool {
+  parse_register(text).is_some_and(|register| register.class == RegisterClass::Vector)
+}
+
+/// Whether the operands fit the 32-bit encoding: no modifiers or source modifiers, and a VGPR in
+/// every `VDST`/`VSRC` slot of the VOP1/VOP2 operand list.
+fn fits_e32(entry: &InstructionEntry, parsed: &InstructionLine<'_>) -> bool {
+  if !parsed.modifiers.is_empty() || parsed.operands.len() != entry.args.len() {
+    return false;
+  }
+  let source_modifier = |text: &str| {
+    let lower = text.to_ascii_lowercase();
+    text.starts_with('-') || text.contains('|') || lower.starts_with("abs(") || lower.starts_with("neg(")
+  };
+  if parsed.operands.iter().any(|operand| source_modifier(operand.text)) {
+    return false;
+  }
+  entry.args.iter().zip(&parsed.operands).all(|(slot, operand)| {
+    let slot = slot.to_ascii_uppercase();
+    !(slot.starts_with("VDST") || slot.starts_with("VSRC")) || is_vgpr(operand.text)
+  })
+}
+
+/// Edits that rewrite every VOP1/VOP2 mnemonic in `style`. Suffixing picks `_e32` when the
+/// operands fit the 32-bit encoding and `_e64` otherwise; the bare style drops `_e32`, and drops
+/// `_e64` only where the assembler would choose VOP3 anyway, so the encoding never changes.
+pub fn suffix_edits(
+  text: &str,
+  index: &HashMap<String, Vec<InstructionEntry>>,
+  filter: Option<&str>,
+  style: SuffixStyle,
+  dialect: Dialect,
+) -> Vec<TextEdit> {
+  let conditionals = analyze_conditionals(text, &collect_symbol_definitions(text, dialect), dialect);
+  let mut edits = Vec::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    if conditionals.inactive_lines.contains(&(line_idx as u32)) {
+      continue;
+    }
+    for parsed in parse_instruction_statements(line, dialect) {
+      let split = split_encoding_variant(parsed.mnemonic.text);
+      let entry = match find_entry(index, &split.base, filter) {
+        Some(entry) if has_short_encoding(entry) => entry,
+        _ => continue,
+      };
+      let mnemonic = parsed.mnemonic;
+      let (start, end, new_text) = match (style, &split.variant) {
+        (SuffixStyle::Suffixed, EncodingVariant::Native) => {
+          let suffix = if fits_e32(entry, &parsed) { "_e32" } else { "_e64" };
+          let uppercase = mnemonic.text.bytes().any(|b| b.is_ascii_uppercase());
+          let suffix = if uppercase { suffix.to_ascii_uppercase() } else { suffix.to_string() };
+          (mnemonic.end(), mnemonic.end(), suffix)
+        }
+        (SuffixStyle::Bare, EncodingVariant::E32) => (mnemonic.start + split.base.len(), mnemonic.end(), String::new()),
+        (SuffixStyle::Bare, EncodingVariant::E64) if !fits_e32(entry, &parsed) => {
+          (mnemonic.start + split.base.len(), mnemonic.end(), String::new())
+        }
+        _ => continue,
+      };
+      let line_idx = line_idx as u32;
+      edits.push(TextEdit::new(
+        Range::new(
+          Position::new(line_idx, byte_offset_to_utf16_position(line, start)),
+          Position::new(line_idx, byte_offset_to_utf16_position(line, end)),
+        ),
+        new_text,
+      ));
+    }
+  }
+  edits
+}
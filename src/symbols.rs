@@ -0,0 +1,223 @@
+use crate::expression::evaluate_expression;
+use crate::tokenizer::{is_label_char, is_label_start, line_comment_start, Dialect};
+use std::collections::HashSet;
+
+/// Names of `.macro` definitions in the document; invocations of these are not instructions.
+pub fn collect_macro_names(text: &str, dialect: Dialect) -> HashSet<String> {
+  let mut names = HashSet::new();
+  for line in text.lines() {
+    let code = match line_comment_start(line, dialect) {
+      Some(comment_start) => &line[..comment_start],
+      None => line,
+    };
+    let mut parts = code.split_whitespace();
+    if parts.next() != Some(".macro") {
+      continue;
+    }
+    if let Some(name) = parts.next() {
+      let name = name.trim_end_matches(',');
+      names.insert(name.to_ascii_lowercase());
+    }
+  }
+  names
+}
+
+/// One parameter of a `.macro`: `name`, `name=default`, `name:req` or `name:vararg`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroParameter {
+  pub name: String,
+  pub default: Option<String>,
+  pub required: bool,
+  pub vararg: bool,
+}
+
+impl MacroParameter {
+  /// The parameter as written in the definition.
+  pub fn label(&self) -> String {
+    let mut label = self.name.clone();
+    if let Some(default) = &self.default {
+      label.push('=');
+      label.push_str(default);
+    }
+    if self.required {
+      label.push_str(":req");
+    }
+    if self.vararg {
+      label.push_str(":vararg");
+    }
+    label
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroSignature {
+  pub name: String,
+  pub parameters: Vec<MacroParameter>,
+  pub line: u32,
+}
+
+fn parse_macro_parameter(text: &str) -> Option<MacroParameter> {
+  let (text, default) = match text.split_once('=') {
+    Some((name, default)) => (name.trim(), Some(default.trim().to_string())),
+    None => (text, None),
+  };
+  let (name, qualifier) = match text.split_once(':') {
+    Some((name, qualifier)) => (name.trim(), qualifier.trim().to_ascii_lowercase()),
+    None => (text.trim(), String::new()),
+  };
+  if name.is_empty() {
+    return None;
+  }
+  Some(MacroParameter {
+    name: name.to_string(),
+    default: default.filter(|default| !default.is_empty()),
+    required: qualifier == "req",
+    vararg: qualifier == "vararg",
+  })
+}
+
+/// `.macro` definitions in the document with their parameter lists, for signature help on
+/// invocations. Parameters may be separated by commas or whitespace.
+pub fn collect_macro_signatures(text: &str, dialect: Dialect) -> Vec<MacroSignature> {
+  let mut signatures = Vec::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    let code = match line_comment_start(line, dialect) {
+      Some(comment_start) => &line[..comment_start],
+      None => line,
+    };
+    let rest = match code.trim_start().strip_prefix(".macro") {
+      Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => rest.trim_start(),
+      _ => continue,
+    };
+    let name_len = rest
+      .find(|ch: char| ch.is_whitespace() || ch == ',')
+      .unwrap_or(rest.len());
+    if name_len == 0 {
+      continue;
+    }
+    // `x = 1` is one parameter with a default; drop the spaces so whitespace separates parameters.
+    let mut params = String::new();
+    for (i, part) in rest[name_len..].split('=').enumerate() {
+      if i > 0 {
+        params.truncate(params.trim_end().len());
+        params.push('=');
+        params.push_str(part.trim_start());
+      } else {
+        params.push_str(part);
+      }
+    }
+    let parameters = params
+      .split(|ch: char| ch == ',' || ch.is_whitespace())
+      .filter_map(parse_macro_parameter)
+      .collect();
+    signatures.push(MacroSignature {
+      name: rest[..name_len].to_string(),
+      parameters,
+      line: line_idx as u32,
+    });
+  }
+  signatures
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDefinition {
+  pub name: String,
+  /// Right-hand side of the assignment, as written.
+  pub value: String,
+  pub line: u32,
+  /// Byte range of the name within its line.
+  pub start: usize,
+  pub end: usize,
+}
+
+/// Splits an assignment into (byte offset of the name part, name part, value part).
+fn split_assignment(code: &str) -> Option<(usize, &str, &str)> {
+  let trimmed = code.trim_start();
+  let offset = code.len() - trimmed.len();
+  for directive in [".set", ".equ", ".equiv"] {
+    if let Some(rest) = trimmed.strip_prefix(directive) && rest.starts_with(|ch: char| ch.is_whitespace()) {
+      let (name, value) = rest.split_once(',')?;
+      return Some((offset + directive.len(), name, value));
+    }
+  }
+  let (name, value) = trimmed.split_once('=')?;
+  if value.starts_with('=') || name.trim().contains(char::is_whitespace) {
+    return None;
+  }
+  Some((offset, name, value))
+}
+
+/// Symbol assignments in the document (`.set name, expr`, `.equ name, expr`, `name = expr`).
+pub fn collect_symbol_definitions(text: &str, dialect: Dialect) -> Vec<SymbolDefinition> {
+  let mut definitions = Vec::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    let code = match line_comment_start(line, dialect) {
+      Some(comment_start) => &line[..comment_start],
+      None => line,
+    };
+    let (offset, name, value) = match split_assignment(code) {
+      Some(parts) => parts,
+      None => continue,
+    };
+    let name_trimmed = name.trim();
+    let bytes = name_trimmed.as_bytes();
+    if bytes.is_empty() || !is_label_start(bytes[0]) || !bytes.iter().all(|&b| is_label_char(b)) {
+      continue;
+    }
+    let start = offset + name.len() - name.trim_start().len();
+    definitions.push(SymbolDefinition {
+      name: name_trimmed.to_string(),
+      value: value.trim().to_string(),
+      line: line_idx as u32,
+      start,
+      end: start + name_trimmed.len(),
+    });
+  }
+  definitions
+}
+
+/// The `.globl name` (or `.global`) directive declaring a symbol: (line, name start, name end).
+pub fn find_global_declaration(text: &str, name: &str, dialect: Dialect) -> Option<(u32, usize, usize)> {
+  for (line_idx, line) in text.lines().enumerate() {
+    let code = match line_comment_start(line, dialect) {
+      Some(comment_start) => &line[..comment_start],
+      None => line,
+    };
+    let trimmed = code.trim_start();
+    let rest = match trimmed.split_once(char::is_whitespace) {
+      Some((".globl" | ".global", rest)) => rest,
+      _ => continue,
+    };
+    // `.globl a, b` declares several symbols.
+    let mut offset = code.len() - rest.len();
+    for symbol in rest.split(',') {
+      let start = offset + symbol.len() - symbol.trim_start().len();
+      if symbol.trim() == name {
+        return Some((line_idx as u32, start, start + name.len()));
+      }
+      offset += symbol.len() + 1;
+    }
+  }
+  None
+}
+
+const MAX_SYMBOL_DEPTH: usize = 16;
+
+fn resolve_symbol(definitions: &[SymbolDefinition], name: &str, line: u32, depth: usize) -> Option<i64> {
+  if depth > MAX_SYMBOL_DEPTH {
+    return None;
+  }
+  // The assignment in effect at `line` is the last one before it, as the assembler sees it; a
+  // forward reference resolves to the final value.
+  let mut matching = definitions.iter().filter(|definition| definition.name == name);
+  let definition = matching
+    .clone()
+    .rfind(|definition| definition.line < line)
+    .or_else(|| matching.next_back())?;
+  evaluate_expression(&definition.value, &|symbol| resolve_symbol(definitions, symbol, definition.line, depth + 1))
+}
+
+/// Value of an expression on `line` using the document's symbol assignments.
+pub fn evaluate_with_symbols(definitions: &[SymbolDefinition], expression: &str, line: u32) -> Option<i64> {
+  evaluate_expression(expression, &|symbol| resolve_symbol(definitions, symbol, line, 0))
+}
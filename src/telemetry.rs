@@ -0,0 +1,83 @@
+use crate::logging::log_line;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tower_lsp::jsonrpc::Request;
+use tower_lsp::lsp_types::MessageType;
+use tower_lsp::Client;
+
+pub const DEFAULT_SLOW_REQUEST_MS: u64 = 200;
+
+/// Slow-request reporting, shared between the server (which reads the options) and `RequestTimer`.
+#[derive(Debug)]
+pub struct TimingSettings {
+  /// Requests taking longer than this are reported; 0 turns reporting off.
+  threshold_ms: AtomicU64,
+  /// Also send the warning to the client as `window/logMessage`, not only to stderr or the log file.
+  log_to_client: AtomicBool,
+}
+
+impl Default for TimingSettings {
+  fn default() -> Self {
+    Self {
+      threshold_ms: AtomicU64::new(DEFAULT_SLOW_REQUEST_MS),
+      log_to_client: AtomicBool::new(false),
+    }
+  }
+}
+
+impl TimingSettings {
+  pub fn set_threshold_ms(&self, threshold_ms: u64) {
+    self.threshold_ms.store(threshold_ms, Ordering::Relaxed);
+  }
+
+  pub fn set_log_to_client(&self, log_to_client: bool) {
+    self.log_to_client.store(log_to_client, Ordering::Relaxed);
+  }
+}
+
+/// `textDocument.uri` of a request, when it targets a document.
+fn request_document(params: Option<&Value>) -> Option<String> {
+  let uri = params?.get("textDocument")?.get("uri")?.as_str()?;
+  Some(uri.to_string())
+}
+
+/// Times one message and, once it is answered, warns if it took longer than the threshold, naming
+/// the method (the feature) and the document it was for.
+pub struct RequestTimer {
+  method: String,
+  document: Option<String>,
+  started: Instant,
+  settings: Arc<TimingSettings>,
+  client: Option<Client>,
+}
+
+impl RequestTimer {
+  pub fn start(request: &Request, settings: Arc<TimingSettings>, client: Option<Client>) -> Self {
+    Self {
+      method: request.method().to_string(),
+      document: request_document(request.params()),
+      started: Instant::now(),
+      settings,
+      client,
+    }
+  }
+
+  pub async fn finish(self) {
+    let threshold_ms = self.settings.threshold_ms.load(Ordering::Relaxed);
+    let elapsed_ms = self.started.elapsed().as_millis() as u64;
+    if threshold_ms == 0 || elapsed_ms <= threshold_ms {
+      return;
+    }
+    let method = &self.method;
+    let message = match &self.document {
+      Some(document) => format!("Slow request: {method} took {elapsed_ms} ms ({document})"),
+      None => format!("Slow request: {method} took {elapsed_ms} ms"),
+    };
+    log_line(&message);
+    if self.settings.log_to_client.load(Ordering::Relaxed) && let Some(client) = self.client {
+      client.log_message(MessageType::WARNING, message).await;
+    }
+  }
+}
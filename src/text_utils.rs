@@ -1,3 +1,4 @@
+use crate::registers::{parse_register, RegisterRange};
 use tower_lsp::lsp_types::Position;
 
 pub fn utf16_position_to_byte_offset(line: &str, position: Position) -> usize {
@@ -22,26 +23,117 @@ pub fn byte_offset_to_utf16_position(line: &str, byte_offset: usize) -> u32 {
   utf16_count
 }
 
-pub fn extract_word_at_position(text: &str, position: Position) -> Option<String> {
+/// Function-style operands (`hwreg(HW_REG_MODE, 0, 4)`) hovered as one token.
+const FUNCTION_OPERANDS: &[&str] = &["hwreg", "sendmsg", "sendmsg_rtn", "swizzle", "gpr_idx"];
+
+/// Structure of the token under the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordKind {
+  /// An alphanumeric run: a mnemonic, symbol, register name (`v4`, `ttmp0`) or number.
+  Plain,
+  /// A bracketed register or register range: `s[0:3]`, `v[4]`, `acc[0:31]`.
+  Register(RegisterRange),
+  /// An interpolation attribute channel: `attr0.x`.
+  Attribute { index: u32, channel: char },
+  /// A function-style operand and its trimmed arguments: `hwreg(HW_REG_MODE, 0, 4)`.
+  Function { name: String, arguments: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+  pub text: String,
+  /// Byte range of the token within its line.
+  pub start: usize,
+  pub end: usize,
+  pub kind: WordKind,
+}
+
+fn is_word_byte(b: u8) -> bool {
+  b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// The token starting with the alphanumeric run `line[start..run_end]`, extended over a register
+/// range, attribute channel or function-style argument list that directly follows it.
+fn operand_token(line: &str, start: usize, run_end: usize) -> Word {
+  let bytes = line.as_bytes();
+  let run = &line[start..run_end];
+  let token = |end: usize, kind: WordKind| Word {
+    text: line[start..end].to_string(),
+    start,
+    end,
+    kind,
+  };
+  match bytes.get(run_end) {
+    Some(b'[') => {
+      if let Some(close) = line[run_end..].find(']').map(|close| run_end + close + 1)
+        && let Some(register) = parse_register(&line[start..close])
+      {
+        return token(close, WordKind::Register(register));
+      }
+    }
+    Some(b'.') => {
+      let index = run.strip_prefix("attr").and_then(|index| index.parse().ok());
+      let channel = bytes.get(run_end + 1).map(|&b| b as char).filter(|channel| "xyzw".contains(*channel));
+      let ends = bytes.get(run_end + 2).is_none_or(|&b| !is_word_byte(b));
+      if let (Some(index), Some(channel), true) = (index, channel, ends) {
+        return token(run_end + 2, WordKind::Attribute { index, channel });
+      }
+    }
+    Some(b'(') if FUNCTION_OPERANDS.contains(&run.to_ascii_lowercase().as_str()) => {
+      let mut depth = 0;
+      for (offset, b) in line[run_end..].bytes().enumerate() {
+        match b {
+          b'(' => depth += 1,
+          b')' => depth -= 1,
+          _ => continue,
+        }
+        if depth == 0 {
+          let end = run_end + offset + 1;
+          let arguments = line[run_end + 1..end - 1]
+            .split(',')
+            .map(|argument| argument.trim().to_string())
+            .filter(|argument| !argument.is_empty())
+            .collect();
+          let name = run.to_string();
+          return token(end, WordKind::Function { name, arguments });
+        }
+      }
+    }
+    _ => {}
+  }
+  token(run_end, WordKind::Plain)
+}
+
+/// The token under the cursor. Register ranges (`s[0:3]`), attribute channels (`attr0.x`) and
+/// function-style operands (`hwreg(...)`) are single tokens, parsed; anything else is the
+/// alphanumeric run under the cursor.
+pub fn extract_word_at_position(text: &str, position: Position) -> Option<Word> {
   let line = text.lines().nth(position.line as usize)?;
   let byte_index = utf16_position_to_byte_offset(line, position);
   let bytes = line.as_bytes();
   if byte_index > bytes.len() {
     return None;
   }
-  let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
-  let mut start = byte_index;
-  while start > 0 && is_word(bytes[start - 1]) {
-    start -= 1;
-  }
-  let mut end = byte_index;
-  while end < bytes.len() && is_word(bytes[end]) {
-    end += 1;
-  }
-  if start == end {
-    return None;
+  let mut idx = 0;
+  while idx < bytes.len() {
+    if !is_word_byte(bytes[idx]) {
+      idx += 1;
+      continue;
+    }
+    let start = idx;
+    while idx < bytes.len() && is_word_byte(bytes[idx]) {
+      idx += 1;
+    }
+    if start > byte_index {
+      break;
+    }
+    let word = operand_token(line, start, idx);
+    if byte_index <= word.end {
+      return Some(word);
+    }
+    idx = idx.max(word.end);
   }
-  Some(line[start..end].to_string())
+  None
 }
 
 pub fn extract_word_prefix_at_position(text: &str, position: Position) -> Option<(String, usize)> {
@@ -51,9 +143,8 @@ pub fn extract_word_prefix_at_position(text: &str, position: Position) -> Option
   if byte_index > bytes.len() {
     return None;
   }
-  let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
   let mut start = byte_index;
-  while start > 0 && is_word(bytes[start - 1]) {
+  while start > 0 && is_word_byte(bytes[start - 1]) {
     start -= 1;
   }
   if start == byte_index {
@@ -0,0 +1,488 @@
+use serde::{Deserialize, Serialize};
+
+pub fn is_label_start(b: u8) -> bool {
+  (b as char).is_ascii_alphabetic() || b == b'_' || b == b'.' || b == b'$'
+}
+
+pub fn is_label_char(b: u8) -> bool {
+  is_label_start(b) || (b as char).is_ascii_digit()
+}
+
+pub fn is_hex_digit(b: u8) -> bool {
+  (b as char).is_ascii_hexdigit()
+}
+
+pub fn strip_leading_label(line: &str) -> (usize, &str) {
+  let trimmed = line.trim_start();
+  let trimmed_offset = line.len() - trimmed.len();
+  let bytes = trimmed.as_bytes();
+  if bytes.is_empty() {
+    return (line.len(), "");
+  }
+  if !is_label_start(bytes[0]) {
+    return (trimmed_offset, trimmed);
+  }
+  let mut idx = 1;
+  while idx < bytes.len() && is_label_char(bytes[idx]) {
+    idx += 1;
+  }
+  if idx < bytes.len() && bytes[idx] == b':' {
+    let after_colon = &trimmed[idx + 1..];
+    let after_ws = after_colon.trim_start();
+    let after_ws_offset = trimmed_offset + idx + 1 + (after_colon.len() - after_ws.len());
+    return (after_ws_offset, after_ws);
+  }
+  (trimmed_offset, trimmed)
+}
+
+/// The `name:` label at the start of a line, if any.
+pub fn leading_label(line: &str) -> Option<Token<'_>> {
+  let (after_offset, _) = strip_leading_label(line);
+  let trimmed = line.trim_start();
+  let start = line.len() - trimmed.len();
+  if after_offset == start || !line[start..after_offset].trim_end().ends_with(':') {
+    return None;
+  }
+  let colon = start + line[start..].find(':')?;
+  Some(Token {
+    text: &line[start..colon],
+    start,
+  })
+}
+
+/// Directives that emit data rather than code; their operands are values, not instruction operands.
+pub const DATA_DIRECTIVES: &[&str] = &[
+  ".byte", ".short", ".hword", ".word", ".int", ".long", ".quad", ".octa", ".float", ".single", ".double",
+  ".ascii", ".asciz", ".string", ".zero", ".fill", ".space", ".skip",
+];
+
+/// Assembler dialect. llvm-mc's AMDGPU syntax reads `;` as a comment; GNU as (on targets whose
+/// comment character is not `;`) as a statement separator. SP3, AMD's shader assembler, keeps
+/// llvm-mc's comments but wraps code in `shader`/`end` blocks with undotted keywords.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+  #[default]
+  Llvm,
+  Gnu,
+  Sp3,
+}
+
+impl Dialect {
+  /// Parses the `assemblerDialect` option; anything else selects llvm-mc's syntax.
+  pub fn from_option(value: &str) -> Self {
+    match value.to_ascii_lowercase().as_str() {
+      "gnu" => Dialect::Gnu,
+      "sp3" => Dialect::Sp3,
+      _ => Dialect::Llvm,
+    }
+  }
+
+  fn separates_statements(self) -> bool {
+    self == Dialect::Gnu
+  }
+}
+
+/// SP3 statements that are not instructions: `shader <name>` ... `end` blocks, `asic(GFX11)`,
+/// `type(CS)` and `var` declarations.
+const SP3_KEYWORDS: &[&str] = &["shader", "end", "asic", "type", "var"];
+
+/// The SP3 keyword a statement starts with, when the SP3 dialect is selected.
+pub fn sp3_keyword(statement: &str, dialect: Dialect) -> Option<&'static str> {
+  if dialect != Dialect::Sp3 {
+    return None;
+  }
+  let statement = statement.trim_start();
+  let word_len = statement.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(statement.len());
+  let word = statement[..word_len].to_ascii_lowercase();
+  SP3_KEYWORDS.iter().copied().find(|keyword| *keyword == word)
+}
+
+/// Walks the code part of a line, calling `on_string` with the byte range (quotes included) of each
+/// `"..."` literal, and returns where a `//` comment (or `;`, with the llvm dialect) starts outside of strings.
+fn scan_line(line: &str, dialect: Dialect, mut on_string: impl FnMut(usize, usize)) -> Option<usize> {
+  let bytes = line.as_bytes();
+  let mut idx = 0;
+  while idx < bytes.len() {
+    match bytes[idx] {
+      b'"' => {
+        let start = idx;
+        idx += 1;
+        while idx < bytes.len() && bytes[idx] != b'"' {
+          // Skip the escaped character, including `\"`.
+          idx += if bytes[idx] == b'\\' { 2 } else { 1 };
+        }
+        // An unterminated string runs to the end of the line.
+        idx = (idx + 1).min(bytes.len());
+        on_string(start, idx);
+        continue;
+      }
+      b';' if !dialect.separates_statements() => return Some(idx),
+      b'/' if bytes.get(idx + 1) == Some(&b'/') => return Some(idx),
+      _ => {}
+    }
+    idx += 1;
+  }
+  None
+}
+
+/// Start of a `//` comment (or `;`, with the llvm dialect), ignoring comment characters inside string literals.
+pub fn line_comment_start(line: &str, dialect: Dialect) -> Option<usize> {
+  scan_line(line, dialect, |_, _| {})
+}
+
+/// Byte ranges of the string literals on a line (quotes included), before any comment.
+pub fn string_literal_ranges(line: &str, dialect: Dialect) -> Vec<(usize, usize)> {
+  let mut ranges = Vec::new();
+  scan_line(line, dialect, |start, end| ranges.push((start, end)));
+  ranges
+}
+
+/// Byte ranges of the statements on a line, before any comment: the whole code with the llvm
+/// dialect, split at each `;` outside string literals with the GNU one.
+pub fn statement_ranges(line: &str, dialect: Dialect) -> Vec<(usize, usize)> {
+  let code_end = line_comment_start(line, dialect).unwrap_or(line.len());
+  if !dialect.separates_statements() {
+    return vec![(0, code_end)];
+  }
+  let strings = string_literal_ranges(line, dialect);
+  let mut ranges = Vec::new();
+  let mut start = 0;
+  for (idx, byte) in line[..code_end].bytes().enumerate() {
+    if byte == b';' && !strings.iter().any(|&(string_start, string_end)| string_start <= idx && idx < string_end) {
+      ranges.push((start, idx));
+      start = idx + 1;
+    }
+  }
+  ranges.push((start, code_end));
+  ranges
+}
+
+pub fn in_string_literal(line: &str, byte: usize, dialect: Dialect) -> bool {
+  string_literal_ranges(line, dialect)
+    .iter()
+    .any(|&(start, end)| start <= byte && byte < end)
+}
+
+/// The data directive (`.byte`, `.quad`, `.asciz`, ...) a line consists of, after any label.
+pub fn data_directive(line: &str, dialect: Dialect) -> Option<Token<'_>> {
+  let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+  let (offset, rest) = strip_leading_label(code);
+  let directive = rest.split_whitespace().next()?;
+  let lower = directive.to_ascii_lowercase();
+  DATA_DIRECTIVES.contains(&lower.as_str()).then_some(Token {
+    text: directive,
+    start: offset,
+  })
+}
+
+/// The encoded bytes llvm-objdump appends to a disassembled instruction, as in
+/// `v_add_f32_e64 v1, v1, v2 // 000000000000: D5030001 00020501`.
+#[derive(Debug, Clone)]
+pub struct EncodingComment<'a> {
+  pub address: Token<'a>,
+  pub dwords: Vec<Token<'a>>,
+}
+
+impl EncodingComment<'_> {
+  pub fn values(&self) -> Vec<u32> {
+    self
+      .dwords
+      .iter()
+      .filter_map(|dword| u32::from_str_radix(dword.text, 16).ok())
+      .collect()
+  }
+}
+
+/// Parses a trailing `// <address>: <dword> <dword>...` comment; anything else in the comment
+/// makes it an ordinary comment.
+pub fn encoding_comment(line: &str, dialect: Dialect) -> Option<EncodingComment<'_>> {
+  let comment_start = line_comment_start(line, dialect)?;
+  let marker_len = if line[comment_start..].starts_with("//") { 2 } else { 1 };
+  let body_start = comment_start + marker_len;
+  let mut words = split_whitespace_tokens(line, body_start);
+  let first = words.next()?;
+  let address_text = first.text.strip_suffix(':')?;
+  if address_text.len() < 4 || !address_text.bytes().all(is_hex_digit) {
+    return None;
+  }
+  let address = Token {
+    text: address_text,
+    start: first.start,
+  };
+  let mut dwords = Vec::new();
+  for word in words {
+    if word.text.len() != 8 || !word.text.bytes().all(is_hex_digit) {
+      return None;
+    }
+    dwords.push(word);
+  }
+  if dwords.is_empty() {
+    return None;
+  }
+  Some(EncodingComment { address, dwords })
+}
+
+fn split_whitespace_tokens(line: &str, start: usize) -> impl Iterator<Item = Token<'_>> {
+  let bytes = line.as_bytes();
+  let mut idx = start;
+  std::iter::from_fn(move || {
+    while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+      idx += 1;
+    }
+    if idx >= bytes.len() {
+      return None;
+    }
+    let token_start = idx;
+    while idx < bytes.len() && !bytes[idx].is_ascii_whitespace() {
+      idx += 1;
+    }
+    Some(Token {
+      text: &line[token_start..idx],
+      start: token_start,
+    })
+  })
+}
+
+pub fn strip_leading_disasm_prefix(line: &str) -> (usize, &str) {
+  let trimmed = line.trim_start();
+  let trimmed_offset = line.len() - trimmed.len();
+  let bytes = trimmed.as_bytes();
+  if bytes.is_empty() {
+    return (line.len(), "");
+  }
+
+  let mut idx = 0;
+  let mut hex_len = 0;
+  while idx < bytes.len() && is_hex_digit(bytes[idx]) {
+    idx += 1;
+    hex_len += 1;
+  }
+  if hex_len >= 4 && idx < bytes.len() && bytes[idx] == b':' {
+    idx += 1;
+    while idx < bytes.len() && (bytes[idx] as char).is_ascii_whitespace() {
+      idx += 1;
+    }
+  } else {
+    idx = 0;
+  }
+
+  loop {
+    if idx + 8 <= bytes.len() && bytes[idx..idx + 8].iter().all(|&b| is_hex_digit(b)) {
+      let mut next = idx + 8;
+      if next < bytes.len() && (bytes[next] as char).is_ascii_whitespace() {
+        while next < bytes.len() && (bytes[next] as char).is_ascii_whitespace() {
+          next += 1;
+        }
+        idx = next;
+        continue;
+      }
+    }
+    break;
+  }
+
+  (trimmed_offset + idx, &trimmed[idx..])
+}
+
+/// Address of a disassembled listing line: the one of its trailing encoding comment, otherwise a
+/// leading `1000:` address column followed by an encoding dword (so a `dead:` label is not one).
+pub fn listing_address(line: &str, dialect: Dialect) -> Option<u64> {
+  if let Some(comment) = encoding_comment(line, dialect) {
+    return u64::from_str_radix(comment.address.text, 16).ok();
+  }
+  let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+  let (address, rest) = code.trim_start().split_once(':')?;
+  let encoded = rest.split_whitespace().next().is_some_and(|word| word.len() == 8 && word.bytes().all(is_hex_digit));
+  if address.len() < 4 || !address.bytes().all(is_hex_digit) || !encoded {
+    return None;
+  }
+  u64::from_str_radix(address, 16).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+  pub text: &'a str,
+  /// Byte offset of the token within its line.
+  pub start: usize,
+}
+
+impl Token<'_> {
+  pub fn end(&self) -> usize {
+    self.start + self.text.len()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstructionLine<'a> {
+  pub mnemonic: Token<'a>,
+  /// Comma-separated operands, trimmed. Trailing whitespace-separated modifiers (`offset:16`,
+  /// `glc`, `op_sel:[0,1]`, ...) are not included.
+  pub operands: Vec<Token<'a>>,
+  pub modifiers: Vec<Token<'a>>,
+}
+
+impl InstructionLine<'_> {
+  /// Value text of a `name:value` modifier.
+  pub fn modifier_value(&self, name: &str) -> Option<(&Token<'_>, &str)> {
+    self.modifiers.iter().find_map(|modifier| {
+      let (key, value) = modifier.text.split_once(':')?;
+      key.eq_ignore_ascii_case(name).then_some((modifier, value))
+    })
+  }
+
+  pub fn has_modifier(&self, name: &str) -> bool {
+    self.modifiers.iter().any(|modifier| modifier.text.eq_ignore_ascii_case(name))
+  }
+}
+
+fn trimmed_token(line: &str, start: usize, end: usize) -> Option<Token<'_>> {
+  let raw = &line[start..end];
+  let text = raw.trim();
+  if text.is_empty() {
+    return None;
+  }
+  let leading = raw.len() - raw.trim_start().len();
+  Some(Token {
+    text,
+    start: start + leading,
+  })
+}
+
+/// Splits `line[start..end]` at separator bytes that are not nested inside `()` or `[]`.
+fn split_top_level(line: &str, start: usize, end: usize, is_separator: impl Fn(u8) -> bool) -> Vec<(usize, usize)> {
+  let mut pieces = Vec::new();
+  let mut depth = 0i32;
+  let mut piece_start = start;
+  for (idx, &byte) in line.as_bytes()[..end].iter().enumerate().skip(start) {
+    match byte {
+      b'(' | b'[' => depth += 1,
+      b')' | b']' => depth -= 1,
+      b if depth <= 0 && is_separator(b) => {
+        pieces.push((piece_start, idx));
+        piece_start = idx + 1;
+      }
+      _ => {}
+    }
+  }
+  pieces.push((piece_start, end));
+  pieces
+}
+
+/// Index of the comma-separated operand at `cursor`. Commas nested in `()` or `[]` (as in
+/// `quad_perm:[0, 1, 2, 3]`) do not separate operands.
+pub fn operand_index_at(line: &str, mnemonic: &Token<'_>, cursor: usize) -> usize {
+  let end = cursor.min(line.len()).max(mnemonic.end());
+  split_top_level(line, mnemonic.end(), end, |b| b == b',').len() - 1
+}
+
+pub fn is_modifier_token(text: &str) -> bool {
+  let bytes = text.as_bytes();
+  if bytes.is_empty() || !(bytes[0].is_ascii_alphabetic() || bytes[0] == b'_') {
+    return false;
+  }
+  let name_len = bytes
+    .iter()
+    .take_while(|&&b| b.is_ascii_alphanumeric() || b == b'_')
+    .count();
+  name_len == bytes.len() || bytes[name_len] == b':'
+}
+
+/// Parses the instruction on a source line (after any label, disassembly address/encoding prefix
+/// and before any comment). Directives, assignments and empty lines yield `None`. With the GNU
+/// dialect this is the line's first statement.
+pub fn parse_instruction_line(line: &str, dialect: Dialect) -> Option<InstructionLine<'_>> {
+  let (_, end) = statement_ranges(line, dialect)[0];
+  parse_statement(&line[..end], dialect)
+}
+
+/// The instructions of every statement on a line, with offsets into the whole line.
+pub fn parse_instruction_statements(line: &str, dialect: Dialect) -> Vec<InstructionLine<'_>> {
+  statement_ranges(line, dialect)
+    .into_iter()
+    .filter_map(|(start, end)| Some(parse_statement(&line[start..end], dialect)?.shifted(start)))
+    .collect()
+}
+
+/// The instruction of the statement holding byte `cursor` of a line.
+pub fn instruction_at(line: &str, cursor: usize, dialect: Dialect) -> Option<InstructionLine<'_>> {
+  let ranges = statement_ranges(line, dialect);
+  let &(start, end) = ranges.iter().find(|&&(_, end)| cursor <= end).unwrap_or(&ranges[ranges.len() - 1]);
+  Some(parse_statement(&line[start..end], dialect)?.shifted(start))
+}
+
+fn parse_statement(code: &str, dialect: Dialect) -> Option<InstructionLine<'_>> {
+  let code_end = code.len();
+  let (label_offset, after_label) = strip_leading_label(code);
+  let (prefix_offset, after_prefix) = strip_leading_disasm_prefix(after_label);
+  let mnemonic_start = label_offset + prefix_offset;
+  let mnemonic_len = after_prefix
+    .find(|ch: char| ch.is_whitespace())
+    .unwrap_or(after_prefix.len());
+  if mnemonic_len == 0 || !is_label_start(after_prefix.as_bytes()[0]) || after_prefix.starts_with('.') {
+    return None;
+  }
+  if sp3_keyword(after_prefix, dialect).is_some() {
+    return None;
+  }
+  let rest = &after_prefix[mnemonic_len..];
+  if rest.trim_start().starts_with('=') {
+    return None;
+  }
+  let mnemonic = Token {
+    text: &after_prefix[..mnemonic_len],
+    start: mnemonic_start,
+  };
+  let args_start = mnemonic.end();
+  let mut operands = Vec::new();
+  let mut modifiers = Vec::new();
+  if code[args_start..].trim().is_empty() {
+    return Some(InstructionLine {
+      mnemonic,
+      operands,
+      modifiers,
+    });
+  }
+
+  let pieces = split_top_level(code, args_start, code_end, |b| b == b',');
+  let last_index = pieces.len() - 1;
+  for (piece_index, &(start, end)) in pieces.iter().enumerate() {
+    if piece_index != last_index {
+      if let Some(token) = trimmed_token(code, start, end) {
+        operands.push(token);
+      }
+      continue;
+    }
+    // The last piece may carry whitespace-separated modifiers after the final operand.
+    let chunks: Vec<Token<'_>> = split_top_level(code, start, end, |b| b.is_ascii_whitespace())
+      .into_iter()
+      .filter_map(|(chunk_start, chunk_end)| trimmed_token(code, chunk_start, chunk_end))
+      .collect();
+    let split_at = (1..chunks.len())
+      .find(|&idx| chunks[idx..].iter().all(|chunk| is_modifier_token(chunk.text)))
+      .unwrap_or(chunks.len());
+    if split_at > 0 {
+      let first = chunks[0];
+      let last = chunks[split_at - 1];
+      operands.push(Token {
+        text: &code[first.start..last.end()],
+        start: first.start,
+      });
+    }
+    modifiers.extend_from_slice(&chunks[split_at..]);
+  }
+  Some(InstructionLine {
+    mnemonic,
+    operands,
+    modifiers,
+  })
+}
+
+impl InstructionLine<'_> {
+  /// Moves every token by `offset` bytes, from a statement to the line holding it.
+  fn shifted(mut self, offset: usize) -> Self {
+    for token in std::iter::once(&mut self.mnemonic).chain(&mut self.operands).chain(&mut self.modifiers) {
+      token.start += offset;
+    }
+    self
+  }
+}
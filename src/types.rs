@@ -1,8 +1,10 @@
-use serde::Deserialize;
+use crate::errata::Erratum;
+use crate::modifiers::MemoryModifier;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tower_lsp::lsp_types::Url;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct InstructionEntry {
   pub name: String,
   pub architectures: Vec<String>,
@@ -10,7 +12,76 @@ pub struct InstructionEntry {
   pub args: Vec<String>,
   pub arg_types: Vec<String>,
   pub arg_data_types: Vec<String>,
+  /// Operand sizes in bits, parallel to `args`. Only present for entries with multi-dword operands.
+  #[serde(default)]
+  pub arg_sizes: Vec<u32>,
   pub available_encodings: Vec<String>,
+  /// Operand lists of encodings that differ from the default `args` (e.g. the VOP3 form of a VOP2 op).
+  #[serde(default)]
+  pub encoding_operands: Vec<EncodingOperands>,
+  #[serde(default)]
+  pub notes: Vec<String>,
+  #[serde(default)]
+  pub execution_unit: Option<ExecutionUnit>,
+  /// Notes merged in from the configured ISA overlay files; never part of `isa.json`.
+  #[serde(skip)]
+  pub overlay_notes: Vec<OverlayNote>,
+}
+
+/// Parameters of the `amdgpu/rawEntry` request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawEntryParams {
+  /// Instruction name; an encoding suffix (`_e64`, `_dpp`, ...) is ignored.
+  pub name: String,
+  /// Only return the records covering this architecture (aliases accepted).
+  #[serde(default)]
+  pub architecture: Option<String>,
+}
+
+/// A note about an instruction from an overlay file, rendered under the overlay's name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlayNote {
+  pub source: String,
+  pub text: String,
+}
+
+/// Unit an instruction issues to on the architectures of its entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionUnit {
+  Salu,
+  Valu,
+  /// The RDNA3+ transcendental unit, which runs alongside the VALU.
+  Trans,
+  Lds,
+  /// The texture path, which also serves buffer, global and scratch accesses.
+  Texture,
+  Smem,
+  Branch,
+}
+
+impl ExecutionUnit {
+  pub fn label(self) -> &'static str {
+    match self {
+      ExecutionUnit::Salu => "scalar ALU",
+      ExecutionUnit::Valu => "vector ALU",
+      ExecutionUnit::Trans => "transcendental",
+      ExecutionUnit::Lds => "LDS",
+      ExecutionUnit::Texture => "texture (vector memory)",
+      ExecutionUnit::Smem => "scalar memory",
+      ExecutionUnit::Branch => "branch",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EncodingOperands {
+  pub encoding: String,
+  pub args: Vec<String>,
+  pub arg_types: Vec<String>,
+  pub arg_data_types: Vec<String>,
+  #[serde(default)]
+  pub arg_sizes: Vec<u32>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -52,10 +123,8 @@ impl SpecialRegisterRange {
         name: format!("{}{}", self.prefix, idx),
         description: self.description.clone(),
       };
-      if let Some(ov) = overrides_by_index.get(&idx) {
-        if ov.description.is_some() {
-          reg.description = ov.description.clone();
-        }
+      if let Some(ov) = overrides_by_index.get(&idx) && ov.description.is_some() {
+        reg.description = ov.description.clone();
       }
       out.push(reg);
     }
@@ -80,6 +149,13 @@ pub enum SpecialRegistersData {
 pub struct IsaData {
   pub instructions: Vec<InstructionEntry>,
   pub special_registers: SpecialRegistersData,
+  /// Alternative target names (gfx processors, codenames, products) per architecture.
+  #[serde(default)]
+  pub architecture_aliases: HashMap<String, Vec<String>>,
+  #[serde(default)]
+  pub memory_modifiers: Vec<MemoryModifier>,
+  #[serde(default)]
+  pub errata: Vec<Erratum>,
 }
 
 #[derive(Default)]
@@ -87,10 +163,16 @@ pub struct DocumentStore {
   pub docs: HashMap<Url, DocumentState>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DocumentState {
   pub text: String,
+  /// Version the client gave the text in `didOpen`/`didChange`.
+  pub version: i32,
   pub language_id: String,
+  /// Architecture guessed from the instructions in the text, used when nothing else names one.
+  pub detected_architecture: Option<String>,
+  /// Effective architecture last reported to the client via `amdgpu/architectureChanged`.
+  pub reported_architecture: Option<Option<String>>,
 }
 
 pub struct IsaLoadInfo {
@@ -98,7 +180,7 @@ pub struct IsaLoadInfo {
   pub load_error: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EncodingVariant {
   Native,
   E32,
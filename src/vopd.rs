@@ -0,0 +1,140 @@
+use crate::index::find_entry;
+use crate::tokenizer::{line_comment_start, parse_instruction_line, Dialect, InstructionLine, Token};
+use crate::types::InstructionEntry;
+use std::collections::HashMap;
+
+/// Which half of a VOPD pair an operation is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DualHalf {
+  X,
+  Y,
+}
+
+/// Operations either half of a VOPD pair may use.
+pub const DUAL_OPCODES: &[&str] = &[
+  "v_dual_fmac_f32", "v_dual_fmaak_f32", "v_dual_fmamk_f32", "v_dual_mul_f32", "v_dual_add_f32", "v_dual_sub_f32",
+  "v_dual_subrev_f32", "v_dual_mul_dx9_zero_f32", "v_dual_mov_b32", "v_dual_cndmask_b32", "v_dual_max_f32",
+  "v_dual_min_f32", "v_dual_dot2acc_f32_f16", "v_dual_dot2acc_f32_bf16",
+];
+
+/// Operations only the Y half may use.
+pub const Y_ONLY_DUAL_OPCODES: &[&str] = &["v_dual_add_nc_u32", "v_dual_lshlrev_b32", "v_dual_and_b32"];
+
+/// Constraints on the two operations of a pair.
+pub const PAIRING_RULES: &[&str] = &[
+  "One destination must be an even VGPR and the other an odd one",
+  "`SRC0X`/`SRC0Y` and `VSRC1X`/`VSRC1Y` must read VGPRs in different banks (register number mod 4)",
+  "Both halves share a single literal constant",
+];
+
+impl DualHalf {
+  pub fn name(self) -> &'static str {
+    match self {
+      DualHalf::X => "X",
+      DualHalf::Y => "Y",
+    }
+  }
+
+  /// Whether the operation may appear in this half.
+  pub fn allows(self, mnemonic: &str) -> bool {
+    let lower = mnemonic.to_ascii_lowercase();
+    DUAL_OPCODES.contains(&lower.as_str()) || (self == DualHalf::Y && Y_ONLY_DUAL_OPCODES.contains(&lower.as_str()))
+  }
+}
+
+/// A `v_dual_* ... :: v_dual_* ...` line. The Y operation is absent until the `::` is typed.
+#[derive(Debug, Clone)]
+pub struct DualLine<'a> {
+  pub x: InstructionLine<'a>,
+  /// Byte offset of the `::` separator.
+  pub separator: Option<usize>,
+  pub y: Option<InstructionLine<'a>>,
+}
+
+fn shifted(token: Token<'_>, offset: usize) -> Token<'_> {
+  Token {
+    text: token.text,
+    start: token.start + offset,
+  }
+}
+
+/// Parses a VOPD line into its X and Y operations, with token offsets relative to `line`.
+pub fn parse_dual_line(line: &str, dialect: Dialect) -> Option<DualLine<'_>> {
+  let code = &line[..line_comment_start(line, dialect).unwrap_or(line.len())];
+  let separator = code.find("::");
+  let x = parse_instruction_line(&code[..separator.unwrap_or(code.len())], dialect)?;
+  if !x.mnemonic.text.to_ascii_lowercase().starts_with("v_dual_") {
+    return None;
+  }
+  let y = separator.and_then(|separator| {
+    let offset = separator + 2;
+    let y = parse_instruction_line(&code[offset..], dialect)?;
+    Some(InstructionLine {
+      mnemonic: shifted(y.mnemonic, offset),
+      operands: y.operands.into_iter().map(|token| shifted(token, offset)).collect(),
+      modifiers: y.modifiers.into_iter().map(|token| shifted(token, offset)).collect(),
+    })
+  });
+  Some(DualLine { x, separator, y })
+}
+
+/// The operations of a parsed statement of `line`: both halves when it is a VOPD pair, else the
+/// statement itself.
+pub fn statement_operations<'a>(line: &'a str, parsed: InstructionLine<'a>, dialect: Dialect) -> Vec<InstructionLine<'a>> {
+  match parse_dual_line(line, dialect).filter(|dual| dual.x.mnemonic.start == parsed.mnemonic.start) {
+    Some(dual) => std::iter::once(dual.x).chain(dual.y).collect(),
+    None => vec![parsed],
+  }
+}
+
+/// Indices of the entry's operands that belong to `half`. VOPD operand fields end in `X` or `Y`
+/// (`VDSTX`, `SRC0Y`, ...); entries without such fields list only their own operands.
+pub fn half_operands(entry: &InstructionEntry, half: DualHalf) -> Vec<usize> {
+  let suffix = match half {
+    DualHalf::X => 'X',
+    DualHalf::Y => 'Y',
+  };
+  let tagged = |arg: &String| arg.to_ascii_uppercase().ends_with(['X', 'Y']);
+  if !entry.args.iter().any(tagged) {
+    return (0..entry.args.len()).collect();
+  }
+  (0..entry.args.len())
+    .filter(|&index| entry.args[index].to_ascii_uppercase().ends_with(suffix))
+    .collect()
+}
+
+/// The entry documenting a VOPD operation: the `v_dual_*` entry itself when the data has one, else
+/// the VOP1/VOP2 instruction it duals (`v_dual_mul_f32` is `v_mul_f32`).
+pub fn dual_entry<'a>(
+  index: &'a HashMap<String, Vec<InstructionEntry>>,
+  mnemonic: &str,
+  filter: Option<&str>,
+) -> Option<&'a InstructionEntry> {
+  let lower = mnemonic.to_ascii_lowercase();
+  let operation = lower.strip_prefix("v_dual_")?;
+  find_entry(index, &lower, filter).or_else(|| find_entry(index, &format!("v_{operation}"), filter))
+}
+
+/// Hover text for one half of a pair: its role, its partner, whether it may sit in that half, and
+/// the pairing rules.
+pub fn dual_half_note(half: DualHalf, mnemonic: &str, partner: Option<&str>) -> String {
+  let mut lines = vec![match partner {
+    Some(partner) => format!("VOPD {} operation, paired with `{partner}`", half.name()),
+    None => format!("VOPD {} operation", half.name()),
+  }];
+  if !half.allows(mnemonic) {
+    let reason = if DualHalf::Y.allows(mnemonic) { "only valid as the Y operation" } else { "not a VOPD operation" };
+    lines.push(format!("⚠️ `{}` is {reason}", mnemonic.to_ascii_lowercase()));
+  }
+  format!("{}\n\n{}", lines.join("  \n"), pairing_rules())
+}
+
+/// The pairing rules and the operations each half accepts, as markdown.
+pub fn pairing_rules() -> String {
+  let mut lines = vec!["**VOPD pairing rules**".to_string()];
+  lines.extend(PAIRING_RULES.iter().map(|rule| format!("- {rule}")));
+  let list = |opcodes: &[&str]| opcodes.iter().map(|opcode| format!("`{opcode}`")).collect::<Vec<_>>().join(", ");
+  lines.push(format!("- X and Y: {}", list(DUAL_OPCODES)));
+  lines.push(format!("- Y only: {}", list(Y_ONLY_DUAL_OPCODES)));
+  lines.join("\n")
+}
@@ -0,0 +1,199 @@
+use crate::expression::parse_integer_literal;
+use crate::tokenizer::{InstructionLine, Token};
+
+/// One counter a wait instruction waits on: execution stalls until at most `value` of its
+/// operations are outstanding. `value == max` does not wait at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitRequirement {
+  pub counter: &'static str,
+  pub value: u32,
+  pub max: u32,
+}
+
+impl WaitRequirement {
+  pub fn waits(&self) -> bool {
+    self.value < self.max
+  }
+}
+
+/// A field of a packed `simm16` wait immediate.
+struct CounterField {
+  counter: &'static str,
+  /// `(shift, width)` bit ranges, low bits first.
+  parts: &'static [(u32, u32)],
+}
+
+const GFX9_WAITCNT: &[CounterField] = &[
+  CounterField { counter: "vmcnt", parts: &[(0, 4), (14, 2)] },
+  CounterField { counter: "expcnt", parts: &[(4, 3)] },
+  CounterField { counter: "lgkmcnt", parts: &[(8, 4)] },
+];
+const GFX10_WAITCNT: &[CounterField] = &[
+  CounterField { counter: "vmcnt", parts: &[(0, 4), (14, 2)] },
+  CounterField { counter: "expcnt", parts: &[(4, 3)] },
+  CounterField { counter: "lgkmcnt", parts: &[(8, 6)] },
+];
+const GFX11_WAITCNT: &[CounterField] = &[
+  CounterField { counter: "vmcnt", parts: &[(10, 6)] },
+  CounterField { counter: "expcnt", parts: &[(0, 3)] },
+  CounterField { counter: "lgkmcnt", parts: &[(4, 6)] },
+];
+const GFX12_LOADCNT_DSCNT: &[CounterField] = &[
+  CounterField { counter: "loadcnt", parts: &[(8, 6)] },
+  CounterField { counter: "dscnt", parts: &[(0, 6)] },
+];
+const GFX12_STORECNT_DSCNT: &[CounterField] = &[
+  CounterField { counter: "storecnt", parts: &[(8, 6)] },
+  CounterField { counter: "dscnt", parts: &[(0, 6)] },
+];
+
+/// gfx12 single-counter waits and gfx10/11 `s_waitcnt_<counter> null, imm` forms.
+const SINGLE_COUNTER_WAITS: &[(&str, &str, u32)] = &[
+  ("s_wait_loadcnt", "loadcnt", 63),
+  ("s_wait_storecnt", "storecnt", 63),
+  ("s_wait_samplecnt", "samplecnt", 63),
+  ("s_wait_bvhcnt", "bvhcnt", 7),
+  ("s_wait_kmcnt", "kmcnt", 31),
+  ("s_wait_dscnt", "dscnt", 63),
+  ("s_wait_expcnt", "expcnt", 7),
+  ("s_waitcnt_vscnt", "vscnt", 63),
+  ("s_waitcnt_vmcnt", "vmcnt", 63),
+  ("s_waitcnt_expcnt", "expcnt", 7),
+  ("s_waitcnt_lgkmcnt", "lgkmcnt", 63),
+];
+
+fn counter_max(field: &CounterField) -> u32 {
+  let width: u32 = field.parts.iter().map(|&(_, width)| width).sum();
+  (1 << width) - 1
+}
+
+fn decode_fields(fields: &[CounterField], imm: u32) -> Vec<WaitRequirement> {
+  fields
+    .iter()
+    .map(|field| {
+      let mut value = 0;
+      let mut offset = 0;
+      for &(shift, width) in field.parts {
+        value |= ((imm >> shift) & ((1 << width) - 1)) << offset;
+        offset += width;
+      }
+      WaitRequirement {
+        counter: field.counter,
+        value,
+        max: counter_max(field),
+      }
+    })
+    .collect()
+}
+
+/// `s_waitcnt` immediate layout for the architecture. gfx12 has no `s_waitcnt`; code being ported
+/// to it (or with no architecture) is read with the gfx11 layout.
+fn legacy_waitcnt_layout(filter: Option<&str>) -> &'static [CounterField] {
+  match filter {
+    Some(filter) if filter.starts_with("cdna") => GFX9_WAITCNT,
+    Some(filter) if filter.starts_with("rdna1") || filter.starts_with("rdna2") => GFX10_WAITCNT,
+    _ => GFX11_WAITCNT,
+  }
+}
+
+/// Symbolic `vmcnt(0) & lgkmcnt(1)` operands; counters that are not named are not waited on.
+fn decode_symbolic(fields: &[CounterField], operands: &str) -> Option<Vec<WaitRequirement>> {
+  let mut requirements: Vec<WaitRequirement> = fields
+    .iter()
+    .map(|field| WaitRequirement {
+      counter: field.counter,
+      value: counter_max(field),
+      max: counter_max(field),
+    })
+    .collect();
+  let mut any = false;
+  for part in operands.split(|ch: char| ch == '&' || ch == ',' || ch.is_whitespace()) {
+    if part.is_empty() {
+      continue;
+    }
+    let (name, rest) = part.split_once('(')?;
+    let value = parse_integer_literal(rest.strip_suffix(')')?)?;
+    let name = name.trim().to_ascii_lowercase();
+    let name = name.strip_suffix("_sat").unwrap_or(&name);
+    let requirement = requirements.iter_mut().find(|requirement| requirement.counter == name)?;
+    requirement.value = (value.max(0) as u32).min(requirement.max);
+    any = true;
+  }
+  any.then_some(requirements)
+}
+
+/// What a wait instruction waits for, from its mnemonic and operand text (everything after the
+/// mnemonic, comments removed).
+pub fn decode_wait(mnemonic: &str, operands: &str, filter: Option<&str>) -> Option<Vec<WaitRequirement>> {
+  let mnemonic = mnemonic.to_ascii_lowercase();
+  let operands = operands.trim();
+  let fields = match mnemonic.as_str() {
+    "s_waitcnt" => legacy_waitcnt_layout(filter),
+    "s_wait_loadcnt_dscnt" => GFX12_LOADCNT_DSCNT,
+    "s_wait_storecnt_dscnt" => GFX12_STORECNT_DSCNT,
+    _ => {
+      let &(_, counter, max) = SINGLE_COUNTER_WAITS.iter().find(|(name, _, _)| *name == mnemonic)?;
+      // `s_waitcnt_vscnt null, 0x0` carries the count as its last operand.
+      let imm = operands.rsplit(',').next()?.trim();
+      let value = parse_integer_literal(imm)?;
+      return Some(vec![WaitRequirement {
+        counter,
+        value: (value.max(0) as u32).min(max),
+        max,
+      }]);
+    }
+  };
+  match parse_integer_literal(operands) {
+    Some(imm) => Some(decode_fields(fields, imm as u32)),
+    None => decode_symbolic(fields, operands),
+  }
+}
+
+/// `Waits until loadcnt ≤ 0, dscnt ≤ 1`, naming counters that are left alone.
+pub fn describe_wait(requirements: &[WaitRequirement]) -> String {
+  let waited: Vec<String> = requirements
+    .iter()
+    .filter(|requirement| requirement.waits())
+    .map(|requirement| format!("`{}` ≤ {}", requirement.counter, requirement.value))
+    .collect();
+  let skipped: Vec<String> = requirements
+    .iter()
+    .filter(|requirement| !requirement.waits())
+    .map(|requirement| format!("`{}`", requirement.counter))
+    .collect();
+  let mut text = if waited.is_empty() {
+    "Does not wait on any counter".to_string()
+  } else {
+    format!("Waits until {}", waited.join(", "))
+  };
+  if !waited.is_empty() && !skipped.is_empty() {
+    text.push_str(&format!(" ({} not waited on)", skipped.join(", ")));
+  }
+  text
+}
+
+/// gfx12 split waits equivalent to a legacy `s_waitcnt`/`s_waitcnt_vscnt`: `vmcnt` covers loads,
+/// samples and BVH traversals, `lgkmcnt` covers LDS and scalar memory, `vscnt` stores.
+pub fn split_wait_replacement(requirements: &[WaitRequirement]) -> Vec<String> {
+  let mut out = Vec::new();
+  for requirement in requirements.iter().filter(|requirement| requirement.waits()) {
+    let targets: &[(&str, u32)] = match requirement.counter {
+      "vmcnt" => &[("s_wait_loadcnt", 63), ("s_wait_samplecnt", 63), ("s_wait_bvhcnt", 7)],
+      "lgkmcnt" => &[("s_wait_dscnt", 63), ("s_wait_kmcnt", 31)],
+      "expcnt" => &[("s_wait_expcnt", 7)],
+      "vscnt" => &[("s_wait_storecnt", 63)],
+      _ => &[],
+    };
+    for &(mnemonic, max) in targets {
+      out.push(format!("{mnemonic} {:#x}", requirement.value.min(max)));
+    }
+  }
+  out
+}
+
+/// What the wait instruction of a parsed statement waits for, from the operands of that statement alone.
+pub fn statement_wait(line: &str, parsed: &InstructionLine<'_>, filter: Option<&str>) -> Option<Vec<WaitRequirement>> {
+  let mnemonic_end = parsed.mnemonic.end();
+  let end = parsed.operands.iter().chain(&parsed.modifiers).map(Token::end).max().unwrap_or(mnemonic_end);
+  decode_wait(parsed.mnemonic.text, &line[mnemonic_end..end], filter)
+}
@@ -0,0 +1,317 @@
+use crate::encoding::split_encoding_variant;
+use crate::outline::document_outline;
+use crate::symbols::collect_symbol_definitions;
+use crate::text_utils::byte_offset_to_utf16_position;
+use crate::tokenizer::{parse_instruction_statements, Dialect, Token};
+use crate::vopd::parse_dual_line;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::{
+  DocumentSymbol, Location, Position, ProgressToken, Range, SymbolInformation, SymbolKind, Url,
+};
+
+const ASSEMBLY_EXTENSIONS: &[&str] = &["s", "asm", "rdna", "rdna3", "rdna35", "rdna4", "cdna", "cdna3", "cdna4"];
+const SKIPPED_DIRECTORIES: &[&str] = &["target", "node_modules", "build"];
+
+/// `$/progress` carrying a batch of partial `workspace/symbol` results.
+pub enum PartialSymbolResults {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialSymbolResultsParams {
+  pub token: ProgressToken,
+  pub value: Vec<SymbolInformation>,
+}
+
+impl Notification for PartialSymbolResults {
+  type Params = PartialSymbolResultsParams;
+  const METHOD: &'static str = "$/progress";
+}
+
+pub fn is_assembly_path(path: &Path) -> bool {
+  match path.extension().and_then(|ext| ext.to_str()) {
+    Some(ext) => ASSEMBLY_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()),
+    None => false,
+  }
+}
+
+/// Assembly files under the workspace roots, skipping hidden and build output directories.
+pub fn collect_assembly_files(roots: &[PathBuf]) -> Vec<PathBuf> {
+  let mut files = Vec::new();
+  let mut pending: Vec<PathBuf> = roots.to_vec();
+  while let Some(dir) = pending.pop() {
+    let entries = match fs::read_dir(&dir) {
+      Ok(entries) => entries,
+      Err(_) => continue,
+    };
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let name = entry.file_name();
+      let name = name.to_string_lossy();
+      let file_type = match entry.file_type() {
+        Ok(file_type) => file_type,
+        Err(_) => continue,
+      };
+      if file_type.is_dir() {
+        if !name.starts_with('.') && !SKIPPED_DIRECTORIES.contains(&name.as_ref()) {
+          pending.push(path);
+        }
+      } else if file_type.is_file() && is_assembly_path(&path) {
+        files.push(path);
+      }
+    }
+  }
+  files.sort();
+  files
+}
+
+#[allow(deprecated)]
+fn flatten_symbols(
+  uri: &Url,
+  symbols: Vec<DocumentSymbol>,
+  container: Option<&str>,
+  query: &str,
+  out: &mut Vec<SymbolInformation>,
+) {
+  for symbol in symbols {
+    // Data directives are outline leaves, not named symbols.
+    if symbol.kind == SymbolKind::CONSTANT {
+      continue;
+    }
+    if query.is_empty() || symbol.name.to_ascii_lowercase().contains(query) {
+      out.push(SymbolInformation {
+        name: symbol.name.clone(),
+        kind: symbol.kind,
+        tags: None,
+        deprecated: None,
+        location: Location {
+          uri: uri.clone(),
+          range: symbol.selection_range,
+        },
+        container_name: container.map(|name| name.to_string()),
+      });
+    }
+    if let Some(children) = symbol.children {
+      flatten_symbols(uri, children, Some(&symbol.name), query, out);
+    }
+  }
+}
+
+/// Kernels, labels, macros and `.set`/`.equ`/`=` symbols of one file whose names contain `query`
+/// (case-insensitive).
+#[allow(deprecated)]
+pub fn file_symbols(uri: &Url, text: &str, query: &str, dialect: Dialect) -> Vec<SymbolInformation> {
+  let query = query.to_ascii_lowercase();
+  let mut out = Vec::new();
+  flatten_symbols(uri, document_outline(text, dialect), None, &query, &mut out);
+  let lines: Vec<&str> = text.lines().collect();
+  for definition in collect_symbol_definitions(text, dialect) {
+    if !query.is_empty() && !definition.name.to_ascii_lowercase().contains(&query) {
+      continue;
+    }
+    let line = lines[definition.line as usize];
+    let position = |byte| Position::new(definition.line, byte_offset_to_utf16_position(line, byte));
+    out.push(SymbolInformation {
+      name: definition.name,
+      kind: SymbolKind::VARIABLE,
+      tags: None,
+      deprecated: None,
+      location: Location {
+        uri: uri.clone(),
+        range: Range::new(position(definition.start), position(definition.end)),
+      },
+      container_name: None,
+    });
+  }
+  out
+}
+
+/// Where a line uses an instruction, keyed by its lowercase mnemonic without encoding suffix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstructionUse {
+  pub mnemonic: String,
+  pub range: Range,
+}
+
+/// Every instruction mnemonic of one file, both halves of VOPD lines included.
+pub fn file_instruction_uses(text: &str, dialect: Dialect) -> Vec<InstructionUse> {
+  let mut out = Vec::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    let mnemonics: Vec<Token<'_>> = match parse_dual_line(line, dialect) {
+      Some(dual) => std::iter::once(dual.x.mnemonic).chain(dual.y.map(|y| y.mnemonic)).collect(),
+      None => parse_instruction_statements(line, dialect).into_iter().map(|parsed| parsed.mnemonic).collect(),
+    };
+    for mnemonic in mnemonics {
+      let position = |byte| Position::new(line_idx as u32, byte_offset_to_utf16_position(line, byte));
+      out.push(InstructionUse {
+        mnemonic: split_encoding_variant(mnemonic.text).base.to_ascii_lowercase(),
+        range: Range::new(position(mnemonic.start), position(mnemonic.end())),
+      });
+    }
+  }
+  out
+}
+
+/// Whether a use of `used` is a use of `mnemonic`: a VOPD half (`v_dual_mul_f32`) also counts as the
+/// VOP1/VOP2 instruction it duals (`v_mul_f32`).
+fn is_use_of(used: &str, mnemonic: &str) -> bool {
+  used == mnemonic || used.strip_prefix("v_dual_").is_some_and(|operation| mnemonic.strip_prefix("v_") == Some(operation))
+}
+
+/// Locations of the uses of `mnemonic` (already lowercase and without encoding suffix).
+pub fn instruction_locations(uri: &Url, uses: &[InstructionUse], mnemonic: &str) -> Vec<Location> {
+  uses
+    .iter()
+    .filter(|instruction| is_use_of(&instruction.mnemonic, mnemonic))
+    .map(|instruction| Location::new(uri.clone(), instruction.range))
+    .collect()
+}
+
+/// Bumped whenever the cached symbol format or the outline rules change.
+const CACHE_VERSION: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+  modified_ns: u128,
+  len: u64,
+}
+
+impl FileStamp {
+  fn of(path: &Path) -> Option<Self> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified_ns = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    Some(Self {
+      modified_ns,
+      len: metadata.len(),
+    })
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+  stamp: FileStamp,
+  symbols: Vec<SymbolInformation>,
+  instructions: Vec<InstructionUse>,
+}
+
+/// Every workspace file's symbols and instruction uses, persisted between sessions so reopening a large repository only
+/// re-scans files whose modification time or size changed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorkspaceIndexCache {
+  version: u32,
+  /// Dialect the files were scanned in; a cache from another one is discarded.
+  dialect: Dialect,
+  files: HashMap<PathBuf, CachedFile>,
+  #[serde(skip)]
+  dirty: bool,
+}
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`, so the cache file name stays put.
+fn stable_hash(bytes: &[u8]) -> u64 {
+  bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn cache_directory() -> Option<PathBuf> {
+  let from_env = |name: &str| env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+  if let Some(dir) = from_env("AMDGPU_LSP_CACHE_DIR") {
+    return Some(dir);
+  }
+  let base = from_env("XDG_CACHE_HOME")
+    .or_else(|| from_env("LOCALAPPDATA"))
+    .or_else(|| from_env("HOME").map(|home| home.join(".cache")))?;
+  Some(base.join("amdgpu-lsp"))
+}
+
+/// Cache file for a set of workspace roots.
+pub fn workspace_cache_path(roots: &[PathBuf]) -> Option<PathBuf> {
+  let key: Vec<String> = roots.iter().map(|root| root.to_string_lossy().into_owned()).collect();
+  let hash = stable_hash(key.join("\n").as_bytes());
+  Some(cache_directory()?.join(format!("workspace-{hash:016x}.json")))
+}
+
+impl WorkspaceIndexCache {
+  /// An empty cache for files scanned in `dialect`.
+  pub fn new(dialect: Dialect) -> Self {
+    Self {
+      version: CACHE_VERSION,
+      dialect,
+      ..Self::default()
+    }
+  }
+
+  /// The cache at `path`, or an empty one when it is missing, unreadable, from another version or
+  /// built for another dialect.
+  pub fn load(path: &Path, dialect: Dialect) -> Self {
+    let cache = fs::read_to_string(path)
+      .ok()
+      .and_then(|contents| serde_json::from_str::<WorkspaceIndexCache>(&contents).ok());
+    match cache {
+      Some(cache) if cache.version == CACHE_VERSION && cache.dialect == dialect => cache,
+      _ => Self::new(dialect),
+    }
+  }
+
+  /// Writes the cache if it changed since it was loaded.
+  pub fn save(&mut self, path: &Path) -> std::io::Result<()> {
+    if !self.dirty {
+      return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let contents = serde_json::to_string(self).map_err(std::io::Error::other)?;
+    // Write then rename so a concurrent reader never sees half a file.
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)?;
+    self.dirty = false;
+    Ok(())
+  }
+
+  /// A file on disk as cached, re-scanned when its modification time or size changed.
+  fn scanned_file(&mut self, uri: &Url, path: &Path) -> Option<&CachedFile> {
+    let stamp = FileStamp::of(path)?;
+    let fresh = self.files.get(path).is_some_and(|cached| cached.stamp == stamp);
+    if !fresh {
+      let text = fs::read_to_string(path).ok()?;
+      self.files.insert(path.to_path_buf(), CachedFile {
+        stamp,
+        symbols: file_symbols(uri, &text, "", self.dialect),
+        instructions: file_instruction_uses(&text, self.dialect),
+      });
+      self.dirty = true;
+    }
+    self.files.get(path)
+  }
+
+  /// All symbols of a file on disk, from the cache when the file is unchanged, else by scanning it.
+  pub fn file_symbols(&mut self, uri: &Url, path: &Path) -> Option<Vec<SymbolInformation>> {
+    Some(self.scanned_file(uri, path)?.symbols.clone())
+  }
+
+  /// Uses of `mnemonic` in a file on disk, from the cache when the file is unchanged.
+  pub fn instruction_locations(&mut self, uri: &Url, path: &Path, mnemonic: &str) -> Option<Vec<Location>> {
+    Some(instruction_locations(uri, &self.scanned_file(uri, path)?.instructions, mnemonic))
+  }
+
+  /// Drops files that are no longer part of the workspace.
+  pub fn retain_files(&mut self, files: &[PathBuf]) {
+    let keep: HashSet<&PathBuf> = files.iter().collect();
+    let before = self.files.len();
+    self.files.retain(|path, _| keep.contains(path));
+    self.dirty |= self.files.len() != before;
+  }
+}
+
+/// Symbols whose names contain `query` (case-insensitive), as `file_symbols` would select them.
+pub fn filter_symbols(symbols: Vec<SymbolInformation>, query: &str) -> Vec<SymbolInformation> {
+  let query = query.to_ascii_lowercase();
+  symbols
+    .into_iter()
+    .filter(|symbol| query.is_empty() || symbol.name.to_ascii_lowercase().contains(&query))
+    .collect()
+}
@@ -0,0 +1,56 @@
+use amdgpu_lsp::{lint_files, sarif_report, RunConfig};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// Writes `text` to a file of the temporary directory, named after the test that uses it.
+fn source_file(name: &str, text: &str) -> PathBuf {
+  let path = std::env::temp_dir().join(format!("amdgpu-lsp-{name}-{}.s", std::process::id()));
+  fs::write(&path, text).unwrap();
+  path
+}
+
+fn config(arch: &str) -> RunConfig {
+  RunConfig {
+    data_path: Some(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/isa.json").to_string()),
+    default_architecture: Some(arch.to_string()),
+    dialect: None,
+  }
+}
+
+#[test]
+fn lint_resolves_gfx_architecture_names() {
+  let path = source_file("lint-gfx", "v_add_f32 v0, v1, v2\ns_endpgm\n");
+  let report = lint_files(config("gfx1100"), std::slice::from_ref(&path)).unwrap();
+  fs::remove_file(&path).unwrap();
+  assert_eq!((report.errors, report.warnings), (0, 0), "{}", report.output);
+}
+
+#[test]
+fn lint_reads_feature_suffixes_as_part_of_the_target() {
+  // `:xnack+` turns a feature on; read as a range it would admit the rdna4-only `s_sleep_var`.
+  let path = source_file("lint-features", "s_sleep_var s0\ns_endpgm\n");
+  for arch in ["gfx1100:xnack+", "gfx1100:sramecc+:xnack+"] {
+    let report = lint_files(config(arch), std::slice::from_ref(&path)).unwrap();
+    assert!(report.output.contains("not available on rdna3"), "{arch}: {}", report.output);
+  }
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn sarif_resolves_gfx_architecture_names() {
+  let path = source_file("sarif-gfx", "v_add_f32 v0, v1, v2\ns_endpgm\n");
+  let report = sarif_report(config("gfx1100"), std::slice::from_ref(&path)).unwrap();
+  fs::remove_file(&path).unwrap();
+  let log: Value = serde_json::from_str(&report.json).unwrap();
+  assert_eq!(log["runs"][0]["results"], Value::Array(Vec::new()), "{}", report.json);
+}
+
+#[test]
+fn conditions_use_gnu_operator_precedence() {
+  // GNU as binds `<<` tighter than `+`, so the condition is `2 + 6 != 8` and the block is skipped.
+  let path = source_file("precedence", ".if 2 + 3 << 1 != 8\nv_bogus v0\n.endif\ns_endpgm\n");
+  let report = lint_files(config("rdna3"), std::slice::from_ref(&path)).unwrap();
+  fs::remove_file(&path).unwrap();
+  assert_eq!((report.errors, report.warnings), (0, 0), "{}", report.output);
+}
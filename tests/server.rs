@@ -0,0 +1,109 @@
+use amdgpu_lsp::{run_server, RunConfig};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, DuplexStream, ReadHalf, WriteHalf};
+
+const URI: &str = "file:///tmp/round_trip.s";
+
+/// The client end of an in-process connection to `run_server`.
+struct Client {
+  reader: BufReader<ReadHalf<DuplexStream>>,
+  writer: WriteHalf<DuplexStream>,
+}
+
+impl Client {
+  fn start() -> Self {
+    let (client, server) = tokio::io::duplex(1 << 16);
+    let (server_reader, server_writer) = tokio::io::split(server);
+    let config = RunConfig {
+      data_path: Some(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/isa.json").to_string()),
+      default_architecture: Some("rdna3".to_string()),
+      dialect: None,
+    };
+    tokio::spawn(run_server(server_reader, server_writer, config));
+    let (reader, writer) = tokio::io::split(client);
+    Self {
+      reader: BufReader::new(reader),
+      writer,
+    }
+  }
+
+  async fn send(&mut self, message: Value) {
+    let body = message.to_string();
+    let frame = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+    self.writer.write_all(frame.as_bytes()).await.unwrap();
+  }
+
+  async fn receive(&mut self) -> Value {
+    let mut length = 0;
+    loop {
+      let mut header = String::new();
+      self.reader.read_line(&mut header).await.unwrap();
+      let header = header.trim_end();
+      if header.is_empty() {
+        break;
+      }
+      if let Some(value) = header.strip_prefix("Content-Length: ") {
+        length = value.parse().unwrap();
+      }
+    }
+    let mut body = vec![0; length];
+    self.reader.read_exact(&mut body).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+  }
+
+  /// Sends a request and returns its result, skipping the notifications that arrive meanwhile.
+  async fn request(&mut self, id: u64, method: &str, params: Value) -> Value {
+    self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })).await;
+    loop {
+      let message = self.receive().await;
+      if message["id"] == id {
+        return message["result"].clone();
+      }
+    }
+  }
+
+  /// Waits for a notification, skipping everything else.
+  async fn notification(&mut self, method: &str) -> Value {
+    loop {
+      let message = self.receive().await;
+      if message["method"] == method {
+        return message["params"].clone();
+      }
+    }
+  }
+}
+
+#[tokio::test]
+async fn hover_and_diagnostics_round_trip() {
+  let mut client = Client::start();
+  let initialized = client
+    .request(0, "initialize", json!({ "capabilities": {}, "initializationOptions": { "discoverTarget": false } }))
+    .await;
+  assert_eq!(initialized["capabilities"]["hoverProvider"], true);
+  client.send(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} })).await;
+
+  let text = "v_add_f32 v0, v1, v2\nv_bogus v0\ns_endpgm\n";
+  let document = json!({ "uri": URI, "languageId": "rdna3", "version": 1, "text": text });
+  client
+    .send(json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": { "textDocument": document } }))
+    .await;
+  let diagnostics = client.notification("textDocument/publishDiagnostics").await;
+  assert_eq!(diagnostics["uri"], URI);
+  let diagnostics = diagnostics["diagnostics"].as_array().unwrap();
+  assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+  assert_eq!(diagnostics[0]["range"]["start"]["line"], 1);
+  assert!(diagnostics[0]["message"].as_str().unwrap().contains("v_bogus"));
+
+  let hover = client
+    .request(
+      1,
+      "textDocument/hover",
+      json!({ "textDocument": { "uri": URI }, "position": { "line": 0, "character": 3 } }),
+    )
+    .await;
+  let contents = hover["contents"]["value"].as_str().unwrap();
+  assert!(contents.contains("**v_add_f32**"), "{contents}");
+  assert!(contents.contains("Add two single-precision floats."), "{contents}");
+
+  assert_eq!(client.request(2, "shutdown", Value::Null).await, Value::Null);
+}
@@ -8,6 +8,8 @@ import {
 } from "vscode-languageclient/node";
 
 let client: LanguageClient | null = null;
+let statusBarItem: vscode.StatusBarItem | null = null;
+const documentArchitectures = new Map<string, string | null>();
 
 function resolveBundledServerPath(context: vscode.ExtensionContext): string | undefined {
   const binaryName = "amdgpu-lsp";
@@ -48,6 +50,71 @@ function resolveArchitectureOverride(): string | undefined {
   return override ? override : undefined;
 }
 
+function resolveHoverSections(): Record<string, boolean> {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<Record<string, boolean>>("hoverSections") ?? {};
+}
+
+function resolveDiscoverTarget(): boolean {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<boolean>("discoverTarget") ?? true;
+}
+
+function resolveSlowRequestMs(): number {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<number>("slowRequestMs") ?? 200;
+}
+
+function resolveLogSlowRequests(): boolean {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<boolean>("logSlowRequests") ?? false;
+}
+
+function resolveWorkspaceIndexCache(): boolean {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<boolean>("workspaceIndexCache") ?? true;
+}
+
+function resolveColorDecorators(): boolean {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<boolean>("colorDecorators") ?? false;
+}
+
+function resolveExplainIdioms(): boolean {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<boolean>("explainIdioms") ?? false;
+}
+
+function resolveEncodingSuffixStyle(): string {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<string>("encodingSuffixStyle") ?? "";
+}
+
+function resolveOperandInlayHints(): string {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<string>("operandInlayHints") ?? "off";
+}
+
+function resolveAssemblerDialect(): string {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<string>("assemblerDialect") ?? "llvm";
+}
+
+function resolveFormatting(): Record<string, number | boolean> {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<Record<string, number | boolean>>("formatting") ?? {};
+}
+
+function resolveIsaOverlays(): string[] {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<string[]>("isaOverlays") ?? [];
+}
+
+function resolveExternalSymbols(): Record<string, string> | string[] {
+  const config = vscode.workspace.getConfiguration("amdgpuLsp");
+  return config.get<Record<string, string> | string[]>("externalSymbols") ?? {};
+}
+
 function resolveServerCwd(): string | undefined {
   const folders = vscode.workspace.workspaceFolders;
   if (folders && folders.length > 0) {
@@ -96,14 +163,72 @@ function createClient(command: string, env: NodeJS.ProcessEnv): LanguageClient {
       { scheme: "file", language: "cdna4" },
     ],
     outputChannelName: "AMDGPU Language Server",
+    synchronize: {
+      configurationSection: "amdgpuLsp",
+    },
     initializationOptions: {
       architectureOverride: resolveArchitectureOverride(),
+      discoverTarget: resolveDiscoverTarget(),
+      hoverSections: resolveHoverSections(),
+      slowRequestMs: resolveSlowRequestMs(),
+      logSlowRequests: resolveLogSlowRequests(),
+      workspaceIndexCache: resolveWorkspaceIndexCache(),
+      colorDecorators: resolveColorDecorators(),
+      explainIdioms: resolveExplainIdioms(),
+      encodingSuffixStyle: resolveEncodingSuffixStyle(),
+      operandInlayHints: resolveOperandInlayHints(),
+      assemblerDialect: resolveAssemblerDialect(),
+      formatting: resolveFormatting(),
+      isaOverlays: resolveIsaOverlays(),
+      externalSymbols: resolveExternalSymbols(),
     },
   };
 
   return new LanguageClient("amdgpuLsp", "AMDGPU Language Server", serverOptions, clientOptions);
 }
 
+interface ArchitectureDetectedParams {
+  uri: string;
+  architecture: string;
+  evidence: string;
+}
+
+async function handleArchitectureDetected(params: ArchitectureDetectedParams): Promise<void> {
+  const file = path.basename(vscode.Uri.parse(params.uri).fsPath);
+  const choice = await vscode.window.showInformationMessage(
+    `AMDGPU: treating ${file} as ${params.architecture} (uses ${params.evidence}).`,
+    "Use for Workspace"
+  );
+  if (choice === "Use for Workspace") {
+    const config = vscode.workspace.getConfiguration("amdgpuLsp");
+    await config.update("architecture", params.architecture, vscode.ConfigurationTarget.Workspace);
+  }
+}
+
+interface ArchitectureChangedParams {
+  uri: string;
+  architecture: string | null;
+}
+
+function updateStatusBar(): void {
+  if (!statusBarItem) {
+    return;
+  }
+  const editor = vscode.window.activeTextEditor;
+  const architecture = editor ? documentArchitectures.get(editor.document.uri.toString()) : undefined;
+  if (architecture === undefined) {
+    statusBarItem.hide();
+    return;
+  }
+  statusBarItem.text = `AMDGPU: ${architecture ?? "any"}`;
+  statusBarItem.show();
+}
+
+function handleArchitectureChanged(params: ArchitectureChangedParams): void {
+  documentArchitectures.set(vscode.Uri.parse(params.uri).toString(), params.architecture);
+  updateStatusBar();
+}
+
 export async function activate(context: vscode.ExtensionContext): Promise<void> {
   const startClient = async () => {
     const command = resolveServerPath(context);
@@ -114,6 +239,8 @@ export async function activate(context: vscode.ExtensionContext): Promise<void>
     }
     const env = resolveServerEnv(context);
     client = createClient(command, env);
+    client.onNotification("amdgpu/architectureDetected", handleArchitectureDetected);
+    client.onNotification("amdgpu/architectureChanged", handleArchitectureChanged);
     client.start();
     context.subscriptions.push(client);
   };
@@ -124,10 +251,16 @@ export async function activate(context: vscode.ExtensionContext): Promise<void>
         await client.stop();
         client = null;
       }
+      documentArchitectures.clear();
       await startClient();
     })
   );
 
+  statusBarItem = vscode.window.createStatusBarItem(vscode.StatusBarAlignment.Right, 100);
+  statusBarItem.tooltip = "Architecture used for AMDGPU instruction lookups";
+  context.subscriptions.push(statusBarItem);
+  context.subscriptions.push(vscode.window.onDidChangeActiveTextEditor(updateStatusBar));
+
   await startClient();
 }
 